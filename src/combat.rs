@@ -0,0 +1,389 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use rand::Rng;
+
+use crate::{
+    components::{Dead, Direction, Health, Stack, Velocity},
+    input::{Action, InputMap, InputSettings},
+    items::{
+        definition::ItemRegistry,
+        dropped::DropItem,
+        durability::WearEquipment,
+        equipment::{EquipSlot, Equipment},
+        lock::key_drop,
+    },
+    menu::GameState,
+    mobs::{chase::follow_paths, Enemy, EnemyKind},
+    physics::apply_velocity,
+    player::{player_movement, Player},
+    ui::PanelState,
+};
+
+// Damage of an unarmed swing, weapons add their damage stat on top
+const BASE_DAMAGE: i32 = 6;
+
+// Distance from the player's center a swing reaches
+const ATTACK_RANGE: f32 = 28.;
+
+// Cosine of the half angle of the swing, targets further to the side are missed
+const ATTACK_ARC: f32 = 0.3;
+
+const ATTACK_COOLDOWN: f32 = 0.4;
+
+// Enemies closer than this to the player hurt it on contact
+const CONTACT_RANGE: f32 = 14.;
+
+const INVULNERABLE_SECONDS: f32 = 0.5;
+
+const KNOCKBACK_SPEED: f32 = 220.;
+
+const KNOCKBACK_SECONDS: f32 = 0.15;
+
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Damage>()
+            .add_event::<Hurt>()
+            .add_event::<Swing>()
+            .add_event::<Killed>()
+            .add_systems(
+                Update,
+                (
+                    player_attack,
+                    contact_damage,
+                    apply_damage,
+                    tick_invulnerability,
+                    despawn_dead_enemies,
+                    apply_knockback
+                        .after(player_movement)
+                        .after(follow_paths)
+                        .before(apply_velocity),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(Update, check_death.run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Event)]
+pub struct Damage {
+    pub target: Entity,
+    pub amount: u8,
+    // Where the hit came from, the target is knocked away from it
+    pub source: Vec2,
+}
+
+// Sent whenever health is actually lost, hits shrugged off while invulnerable send nothing
+#[derive(Event)]
+pub struct Hurt {
+    pub target: Entity,
+    pub amount: u8,
+}
+
+// Sent for every swing of the player, whether or not it hits anything with health
+#[derive(Event)]
+pub struct Swing {
+    pub position: Vec2,
+    pub facing: Vec2,
+}
+
+// Sent once for every enemy slain, as it is removed
+#[derive(Event)]
+pub struct Killed {
+    pub kind: EnemyKind,
+    pub position: Vec2,
+}
+
+// Swing cooldown of an entity able to attack
+#[derive(Component)]
+pub struct MeleeAttack {
+    cooldown: Timer,
+}
+
+impl Default for MeleeAttack {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(ATTACK_COOLDOWN, TimerMode::Once);
+        cooldown.tick(Duration::from_secs_f32(ATTACK_COOLDOWN));
+        MeleeAttack { cooldown }
+    }
+}
+
+// Further damage is ignored until the timer runs out
+#[derive(Component)]
+pub struct Invulnerable(Timer);
+
+// Pushes the entity along the impulse, fading out over the timer
+#[derive(Component)]
+pub struct Knockback {
+    impulse: Vec2,
+    timer: Timer,
+}
+
+// Whether a swing from the position towards the facing reaches the target
+pub fn in_reach(position: Vec2, facing: Vec2, target: Vec2) -> bool {
+    let offset = target - position;
+    let in_arc = !offset
+        .try_normalize()
+        .is_some_and(|offset| offset.dot(facing) < ATTACK_ARC);
+
+    offset.length() <= ATTACK_RANGE && in_arc
+}
+
+// Swings at everything with health in front of the player on space or left click
+#[allow(clippy::too_many_arguments)]
+fn player_attack(
+    time: Res<Time>,
+    kb: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mouse: Res<Input<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    pad: Res<Input<GamepadButton>>,
+    input_settings: Res<InputSettings>,
+    panels: Res<PanelState>,
+    registry: Res<ItemRegistry>,
+    mut player: Query<
+        (Entity, &Transform, &Direction, &Equipment, &mut MeleeAttack),
+        Without<Dead>,
+    >,
+    targets: Query<(Entity, &Transform, &Health), Without<Dead>>,
+    mut damage: EventWriter<Damage>,
+    mut wear: EventWriter<WearEquipment>,
+    mut swings: EventWriter<Swing>,
+) {
+    let Ok((entity, transform, direction, equipment, mut attack)) = player.get_single_mut() else {
+        return;
+    };
+
+    attack.cooldown.tick(time.delta());
+
+    // Clicks on an open panel belong to the panel
+    if panels.is_focused() || !attack.cooldown.finished() {
+        return;
+    }
+
+    let pressed = input_map.just_pressed(Action::Attack, &kb)
+        || mouse.just_pressed(MouseButton::Left)
+        || input_settings.just_pressed(input_settings.attack, &gamepads, &pad);
+    if !pressed {
+        return;
+    }
+
+    attack.cooldown.reset();
+
+    let weapon = equipment
+        .main_hand
+        .map(|stack| registry.stats(&stack).damage)
+        .unwrap_or(0);
+    let amount = (BASE_DAMAGE + weapon).clamp(1, u8::MAX as i32) as u8;
+
+    let position = transform.translation.truncate();
+    let facing = direction.vector();
+    let mut hit = false;
+
+    swings.send(Swing { position, facing });
+
+    for (target, target_transform, _) in targets.iter() {
+        if target == entity {
+            continue;
+        }
+
+        if in_reach(position, facing, target_transform.translation.truncate()) {
+            damage.send(Damage {
+                target,
+                amount,
+                source: position,
+            });
+            hit = true;
+        }
+    }
+
+    if hit && equipment.main_hand.is_some() {
+        wear.send(WearEquipment {
+            slot: EquipSlot::MainHand,
+            amount: 1,
+        });
+    }
+}
+
+// Enemies touching the player hurt it, armor takes the edge off
+fn contact_damage(
+    registry: Res<ItemRegistry>,
+    player: Query<(Entity, &Transform, &Equipment, Has<Invulnerable>), With<Player>>,
+    enemies: Query<(&Enemy, &Transform), Without<Dead>>,
+    mut damage: EventWriter<Damage>,
+    mut wear: EventWriter<WearEquipment>,
+) {
+    let Ok((entity, transform, equipment, invulnerable)) = player.get_single() else {
+        return;
+    };
+
+    if invulnerable {
+        return;
+    }
+
+    let position = transform.translation.truncate();
+
+    let Some((enemy, enemy_transform)) = enemies.iter().find(|(_, enemy_transform)| {
+        enemy_transform.translation.truncate().distance(position) <= CONTACT_RANGE
+    }) else {
+        return;
+    };
+
+    let armor = equipment
+        .body
+        .map(|stack| registry.stats(&stack).armor)
+        .unwrap_or(0);
+    let amount = (enemy.kind.contact_damage() as i32 - armor).max(1) as u8;
+
+    damage.send(Damage {
+        target: entity,
+        amount,
+        source: enemy_transform.translation.truncate(),
+    });
+
+    if equipment.body.is_some() {
+        wear.send(WearEquipment {
+            slot: EquipSlot::Body,
+            amount: 1,
+        });
+    }
+}
+
+fn apply_damage(
+    mut commands: Commands,
+    mut events: EventReader<Damage>,
+    mut targets: Query<(&mut Health, &Transform, Has<Velocity>), Without<Dead>>,
+    invulnerable: Query<(), With<Invulnerable>>,
+    mut hurt: EventWriter<Hurt>,
+) {
+    // Invulnerability is only inserted once commands apply, so repeat hits this frame are tracked here
+    let mut struck = Vec::new();
+
+    for event in events.read() {
+        if struck.contains(&event.target) {
+            continue;
+        }
+
+        if invulnerable.contains(event.target) {
+            continue;
+        }
+
+        let Ok((mut health, transform, has_velocity)) = targets.get_mut(event.target) else {
+            continue;
+        };
+
+        struck.push(event.target);
+
+        health.current = health.current.saturating_sub(event.amount);
+        debug!(
+            "Dealt {} damage, {} health left",
+            event.amount, health.current
+        );
+
+        hurt.send(Hurt {
+            target: event.target,
+            amount: event.amount,
+        });
+
+        let mut target = commands.entity(event.target);
+        target.insert(Invulnerable(Timer::from_seconds(
+            INVULNERABLE_SECONDS,
+            TimerMode::Once,
+        )));
+
+        if has_velocity {
+            let away = (transform.translation.truncate() - event.source)
+                .try_normalize()
+                .unwrap_or(Vec2::Y);
+
+            target.insert(Knockback {
+                impulse: away * KNOCKBACK_SPEED,
+                timer: Timer::from_seconds(KNOCKBACK_SECONDS, TimerMode::Once),
+            });
+        }
+    }
+}
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut invulnerable: Query<(Entity, &mut Invulnerable)>,
+) {
+    for (entity, mut invulnerable) in invulnerable.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+// Movement sets velocity fresh every frame, so knockback is added on top of it
+fn apply_knockback(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut knocked: Query<(Entity, &mut Velocity, &mut Knockback)>,
+) {
+    for (entity, mut velocity, mut knockback) in knocked.iter_mut() {
+        knockback.timer.tick(time.delta());
+
+        if knockback.timer.finished() {
+            commands.entity(entity).remove::<Knockback>();
+            continue;
+        }
+
+        let push = knockback.impulse * knockback.timer.percent_left();
+        velocity.dx += push.x;
+        velocity.dy += push.y;
+    }
+}
+
+// Spawners and the player handle their own deaths, enemies are removed and drop a loot roll
+fn despawn_dead_enemies(
+    mut commands: Commands,
+    registry: Res<ItemRegistry>,
+    dead: Query<(Entity, &Enemy, &Transform), Added<Dead>>,
+    mut drops: EventWriter<DropItem>,
+    mut killed: EventWriter<Killed>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for (entity, enemy, transform) in dead.iter() {
+        info!("Killed {}", enemy.kind.key());
+
+        killed.send(Killed {
+            kind: enemy.kind,
+            position: transform.translation.truncate(),
+        });
+
+        if let Some(stack) = registry.roll_loot(&mut rng) {
+            drops.send(DropItem {
+                stack,
+                position: transform.translation.truncate(),
+            });
+        }
+
+        // Keys to camp chests drop on top of the regular roll
+        if let Some((key, chance)) = key_drop(enemy.kind) {
+            if rng.gen_bool(chance) {
+                drops.send(DropItem {
+                    stack: Stack::new(key, 1),
+                    position: transform.translation.truncate(),
+                });
+            }
+        }
+
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Anything whose health has run out is marked dead, systems reacting to deaths look for the marker
+pub fn check_death(mut commands: Commands, query: Query<(Entity, &Health), Without<Dead>>) {
+    for (entity, health) in query.iter() {
+        if health.current == 0 {
+            commands.entity(entity).insert(Dead);
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use bevy::ecs::component::Component;
+use bevy::{ecs::component::Component, math::Vec2};
+
+use serde::{Deserialize, Serialize};
+
+use crate::items::instance::ItemInstance;
 
 #[derive(Component)]
 pub struct Dead;
@@ -9,7 +13,7 @@ pub struct Velocity {
     pub dy: f32,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Direction {
     Up,
     Down,
@@ -17,11 +21,81 @@ pub enum Direction {
     Right,
 }
 
+impl Direction {
+    pub fn vector(&self) -> Vec2 {
+        match self {
+            Direction::Up => Vec2::Y,
+            Direction::Down => Vec2::NEG_Y,
+            Direction::Left => Vec2::NEG_X,
+            Direction::Right => Vec2::X,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct Health {
     pub current: u8,
     pub max: u8,
 }
 
-#[derive(Component)]
-pub struct Dirty;
+// What a chunk still has to catch up on since its tiles last changed, each part is cleared by the
+// system that redoes it. Every chunk carries one, a clean chunk has nothing set
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Dirty {
+    // Seams are stitched against the neighbors
+    pub stitch: bool,
+    // Nav grid is rebuilt over the chunk's tiles
+    pub nav: bool,
+    // Minimap picture is redrawn
+    pub visual: bool,
+    // Interior differs from the saved one, written out when the chunk unloads
+    pub save: bool,
+}
+
+impl Dirty {
+    pub const ALL: Dirty = Dirty {
+        stitch: true,
+        nav: true,
+        visual: true,
+        save: true,
+    };
+
+    // Tiles changed in place, seams are marked separately where the change reaches them
+    pub const TILES: Dirty = Dirty {
+        stitch: false,
+        nav: true,
+        visual: true,
+        save: true,
+    };
+
+    pub fn mark(&mut self, aspects: Dirty) {
+        self.stitch |= aspects.stitch;
+        self.nav |= aspects.nav;
+        self.visual |= aspects.visual;
+        self.save |= aspects.save;
+    }
+}
+
+// Quantity of a single item, entities holding one may be merged together
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Stack {
+    pub id: u32,
+    pub count: u32,
+    #[serde(default)]
+    pub instance: Option<ItemInstance>,
+}
+
+impl Stack {
+    pub fn new(id: u32, count: u32) -> Stack {
+        Stack {
+            id,
+            count,
+            instance: None,
+        }
+    }
+
+    // Items carrying instance data are unique and never merge
+    pub fn stacks_with(&self, other: &Stack) -> bool {
+        self.id == other.id && self.instance.is_none() && other.instance.is_none()
+    }
+}
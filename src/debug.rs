@@ -2,15 +2,56 @@ use std::vec;
 
 use bevy::prelude::*;
 
-use crate::{player::Player, world::Chunk};
+use crate::{
+    components::Stack,
+    input::{Action, InputMap},
+    items::{dropped::DropItem, durability::WearEquipment, equipment::EquipSlot},
+    menu::GameState,
+    mobs::{behavior::Behavior, chase::NavPath, perception::Perception, Enemy},
+    player::Player,
+    world::{
+        Chunk, ChunkCache, ChunksSaved, HeatmapMode, NavGrid, TeleportRequest, Tile, TileGrid,
+        WfcTrace, WfcTraceSettings, WorldSeed, CHUNK_SIZE,
+    },
+};
 
 pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_font)
-            .add_systems(Update, toggle_debug_info)
-            .add_systems(Update, update_debug_info);
+        app.init_resource::<TraceReplay>()
+            .init_resource::<NavDebug>()
+            .add_systems(OnExit(GameState::MainMenu), setup_font)
+            .add_systems(
+                Update,
+                toggle_debug_info.run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                control_wfc_trace.run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(Update, debug_teleport.run_if(in_state(GameState::InGame)))
+            .add_systems(Update, debug_drop_item.run_if(in_state(GameState::InGame)))
+            .add_systems(
+                Update,
+                debug_wear_equipment.run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(Update, cycle_heatmap.run_if(in_state(GameState::InGame)))
+            .add_systems(
+                Update,
+                (select_nav_target, draw_nav_debug, label_behaviors)
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                update_debug_info.run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (draw_chunk_borders, inspect_hovered_tile)
+                    .after(update_debug_info)
+                    .run_if(in_state(GameState::InGame)),
+            );
     }
 }
 
@@ -20,6 +61,20 @@ pub struct FontResource(Handle<Font>);
 #[derive(Component)]
 pub struct DebugInfo;
 
+// Step-by-step replay of the last WFC contradiction trace
+#[derive(Resource, Default)]
+pub struct TraceReplay {
+    trace: Option<WfcTrace>,
+    step: usize,
+}
+
+// Walkability and path overlay, limited to the selected enemy when one is picked
+#[derive(Resource, Default)]
+pub struct NavDebug {
+    enabled: bool,
+    selected: Option<Entity>,
+}
+
 fn setup_font(mut commands: Commands, asset_server: Res<AssetServer>) {
     let handle = asset_server.load::<Font>("fonts/FiraMono-Medium.ttf");
     commands.insert_resource(FontResource(handle));
@@ -29,10 +84,11 @@ fn toggle_debug_info(
     asset_server: Res<AssetServer>,
     mut commands: Commands,
     input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
     query: Query<Entity, With<DebugInfo>>,
 ) {
     if let Some(font_handle) = asset_server.get_handle::<Font>("fonts/FiraMono-Medium.ttf") {
-        if input.just_pressed(KeyCode::F3) {
+        if input_map.just_pressed(Action::DebugInfo, &input) {
             if let Ok(entity) = query.get_single() {
                 // Delete it
                 commands.entity(entity).despawn();
@@ -50,7 +106,7 @@ fn toggle_debug_info(
                                 },
                                 value: "".into()
                             };
-                            4 as usize
+                            9 as usize
                         ],
                         alignment: TextAlignment::Left,
                         ..Default::default()
@@ -64,15 +120,284 @@ fn toggle_debug_info(
     }
 }
 
+fn control_wfc_trace(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut trace_settings: ResMut<WfcTraceSettings>,
+    mut replay: ResMut<TraceReplay>,
+) {
+    // Toggle capturing of traces
+    if input_map.just_pressed(Action::DebugTrace, &input) {
+        trace_settings.enabled = !trace_settings.enabled;
+        info!("WFC tracing enabled: {}", trace_settings.enabled);
+    }
+
+    // Load the last written trace
+    if input_map.just_pressed(Action::DebugLoadTrace, &input) {
+        replay.trace = trace_settings.read_last();
+        replay.step = 0;
+
+        if replay.trace.is_none() {
+            warn!("No WFC trace available to replay");
+        }
+    }
+
+    // Step through the collapse order
+    if input_map.just_pressed(Action::DebugStepTrace, &input) {
+        if let Some(trace) = &replay.trace {
+            replay.step = (replay.step + 1).min(trace.order.len());
+        }
+    }
+}
+
+// Jumps far into unexplored terrain to exercise destination pregeneration
+fn debug_teleport(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    player_query: Query<&Transform, With<Player>>,
+    mut teleport: EventWriter<TeleportRequest>,
+) {
+    if input_map.just_pressed(Action::DebugTeleport, &input) {
+        if let Ok(transform) = player_query.get_single() {
+            teleport.send(TeleportRequest {
+                destination: transform.translation.truncate() + Vec2::new(4096., 0.),
+                fee: 0,
+            });
+        }
+    }
+}
+
+// Cycles the chunk heatmap through its metrics
+fn cycle_heatmap(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut mode: ResMut<HeatmapMode>,
+) {
+    if input_map.just_pressed(Action::DebugHeatmap, &input) {
+        *mode = mode.next();
+        info!("Chunk heatmap: {:?}", *mode);
+    }
+}
+
+// F1 toggles the nav overlay, clicking while it is shown selects the enemy nearest the cursor
+fn select_nav_target(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mouse: Res<Input<MouseButton>>,
+    mut nav_debug: ResMut<NavDebug>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    enemies: Query<(Entity, &Transform), With<Enemy>>,
+) {
+    if input_map.just_pressed(Action::DebugNav, &keys) {
+        nav_debug.enabled = !nav_debug.enabled;
+        nav_debug.selected = None;
+        info!("Nav debug: {}", nav_debug.enabled);
+    }
+
+    if !nav_debug.enabled || !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single())
+    else {
+        return;
+    };
+
+    let Some(cursor) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+    else {
+        return;
+    };
+
+    nav_debug.selected = enemies
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.truncate().distance(cursor)))
+        .filter(|(_, distance)| *distance < 64.)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity);
+}
+
+fn draw_nav_debug(
+    mut gizmos: Gizmos,
+    nav_debug: Res<NavDebug>,
+    grid: Res<NavGrid>,
+    paths: Query<(Entity, &Transform, &NavPath, &Perception)>,
+) {
+    if !nav_debug.enabled {
+        return;
+    }
+
+    let cell = Vec2::splat(30.);
+
+    for blocked in grid.blocked() {
+        gizmos.rect_2d(NavGrid::center_of(*blocked), 0., cell, Color::RED);
+    }
+
+    for water in grid.water() {
+        gizmos.rect_2d(NavGrid::center_of(*water), 0., cell, Color::BLUE);
+    }
+
+    for occupied in grid.occupied() {
+        gizmos.rect_2d(NavGrid::center_of(*occupied), 0., cell * 0.8, Color::PURPLE);
+    }
+
+    for (entity, transform, path, perception) in paths.iter() {
+        if nav_debug
+            .selected
+            .is_some_and(|selected| selected != entity)
+        {
+            continue;
+        }
+
+        draw_perception(&mut gizmos, transform.translation.truncate(), perception);
+
+        for expanded in path.expanded.iter() {
+            gizmos.rect_2d(NavGrid::center_of(*expanded), 0., cell * 0.5, Color::YELLOW);
+        }
+
+        if path.waypoints.is_empty() {
+            continue;
+        }
+
+        let start = transform.translation.truncate();
+        gizmos.linestrip_2d(
+            std::iter::once(start).chain(path.waypoints.iter().copied()),
+            Color::GREEN,
+        );
+    }
+}
+
+// Vision cone, hearing radius and the remembered player position, brighter while the player is sensed
+fn draw_perception(gizmos: &mut Gizmos, position: Vec2, perception: &Perception) {
+    let color = if perception.sensing {
+        Color::ORANGE_RED
+    } else {
+        Color::CYAN
+    };
+
+    let cone = (0..=8).map(|step| {
+        let angle = perception.vision_angle * (step as f32 / 4. - 1.);
+        position + Vec2::from_angle(angle).rotate(perception.facing) * perception.vision_range
+    });
+
+    gizmos.linestrip_2d(
+        std::iter::once(position)
+            .chain(cone)
+            .chain(std::iter::once(position)),
+        color,
+    );
+    gizmos.circle_2d(position, perception.hearing_radius, color.with_a(0.4));
+
+    if let Some(last_seen) = perception.last_seen {
+        gizmos.rect_2d(last_seen, 0., Vec2::splat(8.), Color::FUCHSIA);
+    }
+}
+
+#[derive(Component)]
+pub struct BehaviorLabel;
+
+// Shows each enemy's active behavior tree branch above it while the nav overlay is on
+fn label_behaviors(
+    mut commands: Commands,
+    nav_debug: Res<NavDebug>,
+    font: Res<FontResource>,
+    enemies: Query<(Entity, &Behavior, Option<&Children>)>,
+    mut labels: Query<(Entity, &mut Text), With<BehaviorLabel>>,
+) {
+    if !nav_debug.enabled {
+        for (entity, _) in labels.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    for (entity, behavior, children) in enemies.iter() {
+        let label = children.and_then(|children| {
+            children
+                .iter()
+                .find(|child| labels.contains(**child))
+                .copied()
+        });
+
+        if let Some(Ok((_, mut text))) = label.map(|label| labels.get_mut(label)) {
+            text.sections[0].value = behavior.active.clone();
+            continue;
+        }
+
+        let label = Text2dBundle {
+            text: Text::from_section(
+                behavior.active.clone(),
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: 24.,
+                    color: Color::WHITE,
+                },
+            )
+            .with_no_wrap(),
+            transform: Transform::from_xyz(0., 16., 0.1).with_scale(Vec3::splat(0.4)),
+            ..default()
+        };
+
+        let label = commands.spawn(label).insert(BehaviorLabel).id();
+        commands.entity(entity).add_child(label);
+    }
+}
+
+// Wears down equipped gear to exercise breakage warnings and repairs
+fn debug_wear_equipment(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut wear: EventWriter<WearEquipment>,
+) {
+    if input_map.just_pressed(Action::DebugWear, &input) {
+        for slot in [EquipSlot::MainHand, EquipSlot::Body] {
+            wear.send(WearEquipment { slot, amount: 25 });
+        }
+    }
+}
+
+// Drops a test stack at the player's feet
+fn debug_drop_item(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    player_query: Query<&Transform, With<Player>>,
+    mut drops: EventWriter<DropItem>,
+) {
+    if input_map.just_pressed(Action::DebugDropItem, &input) {
+        if let Ok(transform) = player_query.get_single() {
+            drops.send(DropItem {
+                stack: Stack::new(0, 1),
+                position: transform.translation.truncate(),
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn update_debug_info(
     mut debug_query: Query<(Entity, &mut Text, &DebugInfo)>,
     player_query: Query<&Transform, With<Player>>,
     chunk_query: Query<(Entity, &Chunk)>,
     entities_query: Query<Entity>,
     time: Res<Time>,
+    cache: Res<ChunkCache>,
+    replay: Res<TraceReplay>,
+    seed: Res<WorldSeed>,
+    atlases: Res<Assets<TextureAtlas>>,
+    mut saved: EventReader<ChunksSaved>,
+    mut saved_total: Local<usize>,
 ) {
+    // Counted even while the overlay is hidden
+    *saved_total += saved.read().map(|event| event.count).sum::<usize>();
+
     if let Ok((_, mut text, _)) = debug_query.get_single_mut() {
-        let player_coords = player_query.get_single().unwrap().translation;
+        let Ok(player_transform) = player_query.get_single() else {
+            return;
+        };
+
+        let player_coords = player_transform.translation;
 
         text.sections[0].value = format!("FPS: {:.2}", 1.0 / time.delta_seconds());
 
@@ -86,5 +411,124 @@ fn update_debug_info(
 
         let n_chunks = chunk_query.iter().collect::<Vec<_>>().len();
         text.sections[3].value = format!("\nChunks Rendered: {}", n_chunks);
+
+        text.sections[4].value = format!(
+            "\nChunk Cache: {}/{} (Hits: {}, Misses: {}, Evictions: {}, Saved: {})",
+            cache.len(),
+            cache.budget,
+            cache.hits,
+            cache.misses,
+            cache.evictions,
+            *saved_total
+        );
+
+        text.sections[5].value = format!("\nWorld Seed: {}", seed.0);
+
+        // Stays flat while chunks stream in now that they share one atlas
+        text.sections[6].value = format!("\nTexture Atlases: {}", atlases.len());
+
+        text.sections[7].value = match &replay.trace {
+            Some(trace) if replay.step < trace.order.len() => {
+                let (x, y, tile) = trace.order[replay.step];
+                format!(
+                    "\nWFC Trace ({},{}): Step {}/{} ({},{}) -> {}",
+                    trace.chunk.0,
+                    trace.chunk.1,
+                    replay.step + 1,
+                    trace.order.len(),
+                    x,
+                    y,
+                    tile
+                )
+            }
+            Some(trace) => {
+                let remaining: Vec<String> = trace
+                    .contradictions
+                    .iter()
+                    .map(|(x, y)| format!("({},{}) {:?}", x, y, trace.constraint_map[*x][*y]))
+                    .collect();
+
+                format!(
+                    "\nWFC Trace ({},{}): Contradictions {}",
+                    trace.chunk.0,
+                    trace.chunk.1,
+                    remaining.join(", ")
+                )
+            }
+            None => "".into(),
+        };
+    }
+}
+
+// Interior bounds of every chunk, stitched seams run in the gaps between them
+fn draw_chunk_borders(
+    mut gizmos: Gizmos,
+    debug_query: Query<(), With<DebugInfo>>,
+    chunks: Query<&Transform, With<Chunk>>,
+) {
+    if debug_query.is_empty() {
+        return;
     }
+
+    for transform in chunks.iter() {
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.,
+            Vec2::splat(CHUNK_SIZE as f32),
+            Color::YELLOW,
+        );
+    }
+}
+
+fn inspect_hovered_tile(
+    mut debug_query: Query<&mut Text, With<DebugInfo>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    tiles: Query<(&Tile, &GlobalTransform, &Parent)>,
+    chunks: Query<(&Transform, &TileGrid), With<Chunk>>,
+) {
+    let Ok(mut text) = debug_query.get_single_mut() else {
+        return;
+    };
+
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single())
+    else {
+        return;
+    };
+
+    let cursor = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor));
+
+    let hovered = cursor.and_then(|cursor| {
+        tiles.iter().find(|(_, transform, _)| {
+            let offset = transform.translation().truncate() - cursor;
+            offset.x.abs() <= 16. && offset.y.abs() <= 16.
+        })
+    });
+
+    text.sections[8].value = match hovered {
+        Some((tile, transform, parent)) => {
+            let position = transform.translation().truncate();
+
+            let Ok((chunk, grid)) = chunks.get(parent.get()) else {
+                return;
+            };
+
+            // Chunk coordinates are the bottom left corner of the chunk's interior
+            let corner = chunk.translation.truncate() - Vec2::splat(CHUNK_SIZE as f32 / 2.);
+
+            format!(
+                "\nTile [{},{}]: Texture {}, Chunk ({},{}) {:?}, {:?}",
+                position.x,
+                position.y,
+                tile.texture_id(),
+                corner.x,
+                corner.y,
+                grid.origin,
+                tile.source()
+            )
+        }
+        None => "".into(),
+    };
 }
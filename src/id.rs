@@ -0,0 +1,140 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs,
+    hash::{Hash, Hasher},
+};
+
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{menu::GameState, world::TILE_SIZE};
+
+const IDS_SAVE_PATH: &str = "saves/ids.json";
+
+// Set on ids derived from the world, allocated ids count up from zero and never reach it
+const GENERATED_BIT: u64 = 1 << 63;
+
+pub struct IdPlugin;
+
+impl Plugin for IdPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EntityIds>()
+            .init_resource::<StableIds>()
+            .add_systems(OnExit(GameState::MainMenu), load_entity_ids)
+            .add_systems(PostUpdate, index_stable_ids)
+            .add_systems(
+                Update,
+                save_entity_ids.run_if(not(in_state(GameState::MainMenu))),
+            );
+    }
+}
+
+// Identifies an entity across sessions and, once there are peers, across machines, where Entity ids
+// are only good for the running app
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct StableId(pub u64);
+
+impl StableId {
+    // Entities the world spawns again every time their chunk loads get the same id each time, from
+    // what they are and the tile they stand on
+    pub fn generated(kind: &str, position: Vec2) -> StableId {
+        let tile = (position / TILE_SIZE as f32).floor().as_ivec2();
+
+        let mut hasher = DefaultHasher::new();
+        (kind, tile.x, tile.y).hash(&mut hasher);
+
+        StableId(hasher.finish() | GENERATED_BIT)
+    }
+
+    pub fn is_generated(&self) -> bool {
+        self.0 & GENERATED_BIT != 0
+    }
+}
+
+// Hands out ids for entities the player creates, counting up per world so none is ever reused
+#[derive(Resource, Default, Deserialize, Serialize)]
+pub struct EntityIds {
+    next: u64,
+}
+
+impl EntityIds {
+    pub fn load() -> EntityIds {
+        fs::read(IDS_SAVE_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let written = fs::create_dir_all("saves").and_then(|_| {
+            fs::write(
+                IDS_SAVE_PATH,
+                serde_json::to_vec(self).map_err(std::io::Error::from)?,
+            )
+        });
+
+        if let Err(err) = written {
+            warn!("Failed to save entity ids! Err {err}");
+        }
+    }
+
+    pub fn allocate(&mut self) -> StableId {
+        let id = StableId(self.next);
+        self.next += 1;
+        id
+    }
+}
+
+fn load_entity_ids(mut commands: Commands) {
+    commands.insert_resource(EntityIds::load());
+}
+
+// Saved as soon as an id is handed out, a crash must not let the next session hand it out again
+fn save_entity_ids(ids: Res<EntityIds>) {
+    if ids.is_changed() {
+        ids.save();
+    }
+}
+
+// Live entity of every stable id, kept in step as entities carrying one spawn and despawn
+#[derive(Resource, Default)]
+pub struct StableIds {
+    entities: HashMap<StableId, Entity>,
+    ids: HashMap<Entity, StableId>,
+}
+
+impl StableIds {
+    pub fn get(&self, id: StableId) -> Option<Entity> {
+        self.entities.get(&id).copied()
+    }
+
+    pub fn id_of(&self, entity: Entity) -> Option<StableId> {
+        self.ids.get(&entity).copied()
+    }
+}
+
+pub fn index_stable_ids(
+    mut index: ResMut<StableIds>,
+    mut removed: RemovedComponents<StableId>,
+    added: Query<(Entity, &StableId), Added<StableId>>,
+) {
+    for entity in removed.read() {
+        if let Some(id) = index.ids.remove(&entity) {
+            // Only forgotten if the id hasn't moved on to a newer entity already
+            if index.entities.get(&id) == Some(&entity) {
+                index.entities.remove(&id);
+            }
+        }
+    }
+
+    for (entity, id) in added.iter() {
+        if let Some(previous) = index.entities.insert(*id, entity) {
+            if previous != entity {
+                debug!("Stable id {} moved from {previous:?} to {entity:?}", id.0);
+            }
+        }
+
+        index.ids.insert(entity, *id);
+    }
+}
@@ -0,0 +1,273 @@
+use std::{collections::HashMap, fs};
+
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_PATH: &str = "input_settings.json";
+
+const KEYBINDINGS_PATH: &str = "keybindings.json";
+
+// Everything the keyboard can trigger, text entry keys in the console and search fields aside
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Attack,
+    Interact,
+    Emote,
+    Ping,
+    // Closes the open panel, or pauses when none is open
+    Back,
+    Inventory,
+    Map,
+    Crafting,
+    Console,
+    Settings,
+    Journal,
+    Hotbar1,
+    Hotbar2,
+    Hotbar3,
+    Hotbar4,
+    Hotbar5,
+    Hotbar6,
+    Hotbar7,
+    Hotbar8,
+    Hotbar9,
+    DebugNav,
+    DebugHeatmap,
+    DebugInfo,
+    DebugTrace,
+    DebugLoadTrace,
+    DebugStepTrace,
+    DebugTeleport,
+    DebugDropItem,
+    DebugWear,
+}
+
+// Keys bound to each action, any of them triggers it
+#[derive(Resource, Clone, Debug, Deserialize, Serialize)]
+pub struct InputMap {
+    bindings: HashMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let bindings = [
+            (Action::MoveUp, vec![KeyCode::W, KeyCode::Up]),
+            (Action::MoveDown, vec![KeyCode::S, KeyCode::Down]),
+            (Action::MoveLeft, vec![KeyCode::A, KeyCode::Left]),
+            (Action::MoveRight, vec![KeyCode::D, KeyCode::Right]),
+            (Action::Attack, vec![KeyCode::Space]),
+            (Action::Interact, vec![KeyCode::F]),
+            (Action::Emote, vec![KeyCode::T]),
+            (Action::Ping, vec![KeyCode::G]),
+            (Action::Back, vec![KeyCode::Escape]),
+            (Action::Inventory, vec![KeyCode::E]),
+            (Action::Map, vec![KeyCode::M]),
+            (Action::Crafting, vec![KeyCode::C]),
+            (Action::Console, vec![KeyCode::Grave]),
+            (Action::Settings, vec![KeyCode::F10]),
+            (Action::Journal, vec![KeyCode::J]),
+            (Action::Hotbar1, vec![KeyCode::Key1]),
+            (Action::Hotbar2, vec![KeyCode::Key2]),
+            (Action::Hotbar3, vec![KeyCode::Key3]),
+            (Action::Hotbar4, vec![KeyCode::Key4]),
+            (Action::Hotbar5, vec![KeyCode::Key5]),
+            (Action::Hotbar6, vec![KeyCode::Key6]),
+            (Action::Hotbar7, vec![KeyCode::Key7]),
+            (Action::Hotbar8, vec![KeyCode::Key8]),
+            (Action::Hotbar9, vec![KeyCode::Key9]),
+            (Action::DebugNav, vec![KeyCode::F1]),
+            (Action::DebugHeatmap, vec![KeyCode::F2]),
+            (Action::DebugInfo, vec![KeyCode::F3]),
+            (Action::DebugTrace, vec![KeyCode::F4]),
+            (Action::DebugLoadTrace, vec![KeyCode::F5]),
+            (Action::DebugStepTrace, vec![KeyCode::F6]),
+            (Action::DebugTeleport, vec![KeyCode::F7]),
+            (Action::DebugDropItem, vec![KeyCode::F8]),
+            (Action::DebugWear, vec![KeyCode::F9]),
+        ];
+
+        InputMap {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl InputMap {
+    // Saved bindings override the defaults, actions missing from the file keep their default keys
+    pub fn load() -> InputMap {
+        let mut map = InputMap::default();
+
+        let saved = fs::read(KEYBINDINGS_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<InputMap>(&bytes).ok());
+
+        if let Some(saved) = saved {
+            map.bindings.extend(saved.bindings);
+        } else {
+            // Written out on first run so there is a file to edit
+            map.save();
+        }
+
+        map
+    }
+
+    pub fn save(&self) {
+        match serde_json::to_vec_pretty(self) {
+            Ok(bytes) => {
+                if let Err(err) = fs::write(KEYBINDINGS_PATH, bytes) {
+                    warn!("Failed to save key bindings! Err {err}");
+                }
+            }
+            Err(err) => warn!("Failed to serialize key bindings! Err {err}"),
+        }
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn pressed(&self, action: Action, input: &Input<KeyCode>) -> bool {
+        input.any_pressed(self.keys(action).iter().copied())
+    }
+
+    pub fn just_pressed(&self, action: Action, input: &Input<KeyCode>) -> bool {
+        input.any_just_pressed(self.keys(action).iter().copied())
+    }
+
+    pub fn just_released(&self, action: Action, input: &Input<KeyCode>) -> bool {
+        self.keys(action)
+            .iter()
+            .any(|key| input.just_released(*key))
+    }
+}
+
+// Face buttons are named by position so the mapping reads the same on every controller layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl PadButton {
+    const ALL: [PadButton; 8] = [
+        PadButton::South,
+        PadButton::East,
+        PadButton::North,
+        PadButton::West,
+        PadButton::LeftBumper,
+        PadButton::RightBumper,
+        PadButton::LeftTrigger,
+        PadButton::RightTrigger,
+    ];
+
+    pub fn next(&self) -> PadButton {
+        let current = PadButton::ALL
+            .iter()
+            .position(|button| button == self)
+            .unwrap_or(0);
+
+        PadButton::ALL[(current + 1) % PadButton::ALL.len()]
+    }
+
+    fn button_type(&self) -> GamepadButtonType {
+        match self {
+            PadButton::South => GamepadButtonType::South,
+            PadButton::East => GamepadButtonType::East,
+            PadButton::North => GamepadButtonType::North,
+            PadButton::West => GamepadButtonType::West,
+            PadButton::LeftBumper => GamepadButtonType::LeftTrigger,
+            PadButton::RightBumper => GamepadButtonType::RightTrigger,
+            PadButton::LeftTrigger => GamepadButtonType::LeftTrigger2,
+            PadButton::RightTrigger => GamepadButtonType::RightTrigger2,
+        }
+    }
+}
+
+// Gamepad bindings, the keyboard and mouse bindings are fixed
+#[derive(Resource, Clone, Debug, Deserialize, Serialize)]
+pub struct InputSettings {
+    // Stick deflection ignored around the center, worn sticks rarely rest at zero
+    pub deadzone: f32,
+    pub attack: PadButton,
+    pub inventory: PadButton,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        InputSettings {
+            deadzone: 0.2,
+            attack: PadButton::West,
+            inventory: PadButton::North,
+        }
+    }
+}
+
+impl InputSettings {
+    // Falls back to defaults when no settings have been saved yet
+    pub fn load() -> InputSettings {
+        fs::read(SETTINGS_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<InputSettings>(&bytes).ok())
+            .map(|mut settings| {
+                settings.deadzone = settings.deadzone.clamp(0., 0.9);
+                settings
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        match serde_json::to_vec_pretty(self) {
+            Ok(bytes) => {
+                if let Err(err) = fs::write(SETTINGS_PATH, bytes) {
+                    warn!("Failed to save input settings! Err {err}");
+                }
+            }
+            Err(err) => warn!("Failed to serialize input settings! Err {err}"),
+        }
+    }
+
+    // Whether the button was pressed this frame on any connected gamepad
+    pub fn just_pressed(
+        &self,
+        button: PadButton,
+        gamepads: &Gamepads,
+        buttons: &Input<GamepadButton>,
+    ) -> bool {
+        gamepads
+            .iter()
+            .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button.button_type())))
+    }
+
+    // Left stick of the first gamepad pushed past the deadzone, rescaled so its length runs from 0 to 1
+    pub fn left_stick(&self, gamepads: &Gamepads, axes: &Axis<GamepadAxis>) -> Vec2 {
+        for gamepad in gamepads.iter() {
+            let stick = Vec2::new(
+                axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                    .unwrap_or(0.),
+                axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                    .unwrap_or(0.),
+            );
+
+            let length = stick.length();
+            if length <= self.deadzone {
+                continue;
+            }
+
+            let magnitude = ((length - self.deadzone) / (1. - self.deadzone)).min(1.);
+            return stick / length * magnitude;
+        }
+
+        Vec2::ZERO
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::{
+    combat::{in_reach, Swing},
+    components::{Direction, Stack},
+    id::{EntityIds, StableId},
+    player::Player,
+    ui::console::{ConsoleCommand, ConsoleOutput},
+    world::{Claims, NavGrid, NavOccupant, WorldTick, LOCAL_PLAYER},
+};
+
+use super::{
+    container::ItemContainer,
+    definition::ItemRegistry,
+    dropped::DropItem,
+    stash::{Stash, StashChest},
+};
+
+pub const CONVEYOR: u32 = 18;
+pub const CRATE: u32 = 19;
+
+const CRATE_SLOTS: usize = 8;
+
+// Ticks a frame may catch up on after a stall, the rest are dropped
+const MAX_TICKS_PER_FRAME: u64 = 4;
+
+// Belt carrying at most one stack a tile forward every world tick
+#[derive(Component)]
+pub struct Conveyor {
+    facing: IVec2,
+    cargo: Option<Stack>,
+}
+
+#[derive(Component)]
+pub struct Crate;
+
+// Shows what a conveyor is carrying
+#[derive(Component)]
+pub struct ConveyorCargo;
+
+fn spawn_conveyor(commands: &mut Commands, cell: IVec2, facing: IVec2) {
+    let sprite = SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgb(0.3, 0.3, 0.35),
+            custom_size: Some(Vec2::splat(28.)),
+            ..default()
+        },
+        transform: Transform::from_translation(NavGrid::center_of(cell).extend(0.1)),
+        ..default()
+    };
+
+    commands
+        .spawn(sprite)
+        .insert(Conveyor {
+            facing,
+            cargo: None,
+        })
+        .with_children(|conveyor| {
+            // Notch on the side items leave from
+            conveyor.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgb(0.55, 0.55, 0.6),
+                    custom_size: Some(Vec2::splat(6.)),
+                    ..default()
+                },
+                transform: Transform::from_translation((facing.as_vec2() * 10.).extend(0.01)),
+                ..default()
+            });
+
+            conveyor
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::splat(12.)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0., 0., 0.02),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                })
+                .insert(ConveyorCargo);
+        });
+}
+
+fn spawn_crate(commands: &mut Commands, cell: IVec2, id: StableId) {
+    let sprite = SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgb(0.45, 0.3, 0.15),
+            custom_size: Some(Vec2::splat(22.)),
+            ..default()
+        },
+        transform: Transform::from_translation(NavGrid::center_of(cell).extend(0.8)),
+        ..default()
+    };
+
+    commands
+        .spawn(sprite)
+        .insert(Crate)
+        .insert(id)
+        .insert(ItemContainer::with_capacity(CRATE_SLOTS))
+        .insert(NavOccupant::Static);
+}
+
+// Console command placing a conveyor or crate from the inventory on the tile the player faces
+#[allow(clippy::too_many_arguments)]
+pub fn handle_place_commands(
+    mut commands: Commands,
+    mut console: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    registry: Res<ItemRegistry>,
+    grid: Res<NavGrid>,
+    claims: Res<Claims>,
+    mut ids: ResMut<EntityIds>,
+    mut player: Query<(&Transform, &Direction, &mut ItemContainer), With<Player>>,
+    conveyors: Query<&Transform, With<Conveyor>>,
+    crates: Query<&Transform, With<Crate>>,
+) {
+    for command in console.read() {
+        if command.name != "place" {
+            continue;
+        }
+
+        let item = match command.args.first().map(String::as_str) {
+            Some("conveyor") => CONVEYOR,
+            Some("crate") => CRATE,
+            _ => {
+                output.send(ConsoleOutput("Usage: place <conveyor|crate>".to_string()));
+                continue;
+            }
+        };
+
+        let Ok((transform, direction, mut inventory)) = player.get_single_mut() else {
+            continue;
+        };
+
+        let facing = direction.vector().as_ivec2();
+        let cell = NavGrid::cell_of(transform.translation.truncate()) + facing;
+
+        let taken = conveyors
+            .iter()
+            .chain(crates.iter())
+            .any(|placed| NavGrid::cell_of(placed.translation.truncate()) == cell);
+
+        if !claims.can_edit(NavGrid::center_of(cell), LOCAL_PLAYER) {
+            output.send(ConsoleOutput(
+                "This ground is claimed by someone else".to_string(),
+            ));
+            continue;
+        }
+
+        if taken || !grid.covers(NavGrid::center_of(cell)) || !grid.is_walkable(cell) {
+            output.send(ConsoleOutput("Nothing can be placed there".to_string()));
+            continue;
+        }
+
+        if !inventory.remove(item, 1) {
+            output.send(ConsoleOutput(format!(
+                "Missing 1 {}",
+                registry.get(item).name
+            )));
+            continue;
+        }
+
+        match item {
+            CONVEYOR => spawn_conveyor(&mut commands, cell, facing),
+            _ => spawn_crate(&mut commands, cell, ids.allocate()),
+        }
+
+        output.send(ConsoleOutput(format!("Placed {}", registry.get(item).name)));
+    }
+}
+
+// Swinging at a placed piece picks it back up, dropping whatever it held
+pub fn pick_up_placed(
+    mut commands: Commands,
+    mut swings: EventReader<Swing>,
+    claims: Res<Claims>,
+    conveyors: Query<(Entity, &Transform, &Conveyor)>,
+    crates: Query<(Entity, &Transform, &ItemContainer), With<Crate>>,
+    mut drops: EventWriter<DropItem>,
+) {
+    for swing in swings.read() {
+        for (entity, transform, conveyor) in conveyors.iter() {
+            let position = transform.translation.truncate();
+
+            if !in_reach(swing.position, swing.facing, position)
+                || !claims.can_edit(position, LOCAL_PLAYER)
+            {
+                continue;
+            }
+
+            for stack in [Some(Stack::new(CONVEYOR, 1)), conveyor.cargo]
+                .into_iter()
+                .flatten()
+            {
+                drops.send(DropItem { stack, position });
+            }
+
+            commands.entity(entity).despawn_recursive();
+        }
+
+        for (entity, transform, container) in crates.iter() {
+            let position = transform.translation.truncate();
+
+            if !in_reach(swing.position, swing.facing, position)
+                || !claims.can_edit(position, LOCAL_PLAYER)
+            {
+                continue;
+            }
+
+            let contents = container.slots.iter().flatten().copied();
+            for stack in std::iter::once(Stack::new(CRATE, 1)).chain(contents) {
+                drops.send(DropItem { stack, position });
+            }
+
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// Takes a single item, unique items always move whole
+fn take_one(container: &mut ItemContainer) -> Option<Stack> {
+    let (slot, stack) = container
+        .slots
+        .iter()
+        .enumerate()
+        .find_map(|(slot, stack)| stack.map(|stack| (slot, stack)))?;
+
+    if stack.instance.is_some() {
+        return container.take(slot);
+    }
+
+    container.remove(stack.id, 1);
+    Some(Stack::new(stack.id, 1))
+}
+
+// Belts in unloaded chunks stand still until the player comes back
+#[allow(clippy::too_many_arguments)]
+pub fn run_conveyors(
+    world_tick: Res<WorldTick>,
+    mut last_tick: Local<Option<u64>>,
+    grid: Res<NavGrid>,
+    registry: Res<ItemRegistry>,
+    mut stash: ResMut<Stash>,
+    mut conveyors: Query<(&Transform, &mut Conveyor)>,
+    mut crates: Query<(&Transform, &mut ItemContainer), With<Crate>>,
+    chests: Query<&Transform, With<StashChest>>,
+    mut drops: EventWriter<DropItem>,
+) {
+    let previous = last_tick
+        .replace(world_tick.tick)
+        .unwrap_or(world_tick.tick);
+    let ticks = (world_tick.tick - previous).min(MAX_TICKS_PER_FRAME);
+
+    if ticks == 0 {
+        return;
+    }
+
+    let crate_cells: HashSet<IVec2> = crates
+        .iter()
+        .map(|(transform, _)| NavGrid::cell_of(transform.translation.truncate()))
+        .collect();
+
+    let chest_cells: HashSet<IVec2> = chests
+        .iter()
+        .map(|transform| NavGrid::cell_of(transform.translation.truncate()))
+        .collect();
+
+    let mut stash_changed = false;
+
+    for _ in 0..ticks {
+        let mut belts: HashMap<IVec2, (IVec2, Option<Stack>)> = conveyors
+            .iter()
+            .map(|(transform, conveyor)| {
+                (
+                    NavGrid::cell_of(transform.translation.truncate()),
+                    (conveyor.facing, conveyor.cargo),
+                )
+            })
+            .filter(|(cell, _)| grid.covers(NavGrid::center_of(*cell)))
+            .collect();
+
+        // Cells whose cargo already moved or arrived this tick, nothing moves twice
+        let mut settled: HashSet<IVec2> = HashSet::new();
+
+        // Repeated until stable, so a packed line advances as one once its head clears
+        loop {
+            let mut progressed = false;
+            let cells: Vec<IVec2> = belts.keys().copied().collect();
+
+            for cell in cells {
+                let (facing, Some(cargo)) = belts[&cell] else {
+                    continue;
+                };
+
+                if settled.contains(&cell) {
+                    continue;
+                }
+
+                let target = cell + facing;
+
+                let delivered = if let Some((_, next_cargo)) = belts.get(&target) {
+                    if next_cargo.is_some() || settled.contains(&target) {
+                        continue;
+                    }
+                    belts.get_mut(&target).unwrap().1 = Some(cargo);
+                    settled.insert(target);
+                    None
+                } else if crate_cells.contains(&target) {
+                    let leftover = crates
+                        .iter_mut()
+                        .find(|(transform, _)| {
+                            NavGrid::cell_of(transform.translation.truncate()) == target
+                        })
+                        .and_then(|(_, mut container)| container.insert(cargo, &registry));
+
+                    if leftover == Some(cargo) {
+                        continue;
+                    }
+                    leftover
+                } else if chest_cells.contains(&target) {
+                    let leftover = stash.contents.insert(cargo, &registry);
+
+                    if leftover == Some(cargo) {
+                        continue;
+                    }
+                    stash_changed = true;
+                    leftover
+                } else if grid.covers(NavGrid::center_of(target)) && grid.is_walkable(target) {
+                    drops.send(DropItem {
+                        stack: cargo,
+                        position: NavGrid::center_of(target),
+                    });
+                    None
+                } else {
+                    continue;
+                };
+
+                belts.get_mut(&cell).unwrap().1 = delivered;
+                settled.insert(cell);
+                progressed = true;
+            }
+
+            if !progressed {
+                break;
+            }
+        }
+
+        // Empty belts pull from the crate or stash chest behind them
+        for (cell, (facing, cargo)) in belts.iter_mut() {
+            if cargo.is_some() || settled.contains(cell) {
+                continue;
+            }
+
+            let source = *cell - *facing;
+
+            if crate_cells.contains(&source) {
+                *cargo = crates
+                    .iter_mut()
+                    .find(|(transform, _)| {
+                        NavGrid::cell_of(transform.translation.truncate()) == source
+                    })
+                    .and_then(|(_, mut container)| take_one(&mut container));
+            } else if chest_cells.contains(&source) {
+                *cargo = take_one(&mut stash.contents);
+                stash_changed |= cargo.is_some();
+            }
+        }
+
+        for (transform, mut conveyor) in conveyors.iter_mut() {
+            let cell = NavGrid::cell_of(transform.translation.truncate());
+
+            if let Some((_, cargo)) = belts.get(&cell) {
+                if conveyor.cargo != *cargo {
+                    conveyor.cargo = *cargo;
+                }
+            }
+        }
+    }
+
+    if stash_changed {
+        stash.save();
+    }
+}
+
+pub fn show_conveyor_cargo(
+    registry: Res<ItemRegistry>,
+    conveyors: Query<(&Conveyor, &Children), Changed<Conveyor>>,
+    mut cargo_sprites: Query<(&mut Sprite, &mut Visibility), With<ConveyorCargo>>,
+) {
+    for (conveyor, children) in conveyors.iter() {
+        for child in children.iter() {
+            let Ok((mut sprite, mut visibility)) = cargo_sprites.get_mut(*child) else {
+                continue;
+            };
+
+            match conveyor.cargo {
+                Some(stack) => {
+                    sprite.color = registry.get(stack.id).rarity.color();
+                    *visibility = Visibility::Inherited;
+                }
+                None => *visibility = Visibility::Hidden,
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::Stack;
+
+use super::definition::ItemRegistry;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Category,
+    Rarity,
+    Name,
+}
+
+// Fixed number of slots holding item stacks, used by the player inventory and storage alike
+#[derive(Component, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ItemContainer {
+    pub slots: Vec<Option<Stack>>,
+}
+
+impl ItemContainer {
+    pub fn with_capacity(capacity: usize) -> ItemContainer {
+        ItemContainer {
+            slots: vec![None; capacity],
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    // Tops up matching stacks before using empty slots, returns whatever did not fit
+    pub fn insert(&mut self, mut stack: Stack, registry: &ItemRegistry) -> Option<Stack> {
+        let max_stack = registry.get(stack.id).max_stack;
+
+        for slot in self.slots.iter_mut().flatten() {
+            if !slot.stacks_with(&stack) || slot.count >= max_stack {
+                continue;
+            }
+
+            let moved = stack.count.min(max_stack - slot.count);
+            slot.count += moved;
+            stack.count -= moved;
+
+            if stack.count == 0 {
+                return None;
+            }
+        }
+
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            let moved = stack.count.min(max_stack);
+            *slot = Some(Stack {
+                count: moved,
+                ..stack
+            });
+            stack.count -= moved;
+
+            if stack.count == 0 {
+                return None;
+            }
+        }
+
+        Some(stack)
+    }
+
+    pub fn take(&mut self, slot: usize) -> Option<Stack> {
+        self.slots.get_mut(slot).and_then(|slot| slot.take())
+    }
+
+    pub fn count(&self, id: u32) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.id == id)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    // Removes the full amount or nothing at all, returns whether it was removed
+    pub fn remove(&mut self, id: u32, mut count: u32) -> bool {
+        if self.count(id) < count {
+            return false;
+        }
+
+        for slot in self.slots.iter_mut() {
+            let Some(stack) = slot else {
+                continue;
+            };
+
+            if stack.id != id {
+                continue;
+            }
+
+            let taken = stack.count.min(count);
+            stack.count -= taken;
+            count -= taken;
+
+            if stack.count == 0 {
+                *slot = None;
+            }
+
+            if count == 0 {
+                break;
+            }
+        }
+
+        true
+    }
+
+    // Slots whose item name contains the query, ignoring case
+    pub fn search(&self, query: &str, registry: &ItemRegistry) -> Vec<usize> {
+        let query = query.to_lowercase();
+
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, slot)| slot.map(|stack| (idx, stack)))
+            .filter(|(_, stack)| registry.get(stack.id).name.to_lowercase().contains(&query))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    // Packs stacks to the front in the given order, ties fall back to name then id
+    pub fn sort(&mut self, key: SortKey, registry: &ItemRegistry) {
+        let mut stacks: Vec<Stack> = self
+            .slots
+            .iter_mut()
+            .filter_map(|slot| slot.take())
+            .collect();
+
+        stacks.sort_by(|a, b| {
+            let (a_def, b_def) = (registry.get(a.id), registry.get(b.id));
+
+            let primary = match key {
+                SortKey::Category => a_def.category.cmp(&b_def.category),
+                // Rarest first
+                SortKey::Rarity => b_def.rarity.cmp(&a_def.rarity),
+                SortKey::Name => std::cmp::Ordering::Equal,
+            };
+
+            primary
+                .then_with(|| a_def.name.cmp(&b_def.name))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        for stack in stacks {
+            // Re-inserting merges partial stacks of the same item
+            self.insert(stack, registry);
+        }
+    }
+
+    // Moves stacks of items the target already holds into it, returns whether anything moved
+    pub fn quick_stack_into(
+        &mut self,
+        target: &mut ItemContainer,
+        registry: &ItemRegistry,
+    ) -> bool {
+        let mut moved = false;
+
+        for slot in self.slots.iter_mut() {
+            let Some(stack) = *slot else {
+                continue;
+            };
+
+            if !target
+                .slots
+                .iter()
+                .flatten()
+                .any(|held| held.stacks_with(&stack))
+            {
+                continue;
+            }
+
+            let leftover = target.insert(stack, registry);
+            moved |= leftover != Some(stack);
+            *slot = leftover;
+        }
+
+        moved
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use rand::Rng;
+
+use crate::{components::Stack, world::TRAP_SPRING};
+
+use super::{
+    equipment::EquipSlot,
+    lock::{BRONZE_KEY, GOLD_KEY, LOCKPICK, SILVER_KEY},
+    traversal::{CLIMBING_GEAR, FLIPPERS},
+    wallet::COIN,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Rarity {
+    // Shared by names, tooltips and drop beams so a tier always reads the same
+    pub fn color(&self) -> Color {
+        match self {
+            Rarity::Common => Color::rgb(0.85, 0.85, 0.85),
+            Rarity::Uncommon => Color::rgb(0.3, 0.85, 0.3),
+            Rarity::Rare => Color::rgb(0.3, 0.5, 1.),
+            Rarity::Epic => Color::rgb(0.7, 0.3, 0.9),
+            Rarity::Legendary => Color::rgb(1., 0.6, 0.1),
+        }
+    }
+
+    // Relative chance of appearing in a loot roll
+    pub fn loot_weight(&self) -> u32 {
+        match self {
+            Rarity::Common => 60,
+            Rarity::Uncommon => 25,
+            Rarity::Rare => 10,
+            Rarity::Epic => 4,
+            Rarity::Legendary => 1,
+        }
+    }
+
+    // Common items lie on the ground without a beam
+    pub fn has_beam(&self) -> bool {
+        *self >= Rarity::Uncommon
+    }
+}
+
+// Broad grouping used when sorting containers, declared in sort order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ItemCategory {
+    Weapon,
+    Tool,
+    Armor,
+    Gear,
+    Consumable,
+    Material,
+    Treasure,
+    Currency,
+}
+
+impl ItemCategory {
+    // Equipment slot items of this category go into, if any
+    pub fn equip_slot(&self) -> Option<EquipSlot> {
+        match self {
+            ItemCategory::Weapon | ItemCategory::Tool => Some(EquipSlot::MainHand),
+            ItemCategory::Armor => Some(EquipSlot::Body),
+            ItemCategory::Gear => Some(EquipSlot::Gear),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ItemStats {
+    pub damage: i32,
+    pub armor: i32,
+    pub speed: i32,
+}
+
+impl ItemStats {
+    // Labelled values in display order
+    pub fn entries(&self) -> [(&'static str, i32); 3] {
+        [
+            ("damage", self.damage),
+            ("armor", self.armor),
+            ("speed", self.speed),
+        ]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ItemDefinition {
+    pub name: String,
+    pub category: ItemCategory,
+    pub rarity: Rarity,
+    pub max_stack: u32,
+    pub stats: ItemStats,
+    // Items without durability never wear out
+    pub max_durability: Option<u32>,
+    // Hardest ore a tool can mine, zero for anything that isn't a pickaxe
+    pub tool_tier: u8,
+}
+
+impl ItemDefinition {
+    // Colored name used wherever an item is displayed, tooltips, hotbar and chat links alike
+    pub fn name_section(&self, style: TextStyle) -> TextSection {
+        TextSection::new(
+            self.name.clone(),
+            TextStyle {
+                color: self.rarity.color(),
+                ..style
+            },
+        )
+    }
+}
+
+#[derive(Resource)]
+pub struct ItemRegistry {
+    definitions: HashMap<u32, ItemDefinition>,
+    unknown: ItemDefinition,
+}
+
+impl Default for ItemRegistry {
+    fn default() -> Self {
+        let mut registry = ItemRegistry {
+            definitions: HashMap::new(),
+            unknown: ItemDefinition {
+                name: "Unknown".to_string(),
+                category: ItemCategory::Material,
+                rarity: Rarity::Common,
+                max_stack: 1,
+                stats: ItemStats::default(),
+                max_durability: None,
+                tool_tier: 0,
+            },
+        };
+
+        registry.register(0, "Stick", ItemCategory::Material, Rarity::Common, 64);
+        registry.register(1, "Slime Gel", ItemCategory::Material, Rarity::Common, 64);
+        registry.register(
+            2,
+            "Iron Shard",
+            ItemCategory::Material,
+            Rarity::Uncommon,
+            32,
+        );
+        registry.register(
+            3,
+            "Wraith Essence",
+            ItemCategory::Consumable,
+            Rarity::Rare,
+            16,
+        );
+        registry.register(4, "Ember Blade", ItemCategory::Weapon, Rarity::Epic, 1);
+        registry.register(
+            5,
+            "Crown of the Traveler",
+            ItemCategory::Treasure,
+            Rarity::Legendary,
+            1,
+        );
+        registry.register(7, "Leather Tunic", ItemCategory::Armor, Rarity::Uncommon, 1);
+        registry.register(COIN, "Coin", ItemCategory::Currency, Rarity::Common, 999);
+        registry.register(8, "Rusty Sword", ItemCategory::Weapon, Rarity::Common, 1);
+        registry.register(9, "Iron Pickaxe", ItemCategory::Tool, Rarity::Common, 1);
+        registry.register(10, "Copper Ore", ItemCategory::Material, Rarity::Common, 64);
+        registry.register(11, "Iron Ore", ItemCategory::Material, Rarity::Uncommon, 64);
+        registry.register(12, "Mythril Ore", ItemCategory::Material, Rarity::Rare, 64);
+        registry.register(
+            13,
+            "Copper Ingot",
+            ItemCategory::Material,
+            Rarity::Common,
+            32,
+        );
+        registry.register(
+            14,
+            "Iron Ingot",
+            ItemCategory::Material,
+            Rarity::Uncommon,
+            32,
+        );
+        registry.register(
+            15,
+            "Mythril Ingot",
+            ItemCategory::Material,
+            Rarity::Rare,
+            32,
+        );
+        registry.register(16, "Copper Pickaxe", ItemCategory::Tool, Rarity::Common, 1);
+        registry.register(17, "Mythril Pickaxe", ItemCategory::Tool, Rarity::Rare, 1);
+        registry.register(18, "Conveyor", ItemCategory::Material, Rarity::Common, 64);
+        registry.register(19, "Crate", ItemCategory::Material, Rarity::Common, 16);
+        registry.register(
+            20,
+            "Claim Stone",
+            ItemCategory::Material,
+            Rarity::Uncommon,
+            4,
+        );
+        registry.register(
+            LOCKPICK,
+            "Lockpick",
+            ItemCategory::Material,
+            Rarity::Common,
+            16,
+        );
+        registry.register(
+            BRONZE_KEY,
+            "Bronze Key",
+            ItemCategory::Material,
+            Rarity::Uncommon,
+            8,
+        );
+        registry.register(
+            SILVER_KEY,
+            "Silver Key",
+            ItemCategory::Material,
+            Rarity::Rare,
+            8,
+        );
+        registry.register(
+            GOLD_KEY,
+            "Gold Key",
+            ItemCategory::Material,
+            Rarity::Epic,
+            8,
+        );
+
+        registry.register(
+            TRAP_SPRING,
+            "Trap Spring",
+            ItemCategory::Material,
+            Rarity::Common,
+            32,
+        );
+
+        registry.register(FLIPPERS, "Flippers", ItemCategory::Gear, Rarity::Rare, 1);
+        registry.register(
+            CLIMBING_GEAR,
+            "Climbing Gear",
+            ItemCategory::Gear,
+            Rarity::Rare,
+            1,
+        );
+
+        registry.set_durability(4, 200);
+        registry.set_durability(7, 120);
+        registry.set_durability(8, 80);
+        registry.set_durability(9, 150);
+        registry.set_durability(16, 100);
+        registry.set_durability(17, 300);
+
+        // Each pickaxe mines the ores up to its tier, copper ore needs no pickaxe at all
+        registry.set_tool_tier(16, 1);
+        registry.set_tool_tier(9, 2);
+        registry.set_tool_tier(17, 3);
+
+        registry.set_stats(
+            4,
+            ItemStats {
+                damage: 14,
+                speed: -2,
+                ..default()
+            },
+        );
+        registry.set_stats(
+            7,
+            ItemStats {
+                armor: 4,
+                ..default()
+            },
+        );
+        registry.set_stats(
+            8,
+            ItemStats {
+                damage: 6,
+                ..default()
+            },
+        );
+
+        registry
+    }
+}
+
+impl ItemRegistry {
+    pub fn register(
+        &mut self,
+        id: u32,
+        name: &str,
+        category: ItemCategory,
+        rarity: Rarity,
+        max_stack: u32,
+    ) {
+        self.definitions.insert(
+            id,
+            ItemDefinition {
+                name: name.to_string(),
+                category,
+                rarity,
+                max_stack,
+                stats: ItemStats::default(),
+                max_durability: None,
+                tool_tier: 0,
+            },
+        );
+    }
+
+    pub fn set_durability(&mut self, id: u32, max_durability: u32) {
+        if let Some(definition) = self.definitions.get_mut(&id) {
+            definition.max_durability = Some(max_durability);
+        }
+    }
+
+    // Remaining and maximum durability of the stack, if its item wears out
+    pub fn durability(&self, stack: &Stack) -> Option<(u32, u32)> {
+        let max = self.get(stack.id).max_durability?;
+        let wear = stack.instance.map(|instance| instance.wear).unwrap_or(0);
+
+        Some((max.saturating_sub(wear), max))
+    }
+
+    pub fn set_tool_tier(&mut self, id: u32, tier: u8) {
+        if let Some(definition) = self.definitions.get_mut(&id) {
+            definition.tool_tier = tier;
+        }
+    }
+
+    pub fn set_stats(&mut self, id: u32, stats: ItemStats) {
+        if let Some(definition) = self.definitions.get_mut(&id) {
+            definition.stats = stats;
+        }
+    }
+
+    pub fn get(&self, id: u32) -> &ItemDefinition {
+        self.definitions.get(&id).unwrap_or(&self.unknown)
+    }
+
+    // Definition stats with the stack's own modifiers applied
+    pub fn stats(&self, stack: &Stack) -> ItemStats {
+        let base = self.get(stack.id).stats;
+
+        match stack.instance {
+            Some(instance) => instance.apply(base),
+            None => base,
+        }
+    }
+
+    // Picks a stack weighted by rarity, rarer items drop in smaller counts
+    pub fn roll_loot(&self, rng: &mut impl Rng) -> Option<Stack> {
+        self.roll_loot_from(rng, Rarity::Common)
+    }
+
+    // Rolls only among items of at least the given rarity
+    pub fn roll_loot_from(&self, rng: &mut impl Rng, min_rarity: Rarity) -> Option<Stack> {
+        let mut ids: Vec<&u32> = self
+            .definitions
+            .iter()
+            .filter(|(_, definition)| definition.rarity >= min_rarity)
+            .map(|(id, _)| id)
+            .collect();
+        ids.sort();
+
+        let total: u32 = ids
+            .iter()
+            .map(|id| self.get(**id).rarity.loot_weight())
+            .sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0..total);
+
+        for id in ids {
+            let definition = self.get(*id);
+            let weight = definition.rarity.loot_weight();
+
+            if roll < weight {
+                let count = rng.gen_range(1..=definition.max_stack.min(4));
+                return Some(Stack::new(*id, count));
+            }
+
+            roll -= weight;
+        }
+
+        None
+    }
+}
@@ -0,0 +1,238 @@
+use bevy::prelude::*;
+
+use super::{container::ItemContainer, definition::ItemRegistry, wallet::COIN};
+
+use crate::{
+    components::Stack,
+    player::Player,
+    world::{ChunkOccupant, OccupantKind},
+};
+
+#[derive(Resource)]
+pub struct DroppedItemSettings {
+    // Seconds a dropped item lies on the ground before despawning
+    pub timeout: f32,
+    // Identical stacks closer than this are merged
+    pub merge_radius: f32,
+    // Seconds before a fresh drop can be picked up, so items thrown away aren't grabbed straight back
+    pub pickup_delay: f32,
+    // Player reach for walking over items
+    pub pickup_radius: f32,
+}
+
+impl Default for DroppedItemSettings {
+    fn default() -> Self {
+        DroppedItemSettings {
+            timeout: 300.,
+            merge_radius: 24.,
+            pickup_delay: 1.5,
+            pickup_radius: 20.,
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct DropItem {
+    pub stack: Stack,
+    pub position: Vec2,
+}
+
+// Sent for everything the player picks up off the ground, leftovers that didn't fit aren't counted
+#[derive(Event)]
+pub struct ItemCollected {
+    pub id: u32,
+    pub count: u32,
+}
+
+// Remaining time is kept in plain seconds so it can be persisted with the world
+#[derive(Component, Clone, Copy, Debug)]
+pub struct DroppedItem {
+    pub remaining: f32,
+    pub pickup_delay: f32,
+}
+
+// Child sprite of a dropped item, animated separately so bobbing doesn't move the item itself
+#[derive(Component)]
+pub struct DroppedItemVisual;
+
+// Column of light above uncommon and better drops, tinted by rarity
+#[derive(Component)]
+pub struct DropBeam;
+
+pub fn spawn_dropped_items(
+    mut commands: Commands,
+    mut drops: EventReader<DropItem>,
+    settings: Res<DroppedItemSettings>,
+    registry: Res<ItemRegistry>,
+) {
+    for drop in drops.read() {
+        let definition = registry.get(drop.stack.id);
+        let rarity = definition.rarity;
+
+        let name = definition.name_section(TextStyle {
+            font_size: 24.,
+            ..default()
+        });
+
+        let label = Text2dBundle {
+            text: Text::from_sections([name]).with_no_wrap(),
+            transform: Transform::from_xyz(0., 14., 0.1).with_scale(Vec3::splat(0.4)),
+            ..default()
+        };
+
+        let visual = SpriteBundle {
+            sprite: Sprite {
+                color: rarity.color(),
+                custom_size: Some(Vec2::new(10., 10.)),
+                ..default()
+            },
+            ..default()
+        };
+
+        let beam = SpriteBundle {
+            sprite: Sprite {
+                color: rarity.color().with_a(0.35),
+                custom_size: Some(Vec2::new(4., 48.)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0., 24., -0.1),
+            ..default()
+        };
+
+        commands
+            .spawn(SpatialBundle::from_transform(Transform::from_translation(
+                drop.position.extend(0.5),
+            )))
+            .insert(drop.stack)
+            .insert(DroppedItem {
+                remaining: settings.timeout,
+                pickup_delay: settings.pickup_delay,
+            })
+            .insert(ChunkOccupant(OccupantKind::Item))
+            .with_children(|parent| {
+                parent.spawn(visual).insert(DroppedItemVisual);
+
+                parent.spawn(label);
+
+                if rarity.has_beam() {
+                    parent.spawn(beam).insert(DropBeam);
+                }
+            });
+    }
+}
+
+pub fn merge_dropped_items(
+    mut commands: Commands,
+    settings: Res<DroppedItemSettings>,
+    mut items: Query<(Entity, &Transform, &mut Stack, &mut DroppedItem)>,
+) {
+    let mut merged = Vec::new();
+    let mut combinations = items.iter_combinations_mut();
+
+    while let Some([(a, a_transform, mut a_stack, mut a_item), (b, b_transform, b_stack, b_item)]) =
+        combinations.fetch_next()
+    {
+        if merged.contains(&a) || merged.contains(&b) || !a_stack.stacks_with(&b_stack) {
+            continue;
+        }
+
+        if a_transform
+            .translation
+            .truncate()
+            .distance(b_transform.translation.truncate())
+            > settings.merge_radius
+        {
+            continue;
+        }
+
+        // Fold into the first stack, keeping the longest remaining lifetime
+        a_stack.count += b_stack.count;
+        a_item.remaining = a_item.remaining.max(b_item.remaining);
+        a_item.pickup_delay = a_item.pickup_delay.min(b_item.pickup_delay);
+
+        merged.push(b);
+        commands.entity(b).despawn_recursive();
+    }
+}
+
+// Walking over a drop moves as much of it as fits into the inventory, the rest stays on the ground
+pub fn pickup_dropped_items(
+    mut commands: Commands,
+    settings: Res<DroppedItemSettings>,
+    registry: Res<ItemRegistry>,
+    mut player: Query<(&Transform, &mut ItemContainer), With<Player>>,
+    mut items: Query<(Entity, &Transform, &mut Stack, &DroppedItem)>,
+    mut collected: EventWriter<ItemCollected>,
+) {
+    let Ok((player_transform, mut inventory)) = player.get_single_mut() else {
+        return;
+    };
+
+    let player_position = player_transform.translation.truncate();
+
+    for (entity, transform, mut stack, item) in items.iter_mut() {
+        // Currency goes to the wallet instead
+        if stack.id == COIN || item.pickup_delay > 0. {
+            continue;
+        }
+
+        if transform.translation.truncate().distance(player_position) > settings.pickup_radius {
+            continue;
+        }
+
+        match inventory.insert(*stack, &registry) {
+            None => {
+                debug!("Picked up {} {}", stack.count, registry.get(stack.id).name);
+                collected.send(ItemCollected {
+                    id: stack.id,
+                    count: stack.count,
+                });
+                commands.entity(entity).despawn_recursive();
+            }
+            Some(leftover) if leftover.count != stack.count => {
+                collected.send(ItemCollected {
+                    id: stack.id,
+                    count: stack.count - leftover.count,
+                });
+                *stack = leftover;
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+pub fn expire_dropped_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut items: Query<(Entity, &mut DroppedItem)>,
+) {
+    for (entity, mut item) in items.iter_mut() {
+        item.remaining -= time.delta_seconds();
+        item.pickup_delay = (item.pickup_delay - time.delta_seconds()).max(0.);
+
+        if item.remaining <= 0. {
+            debug!("Despawning expired dropped item");
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn animate_dropped_items(
+    time: Res<Time>,
+    mut visuals: Query<&mut Transform, With<DroppedItemVisual>>,
+) {
+    let elapsed = time.elapsed_seconds();
+
+    for mut transform in visuals.iter_mut() {
+        transform.translation.y = 3. * (elapsed * 2.5).sin();
+        transform.rotation = Quat::from_rotation_z(elapsed);
+    }
+}
+
+pub fn pulse_drop_beams(time: Res<Time>, mut beams: Query<&mut Sprite, With<DropBeam>>) {
+    let alpha = 0.25 + 0.15 * (time.elapsed_seconds() * 3.).sin();
+
+    for mut sprite in beams.iter_mut() {
+        sprite.color.set_a(alpha);
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+
+use crate::{components::Stack, player::Player, world::WorldTick};
+
+use super::{
+    container::ItemContainer,
+    definition::{ItemCategory, ItemRegistry},
+    equipment::{EquipSlot, Equipment},
+    instance::ItemInstance,
+};
+
+// Warn once durability drops below this fraction
+const WARNING_THRESHOLD: f32 = 0.2;
+
+const WARNING_SECONDS: f32 = 4.;
+
+// Sent whenever equipped gear is used, by attacking, mining or taking hits
+#[derive(Event)]
+pub struct WearEquipment {
+    pub slot: EquipSlot,
+    pub amount: u32,
+}
+
+#[derive(Component)]
+pub struct BreakageWarning {
+    timer: Timer,
+}
+
+// Pressing it repairs the inventory slot at the station
+#[derive(Component)]
+pub struct RepairSlot(pub usize);
+
+// Material and amount needed to restore an item of the category from zero durability
+pub fn repair_recipe(category: ItemCategory) -> Option<(u32, u32)> {
+    match category {
+        ItemCategory::Weapon | ItemCategory::Tool => Some((2, 4)),
+        ItemCategory::Armor => Some((1, 6)),
+        _ => None,
+    }
+}
+
+// Cost scales with the share of durability missing, any damage costs at least one material
+pub fn repair_cost(stack: &Stack, registry: &ItemRegistry) -> Option<(u32, u32)> {
+    let (current, max) = registry.durability(stack)?;
+    if current == max {
+        return None;
+    }
+
+    let (material, full_cost) = repair_recipe(registry.get(stack.id).category)?;
+    let cost = (full_cost * (max - current)).div_ceil(max).max(1);
+
+    Some((material, cost))
+}
+
+pub fn spawn_breakage_warning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_bundle = TextBundle {
+        text: Text::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                font_size: 20.0,
+                color: Color::rgb(0.95, 0.4, 0.3),
+            },
+        ),
+        style: Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(48.),
+            left: Val::Percent(40.),
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    commands.spawn(text_bundle).insert(BreakageWarning {
+        timer: Timer::from_seconds(WARNING_SECONDS, TimerMode::Once),
+    });
+}
+
+pub fn apply_equipment_wear(
+    registry: Res<ItemRegistry>,
+    world_tick: Res<WorldTick>,
+    mut events: EventReader<WearEquipment>,
+    mut equipment: Query<&mut Equipment, With<Player>>,
+    mut warning: Query<(&mut Text, &mut Visibility, &mut BreakageWarning)>,
+) {
+    let Ok(mut equipment) = equipment.get_single_mut() else {
+        return;
+    };
+
+    for event in events.read() {
+        let Some(mut stack) = equipment.get(event.slot) else {
+            continue;
+        };
+
+        let Some((before, max)) = registry.durability(&stack) else {
+            continue;
+        };
+
+        let mut instance = stack
+            .instance
+            .unwrap_or_else(|| ItemInstance::new((stack.id, world_tick.tick)));
+        instance.wear = (instance.wear + event.amount).min(max);
+        stack.instance = Some(instance);
+
+        let name = &registry.get(stack.id).name;
+        let (after, _) = registry.durability(&stack).unwrap_or((0, max));
+
+        let message = if after == 0 {
+            warn!("{} broke", name);
+            equipment.clear(event.slot);
+            Some(format!("{} broke!", name))
+        } else {
+            equipment.equip(event.slot, stack);
+
+            let threshold = (max as f32 * WARNING_THRESHOLD) as u32;
+            (before > threshold && after <= threshold).then(|| {
+                info!("{} is about to break", name);
+                format!("{} is about to break ({}/{})", name, after, max)
+            })
+        };
+
+        if let (Some(message), Ok((mut text, mut visibility, mut warning))) =
+            (message, warning.get_single_mut())
+        {
+            text.sections[0].value = message;
+            *visibility = Visibility::Visible;
+            warning.timer.reset();
+        }
+    }
+}
+
+pub fn fade_breakage_warning(
+    time: Res<Time>,
+    mut warning: Query<(&mut Visibility, &mut BreakageWarning)>,
+) {
+    for (mut visibility, mut warning) in warning.iter_mut() {
+        if warning.timer.tick(time.delta()).just_finished() {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+pub fn handle_repair_slots(
+    registry: Res<ItemRegistry>,
+    slots: Query<(&Interaction, &RepairSlot), Changed<Interaction>>,
+    mut inventory: Query<&mut ItemContainer, With<Player>>,
+) {
+    let Ok(mut inventory) = inventory.get_single_mut() else {
+        return;
+    };
+
+    for (interaction, slot) in slots.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let Some(mut stack) = inventory.slots[slot.0] else {
+            continue;
+        };
+
+        let Some((material, cost)) = repair_cost(&stack, &registry) else {
+            continue;
+        };
+
+        if !inventory.remove(material, cost) {
+            info!("Not enough {} to repair", registry.get(material).name);
+            continue;
+        }
+
+        if let Some(instance) = stack.instance.as_mut() {
+            instance.wear = 0;
+        }
+
+        info!("Repaired {}", registry.get(stack.id).name);
+        inventory.slots[slot.0] = Some(stack);
+    }
+}
+
+// Thin bar under an item row, green when whole fading to red as it wears out
+pub fn spawn_durability_bar(parent: &mut ChildBuilder, current: u32, max: u32) {
+    let fraction = current as f32 / max.max(1) as f32;
+
+    let track = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.),
+            height: Val::Px(3.),
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.6).into(),
+        ..default()
+    };
+
+    let fill = NodeBundle {
+        style: Style {
+            width: Val::Percent(fraction * 100.),
+            height: Val::Percent(100.),
+            ..default()
+        },
+        background_color: Color::rgb(1. - fraction, fraction, 0.2).into(),
+        ..default()
+    };
+
+    parent.spawn(track).with_children(|parent| {
+        parent.spawn(fill);
+    });
+}
@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+
+use crate::components::Stack;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EquipSlot {
+    MainHand,
+    Body,
+    // Travel gear, opens up terrain that otherwise blocks the player
+    Gear,
+}
+
+// Items worn by an entity, one per slot, kept whole so instance data stays with them
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Equipment {
+    pub main_hand: Option<Stack>,
+    pub body: Option<Stack>,
+    pub gear: Option<Stack>,
+}
+
+impl Equipment {
+    pub fn get(&self, slot: EquipSlot) -> Option<Stack> {
+        match slot {
+            EquipSlot::MainHand => self.main_hand,
+            EquipSlot::Body => self.body,
+            EquipSlot::Gear => self.gear,
+        }
+    }
+
+    // Returns whatever was previously equipped in the slot
+    pub fn equip(&mut self, slot: EquipSlot, stack: Stack) -> Option<Stack> {
+        let previous = match slot {
+            EquipSlot::MainHand => &mut self.main_hand,
+            EquipSlot::Body => &mut self.body,
+            EquipSlot::Gear => &mut self.gear,
+        };
+
+        previous.replace(stack)
+    }
+
+    pub fn clear(&mut self, slot: EquipSlot) -> Option<Stack> {
+        match slot {
+            EquipSlot::MainHand => self.main_hand.take(),
+            EquipSlot::Body => self.body.take(),
+            EquipSlot::Gear => self.gear.take(),
+        }
+    }
+}
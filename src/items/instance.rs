@@ -0,0 +1,121 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use serde::{Deserialize, Serialize};
+
+use super::definition::ItemStats;
+
+// Modifier slots on a single item
+pub const MAX_MODIFIERS: usize = 3;
+
+const MAX_MODIFIER_LEVEL: u8 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum ModifierKind {
+    Sharp,
+    Sturdy,
+    Swift,
+}
+
+impl ModifierKind {
+    const ALL: [ModifierKind; 3] = [
+        ModifierKind::Sharp,
+        ModifierKind::Sturdy,
+        ModifierKind::Swift,
+    ];
+
+    fn apply(&self, level: u8, stats: &mut ItemStats) {
+        let level = level as i32;
+
+        match self {
+            ModifierKind::Sharp => stats.damage += 2 * level,
+            ModifierKind::Sturdy => stats.armor += 2 * level,
+            ModifierKind::Swift => stats.speed += level,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Modifier {
+    pub kind: ModifierKind,
+    pub level: u8,
+}
+
+// Data owned by one specific item rather than its definition, such items never stack
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ItemInstance {
+    // Upgrade outcomes are drawn from this seed so retrying can't reroll them
+    pub seed: u64,
+    pub upgrades: u8,
+    pub modifiers: [Option<Modifier>; MAX_MODIFIERS],
+    // Durability lost so far, pristine items have no instance at all
+    #[serde(default)]
+    pub wear: u32,
+}
+
+impl ItemInstance {
+    pub fn new(seed: impl Hash) -> ItemInstance {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+
+        ItemInstance {
+            seed: hasher.finish(),
+            ..Default::default()
+        }
+    }
+
+    pub fn apply(&self, mut stats: ItemStats) -> ItemStats {
+        for modifier in self.modifiers.iter().flatten() {
+            modifier.kind.apply(modifier.level, &mut stats);
+        }
+
+        stats
+    }
+
+    // Each attempt advances the seed, higher levels fail more often, returns whether it succeeded
+    pub fn upgrade(&mut self) -> bool {
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(self.upgrades as u64));
+        self.upgrades = self.upgrades.saturating_add(1);
+
+        let total_levels: u32 = self
+            .modifiers
+            .iter()
+            .flatten()
+            .map(|modifier| modifier.level as u32)
+            .sum();
+
+        let failure_chance = (0.1 + 0.1 * total_levels as f64).min(0.8);
+        if rng.gen_bool(failure_chance) {
+            return false;
+        }
+
+        let kind = ModifierKind::ALL[rng.gen_range(0..ModifierKind::ALL.len())];
+
+        if let Some(existing) = self
+            .modifiers
+            .iter_mut()
+            .flatten()
+            .find(|modifier| modifier.kind == kind)
+        {
+            existing.level = (existing.level + 1).min(MAX_MODIFIER_LEVEL);
+            return true;
+        }
+
+        if let Some(free) = self.modifiers.iter_mut().find(|slot| slot.is_none()) {
+            *free = Some(Modifier { kind, level: 1 });
+            return true;
+        }
+
+        // All slots taken by other kinds, improve one of them instead
+        let existing = &mut self.modifiers[rng.gen_range(0..MAX_MODIFIERS)];
+        if let Some(modifier) = existing {
+            modifier.level = (modifier.level + 1).min(MAX_MODIFIER_LEVEL);
+        }
+
+        true
+    }
+}
@@ -0,0 +1,480 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use rand::Rng;
+
+use crate::{
+    input::{Action, InputMap},
+    mobs::EnemyKind,
+    player::{
+        channel::{ChannelAction, ChannelFinished, StartChannel},
+        Player,
+    },
+    ui::{Panel, PanelRoot, PanelState, TogglePanel},
+    world::NavOccupant,
+};
+
+use super::{
+    container::ItemContainer,
+    definition::{ItemRegistry, Rarity},
+    dropped::DropItem,
+};
+
+pub const LOCKPICK: u32 = 21;
+pub const BRONZE_KEY: u32 = 22;
+pub const SILVER_KEY: u32 = 23;
+pub const GOLD_KEY: u32 = 24;
+
+// Distance from a locked chest at which it can be unlocked or picked
+const CHEST_REACH: f32 = 40.;
+
+// Turning a key is quicker than picking, but still leaves the player open
+const UNLOCK_SECONDS: f32 = 0.6;
+
+const TRACK_WIDTH: f32 = 300.;
+
+// Harder locks are rarer, hold more loot and only roll the better items
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockTier {
+    Simple,
+    Sturdy,
+    Masterwork,
+}
+
+impl LockTier {
+    pub fn roll(rng: &mut impl Rng) -> LockTier {
+        match rng.gen_range(0..10) {
+            0..=5 => LockTier::Simple,
+            6..=8 => LockTier::Sturdy,
+            _ => LockTier::Masterwork,
+        }
+    }
+
+    fn key(&self) -> u32 {
+        match self {
+            LockTier::Simple => BRONZE_KEY,
+            LockTier::Sturdy => SILVER_KEY,
+            LockTier::Masterwork => GOLD_KEY,
+        }
+    }
+
+    // Pins set in a row to pick the lock
+    fn pins(&self) -> usize {
+        match self {
+            LockTier::Simple => 1,
+            LockTier::Sturdy => 2,
+            LockTier::Masterwork => 3,
+        }
+    }
+
+    // Share of the track the marker has to be stopped in
+    fn sweet_spot(&self) -> f32 {
+        match self {
+            LockTier::Simple => 0.25,
+            LockTier::Sturdy => 0.16,
+            LockTier::Masterwork => 0.1,
+        }
+    }
+
+    // Track lengths the marker sweeps per second
+    fn marker_speed(&self) -> f32 {
+        match self {
+            LockTier::Simple => 0.8,
+            LockTier::Sturdy => 1.1,
+            LockTier::Masterwork => 1.4,
+        }
+    }
+
+    fn loot_rolls(&self) -> u32 {
+        match self {
+            LockTier::Simple => 2,
+            LockTier::Sturdy => 3,
+            LockTier::Masterwork => 4,
+        }
+    }
+
+    fn min_rarity(&self) -> Rarity {
+        match self {
+            LockTier::Simple => Rarity::Common,
+            LockTier::Sturdy => Rarity::Uncommon,
+            LockTier::Masterwork => Rarity::Rare,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            LockTier::Simple => Color::rgb(0.6, 0.4, 0.2),
+            LockTier::Sturdy => Color::rgb(0.7, 0.7, 0.75),
+            LockTier::Masterwork => Color::rgb(0.9, 0.75, 0.2),
+        }
+    }
+}
+
+// Key dropped by a slain enemy of the kind and the chance of it dropping
+pub fn key_drop(kind: EnemyKind) -> Option<(u32, f64)> {
+    match kind {
+        EnemyKind::Slime => Some((BRONZE_KEY, 0.1)),
+        EnemyKind::Wraith => Some((SILVER_KEY, 0.08)),
+        EnemyKind::Guard => Some((GOLD_KEY, 0.12)),
+        EnemyKind::Eel => None,
+    }
+}
+
+#[derive(Component)]
+pub struct LockedChest {
+    pub tier: LockTier,
+}
+
+// Lock being picked, the minigame runs while its panel is open
+#[derive(Resource, Default)]
+pub struct Lockpicking {
+    chest: Option<(Entity, LockTier)>,
+    // Set once the panel has been seen open, closing it after that abandons the lock
+    open: bool,
+    pins_set: usize,
+    // Marker and sweet spot center as fractions of the track
+    marker: f32,
+    direction: f32,
+    target: f32,
+}
+
+#[derive(Component)]
+pub struct LockpickLabel;
+
+#[derive(Component)]
+pub struct LockpickMarker;
+
+#[derive(Component)]
+pub struct LockpickSweetSpot;
+
+pub fn spawn_locked_chest(commands: &mut Commands, tier: LockTier, position: Vec2) -> Entity {
+    let sprite = SpriteBundle {
+        sprite: Sprite {
+            color: tier.color(),
+            custom_size: Some(Vec2::new(18., 14.)),
+            ..default()
+        },
+        transform: Transform::from_translation(position.extend(0.8)),
+        ..default()
+    };
+
+    commands
+        .spawn(sprite)
+        .insert(LockedChest { tier })
+        .insert(NavOccupant::Static)
+        .id()
+}
+
+pub fn spawn_lockpick_panel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    let track = NodeBundle {
+        style: Style {
+            width: Val::Px(TRACK_WIDTH),
+            height: Val::Px(16.),
+            ..default()
+        },
+        background_color: Color::rgba(0.05, 0.05, 0.08, 0.9).into(),
+        ..default()
+    };
+
+    let sweet_spot = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            height: Val::Percent(100.),
+            ..default()
+        },
+        background_color: Color::rgba(0.3, 0.85, 0.3, 0.6).into(),
+        ..default()
+    };
+
+    let marker = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Px(4.),
+            height: Val::Percent(100.),
+            ..default()
+        },
+        background_color: Color::WHITE.into(),
+        ..default()
+    };
+
+    commands
+        .spawn(container_node)
+        .insert(PanelRoot(Panel::Lockpick))
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font,
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(LockpickLabel);
+
+            parent.spawn(track).with_children(|parent| {
+                parent.spawn(sweet_spot).insert(LockpickSweetSpot);
+                parent.spawn(marker).insert(LockpickMarker);
+            });
+        });
+}
+
+// A matching key opens the chest after a short channel, otherwise a lockpick starts the minigame
+#[allow(clippy::too_many_arguments)]
+pub fn interact_locked_chests(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    state: Res<PanelState>,
+    registry: Res<ItemRegistry>,
+    player: Query<(&Transform, &ItemContainer), With<Player>>,
+    chests: Query<(Entity, &Transform, &LockedChest)>,
+    mut lockpicking: ResMut<Lockpicking>,
+    mut channels: EventWriter<StartChannel>,
+    mut toggles: EventWriter<TogglePanel>,
+) {
+    if !input_map.just_pressed(Action::Interact, &input) || state.is_focused() {
+        return;
+    }
+
+    let Ok((transform, inventory)) = player.get_single() else {
+        return;
+    };
+
+    let position = transform.translation.truncate();
+
+    let Some((entity, _, chest)) = chests
+        .iter()
+        .find(|(_, chest, _)| chest.translation.truncate().distance(position) <= CHEST_REACH)
+    else {
+        return;
+    };
+
+    // The key is only used up once the chest opens
+    if inventory.count(chest.tier.key()) > 0 {
+        info!("Unlocking {:?} chest", chest.tier);
+
+        channels.send(StartChannel {
+            action: ChannelAction::Unlock(entity),
+            duration: Duration::from_secs_f32(UNLOCK_SECONDS),
+        });
+        return;
+    }
+
+    if inventory.count(LOCKPICK) == 0 {
+        info!(
+            "The {:?} chest needs a {} or a lockpick",
+            chest.tier,
+            registry.get(chest.tier.key()).name
+        );
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    *lockpicking = Lockpicking {
+        chest: Some((entity, chest.tier)),
+        open: false,
+        pins_set: 0,
+        marker: 0.,
+        direction: 1.,
+        target: random_target(chest.tier, &mut rng),
+    };
+
+    toggles.send(TogglePanel(Panel::Lockpick));
+}
+
+// The marker sweeps back and forth, stopping it in the sweet spot sets a pin and a miss breaks a pick
+#[allow(clippy::too_many_arguments)]
+pub fn play_lockpicking(
+    mut commands: Commands,
+    time: Res<Time>,
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    state: Res<PanelState>,
+    registry: Res<ItemRegistry>,
+    mut lockpicking: ResMut<Lockpicking>,
+    mut player: Query<&mut ItemContainer, With<Player>>,
+    chests: Query<&Transform, With<LockedChest>>,
+    mut drops: EventWriter<DropItem>,
+    mut toggles: EventWriter<TogglePanel>,
+) {
+    let Some((entity, tier)) = lockpicking.chest else {
+        return;
+    };
+
+    // Closing the panel abandons the lock, picks already broken stay broken
+    if state.active != Some(Panel::Lockpick) {
+        if lockpicking.open {
+            lockpicking.chest = None;
+        }
+        return;
+    }
+
+    let first_frame = !lockpicking.open;
+    lockpicking.open = true;
+
+    // The chest's chunk may have unloaded underneath the panel
+    let (Ok(mut inventory), Ok(chest)) = (player.get_single_mut(), chests.get(entity)) else {
+        lockpicking.chest = None;
+        toggles.send(TogglePanel(Panel::Lockpick));
+        return;
+    };
+
+    let step = tier.marker_speed() * time.delta_seconds() * lockpicking.direction;
+    lockpicking.marker += step;
+    if !(0. ..=1.).contains(&lockpicking.marker) {
+        lockpicking.marker = lockpicking.marker.clamp(0., 1.);
+        lockpicking.direction = -lockpicking.direction;
+    }
+
+    // The key press that opened the panel does not count as an attempt
+    if first_frame || !input_map.just_pressed(Action::Interact, &input) {
+        return;
+    }
+
+    if (lockpicking.marker - lockpicking.target).abs() <= tier.sweet_spot() / 2. {
+        lockpicking.pins_set += 1;
+        debug!("Set pin {} of {}", lockpicking.pins_set, tier.pins());
+
+        if lockpicking.pins_set == tier.pins() {
+            info!("Picked {:?} chest", tier);
+
+            open_chest(
+                &mut commands,
+                entity,
+                tier,
+                chest.translation.truncate(),
+                &registry,
+                &mut drops,
+            );
+
+            lockpicking.chest = None;
+            toggles.send(TogglePanel(Panel::Lockpick));
+            return;
+        }
+
+        let target = random_target(tier, &mut rand::thread_rng());
+        lockpicking.target = target;
+        return;
+    }
+
+    inventory.remove(LOCKPICK, 1);
+    info!("Lockpick broke, {} left", inventory.count(LOCKPICK));
+
+    if inventory.count(LOCKPICK) == 0 {
+        lockpicking.chest = None;
+        toggles.send(TogglePanel(Panel::Lockpick));
+    }
+}
+
+pub fn finish_unlocking(
+    mut commands: Commands,
+    mut finished: EventReader<ChannelFinished>,
+    registry: Res<ItemRegistry>,
+    mut player: Query<&mut ItemContainer, With<Player>>,
+    chests: Query<(&Transform, &LockedChest)>,
+    mut drops: EventWriter<DropItem>,
+) {
+    for ChannelFinished(action) in finished.read() {
+        let ChannelAction::Unlock(entity) = action else {
+            continue;
+        };
+
+        let (Ok(mut inventory), Ok((transform, chest))) =
+            (player.get_single_mut(), chests.get(*entity))
+        else {
+            continue;
+        };
+
+        // Dropped or stashed while the key was turning
+        if !inventory.remove(chest.tier.key(), 1) {
+            continue;
+        }
+
+        open_chest(
+            &mut commands,
+            *entity,
+            chest.tier,
+            transform.translation.truncate(),
+            &registry,
+            &mut drops,
+        );
+    }
+}
+
+pub fn update_lockpick_panel(
+    lockpicking: Res<Lockpicking>,
+    player: Query<&ItemContainer, With<Player>>,
+    mut label: Query<&mut Text, With<LockpickLabel>>,
+    mut marker: Query<&mut Style, (With<LockpickMarker>, Without<LockpickSweetSpot>)>,
+    mut sweet_spot: Query<&mut Style, (With<LockpickSweetSpot>, Without<LockpickMarker>)>,
+) {
+    let Some((_, tier)) = lockpicking.chest else {
+        return;
+    };
+
+    let picks = player
+        .get_single()
+        .map(|inventory| inventory.count(LOCKPICK))
+        .unwrap_or(0);
+
+    if let Ok(mut text) = label.get_single_mut() {
+        text.sections[0].value = format!(
+            "Pin {} of {}, {} lockpicks",
+            lockpicking.pins_set + 1,
+            tier.pins(),
+            picks
+        );
+    }
+
+    if let Ok(mut style) = marker.get_single_mut() {
+        style.left = Val::Px(lockpicking.marker * (TRACK_WIDTH - 4.));
+    }
+
+    if let Ok(mut style) = sweet_spot.get_single_mut() {
+        let width = tier.sweet_spot();
+        style.left = Val::Percent((lockpicking.target - width / 2.) * 100.);
+        style.width = Val::Percent(width * 100.);
+    }
+}
+
+// Kept clear of the ends so the whole sweet spot is on the track
+fn random_target(tier: LockTier, rng: &mut impl Rng) -> f32 {
+    let half = tier.sweet_spot() / 2.;
+    rng.gen_range(half..1. - half)
+}
+
+fn open_chest(
+    commands: &mut Commands,
+    entity: Entity,
+    tier: LockTier,
+    position: Vec2,
+    registry: &ItemRegistry,
+    drops: &mut EventWriter<DropItem>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..tier.loot_rolls() {
+        if let Some(stack) = registry.roll_loot_from(&mut rng, tier.min_rarity()) {
+            drops.send(DropItem { stack, position });
+        }
+    }
+
+    commands.entity(entity).despawn_recursive();
+}
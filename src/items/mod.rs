@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+
+use crate::menu::GameState;
+
+use self::{
+    definition::ItemRegistry,
+    dropped::{DropItem, DroppedItemSettings, ItemCollected},
+    durability::WearEquipment,
+    recipe::Recipes,
+    stash::Stash,
+    wallet::Wallet,
+};
+
+pub mod automation;
+pub mod container;
+pub mod definition;
+pub mod dropped;
+pub mod durability;
+pub mod equipment;
+pub mod instance;
+pub mod lock;
+pub mod recipe;
+pub mod stash;
+pub mod traversal;
+pub mod upgrade;
+pub mod wallet;
+
+pub struct ItemsPlugin;
+
+impl Plugin for ItemsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ItemRegistry>()
+            .init_resource::<DroppedItemSettings>()
+            .init_resource::<Wallet>()
+            .init_resource::<Stash>()
+            .init_resource::<lock::Lockpicking>()
+            .init_resource::<Recipes>()
+            .add_systems(
+                Update,
+                recipe::handle_craft_commands.run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (
+                    automation::handle_place_commands,
+                    automation::pick_up_placed,
+                    automation::run_conveyors,
+                    automation::show_conveyor_cargo,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(OnExit(GameState::MainMenu), stash::load_stash)
+            .add_systems(
+                Startup,
+                (stash::spawn_stash_chest, stash::spawn_stash_panel),
+            )
+            .add_event::<WearEquipment>()
+            .add_systems(
+                Startup,
+                (
+                    upgrade::spawn_upgrade_station,
+                    upgrade::spawn_upgrade_panel,
+                    durability::spawn_breakage_warning,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    durability::apply_equipment_wear,
+                    durability::fade_breakage_warning,
+                )
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (
+                    upgrade::open_upgrade_station,
+                    upgrade::handle_upgrade_slots,
+                    durability::handle_repair_slots,
+                    upgrade::update_upgrade_list,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (
+                    stash::open_stash,
+                    stash::finish_opening_stash,
+                    stash::type_stash_search,
+                    stash::handle_stash_buttons,
+                    stash::update_stash_list,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(Startup, lock::spawn_lockpick_panel)
+            .add_systems(
+                Update,
+                (
+                    lock::interact_locked_chests,
+                    lock::finish_unlocking,
+                    lock::play_lockpicking,
+                    lock::update_lockpick_panel,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                traversal::update_traversal.run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                stash::save_stash_on_exit.run_if(not(in_state(GameState::MainMenu))),
+            )
+            .add_event::<DropItem>()
+            .add_event::<ItemCollected>()
+            .add_systems(
+                Update,
+                (
+                    dropped::spawn_dropped_items,
+                    dropped::merge_dropped_items,
+                    wallet::collect_currency,
+                    dropped::pickup_dropped_items,
+                    dropped::expire_dropped_items,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(Startup, wallet::spawn_wallet_display)
+            .add_systems(
+                Update,
+                wallet::update_wallet_display.run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (dropped::animate_dropped_items, dropped::pulse_drop_beams)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
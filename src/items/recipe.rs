@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::Stack,
+    player::Player,
+    ui::console::{ConsoleCommand, ConsoleOutput},
+    world::TRAP_SPRING,
+};
+
+use super::{
+    container::ItemContainer,
+    definition::ItemRegistry,
+    dropped::DropItem,
+    lock::LOCKPICK,
+    traversal::{CLIMBING_GEAR, FLIPPERS},
+};
+
+const STICK: u32 = 0;
+
+pub struct Recipe {
+    // Item ids and counts consumed
+    pub inputs: Vec<(u32, u32)>,
+    pub output: Stack,
+}
+
+// Smelting turns ore into ingots, ingots become the pickaxe able to mine the next ore
+#[derive(Resource)]
+pub struct Recipes(Vec<Recipe>);
+
+impl Default for Recipes {
+    fn default() -> Self {
+        let smelt = |ore: u32, ingot: u32| Recipe {
+            inputs: vec![(ore, 2)],
+            output: Stack::new(ingot, 1),
+        };
+
+        let pickaxe = |ingot: u32, pickaxe: u32| Recipe {
+            inputs: vec![(ingot, 3), (STICK, 2)],
+            output: Stack::new(pickaxe, 1),
+        };
+
+        Recipes(vec![
+            smelt(10, 13),
+            smelt(11, 14),
+            smelt(12, 15),
+            pickaxe(13, 16),
+            pickaxe(14, 9),
+            pickaxe(15, 17),
+            // Belts and the crates they feed for moving items around a base
+            Recipe {
+                inputs: vec![(14, 1), (STICK, 2)],
+                output: Stack::new(18, 4),
+            },
+            Recipe {
+                inputs: vec![(STICK, 4)],
+                output: Stack::new(19, 1),
+            },
+            // Protects the chunk it is set down in
+            Recipe {
+                inputs: vec![(13, 2), (STICK, 1)],
+                output: Stack::new(20, 1),
+            },
+            // Broken on every missed pin, so they come in bundles
+            Recipe {
+                inputs: vec![(2, 1), (STICK, 1)],
+                output: Stack::new(LOCKPICK, 3),
+            },
+            // Travel gear, each opens up terrain the player can't otherwise cross
+            Recipe {
+                inputs: vec![(1, 6), (STICK, 2)],
+                output: Stack::new(FLIPPERS, 1),
+            },
+            Recipe {
+                inputs: vec![(14, 2), (TRAP_SPRING, 1)],
+                output: Stack::new(CLIMBING_GEAR, 1),
+            },
+        ])
+    }
+}
+
+// Recipes are named after their output, "Copper Ingot" is crafted with "craft copper_ingot"
+fn recipe_key(recipe: &Recipe, registry: &ItemRegistry) -> String {
+    registry
+        .get(recipe.output.id)
+        .name
+        .to_lowercase()
+        .replace(' ', "_")
+}
+
+// Console command crafting from the player's inventory, anything that doesn't fit is dropped at their feet
+pub fn handle_craft_commands(
+    mut console: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut drops: EventWriter<DropItem>,
+    recipes: Res<Recipes>,
+    registry: Res<ItemRegistry>,
+    mut player: Query<(&Transform, &mut ItemContainer), With<Player>>,
+) {
+    for command in console.read() {
+        if command.name != "craft" {
+            continue;
+        }
+
+        let Some(recipe) = command.args.first().and_then(|key| {
+            recipes
+                .0
+                .iter()
+                .find(|recipe| recipe_key(recipe, &registry) == *key)
+        }) else {
+            let keys: Vec<String> = recipes
+                .0
+                .iter()
+                .map(|recipe| recipe_key(recipe, &registry))
+                .collect();
+            output.send(ConsoleOutput(format!("Usage: craft <{}>", keys.join("|"))));
+            continue;
+        };
+
+        let Ok((transform, mut inventory)) = player.get_single_mut() else {
+            continue;
+        };
+
+        let missing: Vec<String> = recipe
+            .inputs
+            .iter()
+            .filter(|(id, count)| inventory.count(*id) < *count)
+            .map(|(id, count)| format!("{} {}", count, registry.get(*id).name))
+            .collect();
+
+        if !missing.is_empty() {
+            output.send(ConsoleOutput(format!("Missing {}", missing.join(", "))));
+            continue;
+        }
+
+        for (id, count) in recipe.inputs.iter() {
+            inventory.remove(*id, *count);
+        }
+
+        if let Some(leftover) = inventory.insert(recipe.output, &registry) {
+            drops.send(DropItem {
+                stack: leftover,
+                position: transform.translation.truncate(),
+            });
+        }
+
+        output.send(ConsoleOutput(format!(
+            "Crafted {}",
+            registry.get(recipe.output.id).name
+        )));
+    }
+}
@@ -0,0 +1,391 @@
+use std::{fs, time::Duration};
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    id::StableId,
+    input::{Action, InputMap},
+    player::{
+        channel::{ChannelAction, ChannelFinished, StartChannel},
+        Player,
+    },
+    ui::{Panel, PanelRoot, PanelState, TogglePanel},
+    world::NavOccupant,
+};
+
+use super::{
+    container::{ItemContainer, SortKey},
+    definition::ItemRegistry,
+};
+
+const STASH_PATH: &str = "saves/stash.json";
+
+// Four times the slots of the player inventory
+pub const STASH_SLOTS: usize = 96;
+
+// Distance from a stash chest at which it can be opened
+pub const STASH_REACH: f32 = 48.;
+
+// Storage shared by every stash chest in the world, kept across deaths
+#[derive(Resource)]
+pub struct Stash {
+    pub contents: ItemContainer,
+    pub query: String,
+}
+
+impl Default for Stash {
+    fn default() -> Self {
+        Stash {
+            contents: ItemContainer::with_capacity(STASH_SLOTS),
+            query: String::new(),
+        }
+    }
+}
+
+impl Stash {
+    pub fn load() -> Stash {
+        let contents = fs::read(STASH_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<ItemContainer>(&bytes).ok());
+
+        match contents {
+            Some(mut contents) => {
+                // Saves from before a capacity change still load
+                contents
+                    .slots
+                    .resize(STASH_SLOTS.max(contents.capacity()), None);
+
+                Stash {
+                    contents,
+                    query: String::new(),
+                }
+            }
+            None => Stash::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let written = fs::create_dir_all("saves").and_then(|_| {
+            fs::write(
+                STASH_PATH,
+                serde_json::to_vec(&self.contents).map_err(std::io::Error::from)?,
+            )
+        });
+
+        if let Err(err) = written {
+            warn!("Failed to save stash! Err {err}");
+        }
+    }
+}
+
+// Chest giving access to the stash, placed in villages
+#[derive(Component)]
+pub struct StashChest;
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum StashButton {
+    Sort,
+    DepositAll,
+}
+
+// Row of the stash list, pressing it withdraws the slot into the player inventory
+#[derive(Component)]
+pub struct StashSlot(usize);
+
+#[derive(Component)]
+pub struct StashSearch;
+
+#[derive(Component)]
+pub struct StashList;
+
+pub fn load_stash(mut commands: Commands) {
+    commands.insert_resource(Stash::load());
+}
+
+// Until villages are generated the only chest sits at the world origin
+pub fn spawn_stash_chest(mut commands: Commands) {
+    let position = Vec2::new(64., 0.);
+
+    let sprite = SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgb(0.55, 0.35, 0.15),
+            custom_size: Some(Vec2::new(24., 18.)),
+            ..default()
+        },
+        transform: Transform::from_translation(position.extend(0.8)),
+        ..default()
+    };
+
+    commands
+        .spawn(sprite)
+        .insert(StashChest)
+        .insert(StableId::generated("stash", position))
+        .insert(NavOccupant::Static);
+}
+
+pub fn spawn_stash_panel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    let text_style = TextStyle {
+        font: font.clone(),
+        font_size: 20.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn(container_node)
+        .insert(PanelRoot(Panel::Stash))
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section("Search: ", text_style.clone()))
+                .insert(StashSearch);
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(8.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (button, label) in [
+                        (StashButton::Sort, "Sort"),
+                        (StashButton::DepositAll, "Deposit all"),
+                    ] {
+                        let button_bundle = ButtonBundle {
+                            style: Style {
+                                width: Val::Px(156.),
+                                padding: UiRect::all(Val::Px(8.)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            background_color: Color::rgba(0.1, 0.1, 0.15, 0.9).into(),
+                            ..default()
+                        };
+
+                        parent
+                            .spawn(button_bundle)
+                            .insert(button)
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                            });
+                    }
+                });
+
+            let list_node = NodeBundle {
+                style: Style {
+                    width: Val::Px(320.),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(2.),
+                    ..default()
+                },
+                background_color: Color::rgba(0.05, 0.05, 0.08, 0.9).into(),
+                ..default()
+            };
+
+            parent.spawn(list_node).insert(StashList);
+        });
+}
+
+// Unlocking a chest takes a moment, the stash opens once the channel finishes
+const OPEN_SECONDS: f32 = 0.5;
+
+pub fn open_stash(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    state: Res<PanelState>,
+    player: Query<&Transform, With<Player>>,
+    chests: Query<&Transform, With<StashChest>>,
+    mut channels: EventWriter<StartChannel>,
+) {
+    // Typing in the search field must not reopen the stash, Back closes it
+    if !input_map.just_pressed(Action::Interact, &input) || state.is_focused() {
+        return;
+    }
+
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    let in_reach = chests.iter().any(|chest| {
+        chest
+            .translation
+            .truncate()
+            .distance(player.translation.truncate())
+            <= STASH_REACH
+    });
+
+    if in_reach {
+        channels.send(StartChannel {
+            action: ChannelAction::OpenStash,
+            duration: Duration::from_secs_f32(OPEN_SECONDS),
+        });
+    }
+}
+
+pub fn finish_opening_stash(
+    mut finished: EventReader<ChannelFinished>,
+    mut toggles: EventWriter<TogglePanel>,
+) {
+    for ChannelFinished(action) in finished.read() {
+        if *action == ChannelAction::OpenStash {
+            toggles.send(TogglePanel(Panel::Stash));
+        }
+    }
+}
+
+pub fn type_stash_search(
+    state: Res<PanelState>,
+    input: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut stash: ResMut<Stash>,
+) {
+    if state.active != Some(Panel::Stash) {
+        characters.clear();
+        return;
+    }
+
+    for event in characters.read() {
+        if !event.char.is_control() {
+            stash.query.push(event.char);
+        }
+    }
+
+    if input.just_pressed(KeyCode::Back) {
+        stash.query.pop();
+    }
+}
+
+pub fn handle_stash_buttons(
+    registry: Res<ItemRegistry>,
+    mut stash: ResMut<Stash>,
+    buttons: Query<(&Interaction, &StashButton), Changed<Interaction>>,
+    slots: Query<(&Interaction, &StashSlot), Changed<Interaction>>,
+    mut inventory: Query<&mut ItemContainer, With<Player>>,
+) {
+    let Ok(mut inventory) = inventory.get_single_mut() else {
+        return;
+    };
+
+    let mut changed = false;
+
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            StashButton::Sort => stash.contents.sort(SortKey::Name, &registry),
+            StashButton::DepositAll => {
+                for slot in inventory.slots.iter_mut() {
+                    if let Some(stack) = slot.take() {
+                        *slot = stash.contents.insert(stack, &registry);
+                    }
+                }
+            }
+        }
+
+        changed = true;
+    }
+
+    for (interaction, slot) in slots.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if let Some(stack) = stash.contents.take(slot.0) {
+            // Anything that doesn't fit in the inventory stays in the stash
+            if let Some(leftover) = inventory.insert(stack, &registry) {
+                stash.contents.insert(leftover, &registry);
+            }
+        }
+
+        changed = true;
+    }
+
+    if changed {
+        stash.save();
+    }
+}
+
+pub fn update_stash_list(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    registry: Res<ItemRegistry>,
+    stash: Res<Stash>,
+    list: Query<Entity, With<StashList>>,
+    mut search: Query<&mut Text, With<StashSearch>>,
+) {
+    if !stash.is_changed() {
+        return;
+    }
+
+    if let Ok(mut text) = search.get_single_mut() {
+        text.sections[0].value = format!("Search: {}", stash.query);
+    }
+
+    let Ok(list) = list.get_single() else {
+        return;
+    };
+
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+
+    commands.entity(list).despawn_descendants();
+    commands.entity(list).with_children(|parent| {
+        for idx in stash.contents.search(&stash.query, &registry) {
+            let Some(stack) = stash.contents.slots[idx] else {
+                continue;
+            };
+
+            let name = registry.get(stack.id).name_section(TextStyle {
+                font: font.clone(),
+                font_size: 18.0,
+                ..default()
+            });
+
+            let count = TextSection::new(
+                format!(" x{}", stack.count),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                },
+            );
+
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        padding: UiRect::horizontal(Val::Px(8.)),
+                        ..default()
+                    },
+                    background_color: Color::NONE.into(),
+                    ..default()
+                })
+                .insert(StashSlot(idx))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_sections([name, count]));
+                });
+        }
+    });
+}
+
+pub fn save_stash_on_exit(exit: EventReader<AppExit>, stash: Res<Stash>) {
+    if !exit.is_empty() {
+        stash.save();
+    }
+}
@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+use crate::{physics::Traversal, player::Player};
+
+use super::equipment::Equipment;
+
+pub const FLIPPERS: u32 = 26;
+pub const CLIMBING_GEAR: u32 = 27;
+
+// Terrain the player can cross follows whatever travel gear is worn
+pub fn update_traversal(mut player: Query<(Ref<Equipment>, &mut Traversal), With<Player>>) {
+    let Ok((equipment, mut traversal)) = player.get_single_mut() else {
+        return;
+    };
+
+    if !equipment.is_changed() {
+        return;
+    }
+
+    let gear = equipment.gear.map(|stack| stack.id);
+
+    let updated = Traversal {
+        swim: gear == Some(FLIPPERS),
+        climb: gear == Some(CLIMBING_GEAR),
+    };
+
+    if *traversal != updated {
+        info!("Traversal now {:?}", updated);
+        *traversal = updated;
+    }
+}
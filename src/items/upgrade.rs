@@ -0,0 +1,249 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::{Action, InputMap},
+    player::Player,
+    ui::{Panel, PanelRoot, PanelState, TogglePanel},
+    world::{NavOccupant, WorldTick},
+};
+
+use super::{
+    container::ItemContainer,
+    definition::ItemRegistry,
+    durability::{repair_cost, RepairSlot},
+    instance::ItemInstance,
+};
+
+// Iron shards consumed by every upgrade attempt, successful or not
+const UPGRADE_MATERIAL: u32 = 2;
+const UPGRADE_COST: u32 = 3;
+
+const STATION_REACH: f32 = 48.;
+
+#[derive(Component)]
+pub struct UpgradeStation;
+
+// Row of the upgrade list, pressing it attempts an upgrade of that inventory slot
+#[derive(Component)]
+pub struct UpgradeSlot(usize);
+
+#[derive(Component)]
+pub struct UpgradeList;
+
+// Until villages are generated the only station sits next to the stash
+pub fn spawn_upgrade_station(mut commands: Commands) {
+    let sprite = SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgb(0.45, 0.45, 0.5),
+            custom_size: Some(Vec2::new(22., 22.)),
+            ..default()
+        },
+        transform: Transform::from_xyz(-64., 0., 0.8),
+        ..default()
+    };
+
+    commands
+        .spawn(sprite)
+        .insert(UpgradeStation)
+        .insert(NavOccupant::Static);
+}
+
+pub fn spawn_upgrade_panel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    let list_node = NodeBundle {
+        style: Style {
+            width: Val::Px(320.),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.),
+            ..default()
+        },
+        background_color: Color::rgba(0.05, 0.05, 0.08, 0.9).into(),
+        ..default()
+    };
+
+    commands
+        .spawn(container_node)
+        .insert(PanelRoot(Panel::Upgrade))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Upgrade cost: {} Iron Shard", UPGRADE_COST),
+                TextStyle {
+                    font,
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            parent.spawn(list_node).insert(UpgradeList);
+        });
+}
+
+pub fn open_upgrade_station(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    state: Res<PanelState>,
+    player: Query<&Transform, With<Player>>,
+    stations: Query<&Transform, With<UpgradeStation>>,
+    mut toggles: EventWriter<TogglePanel>,
+) {
+    if !input_map.just_pressed(Action::Interact, &input) || state.is_focused() {
+        return;
+    }
+
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    let in_reach = stations.iter().any(|station| {
+        station
+            .translation
+            .truncate()
+            .distance(player.translation.truncate())
+            <= STATION_REACH
+    });
+
+    if in_reach {
+        toggles.send(TogglePanel(Panel::Upgrade));
+    }
+}
+
+pub fn handle_upgrade_slots(
+    registry: Res<ItemRegistry>,
+    world_tick: Res<WorldTick>,
+    slots: Query<(&Interaction, &UpgradeSlot), Changed<Interaction>>,
+    mut inventory: Query<&mut ItemContainer, With<Player>>,
+) {
+    let Ok(mut inventory) = inventory.get_single_mut() else {
+        return;
+    };
+
+    for (interaction, slot) in slots.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let Some(mut stack) = inventory.slots[slot.0] else {
+            continue;
+        };
+
+        if !inventory.remove(UPGRADE_MATERIAL, UPGRADE_COST) {
+            info!("Not enough materials to upgrade");
+            continue;
+        }
+
+        // First upgrade turns the item into a unique instance
+        let mut instance = stack
+            .instance
+            .unwrap_or_else(|| ItemInstance::new((stack.id, slot.0, world_tick.tick)));
+
+        let name = &registry.get(stack.id).name;
+        if instance.upgrade() {
+            info!("Upgraded {}", name);
+        } else {
+            info!("Upgrading {} failed", name);
+        }
+
+        stack.instance = Some(instance);
+        inventory.slots[slot.0] = Some(stack);
+    }
+}
+
+pub fn update_upgrade_list(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    registry: Res<ItemRegistry>,
+    inventory: Query<Ref<ItemContainer>, With<Player>>,
+    list: Query<Entity, With<UpgradeList>>,
+) {
+    let (Ok(inventory), Ok(list)) = (inventory.get_single(), list.get_single()) else {
+        return;
+    };
+
+    if !inventory.is_changed() {
+        return;
+    }
+
+    let style = TextStyle {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: 18.0,
+        color: Color::WHITE,
+    };
+
+    commands.entity(list).despawn_descendants();
+    commands.entity(list).with_children(|parent| {
+        for (idx, stack) in inventory.slots.iter().enumerate() {
+            let Some(stack) = stack else {
+                continue;
+            };
+
+            // Only gear carries modifiers
+            let definition = registry.get(stack.id);
+            if definition.category.equip_slot().is_none() {
+                continue;
+            }
+
+            let upgrades = stack
+                .instance
+                .map(|instance| instance.upgrades)
+                .unwrap_or(0);
+            let attempts = TextSection::new(format!(" ({} attempts)", upgrades), style.clone());
+
+            let row = NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                ..default()
+            };
+
+            let button_bundle = ButtonBundle {
+                style: Style {
+                    padding: UiRect::horizontal(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::NONE.into(),
+                ..default()
+            };
+
+            parent.spawn(row).with_children(|parent| {
+                parent
+                    .spawn(button_bundle.clone())
+                    .insert(UpgradeSlot(idx))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_sections([
+                            definition.name_section(style.clone()),
+                            attempts,
+                        ]));
+                    });
+
+                // Damaged gear can be repaired at the same station
+                if let Some((material, cost)) = repair_cost(stack, &registry) {
+                    parent
+                        .spawn(button_bundle)
+                        .insert(RepairSlot(idx))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                format!("Repair ({} {})", cost, registry.get(material).name),
+                                style.clone(),
+                            ));
+                        });
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+use crate::{components::Stack, player::Player};
+
+use super::dropped::DroppedItem;
+
+// Item id of coins, collected straight into the wallet instead of the inventory
+pub const COIN: u32 = 6;
+
+// Distance at which dropped coins are picked up automatically
+const COLLECT_RADIUS: f32 = 32.;
+
+#[derive(Resource, Default)]
+pub struct Wallet {
+    pub coins: u64,
+}
+
+impl Wallet {
+    // Deducts the amount only if the wallet can cover it in full
+    pub fn try_spend(&mut self, amount: u64) -> bool {
+        if self.coins < amount {
+            return false;
+        }
+
+        self.coins -= amount;
+        true
+    }
+}
+
+#[derive(Component)]
+pub struct WalletDisplay;
+
+pub fn spawn_wallet_display(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_bundle = TextBundle {
+        text: Text::from_section(
+            "0 coins",
+            TextStyle {
+                font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                font_size: 18.0,
+                color: Color::rgb(1., 0.85, 0.3),
+            },
+        ),
+        style: Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(12.),
+            top: Val::Px(12.),
+            ..default()
+        },
+        ..default()
+    };
+
+    commands.spawn(text_bundle).insert(WalletDisplay);
+}
+
+pub fn collect_currency(
+    mut commands: Commands,
+    mut wallet: ResMut<Wallet>,
+    player: Query<&Transform, With<Player>>,
+    items: Query<(Entity, &Transform, &Stack), With<DroppedItem>>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    for (entity, transform, stack) in items.iter() {
+        if stack.id != COIN {
+            continue;
+        }
+
+        if transform
+            .translation
+            .truncate()
+            .distance(player.translation.truncate())
+            > COLLECT_RADIUS
+        {
+            continue;
+        }
+
+        debug!("Collected {} coins", stack.count);
+        wallet.coins += stack.count as u64;
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn update_wallet_display(
+    wallet: Res<Wallet>,
+    mut display: Query<&mut Text, With<WalletDisplay>>,
+) {
+    if !wallet.is_changed() {
+        return;
+    }
+
+    if let Ok(mut text) = display.get_single_mut() {
+        text.sections[0].value = format!("{} coins", wallet.coins);
+    }
+}
@@ -0,0 +1,36 @@
+// Game modules live in the library so integration tests can build apps from the same plugins and systems
+pub mod combat;
+
+pub mod components;
+
+pub mod debug;
+
+pub mod id;
+
+pub mod input;
+
+pub mod items;
+
+pub mod menu;
+
+pub mod mobs;
+
+pub mod npc;
+
+pub mod particles;
+
+pub mod physics;
+
+pub mod player;
+
+pub mod pool;
+
+pub mod quest;
+
+pub mod status;
+
+pub mod ui;
+
+pub mod work;
+
+pub mod world;
@@ -1,36 +1,61 @@
-use bevy::{
-    app::{Startup, Update},
-    core_pipeline::core_2d::Camera2dBundle,
-    prelude::*,
-    window::{WindowMode, WindowTheme},
+use bevy::{app::Startup, core_pipeline::core_2d::Camera2dBundle, prelude::*};
+use travelers::{
+    combat, debug, id, items, menu, mobs, npc, particles, physics, player, quest, status, ui,
+    ui::settings::GraphicsSettings, work, world,
 };
-use components::{Dead, Health, Velocity};
 
-mod player;
+fn main() {
+    // Dev tooling for schematics, run in place of launching the game
+    let args: Vec<String> = std::env::args().collect();
+    if let [_, command, input, output] = args.as_slice() {
+        let result = match command.as_str() {
+            "convert-schematic" => Some(world::convert_schematic_file(input, output)),
+            "learn-schematic" => Some(world::learn_schematic_file(input, output)),
+            _ => None,
+        };
 
-mod components;
+        if let Some(result) = result {
+            match result {
+                Ok(_) => println!("Wrote schematic {output} from {input}"),
+                Err(err) => eprintln!("Failed to {command}! Err {err}"),
+            }
+            return;
+        }
+    }
 
-mod world;
+    info!("Starting Travelers...");
 
-mod debug;
+    // Saved display settings are applied to the window before it is created
+    let settings = GraphicsSettings::load();
+    let mut window = Window {
+        title: "Travelers".into(),
+        ..default()
+    };
+    settings.apply(&mut window);
 
-fn main() {
-    info!("Starting Travelers...");
     App::new()
+        .insert_resource(settings)
+        .insert_resource(world::WorldSeed::load(&args))
         .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Travelers".into(),
-                mode: WindowMode::Fullscreen,
-                ..default()
-            }),
+            primary_window: Some(window),
             ..default()
         }))
+        .add_plugins(menu::MenuPlugin)
         .add_plugins(debug::DebugPlugin)
+        .add_plugins(ui::UiPlugin)
         .add_plugins(world::WorldPlugin)
         .add_plugins(player::PlayerPlugin)
+        .add_plugins(items::ItemsPlugin)
+        .add_plugins(mobs::MobsPlugin)
+        .add_plugins(npc::NpcPlugin)
+        .add_plugins(quest::QuestPlugin)
+        .add_plugins(id::IdPlugin)
+        .add_plugins(physics::PhysicsPlugin)
+        .add_plugins(combat::CombatPlugin)
+        .add_plugins(status::StatusPlugin)
+        .add_plugins(particles::ParticlePlugin)
+        .add_plugins(work::WorkPlugin)
         .add_systems(Startup, setup)
-        .add_systems(Update, movement_system)
-        .add_systems(Update, check_death)
         .run();
 }
 
@@ -42,19 +67,3 @@ fn setup(mut commands: Commands, _: Res<AssetServer>) {
     cam.projection.scale *= 0.5;
     commands.spawn(cam);
 }
-
-fn movement_system(time: Res<Time>, mut query: Query<(&mut Transform, &Velocity)>) {
-    for (mut transform, velocity) in query.iter_mut() {
-        let translation: &mut Vec3 = &mut transform.translation;
-        translation.x += velocity.dx * time.delta_seconds();
-        translation.y += velocity.dy * time.delta_seconds();
-    }
-}
-
-fn check_death(mut commands: Commands, query: Query<(Entity, &Health), Without<Dead>>) {
-    for (entity, health) in query.iter() {
-        if health.current <= 0 {
-            commands.entity(entity).insert(components::Dead);
-        }
-    }
-}
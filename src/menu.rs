@@ -0,0 +1,286 @@
+use std::{fs, io::ErrorKind, path::Path};
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    input::{Action, InputMap},
+    ui::{
+        transition::{self, ScreenTransition, TransitionCovered, TransitionKind},
+        Panel, PanelState, TogglePanel,
+    },
+    world::WorldSeed,
+};
+
+// World state is saved under here, a new game starts from an empty directory
+const SAVES_PATH: &str = "saves";
+
+#[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    InGame,
+    Paused,
+}
+
+#[derive(Component)]
+pub struct MainMenu;
+
+#[derive(Component)]
+pub struct PausedOverlay;
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum MenuButton {
+    NewGame,
+    Continue,
+    Resume,
+    Settings,
+    Quit,
+}
+
+impl MenuButton {
+    fn label(&self) -> &'static str {
+        match self {
+            MenuButton::NewGame => "New Game",
+            MenuButton::Continue => "Continue",
+            MenuButton::Resume => "Resume",
+            MenuButton::Settings => "Settings",
+            MenuButton::Quit => "Quit",
+        }
+    }
+}
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
+            .add_systems(OnExit(GameState::MainMenu), despawn_main_menu)
+            .add_systems(
+                Update,
+                handle_menu_buttons.run_if(not(in_state(GameState::InGame))),
+            )
+            .add_systems(
+                Update,
+                enter_world_when_covered.run_if(in_state(GameState::MainMenu)),
+            )
+            .add_systems(OnEnter(GameState::Paused), spawn_paused_overlay)
+            .add_systems(OnExit(GameState::Paused), despawn_paused_overlay)
+            .add_systems(
+                Update,
+                hide_paused_overlay.run_if(in_state(GameState::Paused)),
+            )
+            // Before panels react to the same Escape press, an open panel closes instead of pausing
+            .add_systems(
+                PreUpdate,
+                toggle_pause
+                    .after(transition::block_transition_input)
+                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+            );
+    }
+}
+
+fn has_saves() -> bool {
+    Path::new(SAVES_PATH).exists()
+}
+
+fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        background_color: Color::rgb(0.05, 0.05, 0.08).into(),
+        ..default()
+    };
+
+    // Continue is only offered once there is a world to continue
+    let mut buttons = vec![MenuButton::NewGame];
+    if has_saves() {
+        buttons.insert(0, MenuButton::Continue);
+    }
+    buttons.push(MenuButton::Quit);
+
+    commands
+        .spawn(container_node)
+        .insert(MainMenu)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Travelers",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 48.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            for button in buttons {
+                spawn_menu_button(parent, button, font.clone());
+            }
+        });
+}
+
+fn spawn_menu_button(parent: &mut ChildBuilder, button: MenuButton, font: Handle<Font>) {
+    let button_bundle = ButtonBundle {
+        style: Style {
+            width: Val::Px(320.),
+            padding: UiRect::all(Val::Px(8.)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0.1, 0.1, 0.15, 0.9).into(),
+        ..default()
+    };
+
+    parent
+        .spawn(button_bundle)
+        .insert(button)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                button.label(),
+                TextStyle {
+                    font,
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+fn despawn_main_menu(mut commands: Commands, menus: Query<Entity, With<MainMenu>>) {
+    for menu in menus.iter() {
+        commands.entity(menu).despawn_recursive();
+    }
+}
+
+fn handle_menu_buttons(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut transition: ResMut<ScreenTransition>,
+    mut toggles: EventWriter<TogglePanel>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            MenuButton::NewGame => {
+                // Per world saves are read as the game starts, clearing them here starts fresh
+                match fs::remove_dir_all(SAVES_PATH) {
+                    Ok(_) => info!("Cleared previous world saves"),
+                    Err(err) if err.kind() == ErrorKind::NotFound => {}
+                    Err(err) => warn!("Failed to clear previous world saves! Err {err}"),
+                }
+
+                let seed = WorldSeed::random();
+                info!("Starting a new world with seed {}", seed.0);
+                commands.insert_resource(seed);
+
+                transition.start(TransitionKind::EnterWorld);
+            }
+            MenuButton::Continue => {
+                transition.start(TransitionKind::EnterWorld);
+            }
+            MenuButton::Resume => next_state.set(GameState::InGame),
+            MenuButton::Settings => toggles.send(TogglePanel(Panel::Settings)),
+            MenuButton::Quit => exit.send(AppExit),
+        }
+    }
+}
+
+// The world starts loading behind the curtain and is revealed as it streams in
+fn enter_world_when_covered(
+    mut covered: EventReader<TransitionCovered>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if covered
+        .read()
+        .any(|TransitionCovered(kind)| *kind == TransitionKind::EnterWorld)
+    {
+        next_state.set(GameState::InGame);
+    }
+}
+
+// Back pauses the world when no panel is open to close instead
+fn toggle_pause(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    panels: Res<PanelState>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !input_map.just_pressed(Action::Back, &input) || panels.is_focused() {
+        return;
+    }
+
+    next_state.set(match state.get() {
+        GameState::Paused => GameState::InGame,
+        _ => GameState::Paused,
+    });
+}
+
+fn spawn_paused_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.5).into(),
+        ..default()
+    };
+
+    commands
+        .spawn(container_node)
+        .insert(PausedOverlay)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 48.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            for button in [MenuButton::Resume, MenuButton::Settings, MenuButton::Quit] {
+                spawn_menu_button(parent, button, font.clone());
+            }
+        });
+}
+
+// Panels opened from the pause menu draw in place of it, closing them brings it back
+fn hide_paused_overlay(
+    panels: Res<PanelState>,
+    mut overlays: Query<&mut Visibility, With<PausedOverlay>>,
+) {
+    for mut visibility in overlays.iter_mut() {
+        *visibility = if panels.is_focused() {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+fn despawn_paused_overlay(mut commands: Commands, overlays: Query<Entity, With<PausedOverlay>>) {
+    for overlay in overlays.iter() {
+        commands.entity(overlay).despawn_recursive();
+    }
+}
@@ -0,0 +1,196 @@
+use std::{collections::HashMap, fs};
+
+use bevy::prelude::*;
+
+use serde::Deserialize;
+
+use crate::components::Health;
+
+use super::{perception::Perception, Enemy, EnemyKind};
+
+const BEHAVIORS_PATH: &str = "assets/behaviors.json";
+
+// Composable tree node, leaves read the world while composites decide which branch runs
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BehaviorNode {
+    // Runs children in order, stopping at the first that does not succeed
+    Sequence(Vec<BehaviorNode>),
+    // Runs children in order, stopping at the first that does not fail
+    Selector(Vec<BehaviorNode>),
+    Condition(Condition),
+    Action(Action),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    // Only passes while the player is seen or heard
+    PlayerWithin(f32),
+    RemembersPlayer,
+    HeardNoise,
+    HealthBelow(f32),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    #[default]
+    Idle,
+    Wander,
+    Chase,
+    // Heads for where the player was last perceived
+    Search,
+    // Heads for the last noise heard
+    Investigate,
+    // Walks the entity's patrol route, idling without one
+    Patrol,
+    Flee,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+    Success,
+    Failure,
+    Running,
+}
+
+// What a tree can see of its entity each tick
+struct Blackboard {
+    player_distance: Option<f32>,
+    remembers_player: bool,
+    heard_noise: bool,
+    health_fraction: f32,
+}
+
+impl BehaviorNode {
+    fn name(&self) -> String {
+        match self {
+            BehaviorNode::Sequence(_) => "Sequence".to_string(),
+            BehaviorNode::Selector(_) => "Selector".to_string(),
+            BehaviorNode::Condition(condition) => format!("{condition:?}"),
+            BehaviorNode::Action(action) => format!("{action:?}"),
+        }
+    }
+
+    // Actions always keep running, the path to the one reached is recorded for debugging
+    fn tick(&self, board: &Blackboard, active: &mut Vec<String>) -> (Status, Option<Action>) {
+        active.push(self.name());
+
+        let result = match self {
+            BehaviorNode::Sequence(children) => {
+                Self::run_children(children, board, active, Status::Success)
+            }
+            BehaviorNode::Selector(children) => {
+                Self::run_children(children, board, active, Status::Failure)
+            }
+            BehaviorNode::Condition(condition) => {
+                let passed = match condition {
+                    Condition::PlayerWithin(radius) => board
+                        .player_distance
+                        .is_some_and(|distance| distance <= *radius),
+                    Condition::RemembersPlayer => board.remembers_player,
+                    Condition::HeardNoise => board.heard_noise,
+                    Condition::HealthBelow(fraction) => board.health_fraction < *fraction,
+                };
+
+                if passed {
+                    (Status::Success, None)
+                } else {
+                    (Status::Failure, None)
+                }
+            }
+            BehaviorNode::Action(action) => (Status::Running, Some(*action)),
+        };
+
+        if result.0 == Status::Failure {
+            active.pop();
+        }
+
+        result
+    }
+
+    // Composites continue past children returning the given status and stop at anything else
+    fn run_children(
+        children: &[BehaviorNode],
+        board: &Blackboard,
+        active: &mut Vec<String>,
+        pass: Status,
+    ) -> (Status, Option<Action>) {
+        for child in children {
+            let depth = active.len();
+            let (status, action) = child.tick(board, active);
+
+            if status != pass {
+                return (status, action);
+            }
+
+            active.truncate(depth);
+        }
+
+        (pass, None)
+    }
+}
+
+// Trees per enemy kind, read from the assets folder so they can be tuned without rebuilding
+#[derive(Resource)]
+pub struct BehaviorTrees(HashMap<String, BehaviorNode>);
+
+impl BehaviorTrees {
+    pub fn load() -> BehaviorTrees {
+        let trees = fs::read(BEHAVIORS_PATH)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|err| err.to_string()));
+
+        match trees {
+            Ok(trees) => BehaviorTrees(trees),
+            Err(err) => {
+                warn!("Failed to load behavior trees, enemies will idle! Err {err}");
+                BehaviorTrees(HashMap::new())
+            }
+        }
+    }
+
+    fn get(&self, kind: EnemyKind) -> Option<&BehaviorNode> {
+        self.0.get(kind.key())
+    }
+}
+
+// Result of the last tick, read by movement and the AI debug view
+#[derive(Component, Default)]
+pub struct Behavior {
+    pub action: Action,
+    pub active: String,
+}
+
+pub fn tick_behaviors(
+    trees: Res<BehaviorTrees>,
+    mut enemies: Query<(&Enemy, &Transform, &Health, &Perception, &mut Behavior)>,
+) {
+    for (enemy, transform, health, perception, mut behavior) in enemies.iter_mut() {
+        let Some(tree) = trees.get(enemy.kind) else {
+            continue;
+        };
+
+        let board = Blackboard {
+            player_distance: perception
+                .last_seen
+                .filter(|_| perception.sensing)
+                .map(|player| player.distance(transform.translation.truncate())),
+            remembers_player: perception.last_seen.is_some(),
+            heard_noise: perception.heard.is_some(),
+            health_fraction: health.current as f32 / health.max as f32,
+        };
+
+        let mut active = Vec::new();
+        let (_, action) = tree.tick(&board, &mut active);
+        let action = action.unwrap_or_default();
+
+        if behavior.action != action {
+            debug!("{:?} switched to {:?}", enemy.kind, action);
+        }
+
+        behavior.action = action;
+        behavior.active = active.join(" > ");
+    }
+}
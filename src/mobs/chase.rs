@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+use rand::Rng;
+
+use crate::{
+    components::Velocity,
+    world::{find_path, NavGrid},
+};
+
+use super::{
+    behavior::{Action, Behavior},
+    patrol::Patrol,
+    perception::Perception,
+    tactics::AttackSlot,
+    Enemy,
+};
+
+// Seconds between path searches, the player rarely moves far enough within one interval to matter
+const REPATH_INTERVAL: f32 = 0.5;
+
+// How far wandering enemies stray from where they spawned
+const WANDER_RADIUS: f32 = 96.;
+
+// Distance at which a patrol waypoint counts as reached
+const PATROL_REACH: f32 = 12.;
+
+// How far fleeing enemies try to put themselves from the player
+const FLEE_DISTANCE: f32 = 160.;
+
+// Route an enemy is walking, expanded cells are kept for the nav debug overlay
+#[derive(Component)]
+pub struct NavPath {
+    pub waypoints: Vec<Vec2>,
+    pub expanded: Vec<IVec2>,
+    home: Vec2,
+    timer: Timer,
+}
+
+impl NavPath {
+    pub fn new(home: Vec2) -> NavPath {
+        NavPath {
+            waypoints: Vec::new(),
+            expanded: Vec::new(),
+            home,
+            timer: Timer::from_seconds(REPATH_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+// Turns each enemy's current behavior action into a destination and searches a path to it
+pub fn plan_paths(
+    time: Res<Time>,
+    grid: Res<NavGrid>,
+    mut enemies: Query<(
+        Entity,
+        &Enemy,
+        &Transform,
+        &Behavior,
+        &Perception,
+        &AttackSlot,
+        &mut NavPath,
+    )>,
+    mut patrols: Query<&mut Patrol>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for (entity, enemy, transform, behavior, perception, slot, mut path) in enemies.iter_mut() {
+        if !path.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+
+        // Enemies only ever move relative to where they believe the player is
+        let destination = match (behavior.action, perception.last_seen) {
+            // Chasers head for their claimed slot, falling back to the player when every slot is taken
+            (Action::Chase, Some(player)) => Some(slot.0.unwrap_or(player)),
+            (Action::Search, Some(player)) => Some(player),
+            (Action::Investigate, _) => perception.heard,
+            // Guards move on to the next waypoint once they reach one, or find it unreachable
+            (Action::Patrol, _) => patrols.get_mut(entity).ok().and_then(|mut patrol| {
+                let waypoint = patrol.waypoint()?;
+
+                if position.distance(waypoint) <= PATROL_REACH {
+                    patrol.advance();
+                } else if path.waypoints.is_empty() && !path.expanded.is_empty() {
+                    path.expanded.clear();
+                    patrol.advance();
+                }
+
+                patrol.waypoint()
+            }),
+            (Action::Flee, Some(player)) => {
+                Some(position + (position - player).normalize_or_zero() * FLEE_DISTANCE)
+            }
+            // Wanderers only pick somewhere new once they reach their last spot
+            (Action::Wander, _) if path.waypoints.is_empty() => {
+                let offset = Vec2::new(
+                    rng.gen_range(-WANDER_RADIUS..WANDER_RADIUS),
+                    rng.gen_range(-WANDER_RADIUS..WANDER_RADIUS),
+                );
+                Some(path.home + offset)
+            }
+            (Action::Wander, _) => continue,
+            _ => None,
+        };
+
+        let Some(destination) = destination else {
+            path.waypoints.clear();
+            path.expanded.clear();
+            continue;
+        };
+
+        let search = find_path(&grid, position, destination, enemy.movement);
+        path.waypoints = search.waypoints;
+        path.expanded = search.expanded;
+    }
+}
+
+pub fn follow_paths(
+    time: Res<Time>,
+    mut enemies: Query<(
+        &Enemy,
+        &mut Transform,
+        &mut NavPath,
+        &mut Perception,
+        &mut Velocity,
+    )>,
+) {
+    for (enemy, mut transform, mut path, mut perception, mut velocity) in enemies.iter_mut() {
+        // Knockback is added back on top every frame while it lasts
+        velocity.dx = 0.;
+        velocity.dy = 0.;
+
+        let Some(next) = path.waypoints.first().copied() else {
+            continue;
+        };
+
+        let position = transform.translation.truncate();
+        let step = enemy.kind.speed() * time.delta_seconds();
+        let offset = next - position;
+        perception.facing = offset.try_normalize().unwrap_or(perception.facing);
+
+        if offset.length() <= step {
+            transform.translation = next.extend(transform.translation.z);
+            path.waypoints.remove(0);
+        } else {
+            transform.translation += (offset.normalize() * step).extend(0.);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{Health, Velocity},
+    menu::GameState,
+    physics::Hitbox,
+    world::{ChunkOccupant, Movement, NavOccupant, OccupantKind, SpawnSafely},
+};
+
+pub mod behavior;
+pub mod chase;
+pub mod patrol;
+pub mod perception;
+pub mod separation;
+pub mod spawner;
+pub mod tactics;
+
+pub struct MobsPlugin;
+
+impl Plugin for MobsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(behavior::BehaviorTrees::load())
+            .add_event::<spawner::SpawnerDestroyed>()
+            .add_event::<perception::Noise>()
+            .add_systems(
+                Update,
+                (
+                    spawner::tick_spawners,
+                    spawner::destroy_spawners,
+                    spawner::forget_despawned_mobs,
+                    spawner::handle_spawn_commands,
+                    (
+                        perception::hear_noises,
+                        perception::perceive_player,
+                        behavior::tick_behaviors,
+                        tactics::assign_attack_slots,
+                        chase::plan_paths,
+                        chase::follow_paths,
+                        separation::separate_crowds,
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(GameState::InGame)),
+            )
+            // After Update, so the nests of a reloaded chunk are placed by the time it catches up
+            .add_systems(
+                PostUpdate,
+                spawner::catch_up_spawners.run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnemyKind {
+    Slime,
+    Wraith,
+    Guard,
+    Eel,
+}
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 4] = [
+        EnemyKind::Slime,
+        EnemyKind::Wraith,
+        EnemyKind::Guard,
+        EnemyKind::Eel,
+    ];
+
+    pub fn from_key(key: &str) -> Option<EnemyKind> {
+        EnemyKind::ALL.into_iter().find(|kind| kind.key() == key)
+    }
+
+    fn max_health(&self) -> u8 {
+        match self {
+            EnemyKind::Slime => 20,
+            EnemyKind::Wraith => 40,
+            EnemyKind::Guard => 50,
+            EnemyKind::Eel => 30,
+        }
+    }
+
+    // Name of the kind's tree in the behaviors file
+    pub fn key(&self) -> &'static str {
+        match self {
+            EnemyKind::Slime => "slime",
+            EnemyKind::Wraith => "wraith",
+            EnemyKind::Guard => "guard",
+            EnemyKind::Eel => "eel",
+        }
+    }
+
+    // Damage dealt to the player on touching it
+    pub fn contact_damage(&self) -> u8 {
+        match self {
+            EnemyKind::Slime => 5,
+            EnemyKind::Wraith => 8,
+            EnemyKind::Guard => 10,
+            EnemyKind::Eel => 6,
+        }
+    }
+
+    fn speed(&self) -> f32 {
+        match self {
+            EnemyKind::Slime => 48.,
+            EnemyKind::Wraith => 72.,
+            EnemyKind::Guard => 56.,
+            EnemyKind::Eel => 64.,
+        }
+    }
+
+    // Wraiths drift over terrain and eels never leave the water
+    fn movement(&self) -> Movement {
+        match self {
+            EnemyKind::Slime | EnemyKind::Guard => Movement::Ground,
+            EnemyKind::Wraith => Movement::Flying,
+            EnemyKind::Eel => Movement::Aquatic,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            EnemyKind::Slime => Color::rgb(0.3, 0.8, 0.3),
+            EnemyKind::Wraith => Color::rgb(0.6, 0.5, 0.8),
+            EnemyKind::Guard => Color::rgb(0.8, 0.6, 0.3),
+            EnemyKind::Eel => Color::rgb(0.2, 0.5, 0.7),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Enemy {
+    pub kind: EnemyKind,
+    pub movement: Movement,
+}
+
+pub fn spawn_enemy(commands: &mut Commands, kind: EnemyKind, position: Vec2) -> Entity {
+    let sprite = SpriteBundle {
+        sprite: Sprite {
+            color: kind.color(),
+            custom_size: Some(Vec2::new(16., 16.)),
+            ..default()
+        },
+        transform: Transform::from_translation(position.extend(1.)),
+        ..default()
+    };
+
+    commands
+        .spawn(sprite)
+        .insert(Enemy {
+            kind,
+            movement: kind.movement(),
+        })
+        .insert(Health {
+            current: kind.max_health(),
+            max: kind.max_health(),
+        })
+        .insert(ChunkOccupant(OccupantKind::Enemy))
+        .insert(perception::Perception::new(kind))
+        .insert(behavior::Behavior::default())
+        .insert(tactics::AttackSlot::default())
+        .insert(chase::NavPath::new(position))
+        .insert(SpawnSafely(kind.movement()))
+        .insert(separation::Separation { radius: 8. })
+        .insert(NavOccupant::Dynamic)
+        // Paths move the transform directly, velocity only carries knockback
+        .insert(Velocity { dx: 0., dy: 0. })
+        .insert(Hitbox {
+            size: Vec2::new(12., 12.),
+            offset: Vec2::ZERO,
+        })
+        .id()
+}
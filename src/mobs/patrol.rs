@@ -0,0 +1,23 @@
+use bevy::prelude::*;
+
+// Loop of waypoints a guard walks while nothing has its attention
+#[derive(Component)]
+pub struct Patrol {
+    route: Vec<Vec2>,
+    next: usize,
+}
+
+impl Patrol {
+    pub fn new(route: Vec<Vec2>, start: usize) -> Patrol {
+        let next = start % route.len().max(1);
+        Patrol { route, next }
+    }
+
+    pub fn waypoint(&self) -> Option<Vec2> {
+        self.route.get(self.next).copied()
+    }
+
+    pub fn advance(&mut self) {
+        self.next = (self.next + 1) % self.route.len().max(1);
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+
+use crate::{player::Player, world::NavGrid};
+
+use super::{Enemy, EnemyKind};
+
+// Seconds an enemy keeps hunting a player it lost track of
+const MEMORY_SECONDS: f32 = 6.;
+
+// Distance at which a searching enemy decides the last known position is empty
+const SEARCH_REACH: f32 = 16.;
+
+// Loud events enemies within the radius turn to investigate
+#[derive(Event)]
+pub struct Noise {
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+// What an enemy knows about the player, tracking only holds while they can see or hear them
+#[derive(Component)]
+pub struct Perception {
+    pub facing: Vec2,
+    pub vision_range: f32,
+    // Half angle of the vision cone in radians
+    pub vision_angle: f32,
+    pub hearing_radius: f32,
+    pub sensing: bool,
+    pub last_seen: Option<Vec2>,
+    pub heard: Option<Vec2>,
+    memory: Timer,
+    curiosity: Timer,
+}
+
+impl Perception {
+    pub fn new(kind: EnemyKind) -> Perception {
+        let (vision_range, vision_degrees, hearing_radius) = match kind {
+            EnemyKind::Slime => (192., 60., 64.),
+            EnemyKind::Wraith => (320., 35., 128.),
+            EnemyKind::Guard => (224., 50., 96.),
+            EnemyKind::Eel => (160., 70., 96.),
+        };
+
+        Perception {
+            facing: Vec2::NEG_Y,
+            vision_range,
+            vision_angle: f32::to_radians(vision_degrees),
+            hearing_radius,
+            sensing: false,
+            last_seen: None,
+            heard: None,
+            memory: Timer::from_seconds(MEMORY_SECONDS, TimerMode::Once),
+            curiosity: Timer::from_seconds(MEMORY_SECONDS * 2., TimerMode::Once),
+        }
+    }
+
+    fn sees(&self, grid: &NavGrid, position: Vec2, target: Vec2) -> bool {
+        let offset = target - position;
+
+        offset.length() <= self.vision_range
+            && self.facing.angle_between(offset).abs() <= self.vision_angle
+            && grid.line_of_sight(position, target)
+    }
+}
+
+pub fn hear_noises(
+    time: Res<Time>,
+    mut noises: EventReader<Noise>,
+    mut enemies: Query<(&Transform, &mut Perception), With<Enemy>>,
+) {
+    let noises: Vec<&Noise> = noises.read().collect();
+
+    for (transform, mut perception) in enemies.iter_mut() {
+        let position = transform.translation.truncate();
+
+        let heard = noises
+            .iter()
+            .filter(|noise| noise.position.distance(position) <= noise.radius)
+            .min_by(|a, b| {
+                a.position
+                    .distance(position)
+                    .total_cmp(&b.position.distance(position))
+            });
+
+        if let Some(noise) = heard {
+            perception.heard = Some(noise.position);
+            perception.curiosity.reset();
+            continue;
+        }
+
+        let Some(heard) = perception.heard else {
+            continue;
+        };
+
+        // Investigations end once the source is reached or interest runs out
+        perception.curiosity.tick(time.delta());
+        if perception.curiosity.finished() || position.distance(heard) <= SEARCH_REACH {
+            perception.heard = None;
+        }
+    }
+}
+
+pub fn perceive_player(
+    time: Res<Time>,
+    grid: Res<NavGrid>,
+    player: Query<&Transform, With<Player>>,
+    mut enemies: Query<(&Transform, &mut Perception), With<Enemy>>,
+) {
+    let player = player
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation.truncate());
+
+    for (transform, mut perception) in enemies.iter_mut() {
+        let position = transform.translation.truncate();
+
+        let sensed = player.filter(|player| {
+            position.distance(*player) <= perception.hearing_radius
+                || perception.sees(&grid, position, *player)
+        });
+
+        perception.sensing = sensed.is_some();
+
+        if let Some(player) = sensed {
+            perception.last_seen = Some(player);
+            perception.memory.reset();
+            continue;
+        }
+
+        let Some(last_seen) = perception.last_seen else {
+            continue;
+        };
+
+        // Forgotten over time, or given up on once the spot turns out to be empty
+        perception.memory.tick(time.delta());
+        if perception.memory.finished() || position.distance(last_seen) <= SEARCH_REACH {
+            debug!("Enemy lost track of the player");
+            perception.last_seen = None;
+        }
+    }
+}
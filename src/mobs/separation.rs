@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::world::NavGrid;
+
+use super::Enemy;
+
+// Speed at which two fully overlapping entities are pushed apart, gentle enough to read as shuffling
+const SEPARATION_SPEED: f32 = 40.;
+
+// Personal space kept around an entity in a crowd
+#[derive(Component)]
+pub struct Separation {
+    pub radius: f32,
+}
+
+// Neighbors are bucketed by nav cell so each entity only checks the cells around it
+pub fn separate_crowds(
+    time: Res<Time>,
+    grid: Res<NavGrid>,
+    mut crowd: Query<(Entity, &Enemy, &Separation, &mut Transform)>,
+) {
+    let mut buckets: HashMap<IVec2, Vec<(Entity, Vec2, f32)>> = HashMap::new();
+
+    for (entity, _, separation, transform) in crowd.iter() {
+        let position = transform.translation.truncate();
+        buckets
+            .entry(NavGrid::cell_of(position))
+            .or_default()
+            .push((entity, position, separation.radius));
+    }
+
+    for (entity, enemy, separation, mut transform) in crowd.iter_mut() {
+        let position = transform.translation.truncate();
+        let cell = NavGrid::cell_of(position);
+
+        let mut push = Vec2::ZERO;
+
+        for x in -1..=1 {
+            for y in -1..=1 {
+                let Some(neighbors) = buckets.get(&(cell + IVec2::new(x, y))) else {
+                    continue;
+                };
+
+                for (other, other_position, other_radius) in neighbors {
+                    if *other == entity {
+                        continue;
+                    }
+
+                    let offset = position - *other_position;
+                    let reach = separation.radius + other_radius;
+                    let distance = offset.length();
+
+                    if distance >= reach {
+                        continue;
+                    }
+
+                    // Entities stacked exactly on top of each other are split along an arbitrary but stable axis
+                    let away = offset
+                        .try_normalize()
+                        .unwrap_or(Vec2::from_angle(entity.index() as f32));
+                    push += away * (1. - distance / reach);
+                }
+            }
+        }
+
+        if push == Vec2::ZERO {
+            continue;
+        }
+
+        let moved = position + push.clamp_length_max(1.) * SEPARATION_SPEED * time.delta_seconds();
+
+        // Never shoved somewhere the entity couldn't walk itself
+        if grid.cost(NavGrid::cell_of(moved), enemy.movement).is_some() {
+            transform.translation.x = moved.x;
+            transform.translation.y = moved.y;
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{Dead, Health, Stack},
+    items::dropped::DropItem,
+    player::Player,
+    ui::console::{ConsoleCommand, ConsoleOutput},
+    world::{ChunkCatchUp, Claims, PropOwner, WorldRules},
+};
+
+use super::{perception::Noise, spawn_enemy, EnemyKind};
+
+// How far the crash of a destroyed spawner carries
+const DESTRUCTION_NOISE_RADIUS: f32 = 384.;
+
+// Nest or portal that keeps spawning enemies around it until destroyed
+#[derive(Component)]
+pub struct Spawner {
+    pub kind: EnemyKind,
+    pub timer: Timer,
+    // Rate limit on how many of its spawns may be alive at once
+    pub max_alive: usize,
+    pub alive: Vec<Entity>,
+    pub loot: Stack,
+}
+
+impl Spawner {
+    pub fn new(kind: EnemyKind, interval: f32, max_alive: usize, loot: Stack) -> Spawner {
+        Spawner {
+            kind,
+            timer: Timer::new(Duration::from_secs_f32(interval), TimerMode::Repeating),
+            max_alive,
+            alive: Vec::new(),
+            loot,
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct SpawnerDestroyed {
+    pub position: Vec2,
+    // Chunk whose camp the spawner belonged to
+    pub camp: Option<Entity>,
+}
+
+pub fn spawn_spawner(
+    commands: &mut Commands,
+    spawner: Spawner,
+    health: u8,
+    portal: bool,
+    position: Vec2,
+) -> Entity {
+    let color = if portal {
+        Color::rgb(0.35, 0.15, 0.45)
+    } else {
+        Color::rgb(0.4, 0.15, 0.15)
+    };
+
+    let sprite = SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::new(28., 28.)),
+            ..default()
+        },
+        transform: Transform::from_translation(position.extend(0.8)),
+        ..default()
+    };
+
+    commands
+        .spawn(sprite)
+        .insert(spawner)
+        .insert(Health {
+            current: health,
+            max: health,
+        })
+        .id()
+}
+
+pub fn tick_spawners(
+    mut commands: Commands,
+    time: Res<Time>,
+    rules: Res<WorldRules>,
+    claims: Res<Claims>,
+    mut spawners: Query<(&mut Spawner, &Transform), Without<Dead>>,
+) {
+    if !rules.enemy_spawning {
+        return;
+    }
+
+    for (mut spawner, transform) in spawners.iter_mut() {
+        spawner.timer.tick(time.delta());
+
+        if !spawner.timer.just_finished() || spawner.alive.len() >= spawner.max_alive {
+            continue;
+        }
+
+        // Spawners left standing in a chunk claimed since go quiet
+        if claims.protects(transform.translation.truncate()) {
+            continue;
+        }
+
+        spawn_from(&mut commands, &mut spawner, transform);
+    }
+}
+
+fn spawn_from(commands: &mut Commands, spawner: &mut Spawner, transform: &Transform) {
+    // Spread spawns around the spawner so they don't stack
+    let angle = spawner.alive.len() as f32 * 2.4;
+    let offset = Vec2::new(angle.cos(), angle.sin()) * 24.;
+
+    let enemy = spawn_enemy(
+        commands,
+        spawner.kind,
+        transform.translation.truncate() + offset,
+    );
+    spawner.alive.push(enemy);
+}
+
+// Spawners placed again with their chunk run through the time it was unloaded, spawning what they
+// would have meanwhile up to their limit
+pub fn catch_up_spawners(
+    mut commands: Commands,
+    rules: Res<WorldRules>,
+    claims: Res<Claims>,
+    mut catch_up: EventReader<ChunkCatchUp>,
+    mut spawners: Query<(&mut Spawner, &Transform, &PropOwner), Without<Dead>>,
+) {
+    for event in catch_up.read() {
+        for (mut spawner, transform, owner) in spawners.iter_mut() {
+            if owner.chunk() != event.chunk {
+                continue;
+            }
+
+            spawner.timer.tick(event.elapsed());
+
+            if !rules.enemy_spawning || claims.protects(transform.translation.truncate()) {
+                continue;
+            }
+
+            let owed = (spawner.timer.times_finished_this_tick() as usize)
+                .min(spawner.max_alive.saturating_sub(spawner.alive.len()));
+
+            debug!(
+                "Spawner caught up {} ticks, spawning {}",
+                event.elapsed_ticks, owed
+            );
+
+            for _ in 0..owed {
+                spawn_from(&mut commands, &mut spawner, transform);
+            }
+        }
+    }
+}
+
+pub fn forget_despawned_mobs(mut spawners: Query<&mut Spawner>, alive: Query<(), Without<Dead>>) {
+    for mut spawner in spawners.iter_mut() {
+        spawner.alive.retain(|enemy| alive.contains(*enemy));
+    }
+}
+
+pub fn destroy_spawners(
+    mut commands: Commands,
+    spawners: Query<(Entity, &Spawner, &Transform, Option<&PropOwner>), Added<Dead>>,
+    mut drops: EventWriter<DropItem>,
+    mut destroyed: EventWriter<SpawnerDestroyed>,
+    mut noises: EventWriter<Noise>,
+) {
+    for (entity, spawner, transform, owner) in spawners.iter() {
+        info!("Spawner destroyed");
+
+        noises.send(Noise {
+            position: transform.translation.truncate(),
+            radius: DESTRUCTION_NOISE_RADIUS,
+        });
+
+        destroyed.send(SpawnerDestroyed {
+            position: transform.translation.truncate(),
+            camp: owner.map(PropOwner::chunk),
+        });
+
+        drops.send(DropItem {
+            stack: spawner.loot,
+            position: transform.translation.truncate(),
+        });
+
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Console command spawning an enemy next to the player, for trying out kinds that rarely appear
+pub fn handle_spawn_commands(
+    mut commands: Commands,
+    mut console: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    player: Query<&Transform, With<Player>>,
+) {
+    for command in console.read() {
+        if command.name != "spawn" {
+            continue;
+        }
+
+        let Some(kind) = command
+            .args
+            .first()
+            .and_then(|key| EnemyKind::from_key(key))
+        else {
+            let kinds: Vec<&str> = EnemyKind::ALL.iter().map(|kind| kind.key()).collect();
+            output.send(ConsoleOutput(format!("Usage: spawn <{}>", kinds.join("|"))));
+            continue;
+        };
+
+        let Ok(transform) = player.get_single() else {
+            continue;
+        };
+
+        spawn_enemy(
+            &mut commands,
+            kind,
+            transform.translation.truncate() + Vec2::new(48., 0.),
+        );
+        output.send(ConsoleOutput(format!("Spawned {}", kind.key())));
+    }
+}
@@ -0,0 +1,61 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::world::NavGrid;
+
+use super::{
+    behavior::{Action, Behavior},
+    perception::Perception,
+};
+
+// Rings of slots around the player as (radius, count), outer rings hold enemies waiting their turn
+const SLOT_RINGS: [(f32, usize); 2] = [(40., 8), (88., 12)];
+
+// Position around the player this enemy has claimed while chasing
+#[derive(Component, Default)]
+pub struct AttackSlot(pub Option<Vec2>);
+
+// Closest chasers claim first, each taking the nearest free walkable slot so squads spread around the player
+pub fn assign_attack_slots(
+    grid: Res<NavGrid>,
+    mut enemies: Query<(Entity, &Transform, &Behavior, &Perception, &mut AttackSlot)>,
+) {
+    let mut chasers = Vec::new();
+
+    for (entity, transform, behavior, perception, mut slot) in enemies.iter_mut() {
+        match (behavior.action, perception.last_seen) {
+            (Action::Chase, Some(target)) => {
+                let position = transform.translation.truncate();
+                chasers.push((entity, position, target, position.distance(target)));
+            }
+            _ => slot.0 = None,
+        }
+    }
+
+    chasers.sort_by(|a, b| a.3.total_cmp(&b.3));
+
+    let mut claimed: Vec<Vec2> = Vec::new();
+
+    for (entity, position, target, _) in chasers {
+        let slots = SLOT_RINGS.iter().flat_map(|(radius, count)| {
+            (0..*count).map(move |index| {
+                target + Vec2::from_angle(TAU * index as f32 / *count as f32) * *radius
+            })
+        });
+
+        // Slots a tile apart from every claimed one, so neighbors on the snapped grid stay distinct
+        let free = slots
+            .filter(|slot| grid.is_walkable(NavGrid::cell_of(*slot)))
+            .filter(|slot| claimed.iter().all(|other| other.distance(*slot) >= 24.))
+            .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)));
+
+        if let Some(free) = free {
+            claimed.push(free);
+        }
+
+        if let Ok((_, _, _, _, mut slot)) = enemies.get_mut(entity) {
+            slot.0 = free;
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::{collections::HashMap, fs};
+
+use bevy::prelude::*;
+
+use serde::Deserialize;
+
+use crate::ui::{Panel, PanelRoot, PanelState, TogglePanel};
+
+use super::Npc;
+
+const DIALOGUE_PATH: &str = "assets/dialogue.json";
+
+// Conversation tree of an NPC, starting at the start node and branching on the player's choices
+#[derive(Component, Clone, Debug, Deserialize)]
+pub struct Dialogue {
+    pub speaker: String,
+    pub start: String,
+    pub nodes: HashMap<String, DialogueNode>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DialogueNode {
+    pub text: String,
+    // Without choices the node just offers a way out
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DialogueChoice {
+    pub text: String,
+    // Node the choice leads to, the conversation ends without one
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+impl Dialogue {
+    // Every node a choice leads to has to exist, a broken tree would strand the player
+    fn missing_node(&self) -> Option<&String> {
+        let mut targets = self
+            .nodes
+            .values()
+            .flat_map(|node| node.choices.iter())
+            .filter_map(|choice| choice.next.as_ref());
+
+        std::iter::once(&self.start)
+            .chain(&mut targets)
+            .find(|name| !self.nodes.contains_key(*name))
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DialogueDefinitions(HashMap<String, Dialogue>);
+
+impl DialogueDefinitions {
+    pub fn load() -> DialogueDefinitions {
+        let dialogues = fs::read(DIALOGUE_PATH)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| {
+                serde_json::from_slice::<HashMap<String, Dialogue>>(&bytes)
+                    .map_err(|err| err.to_string())
+            });
+
+        match dialogues {
+            Ok(mut dialogues) => {
+                dialogues.retain(|name, dialogue| match dialogue.missing_node() {
+                    Some(node) => {
+                        warn!("Dropping dialogue {name}, it leads to missing node {node}");
+                        false
+                    }
+                    None => true,
+                });
+
+                DialogueDefinitions(dialogues)
+            }
+            Err(err) => {
+                warn!("Failed to load dialogue, villagers will have nothing to say! Err {err}");
+                DialogueDefinitions::default()
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Dialogue> {
+        self.0.get(name)
+    }
+}
+
+// NPC being talked to and the node of its dialogue on screen
+#[derive(Resource, Default)]
+pub struct Conversation(pub Option<(Entity, String)>);
+
+// Button of a choice on screen, pressing it follows the choice's branch
+#[derive(Component)]
+pub struct ChoiceButton(Option<String>);
+
+#[derive(Component)]
+pub struct DialogueBox;
+
+pub fn spawn_dialogue_panel(mut commands: Commands) {
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::FlexEnd,
+            padding: UiRect::bottom(Val::Px(48.)),
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    let box_node = NodeBundle {
+        style: Style {
+            width: Val::Px(480.),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(6.),
+            padding: UiRect::all(Val::Px(12.)),
+            ..default()
+        },
+        background_color: Color::rgba(0.05, 0.05, 0.08, 0.9).into(),
+        ..default()
+    };
+
+    commands
+        .spawn(container_node)
+        .insert(PanelRoot(Panel::Dialogue))
+        .with_children(|parent| {
+            parent.spawn(box_node).insert(DialogueBox);
+        });
+}
+
+// Choices either move the conversation along or end it, closing the panel
+pub fn choose_dialogue_options(
+    mut conversation: ResMut<Conversation>,
+    choices: Query<(&Interaction, &ChoiceButton), Changed<Interaction>>,
+    mut toggles: EventWriter<TogglePanel>,
+) {
+    let Some((_, choice)) = choices
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+    else {
+        return;
+    };
+
+    match (&choice.0, conversation.0.as_mut()) {
+        (Some(next), Some((_, node))) => *node = next.clone(),
+        _ => {
+            conversation.0 = None;
+            toggles.send(TogglePanel(Panel::Dialogue));
+        }
+    }
+}
+
+// Closing the panel any other way, or the NPC unloading mid sentence, ends the conversation too
+pub fn end_abandoned_conversations(
+    state: Res<PanelState>,
+    mut conversation: ResMut<Conversation>,
+    npcs: Query<(), With<Npc>>,
+    mut toggles: EventWriter<TogglePanel>,
+) {
+    let Some((npc, _)) = conversation.0 else {
+        return;
+    };
+
+    if !npcs.contains(npc) {
+        conversation.0 = None;
+
+        if state.active == Some(Panel::Dialogue) {
+            toggles.send(TogglePanel(Panel::Dialogue));
+        }
+    } else if state.is_changed() && state.active != Some(Panel::Dialogue) {
+        conversation.0 = None;
+    }
+}
+
+pub fn update_dialogue_box(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    conversation: Res<Conversation>,
+    dialogues: Query<&Dialogue>,
+    dialogue_box: Query<Entity, With<DialogueBox>>,
+) {
+    if !conversation.is_changed() {
+        return;
+    }
+
+    let Ok(dialogue_box) = dialogue_box.get_single() else {
+        return;
+    };
+
+    commands.entity(dialogue_box).despawn_descendants();
+
+    let Some((dialogue, node)) = conversation.0.as_ref().and_then(|(npc, node)| {
+        let dialogue = dialogues.get(*npc).ok()?;
+        Some((dialogue, dialogue.nodes.get(node)?))
+    }) else {
+        return;
+    };
+
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let style = |size: f32, color: Color| TextStyle {
+        font: font.clone(),
+        font_size: size,
+        color,
+    };
+
+    // A node without choices still needs a way out
+    let farewell = [DialogueChoice {
+        text: "Goodbye".into(),
+        next: None,
+    }];
+    let choices = if node.choices.is_empty() {
+        &farewell[..]
+    } else {
+        &node.choices[..]
+    };
+
+    commands.entity(dialogue_box).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            dialogue.speaker.clone(),
+            style(18., Color::rgb(0.95, 0.8, 0.45)),
+        ));
+
+        parent.spawn(
+            TextBundle::from_section(node.text.clone(), style(18., Color::WHITE)).with_style(
+                Style {
+                    max_width: Val::Px(456.),
+                    ..default()
+                },
+            ),
+        );
+
+        for (idx, choice) in choices.iter().enumerate() {
+            let button_bundle = ButtonBundle {
+                style: Style {
+                    padding: UiRect::horizontal(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.1, 0.1, 0.15, 0.9).into(),
+                ..default()
+            };
+
+            parent
+                .spawn(button_bundle)
+                .insert(ChoiceButton(choice.next.clone()))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        format!("{}. {}", idx + 1, choice.text),
+                        style(16., Color::rgb(0.8, 0.85, 1.)),
+                    ));
+                });
+        }
+    });
+}
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::{Action, InputMap},
+    menu::GameState,
+    player::Player,
+    ui::{Panel, PanelState, TogglePanel},
+    world::{Movement, NavOccupant, SpawnSafely},
+};
+
+use self::dialogue::{Conversation, Dialogue, DialogueDefinitions};
+
+pub mod dialogue;
+
+// Distance from an NPC the player can strike up a conversation from
+const TALK_REACH: f32 = 40.;
+
+pub struct NpcPlugin;
+
+impl Plugin for NpcPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DialogueDefinitions::load())
+            .init_resource::<Conversation>()
+            .add_systems(Startup, dialogue::spawn_dialogue_panel)
+            .add_systems(
+                Update,
+                (
+                    talk_to_npcs,
+                    dialogue::choose_dialogue_options,
+                    dialogue::end_abandoned_conversations,
+                    dialogue::update_dialogue_box,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+// Villagers stand where they were placed and talk when spoken to
+#[derive(Component)]
+pub struct Npc;
+
+pub fn spawn_npc(commands: &mut Commands, dialogue: Dialogue, position: Vec2) -> Entity {
+    let sprite = SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgb(0.85, 0.7, 0.5),
+            custom_size: Some(Vec2::new(14., 16.)),
+            ..default()
+        },
+        transform: Transform::from_translation(position.extend(1.)),
+        ..default()
+    };
+
+    commands
+        .spawn(sprite)
+        .insert(Npc)
+        .insert(dialogue)
+        .insert(NavOccupant::Dynamic)
+        .insert(SpawnSafely(Movement::Ground))
+        .id()
+}
+
+// The nearest NPC in reach answers, starting its dialogue from the top
+fn talk_to_npcs(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    state: Res<PanelState>,
+    player: Query<&Transform, With<Player>>,
+    npcs: Query<(Entity, &Transform, &Dialogue), With<Npc>>,
+    mut conversation: ResMut<Conversation>,
+    mut toggles: EventWriter<TogglePanel>,
+) {
+    if !input_map.just_pressed(Action::Interact, &input) || state.is_focused() {
+        return;
+    }
+
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    let position = player.translation.truncate();
+
+    let Some((npc, _, dialogue)) = npcs
+        .iter()
+        .map(|(npc, transform, dialogue)| {
+            (
+                npc,
+                transform.translation.truncate().distance(position),
+                dialogue,
+            )
+        })
+        .filter(|(_, distance, _)| *distance <= TALK_REACH)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+    else {
+        return;
+    };
+
+    info!("Talking to {}", dialogue.speaker);
+
+    conversation.0 = Some((npc, dialogue.start.clone()));
+    toggles.send(TogglePanel(Panel::Dialogue));
+}
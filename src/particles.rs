@@ -0,0 +1,298 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use rand::Rng;
+
+use crate::{combat::Hurt, components::Velocity, menu::GameState, player::Player, pool::Pool};
+
+// Particles kept around for reuse, enough for weather plus a few bursts on top
+const POOL_LIMIT: usize = 512;
+
+// Distance walked between puffs of dust
+const STRIDE: f32 = 28.;
+
+// Below the player's center, where its feet are
+const FEET_OFFSET: f32 = 18.;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ParticleBurst>()
+            .add_event::<Hurt>()
+            .insert_resource(Pool::<Particle>::new(POOL_LIMIT))
+            .add_systems(
+                Update,
+                (
+                    spark_on_hits,
+                    dust_on_footsteps,
+                    emit_bursts,
+                    run_spawners,
+                    move_particles,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+// How the particles of an effect look and move, each picks its own speed and lifetime in the ranges
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleEffect {
+    pub color: Color,
+    pub size: f32,
+    // Particles per burst, spawners emit one at a time
+    pub count: usize,
+    pub speed: (f32, f32),
+    // Angle particles leave at, counter clockwise from the right, and how far they may stray from it
+    pub angle: f32,
+    pub spread: f32,
+    pub lifetime: (f32, f32),
+    // Pull towards the bottom of the screen, negative floats upwards
+    pub gravity: f32,
+}
+
+impl ParticleEffect {
+    pub const SPARKS: ParticleEffect = ParticleEffect {
+        color: Color::rgb(1., 0.85, 0.4),
+        size: 2.,
+        count: 8,
+        speed: (60., 120.),
+        angle: 0.,
+        spread: TAU,
+        lifetime: (0.15, 0.3),
+        gravity: 0.,
+    };
+
+    pub const DUST: ParticleEffect = ParticleEffect {
+        color: Color::rgba(0.7, 0.62, 0.5, 0.7),
+        size: 3.,
+        count: 3,
+        speed: (8., 20.),
+        angle: TAU / 4.,
+        spread: TAU / 2.,
+        lifetime: (0.3, 0.5),
+        gravity: -10.,
+    };
+
+    pub const LEAVES: ParticleEffect = ParticleEffect {
+        color: Color::rgb(0.55, 0.7, 0.25),
+        size: 3.,
+        count: 1,
+        speed: (20., 40.),
+        angle: TAU * 0.6,
+        spread: 0.6,
+        lifetime: (3., 5.),
+        gravity: 4.,
+    };
+
+    pub const RAIN: ParticleEffect = ParticleEffect {
+        color: Color::rgba(0.6, 0.7, 0.9, 0.6),
+        size: 2.,
+        count: 1,
+        speed: (220., 260.),
+        angle: TAU * 0.72,
+        spread: 0.1,
+        lifetime: (0.6, 0.9),
+        gravity: 0.,
+    };
+
+    pub const SNOW: ParticleEffect = ParticleEffect {
+        color: Color::rgba(0.95, 0.95, 1., 0.9),
+        size: 2.,
+        count: 1,
+        speed: (15., 30.),
+        angle: TAU * 0.75,
+        spread: 0.4,
+        lifetime: (4., 6.),
+        gravity: 0.,
+    };
+}
+
+// One off burst of an effect, for anything that happens at a single moment
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ParticleBurst {
+    pub position: Vec2,
+    pub effect: ParticleEffect,
+}
+
+// Emits particles of its effect at random points around the entity for as long as it carries this
+#[derive(Component, Clone, Debug)]
+pub struct ParticleSpawner {
+    pub effect: ParticleEffect,
+    // Particles per second
+    pub rate: f32,
+    // Half the size of the area particles appear in
+    pub area: Vec2,
+    owed: f32,
+}
+
+impl ParticleSpawner {
+    pub fn new(effect: ParticleEffect, rate: f32, area: Vec2) -> ParticleSpawner {
+        ParticleSpawner {
+            effect,
+            rate,
+            area,
+            owed: 0.,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Particle {
+    velocity: Vec2,
+    gravity: f32,
+    alpha: f32,
+    timer: Timer,
+}
+
+fn spawn_particle(
+    commands: &mut Commands,
+    pool: &mut Pool<Particle>,
+    rng: &mut impl Rng,
+    effect: &ParticleEffect,
+    position: Vec2,
+) {
+    let angle = effect.angle + rng.gen_range(-0.5..=0.5) * effect.spread;
+    let speed = rng.gen_range(effect.speed.0..=effect.speed.1);
+    let lifetime = rng.gen_range(effect.lifetime.0..=effect.lifetime.1);
+
+    let sprite = SpriteBundle {
+        sprite: Sprite {
+            color: effect.color,
+            custom_size: Some(Vec2::splat(effect.size)),
+            ..default()
+        },
+        transform: Transform::from_translation(position.extend(5.)),
+        ..default()
+    };
+
+    pool.acquire(
+        commands,
+        (
+            sprite,
+            Particle {
+                velocity: Vec2::from_angle(angle) * speed,
+                gravity: effect.gravity,
+                alpha: effect.color.a(),
+                timer: Timer::from_seconds(lifetime, TimerMode::Once),
+            },
+        ),
+    );
+}
+
+fn emit_bursts(
+    mut commands: Commands,
+    mut pool: ResMut<Pool<Particle>>,
+    mut bursts: EventReader<ParticleBurst>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for burst in bursts.read() {
+        for _ in 0..burst.effect.count {
+            spawn_particle(
+                &mut commands,
+                &mut pool,
+                &mut rng,
+                &burst.effect,
+                burst.position,
+            );
+        }
+    }
+}
+
+fn run_spawners(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<Pool<Particle>>,
+    mut spawners: Query<(&mut ParticleSpawner, &GlobalTransform)>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for (mut spawner, transform) in spawners.iter_mut() {
+        // Fractions carry over, low rates still emit on time
+        spawner.owed += spawner.rate * time.delta_seconds();
+
+        while spawner.owed >= 1. {
+            spawner.owed -= 1.;
+
+            let offset = Vec2::new(
+                rng.gen_range(-1.0..=1.0) * spawner.area.x,
+                rng.gen_range(-1.0..=1.0) * spawner.area.y,
+            );
+
+            spawn_particle(
+                &mut commands,
+                &mut pool,
+                &mut rng,
+                &spawner.effect,
+                transform.translation().truncate() + offset,
+            );
+        }
+    }
+}
+
+// Particles fade over their lifetime and go back to the pool once it is over
+fn move_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<Pool<Particle>>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut particle, mut transform, mut sprite) in particles.iter_mut() {
+        if particle.timer.tick(time.delta()).finished() {
+            pool.release(&mut commands, entity);
+            continue;
+        }
+
+        particle.velocity.y -= particle.gravity * time.delta_seconds();
+        transform.translation += (particle.velocity * time.delta_seconds()).extend(0.);
+
+        let alpha = particle.alpha * particle.timer.percent_left();
+        sprite.color.set_a(alpha);
+    }
+}
+
+fn spark_on_hits(
+    mut hurt: EventReader<Hurt>,
+    targets: Query<&Transform>,
+    mut bursts: EventWriter<ParticleBurst>,
+) {
+    for event in hurt.read() {
+        if let Ok(transform) = targets.get(event.target) {
+            bursts.send(ParticleBurst {
+                position: transform.translation.truncate(),
+                effect: ParticleEffect::SPARKS,
+            });
+        }
+    }
+}
+
+// A puff every stride while the player walks, standing still starts the stride over
+fn dust_on_footsteps(
+    mut walked: Local<f32>,
+    time: Res<Time>,
+    player: Query<(&Transform, &Velocity), With<Player>>,
+    mut bursts: EventWriter<ParticleBurst>,
+) {
+    let Ok((transform, velocity)) = player.get_single() else {
+        return;
+    };
+
+    let speed = Vec2::new(velocity.dx, velocity.dy).length();
+    if speed == 0. {
+        *walked = 0.;
+        return;
+    }
+
+    *walked += speed * time.delta_seconds();
+
+    if *walked >= STRIDE {
+        *walked -= STRIDE;
+        bursts.send(ParticleBurst {
+            position: transform.translation.truncate() - Vec2::Y * FEET_OFFSET,
+            effect: ParticleEffect::DUST,
+        });
+    }
+}
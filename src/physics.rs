@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+
+use crate::{components::Velocity, menu::GameState};
+
+// Impassable tiles are this wide, colliders are squares centered on their entity
+const COLLIDER_SIZE: f32 = 32.;
+
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_velocity.run_if(in_state(GameState::InGame)));
+    }
+}
+
+// Blocks anything with a hitbox from moving through the entity
+#[derive(Component)]
+pub struct Collider;
+
+// Terrain a collider stands for, movers able to cross it pass through
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Barrier {
+    Water,
+    Cliff,
+}
+
+// Barriers a mover can cross, anything without one is stopped by every collider
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Traversal {
+    pub swim: bool,
+    pub climb: bool,
+}
+
+impl Traversal {
+    pub fn crosses(&self, barrier: Barrier) -> bool {
+        match barrier {
+            Barrier::Water => self.swim,
+            Barrier::Cliff => self.climb,
+        }
+    }
+}
+
+// Box that collides with colliders, offset from the entity's center
+#[derive(Component)]
+pub struct Hitbox {
+    pub size: Vec2,
+    pub offset: Vec2,
+}
+
+impl Hitbox {
+    fn bounds(&self, position: Vec2) -> Rect {
+        Rect::from_center_size(position + self.offset, self.size)
+    }
+}
+
+fn overlaps(a: Rect, b: Rect) -> bool {
+    !a.intersect(b).is_empty()
+}
+
+// Each axis is resolved on its own so blocked movement slides along walls instead of sticking
+pub fn apply_velocity(
+    time: Res<Time>,
+    mut movers: Query<(
+        &mut Transform,
+        &Velocity,
+        Option<&Hitbox>,
+        Option<&Traversal>,
+    )>,
+    colliders: Query<(&GlobalTransform, Option<&Barrier>), With<Collider>>,
+) {
+    for (mut transform, velocity, hitbox, traversal) in movers.iter_mut() {
+        let delta = Vec2::new(velocity.dx, velocity.dy) * time.delta_seconds();
+
+        let Some(hitbox) = hitbox else {
+            transform.translation += delta.extend(0.);
+            continue;
+        };
+
+        let start = transform.translation.truncate();
+        let reach = hitbox.size.length() + delta.length() + COLLIDER_SIZE;
+
+        // Colliders already overlapped are ignored so nothing spawned inside a wall gets stuck
+        let nearby: Vec<Rect> = colliders
+            .iter()
+            .filter(|(_, barrier)| {
+                !barrier.is_some_and(|barrier| {
+                    traversal.is_some_and(|traversal| traversal.crosses(*barrier))
+                })
+            })
+            .map(|(collider, _)| {
+                Rect::from_center_size(
+                    collider.translation().truncate(),
+                    Vec2::splat(COLLIDER_SIZE),
+                )
+            })
+            .filter(|collider| collider.center().distance(start) <= reach)
+            .filter(|collider| !overlaps(hitbox.bounds(start), *collider))
+            .collect();
+
+        let mut position = start;
+
+        for axis in [Vec2::X, Vec2::Y] {
+            let step = delta * axis;
+            if step == Vec2::ZERO {
+                continue;
+            }
+
+            let moved = hitbox.bounds(position + step);
+
+            // The first collider hit along the axis is the one stopped against
+            let blocking = nearby
+                .iter()
+                .filter(|collider| overlaps(moved, **collider))
+                .min_by(|a, b| {
+                    let a = ((a.center() - position) * axis).length();
+                    let b = ((b.center() - position) * axis).length();
+                    a.total_cmp(&b)
+                });
+
+            position = match blocking {
+                // Stop flush against the collider's face
+                Some(collider) => {
+                    let bounds = hitbox.bounds(position);
+
+                    if axis == Vec2::X {
+                        let x = if step.x > 0. {
+                            collider.min.x - bounds.half_size().x - hitbox.offset.x
+                        } else {
+                            collider.max.x + bounds.half_size().x - hitbox.offset.x
+                        };
+                        Vec2::new(x, position.y)
+                    } else {
+                        let y = if step.y > 0. {
+                            collider.min.y - bounds.half_size().y - hitbox.offset.y
+                        } else {
+                            collider.max.y + bounds.half_size().y - hitbox.offset.y
+                        };
+                        Vec2::new(position.x, y)
+                    }
+                }
+                None => position + step,
+            };
+        }
+
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+use crate::components::{Direction, Velocity};
+
+pub const SHEET_PATH: &str = "sprites/player/character.png";
+
+// Frames are laid out one direction per row, idle frames first and walk frames after
+pub const FRAME_SIZE: Vec2 = Vec2::new(20., 40.);
+pub const SHEET_COLUMNS: usize = 6;
+pub const SHEET_ROWS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationState {
+    Idle,
+    Walk,
+}
+
+impl AnimationState {
+    // First column and frame count within a direction's row
+    fn frames(&self) -> (usize, usize) {
+        match self {
+            AnimationState::Idle => (0, 2),
+            AnimationState::Walk => (2, 4),
+        }
+    }
+
+    fn frame_seconds(&self) -> f32 {
+        match self {
+            AnimationState::Idle => 0.5,
+            AnimationState::Walk => 0.12,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct PlayerAnimation {
+    state: AnimationState,
+    frame: usize,
+    timer: Timer,
+}
+
+impl Default for PlayerAnimation {
+    fn default() -> Self {
+        PlayerAnimation {
+            state: AnimationState::Idle,
+            frame: 0,
+            timer: Timer::from_seconds(AnimationState::Idle.frame_seconds(), TimerMode::Repeating),
+        }
+    }
+}
+
+fn direction_row(direction: &Direction) -> usize {
+    match direction {
+        Direction::Down => 0,
+        Direction::Left => 1,
+        Direction::Right => 2,
+        Direction::Up => 3,
+    }
+}
+
+pub fn animate_player(
+    time: Res<Time>,
+    mut query: Query<(
+        &Velocity,
+        &Direction,
+        &mut PlayerAnimation,
+        &mut TextureAtlasSprite,
+    )>,
+) {
+    for (velocity, direction, mut animation, mut sprite) in query.iter_mut() {
+        let state = if velocity.dx != 0. || velocity.dy != 0. {
+            AnimationState::Walk
+        } else {
+            AnimationState::Idle
+        };
+
+        // Switching states restarts from the new state's first frame
+        if animation.state != state {
+            animation.state = state;
+            animation.frame = 0;
+            animation.timer = Timer::from_seconds(state.frame_seconds(), TimerMode::Repeating);
+        } else if animation.timer.tick(time.delta()).just_finished() {
+            animation.frame = (animation.frame + 1) % state.frames().1;
+        }
+
+        let (first, _) = state.frames();
+        sprite.index = direction_row(direction) * SHEET_COLUMNS + first + animation.frame;
+    }
+}
@@ -0,0 +1,342 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ui::{
+    console::{ConsoleCommand, ConsoleOutput},
+    Panel, PanelRoot, PanelState, TogglePanel,
+};
+
+use super::{animation, Player};
+
+const APPEARANCE_PATH: &str = "saves/appearance.json";
+
+// Optional sheets drawn over the body, laid out frame for frame like the character sheet
+const HAIR_SHEET: &str = "sprites/player/hair.png";
+const CLOTHES_SHEET: &str = "sprites/player/clothes.png";
+
+const BODY_COLORS: [(&str, Color); 4] = [
+    ("Pale", Color::rgb(1., 0.93, 0.86)),
+    ("Tan", Color::rgb(0.92, 0.78, 0.62)),
+    ("Brown", Color::rgb(0.7, 0.52, 0.38)),
+    ("Dark", Color::rgb(0.48, 0.34, 0.25)),
+];
+
+const HAIR_COLORS: [(&str, Color); 5] = [
+    ("Black", Color::rgb(0.15, 0.13, 0.12)),
+    ("Brown", Color::rgb(0.42, 0.27, 0.15)),
+    ("Blonde", Color::rgb(0.93, 0.8, 0.45)),
+    ("Red", Color::rgb(0.7, 0.25, 0.12)),
+    ("White", Color::rgb(0.92, 0.92, 0.92)),
+];
+
+const CLOTHES_COLORS: [(&str, Color); 5] = [
+    ("Blue", Color::rgb(0.25, 0.4, 0.75)),
+    ("Green", Color::rgb(0.3, 0.6, 0.3)),
+    ("Red", Color::rgb(0.7, 0.2, 0.2)),
+    ("Purple", Color::rgb(0.5, 0.3, 0.65)),
+    ("Grey", Color::rgb(0.5, 0.5, 0.5)),
+];
+
+// Palette picks for each layer, serializable so it can be sent along with the player
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Appearance {
+    pub body: usize,
+    pub hair: usize,
+    pub clothes: usize,
+}
+
+impl Appearance {
+    // None until a character has been created for this world
+    pub fn load() -> Option<Appearance> {
+        fs::read(APPEARANCE_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+    }
+
+    pub fn save(&self) {
+        let written = fs::create_dir_all("saves").and_then(|_| {
+            fs::write(
+                APPEARANCE_PATH,
+                serde_json::to_vec(self).map_err(std::io::Error::from)?,
+            )
+        });
+
+        if let Err(err) = written {
+            warn!("Failed to save appearance! Err {err}");
+        }
+    }
+
+    fn color(&self, layer: AppearanceLayer) -> Color {
+        let (palette, pick): (&[(&str, Color)], usize) = match layer {
+            AppearanceLayer::Body => (&BODY_COLORS, self.body),
+            AppearanceLayer::Hair => (&HAIR_COLORS, self.hair),
+            AppearanceLayer::Clothes => (&CLOTHES_COLORS, self.clothes),
+        };
+
+        palette[pick % palette.len()].1
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppearanceLayer {
+    Body,
+    Hair,
+    Clothes,
+}
+
+impl AppearanceLayer {
+    fn sheet(&self) -> Option<&'static str> {
+        match self {
+            AppearanceLayer::Body => None,
+            AppearanceLayer::Hair => Some(HAIR_SHEET),
+            AppearanceLayer::Clothes => Some(CLOTHES_SHEET),
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum CharacterButton {
+    Cycle(AppearanceLayer),
+    Done,
+}
+
+impl CharacterButton {
+    fn label(&self, appearance: &Appearance) -> String {
+        match self {
+            CharacterButton::Cycle(AppearanceLayer::Body) => {
+                format!(
+                    "Body: {}",
+                    BODY_COLORS[appearance.body % BODY_COLORS.len()].0
+                )
+            }
+            CharacterButton::Cycle(AppearanceLayer::Hair) => {
+                format!(
+                    "Hair: {}",
+                    HAIR_COLORS[appearance.hair % HAIR_COLORS.len()].0
+                )
+            }
+            CharacterButton::Cycle(AppearanceLayer::Clothes) => format!(
+                "Clothes: {}",
+                CLOTHES_COLORS[appearance.clothes % CLOTHES_COLORS.len()].0
+            ),
+            CharacterButton::Done => "Done".to_string(),
+        }
+    }
+}
+
+// Layers without a sheet on disk are skipped, the body tint still applies
+pub fn spawn_appearance_layers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    players: Query<(Entity, &Appearance), Added<Player>>,
+) {
+    for (player, appearance) in players.iter() {
+        for layer in [AppearanceLayer::Clothes, AppearanceLayer::Hair] {
+            let Some(sheet) = layer.sheet() else {
+                continue;
+            };
+
+            if !Path::new("assets").join(sheet).exists() {
+                debug!("No {:?} sheet found, skipping the layer", layer);
+                continue;
+            }
+
+            let atlas = TextureAtlas::from_grid(
+                asset_server.load(sheet),
+                animation::FRAME_SIZE,
+                animation::SHEET_COLUMNS,
+                animation::SHEET_ROWS,
+                None,
+                None,
+            );
+
+            // Hair is drawn over clothes
+            let z = match layer {
+                AppearanceLayer::Hair => 0.02,
+                _ => 0.01,
+            };
+
+            let sprite = SpriteSheetBundle {
+                texture_atlas: atlases.add(atlas),
+                sprite: TextureAtlasSprite {
+                    color: appearance.color(layer),
+                    ..TextureAtlasSprite::new(0)
+                },
+                transform: Transform::from_xyz(0., 0., z),
+                ..default()
+            };
+
+            commands.entity(player).with_children(|parent| {
+                parent.spawn(sprite).insert(layer);
+            });
+        }
+    }
+}
+
+pub fn apply_appearance(
+    mut players: Query<
+        (&Appearance, &mut TextureAtlasSprite, Option<&Children>),
+        Changed<Appearance>,
+    >,
+    mut layers: Query<(&AppearanceLayer, &mut TextureAtlasSprite), Without<Appearance>>,
+) {
+    for (appearance, mut body, children) in players.iter_mut() {
+        body.color = appearance.color(AppearanceLayer::Body);
+
+        for child in children.into_iter().flatten() {
+            if let Ok((layer, mut sprite)) = layers.get_mut(*child) {
+                sprite.color = appearance.color(*layer);
+            }
+        }
+    }
+}
+
+// Layers follow the body's animation frame
+pub fn sync_appearance_layers(
+    players: Query<(&TextureAtlasSprite, &Children), With<Player>>,
+    mut layers: Query<&mut TextureAtlasSprite, (With<AppearanceLayer>, Without<Player>)>,
+) {
+    for (body, children) in players.iter() {
+        for child in children.iter() {
+            if let Ok(mut sprite) = layers.get_mut(*child) {
+                sprite.index = body.index;
+                sprite.flip_x = body.flip_x;
+            }
+        }
+    }
+}
+
+pub fn spawn_character_panel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let appearance = Appearance::load().unwrap_or_default();
+
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    commands
+        .spawn(container_node)
+        .insert(PanelRoot(Panel::Character))
+        .with_children(|parent| {
+            for button in [
+                CharacterButton::Cycle(AppearanceLayer::Body),
+                CharacterButton::Cycle(AppearanceLayer::Hair),
+                CharacterButton::Cycle(AppearanceLayer::Clothes),
+                CharacterButton::Done,
+            ] {
+                let button_bundle = ButtonBundle {
+                    style: Style {
+                        width: Val::Px(320.),
+                        padding: UiRect::all(Val::Px(8.)),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.1, 0.1, 0.15, 0.9).into(),
+                    ..default()
+                };
+
+                parent
+                    .spawn(button_bundle)
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            button.label(&appearance),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 20.0,
+                                color: Color::WHITE,
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+// A new world starts on character creation
+pub fn open_character_creation(mut toggles: EventWriter<TogglePanel>) {
+    if Appearance::load().is_none() {
+        info!("No character created yet, opening character creation");
+        toggles.send(TogglePanel(Panel::Character));
+    }
+}
+
+// Console command reopening character creation
+pub fn handle_customize_commands(
+    mut console: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut toggles: EventWriter<TogglePanel>,
+) {
+    for command in console.read() {
+        if command.name != "customize" {
+            continue;
+        }
+
+        toggles.send(TogglePanel(Panel::Character));
+        output.send(ConsoleOutput("Opened character creation".to_string()));
+    }
+}
+
+pub fn handle_character_buttons(
+    mut state: ResMut<PanelState>,
+    mut player: Query<&mut Appearance, With<Player>>,
+    buttons: Query<(&Interaction, &CharacterButton), Changed<Interaction>>,
+) {
+    let Ok(mut appearance) = player.get_single_mut() else {
+        return;
+    };
+
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            CharacterButton::Cycle(AppearanceLayer::Body) => {
+                appearance.body = (appearance.body + 1) % BODY_COLORS.len();
+            }
+            CharacterButton::Cycle(AppearanceLayer::Hair) => {
+                appearance.hair = (appearance.hair + 1) % HAIR_COLORS.len();
+            }
+            CharacterButton::Cycle(AppearanceLayer::Clothes) => {
+                appearance.clothes = (appearance.clothes + 1) % CLOTHES_COLORS.len();
+            }
+            CharacterButton::Done => {
+                state.active = None;
+            }
+        }
+
+        appearance.save();
+    }
+}
+
+pub fn update_character_labels(
+    player: Query<&Appearance, (With<Player>, Changed<Appearance>)>,
+    buttons: Query<(&CharacterButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    let Ok(appearance) = player.get_single() else {
+        return;
+    };
+
+    for (button, children) in buttons.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = button.label(appearance);
+            }
+        }
+    }
+}
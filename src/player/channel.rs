@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{combat::Damage, menu::GameState, physics::apply_velocity};
+
+use super::Player;
+
+// Pause after a channel ends before another can start, a held key does not chain channels
+const INTERACTION_COOLDOWN: f32 = 0.3;
+
+// Drift allowed before a channel counts as interrupted by movement, knockback aside this is jitter
+const MOVE_TOLERANCE: f32 = 2.;
+
+const BAR_WIDTH: f32 = 24.;
+
+const BAR_HEIGHT: f32 = 3.;
+
+const BAR_OFFSET: f32 = 22.;
+
+// What a channel does once it completes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelAction {
+    Harvest(Entity),
+    OpenStash,
+    Unlock(Entity),
+    Disarm(Entity),
+    Gather(Entity),
+}
+
+// Sent by interactions that take time, ignored while another channel runs or on cooldown
+#[derive(Event)]
+pub struct StartChannel {
+    pub action: ChannelAction,
+    pub duration: Duration,
+}
+
+// Sent once a channel runs to the end without being interrupted
+#[derive(Event)]
+pub struct ChannelFinished(pub ChannelAction);
+
+// Stops the running channel early, such as when the key held for it is let go
+#[derive(Event)]
+pub struct CancelChannel;
+
+#[derive(Component)]
+pub struct Channel {
+    pub action: ChannelAction,
+    timer: Timer,
+    origin: Vec2,
+    bar: Entity,
+}
+
+impl Channel {
+    pub fn progress(&self) -> f32 {
+        self.timer.percent()
+    }
+}
+
+#[derive(Component)]
+pub struct InteractionCooldown(Timer);
+
+impl Default for InteractionCooldown {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(INTERACTION_COOLDOWN, TimerMode::Once);
+        timer.tick(timer.duration());
+        InteractionCooldown(timer)
+    }
+}
+
+#[derive(Component)]
+pub struct ChannelBarFill;
+
+pub struct ChannelPlugin;
+
+impl Plugin for ChannelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StartChannel>()
+            .add_event::<ChannelFinished>()
+            .add_event::<CancelChannel>()
+            .add_systems(
+                Update,
+                (start_channels, tick_channels, update_channel_bar)
+                    .chain()
+                    .after(apply_velocity)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+pub fn start_channels(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut starts: EventReader<StartChannel>,
+    mut player: Query<
+        (
+            Entity,
+            &Transform,
+            &mut InteractionCooldown,
+            Option<&Channel>,
+        ),
+        With<Player>,
+    >,
+) {
+    let Ok((entity, transform, mut cooldown, channel)) = player.get_single_mut() else {
+        starts.clear();
+        return;
+    };
+
+    cooldown.0.tick(time.delta());
+
+    // Only the first request of a frame is taken
+    let Some((action, duration)) = starts
+        .read()
+        .next()
+        .map(|start| (start.action, start.duration))
+    else {
+        return;
+    };
+    starts.clear();
+
+    if channel.is_some() || !cooldown.0.finished() {
+        return;
+    }
+
+    debug!("Channeling {:?} for {:?}", action, duration);
+
+    let track = SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgba(0., 0., 0., 0.6),
+            custom_size: Some(Vec2::new(BAR_WIDTH, BAR_HEIGHT)),
+            ..default()
+        },
+        transform: Transform::from_xyz(0., BAR_OFFSET, 5.),
+        ..default()
+    };
+
+    // Anchored on its left edge so it grows to the right as the channel runs
+    let fill = SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgb(0.95, 0.8, 0.3),
+            custom_size: Some(Vec2::new(0., BAR_HEIGHT)),
+            anchor: Anchor::CenterLeft,
+            ..default()
+        },
+        transform: Transform::from_xyz(-BAR_WIDTH / 2., 0., 0.1),
+        ..default()
+    };
+
+    let bar = commands
+        .spawn(track)
+        .with_children(|parent| {
+            parent.spawn(fill).insert(ChannelBarFill);
+        })
+        .id();
+
+    commands.entity(entity).add_child(bar).insert(Channel {
+        action,
+        timer: Timer::new(duration, TimerMode::Once),
+        origin: transform.translation.truncate(),
+        bar,
+    });
+}
+
+// Moving or being hit cancels the channel, finishing it hands the action to whoever started it
+pub fn tick_channels(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut damage: EventReader<Damage>,
+    mut cancels: EventReader<CancelChannel>,
+    mut player: Query<(Entity, &Transform, &mut Channel, &mut InteractionCooldown), With<Player>>,
+    mut finished: EventWriter<ChannelFinished>,
+) {
+    let Ok((entity, transform, mut channel, mut cooldown)) = player.get_single_mut() else {
+        damage.clear();
+        cancels.clear();
+        return;
+    };
+
+    // Every event is read so hits from before the channel started never carry over
+    let hit = damage
+        .read()
+        .filter(|damage| damage.target == entity)
+        .count()
+        > 0;
+    let moved = transform.translation.truncate().distance(channel.origin) > MOVE_TOLERANCE;
+    let cancelled = cancels.read().count() > 0;
+
+    channel.timer.tick(time.delta());
+
+    if hit || moved || cancelled {
+        debug!("Channel {:?} interrupted", channel.action);
+    } else if channel.timer.finished() {
+        finished.send(ChannelFinished(channel.action));
+    } else {
+        return;
+    }
+
+    cooldown.0.reset();
+    commands.entity(channel.bar).despawn_recursive();
+    commands.entity(entity).remove::<Channel>();
+}
+
+pub fn update_channel_bar(
+    player: Query<&Channel, With<Player>>,
+    mut fills: Query<&mut Sprite, With<ChannelBarFill>>,
+) {
+    let Ok(channel) = player.get_single() else {
+        return;
+    };
+
+    for mut sprite in fills.iter_mut() {
+        sprite.custom_size = Some(Vec2::new(BAR_WIDTH * channel.progress(), BAR_HEIGHT));
+    }
+}
@@ -0,0 +1,263 @@
+use std::f32::consts::TAU;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    input::{Action, InputMap},
+    ui::PanelState,
+};
+
+use super::Player;
+
+// Cursor distance from the wheel center before a slice is picked, releasing closer cancels
+const DEADZONE: f32 = 24.;
+
+const WHEEL_RADIUS: f32 = 90.;
+
+const EMOTE_SECONDS: f32 = 2.;
+
+const FADE_SECONDS: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Emote {
+    Wave,
+    Laugh,
+    Heart,
+    Question,
+    Alert,
+    Sleep,
+}
+
+impl Emote {
+    // Clockwise from the top of the wheel
+    const ALL: [Emote; 6] = [
+        Emote::Wave,
+        Emote::Laugh,
+        Emote::Heart,
+        Emote::Question,
+        Emote::Alert,
+        Emote::Sleep,
+    ];
+
+    fn glyph(&self) -> &'static str {
+        match self {
+            Emote::Wave => "o/",
+            Emote::Laugh => "XD",
+            Emote::Heart => "<3",
+            Emote::Question => "?",
+            Emote::Alert => "!",
+            Emote::Sleep => "zZ",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Emote::Wave => Color::rgb(0.95, 0.85, 0.4),
+            Emote::Laugh => Color::rgb(0.95, 0.65, 0.3),
+            Emote::Heart => Color::rgb(0.95, 0.35, 0.45),
+            Emote::Question => Color::rgb(0.5, 0.75, 1.),
+            Emote::Alert => Color::rgb(1., 0.3, 0.25),
+            Emote::Sleep => Color::rgb(0.7, 0.7, 0.9),
+        }
+    }
+
+    // Offset of the emote's slice from the wheel center, y grows downwards in the UI
+    fn slot(index: usize) -> Vec2 {
+        let angle = index as f32 / Emote::ALL.len() as f32 * TAU;
+        Vec2::new(angle.sin(), -angle.cos()) * WHEEL_RADIUS
+    }
+}
+
+#[derive(Component)]
+pub struct EmoteWheel;
+
+#[derive(Component)]
+pub struct EmoteOption(Emote);
+
+// Emote playing above the player
+#[derive(Component)]
+pub struct PlayingEmote {
+    timer: Timer,
+}
+
+pub fn spawn_emote_wheel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    let wheel_size = WHEEL_RADIUS * 2. + 48.;
+
+    let wheel_node = NodeBundle {
+        style: Style {
+            width: Val::Px(wheel_size),
+            height: Val::Px(wheel_size),
+            ..default()
+        },
+        background_color: Color::rgba(0.1, 0.1, 0.15, 0.6).into(),
+        ..default()
+    };
+
+    commands
+        .spawn(container_node)
+        .insert(EmoteWheel)
+        .with_children(|parent| {
+            parent.spawn(wheel_node).with_children(|wheel| {
+                for (index, emote) in Emote::ALL.iter().enumerate() {
+                    let slot = Emote::slot(index) + Vec2::splat(wheel_size / 2.);
+
+                    let option = TextBundle::from_section(
+                        emote.glyph(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 28.0,
+                            color: Color::GRAY,
+                        },
+                    )
+                    .with_style(Style {
+                        position_type: PositionType::Absolute,
+                        // Roughly centers the glyph on its slot
+                        left: Val::Px(slot.x - 14.),
+                        top: Val::Px(slot.y - 14.),
+                        ..default()
+                    });
+
+                    wheel.spawn(option).insert(EmoteOption(*emote));
+                }
+            });
+        });
+}
+
+fn spawn_emote(commands: &mut Commands, player: Entity, emote: Emote, font: Handle<Font>) {
+    let label = Text2dBundle {
+        text: Text::from_section(
+            emote.glyph(),
+            TextStyle {
+                font,
+                font_size: 28.,
+                color: emote.color(),
+            },
+        ),
+        transform: Transform::from_xyz(0., 30., 0.5).with_scale(Vec3::splat(0.5)),
+        ..default()
+    };
+
+    commands.entity(player).with_children(|parent| {
+        parent.spawn(label).insert(PlayingEmote {
+            timer: Timer::from_seconds(EMOTE_SECONDS, TimerMode::Once),
+        });
+    });
+}
+
+// Holding the key shows the wheel, the slice under the cursor plays on release
+#[allow(clippy::too_many_arguments)]
+pub fn use_emote_wheel(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    panels: Res<PanelState>,
+    asset_server: Res<AssetServer>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut wheel: Query<&mut Visibility, With<EmoteWheel>>,
+    mut options: Query<(&EmoteOption, &mut Text)>,
+    player: Query<(Entity, Option<&Children>), With<Player>>,
+    playing: Query<(), With<PlayingEmote>>,
+) {
+    let Ok(mut visibility) = wheel.get_single_mut() else {
+        return;
+    };
+
+    if panels.is_focused() {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    let selected = windows.get_single().ok().and_then(|window| {
+        let center = Vec2::new(window.width(), window.height()) / 2.;
+        let offset = window.cursor_position()? - center;
+
+        if offset.length() < DEADZONE {
+            return None;
+        }
+
+        // Clockwise angle from straight up, matching the slot layout
+        let angle = offset.x.atan2(-offset.y).rem_euclid(TAU);
+        let slice = TAU / Emote::ALL.len() as f32;
+        let index = ((angle + slice / 2.) / slice) as usize % Emote::ALL.len();
+
+        Some(Emote::ALL[index])
+    });
+
+    if input_map.just_pressed(Action::Emote, &input) {
+        *visibility = Visibility::Visible;
+    }
+
+    if input_map.pressed(Action::Emote, &input) {
+        for (option, mut text) in options.iter_mut() {
+            text.sections[0].style.color = if selected == Some(option.0) {
+                option.0.color()
+            } else {
+                Color::GRAY
+            };
+        }
+    }
+
+    if !input_map.just_released(Action::Emote, &input) {
+        return;
+    }
+
+    *visibility = Visibility::Hidden;
+
+    let (Some(emote), Ok((entity, children))) = (selected, player.get_single()) else {
+        return;
+    };
+
+    // A new emote replaces whatever is still playing
+    for child in children.into_iter().flatten() {
+        if playing.contains(*child) {
+            commands.entity(*child).despawn_recursive();
+        }
+    }
+
+    debug!("Playing {:?} emote", emote);
+    spawn_emote(
+        &mut commands,
+        entity,
+        emote,
+        asset_server.load("fonts/FiraMono-Medium.ttf"),
+    );
+}
+
+// Pops in, bobs above the head, then fades out
+pub fn animate_emotes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut emotes: Query<(Entity, &mut PlayingEmote, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut emote, mut transform, mut text) in emotes.iter_mut() {
+        emote.timer.tick(time.delta());
+
+        if emote.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let elapsed = emote.timer.elapsed_secs();
+        let pop = (elapsed * 8.).min(1.);
+
+        transform.scale = Vec3::splat(0.5 * pop);
+        transform.translation.y = 30. + 2. * (elapsed * 6.).sin();
+
+        let alpha = (emote.timer.remaining_secs() / FADE_SECONDS).min(1.);
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
@@ -1,5 +1,25 @@
 use bevy::prelude::*;
 
+use crate::{
+    components::{Dead, Stack},
+    items::{
+        container::{ItemContainer, SortKey},
+        definition::ItemRegistry,
+        dropped::DropItem,
+        durability::spawn_durability_bar,
+        equipment::Equipment,
+        stash::{Stash, StashChest, STASH_REACH},
+    },
+    menu::GameState,
+    ui::{spawn_panel, tooltip::Tooltip, Panel, PanelState},
+    world::WorldRules,
+};
+
+use super::Player;
+
+// Containers within this distance of the player receive quick-stacked items
+const QUICK_STACK_RANGE: f32 = 96.;
+
 #[derive(Clone, Copy, Component)]
 pub struct Inventory;
 
@@ -8,13 +28,44 @@ pub struct Item<'a> {
     name: &'a str,
 }
 
+#[derive(Component, Clone, Copy, Debug)]
+pub enum InventoryButton {
+    Sort(SortKey),
+    UndoSort,
+    QuickStack,
+}
+
+// Row of the inventory list, pressing equippable items equips them
+#[derive(Component)]
+pub struct InventorySlot(usize);
+
+#[derive(Component)]
+pub struct InventoryList;
+
+// Inventory contents from before the last sort
+#[derive(Resource, Default)]
+pub struct LastSort(Option<ItemContainer>);
+
 pub struct InventoryPlugin;
 
 impl Plugin for InventoryPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, initialize_inventory)
-            // .add_systems(Update, inventory_position_system)
-            .add_systems(Update, toggle_inventory_system);
+        app.init_resource::<LastSort>()
+            .add_systems(OnExit(GameState::MainMenu), initialize_inventory)
+            .add_systems(
+                Update,
+                (
+                    handle_inventory_buttons,
+                    equip_from_inventory,
+                    update_inventory_list,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                drop_inventory_on_death.run_if(in_state(GameState::InGame)),
+            );
     }
 }
 
@@ -22,59 +73,321 @@ fn initialize_inventory(mut commands: Commands, assets: Res<AssetServer>) {
     info!("Initializing inventory");
 
     let texture_handle = assets.load::<Image>("sprites/display/items/inventory.png");
+    let font = assets.load("fonts/FiraMono-Medium.ttf");
 
-    let container_node = NodeBundle {
+    // Visibility is owned by the panel manager
+    let container = spawn_panel(&mut commands, texture_handle, Panel::Inventory);
+    commands.entity(container).insert(Inventory {});
+
+    let row = NodeBundle {
         style: Style {
-            width: Val::Percent(100.0),
-            height: Val::Percent(100.0),
-            align_items: AlignItems::Center,
-            justify_content: JustifyContent::Center,
+            column_gap: Val::Px(4.),
             ..default()
         },
         ..default()
     };
 
-    let image_bundle = ImageBundle {
-        image: UiImage {
-            texture: texture_handle,
-            ..Default::default()
-        },
-        style: Style {
-            width: Val::Vw(20.),
-            ..Default::default()
-        },
-        ..Default::default()
+    commands.entity(container).with_children(|parent| {
+        parent.spawn(row).with_children(|parent| {
+            for (button, label) in [
+                (InventoryButton::Sort(SortKey::Category), "Category"),
+                (InventoryButton::Sort(SortKey::Rarity), "Rarity"),
+                (InventoryButton::Sort(SortKey::Name), "Name"),
+                (InventoryButton::UndoSort, "Undo"),
+                (InventoryButton::QuickStack, "Quick stack"),
+            ] {
+                let button_bundle = ButtonBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(6.)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.1, 0.1, 0.15, 0.9).into(),
+                    ..default()
+                };
+
+                parent
+                    .spawn(button_bundle)
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            label,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 16.0,
+                                color: Color::WHITE,
+                            },
+                        ));
+                    });
+            }
+        });
+
+        let list_node = NodeBundle {
+            style: Style {
+                width: Val::Px(320.),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(2.),
+                ..default()
+            },
+            background_color: Color::rgba(0.05, 0.05, 0.08, 0.9).into(),
+            ..default()
+        };
+
+        parent.spawn(list_node).insert(InventoryList);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_inventory_buttons(
+    state: Res<PanelState>,
+    registry: Res<ItemRegistry>,
+    mut last_sort: ResMut<LastSort>,
+    mut stash: ResMut<Stash>,
+    buttons: Query<(&Interaction, &InventoryButton), Changed<Interaction>>,
+    mut player: Query<(&Transform, &mut ItemContainer), With<Player>>,
+    mut containers: Query<(&Transform, &mut ItemContainer), Without<Player>>,
+    chests: Query<&Transform, With<StashChest>>,
+) {
+    if state.active != Some(Panel::Inventory) {
+        return;
+    }
+
+    let Ok((player_transform, mut inventory)) = player.get_single_mut() else {
+        return;
+    };
+    let position = player_transform.translation.truncate();
+
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            InventoryButton::Sort(key) => {
+                last_sort.0 = Some(inventory.clone());
+                inventory.sort(*key, &registry);
+                debug!("Sorted inventory by {:?}", key);
+            }
+            InventoryButton::UndoSort => {
+                if let Some(previous) = last_sort.0.take() {
+                    *inventory = previous;
+                }
+            }
+            InventoryButton::QuickStack => {
+                // Moving items invalidates the snapshot
+                last_sort.0 = None;
+
+                for (transform, mut container) in containers.iter_mut() {
+                    if transform.translation.truncate().distance(position) <= QUICK_STACK_RANGE {
+                        inventory.quick_stack_into(&mut container, &registry);
+                    }
+                }
+
+                let stash_in_reach = chests
+                    .iter()
+                    .any(|chest| chest.translation.truncate().distance(position) <= STASH_REACH);
+
+                if stash_in_reach && inventory.quick_stack_into(&mut stash.contents, &registry) {
+                    stash.save();
+                }
+            }
+        }
+    }
+}
+
+// Scatters the inventory where the player died unless the world keeps inventories
+fn drop_inventory_on_death(
+    rules: Res<WorldRules>,
+    mut dead: Query<(Entity, &Transform, &mut ItemContainer), Added<Dead>>,
+    players: Query<(), With<Player>>,
+    mut drops: EventWriter<DropItem>,
+) {
+    if rules.keep_inventory {
+        return;
+    }
+
+    for (entity, transform, mut inventory) in dead.iter_mut() {
+        if !players.contains(entity) {
+            continue;
+        }
+
+        info!("Dropping inventory on death");
+
+        for (idx, slot) in inventory.slots.iter_mut().enumerate() {
+            if let Some(stack) = slot.take() {
+                let angle = idx as f32 * 2.4;
+
+                drops.send(DropItem {
+                    stack,
+                    position: transform.translation.truncate()
+                        + Vec2::new(angle.cos(), angle.sin()) * 16.,
+                });
+            }
+        }
+    }
+}
+
+fn equip_from_inventory(
+    registry: Res<ItemRegistry>,
+    slots: Query<(&Interaction, &InventorySlot), Changed<Interaction>>,
+    mut player: Query<(&mut ItemContainer, &mut Equipment), With<Player>>,
+) {
+    let Ok((mut inventory, mut equipment)) = player.get_single_mut() else {
+        return;
     };
 
-    let container = commands
-        .spawn(container_node)
-        .insert(Visibility::Hidden)
-        .insert(Inventory {})
-        .id();
+    for (interaction, slot) in slots.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let Some(stack) = inventory.slots[slot.0] else {
+            continue;
+        };
+
+        let Some(equip_slot) = registry.get(stack.id).category.equip_slot() else {
+            continue;
+        };
 
-    let sprite: Entity = commands
-        .spawn(image_bundle)
-        .insert(Visibility::Inherited)
-        .id();
+        // The previously worn item takes the freed slot
+        inventory.slots[slot.0] = equipment.equip(equip_slot, stack);
 
-    commands.entity(container).push_children(&[sprite]);
+        info!("Equipped {}", registry.get(stack.id).name);
+    }
 }
 
-fn toggle_inventory_system(
+fn update_inventory_list(
     mut commands: Commands,
-    mut inventory_query: Query<(Entity, &mut Visibility), With<Inventory>>,
-    input: Res<Input<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    registry: Res<ItemRegistry>,
+    player: Query<(Ref<ItemContainer>, Ref<Equipment>), With<Player>>,
+    list: Query<Entity, With<InventoryList>>,
 ) {
-    if input.just_pressed(KeyCode::E) {
-        let (entity, visibility) = inventory_query.get_single_mut().unwrap();
-
-        let updated: Visibility;
-        if *visibility == Visibility::Hidden {
-            updated = Visibility::Visible;
-        } else {
-            updated = Visibility::Hidden;
+    let (Ok((inventory, equipment)), Ok(list)) = (player.get_single(), list.get_single()) else {
+        return;
+    };
+
+    if !inventory.is_changed() && !equipment.is_changed() {
+        return;
+    }
+
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let style = TextStyle {
+        font,
+        font_size: 18.0,
+        color: Color::WHITE,
+    };
+
+    commands.entity(list).despawn_descendants();
+    commands.entity(list).with_children(|parent| {
+        for (idx, stack) in inventory.slots.iter().enumerate() {
+            let Some(stack) = stack else {
+                continue;
+            };
+
+            let definition = registry.get(stack.id);
+            let count = TextSection::new(format!(" x{}", stack.count), style.clone());
+
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::horizontal(Val::Px(8.)),
+                        ..default()
+                    },
+                    background_color: Color::NONE.into(),
+                    ..default()
+                })
+                .insert(InventorySlot(idx))
+                .insert(item_tooltip(stack, &equipment, &registry, &style))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_sections([
+                        definition.name_section(style.clone()),
+                        count,
+                    ]));
+
+                    if let Some((current, max)) = registry.durability(stack) {
+                        spawn_durability_bar(parent, current, max);
+                    }
+                });
+        }
+    });
+}
+
+// Name, stats and modifiers of the item, with the difference to the equipped item of the same slot
+pub fn item_tooltip(
+    stack: &Stack,
+    equipment: &Equipment,
+    registry: &ItemRegistry,
+    style: &TextStyle,
+) -> Tooltip {
+    let definition = registry.get(stack.id);
+    let stats = registry.stats(stack);
+
+    let mut sections = vec![
+        definition.name_section(style.clone()),
+        TextSection::new(format!("\n{:?}", definition.category), style.clone()),
+    ];
+
+    for (label, value) in stats.entries() {
+        if value != 0 {
+            sections.push(TextSection::new(
+                format!("\n{value} {label}"),
+                style.clone(),
+            ));
+        }
+    }
+
+    if let Some((current, max)) = registry.durability(stack) {
+        sections.push(TextSection::new(
+            format!("\nDurability {current}/{max}"),
+            style.clone(),
+        ));
+    }
+
+    if let Some(instance) = stack.instance {
+        for modifier in instance.modifiers.iter().flatten() {
+            sections.push(TextSection::new(
+                format!("\n{:?} {}", modifier.kind, modifier.level),
+                TextStyle {
+                    color: Color::rgb(0.6, 0.8, 1.),
+                    ..style.clone()
+                },
+            ));
         }
+    }
+
+    let equipped = definition
+        .category
+        .equip_slot()
+        .and_then(|slot| equipment.get(slot));
+
+    if let Some(equipped) = equipped {
+        sections.push(TextSection::new("\n\nCompared to ", style.clone()));
+        sections.push(registry.get(equipped.id).name_section(style.clone()));
 
-        commands.entity(entity).insert(updated);
+        let deltas = stats
+            .entries()
+            .into_iter()
+            .zip(registry.stats(&equipped).entries())
+            .map(|((label, new), (_, old))| (label, new - old))
+            .filter(|(_, delta)| *delta != 0);
+
+        for (label, delta) in deltas {
+            let color = if delta > 0 {
+                Color::rgb(0.3, 0.85, 0.3)
+            } else {
+                Color::rgb(0.9, 0.3, 0.3)
+            };
+
+            sections.push(TextSection::new(
+                format!("\n{delta:+} {label}"),
+                TextStyle {
+                    color,
+                    ..style.clone()
+                },
+            ));
+        }
     }
+
+    Tooltip(sections)
 }
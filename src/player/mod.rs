@@ -4,24 +4,46 @@ use bevy::{
     ecs::{
         component::Component,
         query::Without,
+        schedule::{common_conditions::in_state, IntoSystemConfigs, OnExit},
         system::{Commands, Query, Res, ResMut},
     },
-    input::{keyboard::KeyCode, Input},
+    input::{
+        gamepad::{GamepadAxis, Gamepads},
+        keyboard::KeyCode,
+        Axis, Input,
+    },
     log::{debug, info},
     math::{Vec2, Vec3},
     prelude::default,
-    render::{camera::Camera, color::Color},
-    sprite::{Sprite, SpriteBundle, TextureAtlas},
+    render::camera::{Camera, OrthographicProjection},
+    sprite::{SpriteSheetBundle, TextureAtlas, TextureAtlasSprite},
     transform::components::Transform,
 };
 
+use crate::combat::MeleeAttack;
 use crate::components::{Direction, Health, Velocity};
+use crate::input::{Action, InputMap, InputSettings};
+use crate::items::{container::ItemContainer, equipment::Equipment};
+use crate::menu::GameState;
+use crate::physics::{Hitbox, Traversal};
+use crate::ui::PanelState;
+use crate::world::{CurrentWeather, Interior, Movement, SpawnSafely};
 
 use crate::player::inventory::Inventory;
 
+use self::animation::PlayerAnimation;
+use self::appearance::Appearance;
+use self::channel::{ChannelPlugin, InteractionCooldown};
 use self::inventory::InventoryPlugin;
 
+mod animation;
+mod appearance;
+pub mod channel;
+mod emote;
 mod inventory;
+pub mod ping;
+
+const INVENTORY_SLOTS: usize = 24;
 
 #[derive(Component)]
 pub struct Player {
@@ -32,24 +54,71 @@ pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(InventoryPlugin)
-            .add_systems(Startup, player_spawn_system)
-            .add_systems(Update, camera_follow)
-            .add_systems(Update, player_movement);
+        app.add_plugins((InventoryPlugin, ChannelPlugin))
+            .add_systems(OnExit(GameState::MainMenu), player_spawn_system)
+            .add_systems(Update, camera_follow.run_if(in_state(GameState::InGame)))
+            .add_systems(Update, player_movement.run_if(in_state(GameState::InGame)))
+            .add_systems(
+                Update,
+                animation::animate_player
+                    .after(player_movement)
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(OnExit(GameState::MainMenu), emote::spawn_emote_wheel)
+            .add_systems(
+                Update,
+                (emote::use_emote_wheel, emote::animate_emotes)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_event::<ping::PingPlaced>()
+            .add_systems(OnExit(GameState::MainMenu), ping::spawn_ping_wheel)
+            .add_systems(
+                Update,
+                (ping::use_ping_wheel, ping::place_pings, ping::animate_pings)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                OnExit(GameState::MainMenu),
+                (
+                    appearance::spawn_character_panel,
+                    appearance::open_character_creation,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    appearance::spawn_appearance_layers,
+                    appearance::handle_customize_commands,
+                    appearance::handle_character_buttons,
+                    appearance::apply_appearance,
+                    appearance::update_character_labels,
+                    appearance::sync_appearance_layers.after(animation::animate_player),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
     }
 }
 
 fn player_spawn_system(
     mut commands: Commands,
-    _: ResMut<AssetServer>,
-    _: ResMut<Assets<TextureAtlas>>,
+    asset_server: ResMut<AssetServer>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
 ) {
-    let sprite = SpriteBundle {
-        sprite: Sprite {
-            color: Color::rgb(0.25, 0.25, 0.75),
-            custom_size: Some(Vec2::new(20., 40.)),
-            ..default()
-        },
+    let atlas = TextureAtlas::from_grid(
+        asset_server.load(animation::SHEET_PATH),
+        animation::FRAME_SIZE,
+        animation::SHEET_COLUMNS,
+        animation::SHEET_ROWS,
+        None,
+        None,
+    );
+
+    let sprite = SpriteSheetBundle {
+        texture_atlas: atlases.add(atlas),
+        sprite: TextureAtlasSprite::new(0),
         ..default()
     };
 
@@ -58,45 +127,106 @@ fn player_spawn_system(
         .spawn(sprite)
         .insert(Player { max_speed: 100.0 })
         .insert(Velocity { dx: 0., dy: 0. })
+        .insert(MeleeAttack::default())
+        .insert(InteractionCooldown::default())
         .insert(Transform::from_translation(Vec3::new(0., 0., 1.)))
-        .insert(Direction::Right)
+        .insert(Direction::Down)
+        .insert(PlayerAnimation::default())
+        .insert(SpawnSafely(Movement::Ground))
+        // Only the feet collide so the player can walk up against walls
+        .insert(Hitbox {
+            size: Vec2::new(14., 12.),
+            offset: Vec2::new(0., -14.),
+        })
         .insert(Health {
             current: 100,
             max: 100,
-        });
+        })
+        .insert(ItemContainer::with_capacity(INVENTORY_SLOTS))
+        .insert(Equipment::default())
+        .insert(Traversal::default())
+        .insert(Appearance::load().unwrap_or_default());
 }
 
 fn camera_follow(
     player_query: Query<(&Player, &Transform), Without<Camera>>,
-    mut camera_query: Query<(&mut Transform, &Camera), Without<Player>>,
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), Without<Player>>,
+    interiors: Query<&Interior>,
 ) {
-    if let Ok((mut cam_transform, _)) = camera_query.get_single_mut() {
+    if let Ok((mut cam_transform, projection)) = camera_query.get_single_mut() {
         if let Ok((_, player_transform)) = player_query.get_single() {
             cam_transform.translation = player_transform.translation;
+
+            // Inside an interior the view is kept within its bounds rather than exposing the void
+            let player_pos = player_transform.translation.truncate();
+            if let Some(interior) = interiors
+                .iter()
+                .find(|interior| interior.bounds.contains(player_pos))
+            {
+                let clamped = interior.clamp_view(player_pos, projection.area.half_size());
+                cam_transform.translation.x = clamped.x;
+                cam_transform.translation.y = clamped.y;
+            }
         }
     }
 }
 
-fn player_movement(kb: Res<Input<KeyCode>>, mut query: Query<(&mut Velocity, &Player)>) {
-    if let Ok((mut velocity, player_state)) = query.get_single_mut() {
+#[allow(clippy::too_many_arguments)]
+pub fn player_movement(
+    kb: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    input_settings: Res<InputSettings>,
+    panels: Res<PanelState>,
+    weather: Res<CurrentWeather>,
+    mut query: Query<(&mut Velocity, &mut Direction, &Player)>,
+) {
+    if let Ok((mut velocity, mut direction, player_state)) = query.get_single_mut() {
+        let max_speed = player_state.max_speed * weather.movement_factor();
+
         velocity.dx = 0.0;
-        if kb.pressed(KeyCode::Left) || kb.pressed(KeyCode::A) {
+        velocity.dy = 0.0;
+
+        // Movement is paused while a panel has focus
+        if panels.is_focused() {
+            return;
+        }
+
+        if input_map.pressed(Action::MoveLeft, &kb) {
             debug!("Player moved left!");
-            velocity.dx -= player_state.max_speed;
+            velocity.dx -= max_speed;
         }
-        if kb.pressed(KeyCode::Right) || kb.pressed(KeyCode::D) {
+        if input_map.pressed(Action::MoveRight, &kb) {
             debug!("Player moved right!");
-            velocity.dx += player_state.max_speed;
+            velocity.dx += max_speed;
         }
 
-        velocity.dy = 0.0;
-        if kb.pressed(KeyCode::Up) || kb.pressed(KeyCode::W) {
+        if input_map.pressed(Action::MoveUp, &kb) {
             debug!("Player moved up!");
-            velocity.dy += player_state.max_speed;
+            velocity.dy += max_speed;
         }
-        if kb.pressed(KeyCode::Down) || kb.pressed(KeyCode::S) {
+        if input_map.pressed(Action::MoveDown, &kb) {
             debug!("Player moved down!");
-            velocity.dy -= player_state.max_speed;
+            velocity.dy -= max_speed;
+        }
+
+        // The keyboard wins when both are used, otherwise the stick deflection sets the speed
+        if velocity.dx == 0. && velocity.dy == 0. {
+            let stick = input_settings.left_stick(&gamepads, &axes);
+            velocity.dx = stick.x * max_speed;
+            velocity.dy = stick.y * max_speed;
+        }
+
+        // Facing follows horizontal movement first and is kept while standing still
+        if velocity.dx < 0. {
+            *direction = Direction::Left;
+        } else if velocity.dx > 0. {
+            *direction = Direction::Right;
+        } else if velocity.dy > 0. {
+            *direction = Direction::Up;
+        } else if velocity.dy < 0. {
+            *direction = Direction::Down;
         }
     }
 }
@@ -0,0 +1,271 @@
+use std::f32::consts::TAU;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    input::{Action, InputMap},
+    ui::PanelState,
+    world::LOCAL_PLAYER,
+};
+
+// Cursor distance from the wheel center before a slice is picked, a tap places a go here ping
+const DEADZONE: f32 = 20.;
+
+const WHEEL_RADIUS: f32 = 60.;
+
+const PING_SECONDS: f32 = 8.;
+
+const FADE_SECONDS: f32 = 1.;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PingKind {
+    GoHere,
+    Danger,
+    Loot,
+}
+
+impl PingKind {
+    // Clockwise from the top of the wheel
+    const ALL: [PingKind; 3] = [PingKind::GoHere, PingKind::Danger, PingKind::Loot];
+
+    fn glyph(&self) -> &'static str {
+        match self {
+            PingKind::GoHere => "v",
+            PingKind::Danger => "!",
+            PingKind::Loot => "$",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PingKind::GoHere => Color::rgb(0.4, 0.85, 1.),
+            PingKind::Danger => Color::rgb(1., 0.3, 0.25),
+            PingKind::Loot => Color::rgb(0.95, 0.8, 0.3),
+        }
+    }
+
+    // Offset of the kind's slice from the wheel center, y grows downwards in the UI
+    fn slot(index: usize) -> Vec2 {
+        let angle = index as f32 / PingKind::ALL.len() as f32 * TAU;
+        Vec2::new(angle.sin(), -angle.cos()) * WHEEL_RADIUS
+    }
+}
+
+// Markers are spawned from this event alone, whoever placed the ping. Once there are peers it is
+// what gets sent to them
+#[derive(Event, Clone, Debug)]
+pub struct PingPlaced {
+    pub player: String,
+    pub position: Vec2,
+    pub kind: PingKind,
+}
+
+// Marker left in the world by a ping, each player has at most one at a time
+#[derive(Component)]
+pub struct Ping {
+    pub player: String,
+    pub kind: PingKind,
+    timer: Timer,
+}
+
+#[derive(Component)]
+pub struct PingWheel;
+
+#[derive(Component)]
+pub struct PingOption(PingKind);
+
+pub fn spawn_ping_wheel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+
+    let wheel_size = WHEEL_RADIUS * 2. + 40.;
+
+    // Moved under the cursor whenever it opens
+    let wheel_node = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Px(wheel_size),
+            height: Val::Px(wheel_size),
+            ..default()
+        },
+        background_color: Color::rgba(0.1, 0.1, 0.15, 0.6).into(),
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    commands
+        .spawn(wheel_node)
+        .insert(PingWheel)
+        .with_children(|wheel| {
+            for (index, kind) in PingKind::ALL.iter().enumerate() {
+                let slot = PingKind::slot(index) + Vec2::splat(wheel_size / 2.);
+
+                let option = TextBundle::from_section(
+                    kind.glyph(),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 24.0,
+                        color: Color::GRAY,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    // Roughly centers the glyph on its slot
+                    left: Val::Px(slot.x - 7.),
+                    top: Val::Px(slot.y - 12.),
+                    ..default()
+                });
+
+                wheel.spawn(option).insert(PingOption(*kind));
+            }
+        });
+}
+
+// Holding the key or the middle mouse button opens the wheel under the cursor, the ping lands
+// where the cursor was when it opened with the kind picked on release
+#[allow(clippy::too_many_arguments)]
+pub fn use_ping_wheel(
+    mut target: Local<Option<(Vec2, Vec2)>>,
+    input: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    input_map: Res<InputMap>,
+    panels: Res<PanelState>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut wheel: Query<(&mut Visibility, &mut Style, &Node), With<PingWheel>>,
+    mut options: Query<(&PingOption, &mut Text)>,
+    mut pings: EventWriter<PingPlaced>,
+) {
+    let Ok((mut visibility, mut style, node)) = wheel.get_single_mut() else {
+        return;
+    };
+
+    if panels.is_focused() {
+        *visibility = Visibility::Hidden;
+        *target = None;
+        return;
+    }
+
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single())
+    else {
+        return;
+    };
+
+    let cursor = window.cursor_position();
+
+    if input_map.just_pressed(Action::Ping, &input) || mouse.just_pressed(MouseButton::Middle) {
+        *target = cursor.and_then(|cursor| {
+            let world = camera.viewport_to_world_2d(camera_transform, cursor)?;
+            Some((world, cursor))
+        });
+
+        if let Some((_, anchor)) = *target {
+            let half = node.size() / 2.;
+            style.left = Val::Px(anchor.x - half.x);
+            style.top = Val::Px(anchor.y - half.y);
+            *visibility = Visibility::Visible;
+        }
+    }
+
+    let Some((position, anchor)) = *target else {
+        return;
+    };
+
+    let selected = cursor.and_then(|cursor| {
+        let offset = cursor - anchor;
+
+        if offset.length() < DEADZONE {
+            return None;
+        }
+
+        // Clockwise angle from straight up, matching the slot layout
+        let angle = offset.x.atan2(-offset.y).rem_euclid(TAU);
+        let slice = TAU / PingKind::ALL.len() as f32;
+        let index = ((angle + slice / 2.) / slice) as usize % PingKind::ALL.len();
+
+        Some(PingKind::ALL[index])
+    });
+
+    for (option, mut text) in options.iter_mut() {
+        text.sections[0].style.color = if selected == Some(option.0) {
+            option.0.color()
+        } else {
+            Color::GRAY
+        };
+    }
+
+    if input_map.pressed(Action::Ping, &input) || mouse.pressed(MouseButton::Middle) {
+        return;
+    }
+
+    *visibility = Visibility::Hidden;
+    *target = None;
+
+    let kind = selected.unwrap_or(PingKind::GoHere);
+
+    debug!("Pinging {:?} at ({}, {})", kind, position.x, position.y);
+    pings.send(PingPlaced {
+        player: LOCAL_PLAYER.to_string(),
+        position,
+        kind,
+    });
+}
+
+// A player's new ping replaces the one they placed before
+pub fn place_pings(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut placed: EventReader<PingPlaced>,
+    pings: Query<(Entity, &Ping)>,
+) {
+    for event in placed.read() {
+        for (entity, ping) in pings.iter() {
+            if ping.player == event.player {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        let marker = Text2dBundle {
+            text: Text::from_section(
+                event.kind.glyph(),
+                TextStyle {
+                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                    font_size: 28.,
+                    color: event.kind.color(),
+                },
+            ),
+            transform: Transform::from_translation(event.position.extend(10.))
+                .with_scale(Vec3::splat(0.5)),
+            ..default()
+        };
+
+        commands.spawn(marker).insert(Ping {
+            player: event.player.clone(),
+            kind: event.kind,
+            timer: Timer::from_seconds(PING_SECONDS, TimerMode::Once),
+        });
+    }
+}
+
+// Pulses for a moment when placed, then fades out at the end of its time
+pub fn animate_pings(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pings: Query<(Entity, &mut Ping, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut ping, mut transform, mut text) in pings.iter_mut() {
+        ping.timer.tick(time.delta());
+
+        if ping.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let elapsed = ping.timer.elapsed_secs();
+        let pulse = 1. + 0.5 * (-elapsed * 3.).exp() * (elapsed * 12.).sin().abs();
+
+        transform.scale = Vec3::splat(0.5 * pulse);
+
+        let alpha = (ping.timer.remaining_secs() / FADE_SECONDS).min(1.);
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
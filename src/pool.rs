@@ -0,0 +1,54 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+// Short lived entities are hidden and kept for reuse instead of despawned, T marks the ones in use.
+// Acquiring inserts the whole bundle again, so every component it carries starts over
+#[derive(Resource)]
+pub struct Pool<T: Component> {
+    free: Vec<Entity>,
+    // Released entities past this are despawned, a burst doesn't keep its peak around forever
+    limit: usize,
+    marker: PhantomData<T>,
+}
+
+impl<T: Component> Pool<T> {
+    pub fn new(limit: usize) -> Pool<T> {
+        Pool {
+            free: Vec::new(),
+            limit,
+            marker: PhantomData,
+        }
+    }
+
+    // The bundle should carry T and a visible Visibility, released entities are left without one and hidden
+    pub fn acquire(&mut self, commands: &mut Commands, bundle: impl Bundle) -> Entity {
+        // Entities despawned while released, along with a parent say, are skipped
+        while let Some(entity) = self.free.pop() {
+            if let Some(mut pooled) = commands.get_entity(entity) {
+                pooled.insert(bundle);
+                return entity;
+            }
+        }
+
+        commands.spawn(bundle).id()
+    }
+
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+        let Some(mut pooled) = commands.get_entity(entity) else {
+            return;
+        };
+
+        if self.free.len() >= self.limit {
+            pooled.despawn_recursive();
+            return;
+        }
+
+        pooled.remove::<T>().insert(Visibility::Hidden);
+        self.free.push(entity);
+    }
+
+    pub fn free(&self) -> usize {
+        self.free.len()
+    }
+}
@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+
+use crate::{
+    items::definition::ItemRegistry,
+    ui::{Panel, PanelRoot},
+};
+
+use super::{QuestDefinitions, QuestLog};
+
+#[derive(Component)]
+pub struct JournalList;
+
+pub fn spawn_journal_panel(mut commands: Commands) {
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    let list_node = NodeBundle {
+        style: Style {
+            width: Val::Px(420.),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.),
+            padding: UiRect::all(Val::Px(12.)),
+            ..default()
+        },
+        background_color: Color::rgba(0.05, 0.05, 0.08, 0.9).into(),
+        ..default()
+    };
+
+    commands
+        .spawn(container_node)
+        .insert(PanelRoot(Panel::Journal))
+        .with_children(|parent| {
+            parent.spawn(list_node).insert(JournalList);
+        });
+}
+
+// Open quests come first, finished ones are listed after them greyed out
+pub fn update_journal(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    registry: Res<ItemRegistry>,
+    quests: Res<QuestDefinitions>,
+    log: Res<QuestLog>,
+    list: Query<Entity, With<JournalList>>,
+) {
+    if !log.is_changed() {
+        return;
+    }
+
+    let Ok(list) = list.get_single() else {
+        return;
+    };
+
+    commands.entity(list).despawn_descendants();
+
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let style = |size: f32, color: Color| TextStyle {
+        font: font.clone(),
+        font_size: size,
+        color,
+    };
+
+    let mut started: Vec<_> = quests
+        .0
+        .iter()
+        .filter_map(|(name, quest)| Some((quest, log.progress(name)?)))
+        .collect();
+    started.sort_by_key(|(quest, progress)| quest.is_complete(progress));
+
+    commands.entity(list).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            "Journal",
+            style(22., Color::rgb(0.95, 0.8, 0.45)),
+        ));
+
+        if started.is_empty() {
+            parent.spawn(TextBundle::from_section(
+                "No quests yet",
+                style(16., Color::GRAY),
+            ));
+        }
+
+        for (quest, progress) in started {
+            let complete = quest.is_complete(progress);
+            let (title_color, text_color) = if complete {
+                (Color::GRAY, Color::DARK_GRAY)
+            } else {
+                (Color::WHITE, Color::rgb(0.8, 0.85, 1.))
+            };
+
+            let title = if complete {
+                format!("{} (complete)", quest.title)
+            } else {
+                quest.title.clone()
+            };
+
+            parent.spawn(
+                TextBundle::from_section(title, style(18., title_color)).with_style(Style {
+                    margin: UiRect::top(Val::Px(6.)),
+                    ..default()
+                }),
+            );
+
+            if !quest.description.is_empty() {
+                parent.spawn(
+                    TextBundle::from_section(quest.description.clone(), style(14., text_color))
+                        .with_style(Style {
+                            max_width: Val::Px(396.),
+                            ..default()
+                        }),
+                );
+            }
+
+            for (objective, done) in quest.objectives.iter().zip(progress) {
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "- {} {}/{}",
+                        objective.describe(&registry),
+                        done,
+                        objective.required()
+                    ),
+                    style(14., text_color),
+                ));
+            }
+        }
+    });
+}
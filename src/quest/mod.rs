@@ -0,0 +1,287 @@
+use std::{collections::BTreeMap, fs};
+
+use bevy::{app::AppExit, prelude::*};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    combat::Killed,
+    items::{definition::ItemRegistry, dropped::ItemCollected},
+    menu::GameState,
+    mobs::EnemyKind,
+    ui::ticker::{EventCategory, WorldEvent},
+    world::{ChunkEntered, CHUNK_SIZE, TILE_SIZE},
+};
+
+use travelers_worldgen::ChunkCoords;
+
+pub mod journal;
+
+const QUEST_PATH: &str = "assets/quests.json";
+
+const QUEST_SAVE_PATH: &str = "saves/quests.json";
+
+pub struct QuestPlugin;
+
+impl Plugin for QuestPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(QuestDefinitions::load())
+            .init_resource::<QuestLog>()
+            .add_event::<Killed>()
+            .add_event::<ItemCollected>()
+            .add_event::<ChunkEntered>()
+            .add_event::<WorldEvent>()
+            .add_systems(OnExit(GameState::MainMenu), load_quest_log)
+            .add_systems(Startup, journal::spawn_journal_panel)
+            .add_systems(
+                Update,
+                (advance_objectives, journal::update_journal)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                save_quests_on_exit.run_if(not(in_state(GameState::MainMenu))),
+            );
+    }
+}
+
+// Objectives of a quest progress side by side, the quest is done once all of them are
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Objective {
+    Kill { enemy: String, count: u32 },
+    Collect { item: u32, count: u32 },
+    // Chunks are counted from the one at the origin, one step per chunk along each axis
+    Reach { chunk: (i64, i64) },
+}
+
+impl Objective {
+    // Progress the objective needs to count as done
+    pub fn required(&self) -> u32 {
+        match self {
+            Objective::Kill { count, .. } | Objective::Collect { count, .. } => *count,
+            Objective::Reach { .. } => 1,
+        }
+    }
+
+    fn gain(&self, event: &Progress) -> u32 {
+        match (self, event) {
+            (Objective::Kill { enemy, .. }, Progress::Killed(kind)) if kind.key() == enemy => 1,
+            (Objective::Collect { item, .. }, Progress::Collected(id, count)) if id == item => {
+                *count
+            }
+            (Objective::Reach { chunk }, Progress::Entered(coords))
+                if chunk_offset(*coords) == *chunk =>
+            {
+                1
+            }
+            _ => 0,
+        }
+    }
+
+    pub fn describe(&self, registry: &ItemRegistry) -> String {
+        match self {
+            Objective::Kill { enemy, count } => format!("Slay {count} {enemy}"),
+            Objective::Collect { item, count } => {
+                format!("Collect {count} {}", registry.get(*item).name)
+            }
+            Objective::Reach { chunk } => format!("Reach chunk ({}, {})", chunk.0, chunk.1),
+        }
+    }
+}
+
+// Inverse of the chunk layout, steps of a chunk plus its stitched gap
+fn chunk_offset(coords: ChunkCoords) -> (i64, i64) {
+    (
+        (coords.0 + TILE_SIZE).div_euclid(CHUNK_SIZE + TILE_SIZE),
+        (coords.1 + TILE_SIZE).div_euclid(CHUNK_SIZE + TILE_SIZE),
+    )
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct QuestDefinition {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub objectives: Vec<Objective>,
+}
+
+impl QuestDefinition {
+    pub fn is_complete(&self, progress: &[u32]) -> bool {
+        self.objectives
+            .iter()
+            .zip(progress)
+            .all(|(objective, done)| *done >= objective.required())
+    }
+}
+
+// Keyed by name, the journal lists quests in name order
+#[derive(Resource, Default)]
+pub struct QuestDefinitions(pub BTreeMap<String, QuestDefinition>);
+
+impl QuestDefinitions {
+    pub fn load() -> QuestDefinitions {
+        let quests = fs::read(QUEST_PATH)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| {
+                serde_json::from_slice::<BTreeMap<String, QuestDefinition>>(&bytes)
+                    .map_err(|err| err.to_string())
+            });
+
+        match quests {
+            Ok(mut quests) => {
+                // A quest after an enemy that doesn't exist could never be finished
+                quests.retain(|name, quest| {
+                    let unknown = quest
+                        .objectives
+                        .iter()
+                        .find_map(|objective| match objective {
+                            Objective::Kill { enemy, .. }
+                                if EnemyKind::from_key(enemy).is_none() =>
+                            {
+                                Some(enemy)
+                            }
+                            _ => None,
+                        });
+
+                    if let Some(enemy) = unknown {
+                        warn!("Dropping quest {name}, there is no enemy named {enemy}");
+                    }
+
+                    unknown.is_none()
+                });
+
+                QuestDefinitions(quests)
+            }
+            Err(err) => {
+                warn!("Failed to load quests, the journal will be empty! Err {err}");
+                QuestDefinitions::default()
+            }
+        }
+    }
+}
+
+// Progress of every started quest, one count per objective, persisted with the world
+#[derive(Resource, Default, Deserialize, Serialize)]
+pub struct QuestLog {
+    progress: BTreeMap<String, Vec<u32>>,
+}
+
+impl QuestLog {
+    pub fn load() -> QuestLog {
+        fs::read(QUEST_SAVE_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let written = fs::create_dir_all("saves").and_then(|_| {
+            fs::write(
+                QUEST_SAVE_PATH,
+                serde_json::to_vec(self).map_err(std::io::Error::from)?,
+            )
+        });
+
+        if let Err(err) = written {
+            warn!("Failed to save quests! Err {err}");
+        }
+    }
+
+    // Saved progress is kept, padded or cut to the quest's objectives in case they were edited since
+    pub fn start(&mut self, name: &str, quest: &QuestDefinition) {
+        let progress = self.progress.entry(name.to_string()).or_default();
+        progress.resize(quest.objectives.len(), 0);
+    }
+
+    pub fn progress(&self, name: &str) -> Option<&[u32]> {
+        self.progress.get(name).map(Vec::as_slice)
+    }
+}
+
+// Every quest is handed out when the world loads, there is no one giving them out yet
+fn load_quest_log(mut commands: Commands, quests: Res<QuestDefinitions>) {
+    let mut log = QuestLog::load();
+
+    for (name, quest) in quests.0.iter() {
+        log.start(name, quest);
+    }
+
+    commands.insert_resource(log);
+}
+
+// Anything that happened this frame that an objective might be waiting on
+enum Progress {
+    Killed(EnemyKind),
+    Collected(u32, u32),
+    Entered(ChunkCoords),
+}
+
+pub fn advance_objectives(
+    quests: Res<QuestDefinitions>,
+    mut log: ResMut<QuestLog>,
+    mut killed: EventReader<Killed>,
+    mut collected: EventReader<ItemCollected>,
+    mut entered: EventReader<ChunkEntered>,
+    mut world_events: EventWriter<WorldEvent>,
+) {
+    let events: Vec<Progress> = killed
+        .read()
+        .map(|event| Progress::Killed(event.kind))
+        .chain(
+            collected
+                .read()
+                .map(|event| Progress::Collected(event.id, event.count)),
+        )
+        .chain(entered.read().map(|event| Progress::Entered(event.0)))
+        .collect();
+
+    if events.is_empty() {
+        return;
+    }
+
+    // Only marked changed when something moved, so the journal isn't rebuilt on every kill
+    let mut changed = false;
+
+    for (name, quest) in quests.0.iter() {
+        let Some(progress) = log.bypass_change_detection().progress.get_mut(name) else {
+            continue;
+        };
+
+        if quest.is_complete(progress) {
+            continue;
+        }
+
+        for (objective, done) in quest.objectives.iter().zip(progress.iter_mut()) {
+            let gained = events
+                .iter()
+                .map(|event| objective.gain(event))
+                .sum::<u32>();
+            let advanced = (*done + gained).min(objective.required());
+
+            if advanced != *done {
+                *done = advanced;
+                changed = true;
+            }
+        }
+
+        if quest.is_complete(progress) {
+            info!("Completed quest {name}");
+            world_events.send(WorldEvent::new(
+                EventCategory::Quests,
+                format!("Quest complete: {}", quest.title),
+            ));
+        }
+    }
+
+    if changed {
+        log.set_changed();
+    }
+}
+
+pub fn save_quests_on_exit(exit: EventReader<AppExit>, log: Res<QuestLog>) {
+    if !exit.is_empty() {
+        log.save();
+    }
+}
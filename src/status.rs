@@ -0,0 +1,215 @@
+use std::ops::DerefMut;
+
+use bevy::prelude::*;
+
+use crate::{
+    combat::Hurt,
+    components::{Dead, Health, Velocity},
+    menu::GameState,
+    mobs::chase::follow_paths,
+    physics::apply_velocity,
+    player::player_movement,
+};
+
+// Seconds between poison ticks, it lingers longer than fire but bites less often
+const POISON_INTERVAL: f32 = 1.;
+
+const BURN_INTERVAL: f32 = 0.5;
+
+// Slowest a slow can make anything move, so nothing is ever frozen in place
+const MIN_SLOW_FACTOR: f32 = 0.2;
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ApplyStatus>()
+            .add_event::<Hurt>()
+            .add_systems(
+                Update,
+                (
+                    apply_statuses,
+                    tick_damage_over_time::<Poisoned>,
+                    tick_damage_over_time::<Burning>,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                slow_movement
+                    .after(player_movement)
+                    .after(follow_paths)
+                    .before(apply_velocity)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+// Anything can inflict an effect by sending this, combat, hazards and items alike
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ApplyStatus {
+    pub target: Entity,
+    pub effect: StatusEffect,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatusEffect {
+    Poison { damage: u8, seconds: f32 },
+    Burn { damage: u8, seconds: f32 },
+    // Movement is scaled by the factor for the duration
+    Slow { factor: f32, seconds: f32 },
+}
+
+// Health lost every interval until the effect wears off
+#[derive(Clone, Debug)]
+pub struct DamageOverTime {
+    pub damage: u8,
+    interval: Timer,
+    remaining: Timer,
+}
+
+impl DamageOverTime {
+    fn new(damage: u8, interval: f32, seconds: f32) -> DamageOverTime {
+        DamageOverTime {
+            damage,
+            interval: Timer::from_seconds(interval, TimerMode::Repeating),
+            remaining: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+
+    // Reapplying keeps the harsher damage and the longer time left, the tick rhythm carries on
+    fn refresh(&mut self, other: DamageOverTime) {
+        self.damage = self.damage.max(other.damage);
+
+        if other.remaining.remaining() > self.remaining.remaining() {
+            self.remaining = other.remaining;
+        }
+    }
+}
+
+#[derive(Component, Clone, Debug, Deref, DerefMut)]
+pub struct Poisoned(pub DamageOverTime);
+
+#[derive(Component, Clone, Debug, Deref, DerefMut)]
+pub struct Burning(pub DamageOverTime);
+
+#[derive(Component, Clone, Debug)]
+pub struct Slowed {
+    pub factor: f32,
+    remaining: Timer,
+}
+
+// Effects already on the target are refreshed rather than stacked
+fn apply_statuses(
+    mut commands: Commands,
+    mut events: EventReader<ApplyStatus>,
+    alive: Query<(), (With<Health>, Without<Dead>)>,
+    mut targets: Query<(
+        Option<&mut Poisoned>,
+        Option<&mut Burning>,
+        Option<&mut Slowed>,
+    )>,
+) {
+    for event in events.read() {
+        if !alive.contains(event.target) {
+            continue;
+        }
+
+        let Ok((poisoned, burning, slowed)) = targets.get_mut(event.target) else {
+            continue;
+        };
+
+        debug!("Applying {:?} to {:?}", event.effect, event.target);
+
+        match event.effect {
+            StatusEffect::Poison { damage, seconds } => {
+                let poison = DamageOverTime::new(damage, POISON_INTERVAL, seconds);
+                match poisoned {
+                    Some(mut poisoned) => poisoned.refresh(poison),
+                    None => {
+                        commands.entity(event.target).insert(Poisoned(poison));
+                    }
+                }
+            }
+            StatusEffect::Burn { damage, seconds } => {
+                let burn = DamageOverTime::new(damage, BURN_INTERVAL, seconds);
+                match burning {
+                    Some(mut burning) => burning.refresh(burn),
+                    None => {
+                        commands.entity(event.target).insert(Burning(burn));
+                    }
+                }
+            }
+            StatusEffect::Slow { factor, seconds } => {
+                let factor = factor.clamp(MIN_SLOW_FACTOR, 1.);
+                let remaining = Timer::from_seconds(seconds, TimerMode::Once);
+                match slowed {
+                    Some(mut slowed) => {
+                        slowed.factor = slowed.factor.min(factor);
+                        if remaining.remaining() > slowed.remaining.remaining() {
+                            slowed.remaining = remaining;
+                        }
+                    }
+                    None => {
+                        commands
+                            .entity(event.target)
+                            .insert(Slowed { factor, remaining });
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Ticks bypass invulnerability and knockback, they wear health down rather than strike it
+fn tick_damage_over_time<T: Component + DerefMut<Target = DamageOverTime>>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut affected: Query<(Entity, &mut T, &mut Health), Without<Dead>>,
+    mut hurt: EventWriter<Hurt>,
+) {
+    for (entity, mut effect, mut health) in affected.iter_mut() {
+        let ticks = effect
+            .interval
+            .tick(time.delta())
+            .times_finished_this_tick();
+        let damage = effect
+            .damage
+            .saturating_mul(ticks.min(u8::MAX as u32) as u8);
+
+        if damage > 0 {
+            health.current = health.current.saturating_sub(damage);
+            debug!(
+                "Took {damage} damage over time, {} health left",
+                health.current
+            );
+
+            hurt.send(Hurt {
+                target: entity,
+                amount: damage,
+            });
+        }
+
+        if effect.remaining.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<T>();
+        }
+    }
+}
+
+// Movement sets velocity fresh every frame, so the slow is applied on top of it each frame
+fn slow_movement(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut slowed: Query<(Entity, &mut Velocity, &mut Slowed)>,
+) {
+    for (entity, mut velocity, mut slow) in slowed.iter_mut() {
+        if slow.remaining.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Slowed>();
+            continue;
+        }
+
+        velocity.dx *= slow.factor;
+        velocity.dy *= slow.factor;
+    }
+}
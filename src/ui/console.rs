@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+
+use super::{Panel, PanelRoot, PanelState};
+
+// Lines of output kept on screen
+const LOG_LINES: usize = 12;
+
+// Parsed line entered into the console, handled by whichever system owns the command name
+#[derive(Event, Clone, Debug)]
+pub struct ConsoleCommand {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+#[derive(Event)]
+pub struct ConsoleOutput(pub String);
+
+#[derive(Resource, Default)]
+pub struct ConsoleState {
+    input: String,
+    log: Vec<String>,
+}
+
+#[derive(Component)]
+pub struct ConsoleText;
+
+pub fn spawn_console_panel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(40.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::FlexEnd,
+            padding: UiRect::all(Val::Px(8.)),
+            ..default()
+        },
+        background_color: Color::rgba(0.02, 0.02, 0.04, 0.85).into(),
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    let text_bundle = TextBundle::from_section(
+        "> ",
+        TextStyle {
+            font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+            font_size: 18.0,
+            color: Color::WHITE,
+        },
+    );
+
+    commands
+        .spawn(container_node)
+        .insert(PanelRoot(Panel::Console))
+        .with_children(|parent| {
+            parent.spawn(text_bundle).insert(ConsoleText);
+        });
+}
+
+pub fn type_console_input(
+    panels: Res<PanelState>,
+    input: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut state: ResMut<ConsoleState>,
+    mut commands: EventWriter<ConsoleCommand>,
+) {
+    if panels.active != Some(Panel::Console) {
+        characters.clear();
+        return;
+    }
+
+    for event in characters.read() {
+        // The toggle key itself is never part of a command
+        if !event.char.is_control() && event.char != '`' {
+            state.input.push(event.char);
+        }
+    }
+
+    if input.just_pressed(KeyCode::Back) {
+        state.input.pop();
+    }
+
+    if !input.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    let line = std::mem::take(&mut state.input);
+    let mut words = line.split_whitespace().map(str::to_string);
+
+    if let Some(name) = words.next() {
+        state.log.push(format!("> {line}"));
+        commands.send(ConsoleCommand {
+            name,
+            args: words.collect(),
+        });
+    }
+}
+
+pub fn collect_console_output(
+    mut output: EventReader<ConsoleOutput>,
+    mut state: ResMut<ConsoleState>,
+) {
+    for line in output.read() {
+        info!("{}", line.0);
+        state.log.push(line.0.clone());
+    }
+
+    let overflow = state.log.len().saturating_sub(LOG_LINES);
+    if overflow > 0 {
+        state.log.drain(..overflow);
+    }
+}
+
+pub fn update_console_text(
+    state: Res<ConsoleState>,
+    mut text: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+
+    if let Ok(mut text) = text.get_single_mut() {
+        let mut lines = state.log.clone();
+        lines.push(format!("> {}", state.input));
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::{
+    input::{
+        gamepad::{GamepadAxisType, GamepadButtonType},
+        mouse::MouseButtonInput,
+        ButtonState,
+    },
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use super::PanelState;
+
+// Logical pixels per second at full stick deflection
+const CURSOR_SPEED: f32 = 900.;
+
+const STICK_DEADZONE: f32 = 0.15;
+
+// Idle cursors within this distance of an interactive widget are pulled onto it
+const SNAP_RADIUS: f32 = 48.;
+
+const SNAP_RATE: f32 = 12.;
+
+// Drives the window cursor from the right stick so menus work without a mouse
+pub fn move_virtual_cursor(
+    time: Res<Time>,
+    panels: Res<PanelState>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    widgets: Query<(&Node, &GlobalTransform, &ViewVisibility), With<Interaction>>,
+) {
+    if !panels.is_focused() {
+        return;
+    }
+
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+
+    for gamepad in gamepads.iter() {
+        let stick = Vec2::new(
+            axes.get(GamepadAxis::new(gamepad, GamepadAxisType::RightStickX))
+                .unwrap_or(0.),
+            // Stick up is positive, window coordinates grow downwards
+            -axes
+                .get(GamepadAxis::new(gamepad, GamepadAxisType::RightStickY))
+                .unwrap_or(0.),
+        );
+
+        let size = Vec2::new(window.width(), window.height());
+        let mut position = window.cursor_position().unwrap_or(size / 2.);
+
+        if stick.length() > STICK_DEADZONE {
+            position += stick * CURSOR_SPEED * time.delta_seconds();
+        } else {
+            // Snap assistance towards the closest visible widget
+            let closest = widgets
+                .iter()
+                .filter(|(_, _, visibility)| visibility.get())
+                .map(|(_, transform, _)| transform.translation().truncate())
+                .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)));
+
+            match closest {
+                Some(center) if center.distance(position) < SNAP_RADIUS => {
+                    position = position.lerp(center, (SNAP_RATE * time.delta_seconds()).min(1.));
+                }
+                _ => continue,
+            }
+        }
+
+        window.set_cursor_position(Some(position.clamp(Vec2::ZERO, size)));
+    }
+}
+
+// South face button acts as the left mouse button for drag and drop
+pub fn virtual_cursor_click(
+    panels: Res<PanelState>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    mut mouse: EventWriter<MouseButtonInput>,
+) {
+    if !panels.is_focused() {
+        return;
+    }
+
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    for gamepad in gamepads.iter() {
+        let south = GamepadButton::new(gamepad, GamepadButtonType::South);
+
+        let state = if buttons.just_pressed(south) {
+            ButtonState::Pressed
+        } else if buttons.just_released(south) {
+            ButtonState::Released
+        } else {
+            continue;
+        };
+
+        mouse.send(MouseButtonInput {
+            button: MouseButton::Left,
+            state,
+            window,
+        });
+    }
+}
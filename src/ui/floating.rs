@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+use crate::{
+    combat::Hurt,
+    menu::GameState,
+    player::Player,
+    pool::Pool,
+    world::{TileEditRejected, LOCAL_PLAYER},
+};
+
+// Damage numbers kept around for reuse, enough for a crowded fight
+const POOL_LIMIT: usize = 64;
+
+const FLOAT_SECONDS: f32 = 0.8;
+
+// Pixels per second a number drifts up while it fades
+const RISE_SPEED: f32 = 24.;
+
+// Starts just above the head of whatever was hurt
+const HEAD_OFFSET: f32 = 12.;
+
+pub struct FloatingTextPlugin;
+
+impl Plugin for FloatingTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Hurt>()
+            .add_event::<TileEditRejected>()
+            .insert_resource(Pool::<FloatingText>::new(POOL_LIMIT))
+            .add_systems(
+                Update,
+                (show_damage_numbers, show_rejected_edits, float_text)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct FloatingText {
+    timer: Timer,
+}
+
+// Damage to the player shows in red, damage it deals in white
+fn show_damage_numbers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pool: ResMut<Pool<FloatingText>>,
+    mut hurt: EventReader<Hurt>,
+    targets: Query<(&Transform, Has<Player>)>,
+) {
+    for event in hurt.read() {
+        let Ok((transform, is_player)) = targets.get(event.target) else {
+            continue;
+        };
+
+        let color = if is_player {
+            Color::rgb(1., 0.3, 0.3)
+        } else {
+            Color::WHITE
+        };
+
+        let text = Text2dBundle {
+            text: Text::from_section(
+                event.amount.to_string(),
+                TextStyle {
+                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                    font_size: 12.,
+                    color,
+                },
+            ),
+            transform: Transform::from_translation(
+                (transform.translation.truncate() + Vec2::Y * HEAD_OFFSET).extend(10.),
+            ),
+            ..default()
+        };
+
+        pool.acquire(
+            &mut commands,
+            (
+                text,
+                FloatingText {
+                    timer: Timer::from_seconds(FLOAT_SECONDS, TimerMode::Once),
+                },
+            ),
+        );
+    }
+}
+
+// Tells the player why a harvest came to nothing, right over the tile it was after
+fn show_rejected_edits(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pool: ResMut<Pool<FloatingText>>,
+    mut rejected: EventReader<TileEditRejected>,
+) {
+    for event in rejected.read() {
+        if event.player != LOCAL_PLAYER {
+            continue;
+        }
+
+        let text = Text2dBundle {
+            text: Text::from_section(
+                event.reason.message(),
+                TextStyle {
+                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                    font_size: 10.,
+                    color: Color::rgb(1., 0.75, 0.3),
+                },
+            ),
+            transform: Transform::from_translation(
+                (event.position + Vec2::Y * HEAD_OFFSET).extend(10.),
+            ),
+            ..default()
+        };
+
+        pool.acquire(
+            &mut commands,
+            (
+                text,
+                FloatingText {
+                    timer: Timer::from_seconds(FLOAT_SECONDS, TimerMode::Once),
+                },
+            ),
+        );
+    }
+}
+
+fn float_text(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<Pool<FloatingText>>,
+    mut texts: Query<(Entity, &mut Transform, &mut Text, &mut FloatingText)>,
+) {
+    for (entity, mut transform, mut text, mut floating) in texts.iter_mut() {
+        if floating.timer.tick(time.delta()).finished() {
+            pool.release(&mut commands, entity);
+            continue;
+        }
+
+        transform.translation.y += RISE_SPEED * time.delta_seconds();
+
+        let fade = floating.timer.percent_left();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(fade);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::Health,
+    input::{Action, InputMap},
+    items::{container::ItemContainer, definition::ItemRegistry},
+    menu::GameState,
+    player::Player,
+};
+
+use super::PanelState;
+
+// Slots shown on the hotbar, mirroring the first slots of the inventory
+pub const HOTBAR_SLOTS: usize = 9;
+
+const SLOT_SIZE: f32 = 44.;
+
+const HEALTH_BAR_WIDTH: f32 = 9. * SLOT_SIZE;
+
+const SELECTED_TINT: Color = Color::rgb(1., 0.85, 0.3);
+
+const HOTBAR_ACTIONS: [Action; HOTBAR_SLOTS] = [
+    Action::Hotbar1,
+    Action::Hotbar2,
+    Action::Hotbar3,
+    Action::Hotbar4,
+    Action::Hotbar5,
+    Action::Hotbar6,
+    Action::Hotbar7,
+    Action::Hotbar8,
+    Action::Hotbar9,
+];
+
+// Inventory slot picked on the hotbar
+#[derive(Resource, Default)]
+pub struct Hotbar {
+    pub selected: usize,
+}
+
+#[derive(Component)]
+pub struct HealthBarFill;
+
+#[derive(Component)]
+pub struct HealthLabel;
+
+#[derive(Component)]
+pub struct HotbarSlot(usize);
+
+#[derive(Component)]
+pub struct HotbarLabel(usize);
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Hotbar>()
+            .add_systems(OnExit(GameState::MainMenu), spawn_hud)
+            .add_systems(
+                Update,
+                (
+                    select_hotbar_slot,
+                    update_health_bar,
+                    update_hotbar,
+                    highlight_selected_slot,
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+// Health bar above the hotbar, centered along the bottom of the screen
+fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    info!("Spawning HUD");
+
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let slot_texture = asset_server.load::<Image>("sprites/display/items/slot.png");
+
+    let root = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(12.),
+            width: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(4.),
+            ..default()
+        },
+        ..default()
+    };
+
+    let track = NodeBundle {
+        style: Style {
+            width: Val::Px(HEALTH_BAR_WIDTH),
+            height: Val::Px(14.),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.6).into(),
+        ..default()
+    };
+
+    let fill = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(0.),
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            ..default()
+        },
+        background_color: Color::rgb(0.8, 0.2, 0.2).into(),
+        ..default()
+    };
+
+    let label = TextBundle::from_section(
+        "",
+        TextStyle {
+            font: font.clone(),
+            font_size: 12.0,
+            color: Color::WHITE,
+        },
+    );
+
+    let row = NodeBundle {
+        style: Style {
+            column_gap: Val::Px(2.),
+            ..default()
+        },
+        ..default()
+    };
+
+    commands.spawn(root).with_children(|parent| {
+        parent.spawn(track).with_children(|parent| {
+            parent.spawn(fill).insert(HealthBarFill);
+            parent.spawn(label).insert(HealthLabel);
+        });
+
+        parent.spawn(row).with_children(|parent| {
+            for idx in 0..HOTBAR_SLOTS {
+                let slot = ImageBundle {
+                    image: UiImage {
+                        texture: slot_texture.clone(),
+                        ..default()
+                    },
+                    style: Style {
+                        width: Val::Px(SLOT_SIZE),
+                        height: Val::Px(SLOT_SIZE),
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::SpaceBetween,
+                        padding: UiRect::all(Val::Px(3.)),
+                        ..default()
+                    },
+                    ..default()
+                };
+
+                let key = TextBundle::from_section(
+                    format!("{}", idx + 1),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 10.0,
+                        color: Color::rgba(1., 1., 1., 0.6),
+                    },
+                );
+
+                parent
+                    .spawn(slot)
+                    .insert(HotbarSlot(idx))
+                    .with_children(|parent| {
+                        parent.spawn(key);
+                        parent.spawn(TextBundle::default()).insert(HotbarLabel(idx));
+                    });
+            }
+        });
+    });
+}
+
+fn select_hotbar_slot(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    panels: Res<PanelState>,
+    mut hotbar: ResMut<Hotbar>,
+) {
+    // Number keys are typed into the console and stash search instead
+    if panels.is_focused() {
+        return;
+    }
+
+    if let Some(slot) = HOTBAR_ACTIONS
+        .iter()
+        .position(|action| input_map.just_pressed(*action, &input))
+    {
+        hotbar.selected = slot;
+        debug!("Selected hotbar slot {}", slot + 1);
+    }
+}
+
+fn update_health_bar(
+    player: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut fill: Query<&mut Style, With<HealthBarFill>>,
+    mut label: Query<&mut Text, With<HealthLabel>>,
+) {
+    let Ok(health) = player.get_single() else {
+        return;
+    };
+
+    let fraction = health.current as f32 / health.max.max(1) as f32;
+
+    if let Ok(mut style) = fill.get_single_mut() {
+        style.width = Val::Percent(fraction * 100.);
+    }
+
+    if let Ok(mut text) = label.get_single_mut() {
+        text.sections[0].value = format!("{}/{}", health.current, health.max);
+    }
+}
+
+fn update_hotbar(
+    asset_server: Res<AssetServer>,
+    registry: Res<ItemRegistry>,
+    player: Query<Ref<ItemContainer>, With<Player>>,
+    mut labels: Query<(&HotbarLabel, &mut Text)>,
+) {
+    let Ok(inventory) = player.get_single() else {
+        return;
+    };
+
+    if !inventory.is_changed() {
+        return;
+    }
+
+    let style = TextStyle {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: 10.0,
+        color: Color::WHITE,
+    };
+
+    for (label, mut text) in labels.iter_mut() {
+        text.sections = match inventory.slots.get(label.0).copied().flatten() {
+            Some(stack) => vec![
+                registry.get(stack.id).name_section(style.clone()),
+                TextSection::new(format!(" x{}", stack.count), style.clone()),
+            ],
+            None => Vec::new(),
+        };
+    }
+}
+
+fn highlight_selected_slot(
+    hotbar: Res<Hotbar>,
+    mut slots: Query<(&HotbarSlot, &mut BackgroundColor)>,
+) {
+    if !hotbar.is_changed() {
+        return;
+    }
+
+    for (slot, mut color) in slots.iter_mut() {
+        *color = if slot.0 == hotbar.selected {
+            SELECTED_TINT.into()
+        } else {
+            Color::WHITE.into()
+        };
+    }
+}
@@ -0,0 +1,264 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::input::{Action, InputMap, InputSettings};
+
+use self::settings::GraphicsSettings;
+
+mod cursor;
+
+pub mod console;
+pub mod floating;
+pub mod hud;
+pub mod settings;
+pub mod ticker;
+pub mod tooltip;
+pub mod transition;
+
+// Minimum time between panel toggles so held or bouncing keys don't flicker panels
+const TOGGLE_COOLDOWN: f32 = 0.15;
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((hud::HudPlugin, ticker::TickerPlugin))
+            .add_plugins(floating::FloatingTextPlugin)
+            .init_resource::<PanelState>()
+            .init_resource::<console::ConsoleState>()
+            .add_event::<console::ConsoleCommand>()
+            .add_event::<console::ConsoleOutput>()
+            .add_systems(Startup, console::spawn_console_panel)
+            .add_systems(
+                Update,
+                (
+                    console::type_console_input,
+                    console::collect_console_output,
+                    console::update_console_text,
+                )
+                    .chain(),
+            )
+            .init_resource::<GraphicsSettings>()
+            .insert_resource(InputSettings::load())
+            .insert_resource(InputMap::load())
+            .add_event::<TogglePanel>()
+            .add_systems(Startup, spawn_crafting_panel)
+            .add_systems(Startup, settings::spawn_settings_panel)
+            .add_systems(Startup, tooltip::spawn_tooltip_popup)
+            .init_resource::<transition::ScreenTransition>()
+            .add_event::<transition::TransitionCovered>()
+            .add_event::<transition::TransitionFinished>()
+            .add_systems(Startup, transition::spawn_transition_curtain)
+            .add_systems(Update, transition::play_transitions)
+            // Cleared after input is read but before buttons see the mouse
+            .add_systems(
+                PreUpdate,
+                transition::block_transition_input
+                    .after(bevy::input::InputSystem)
+                    .before(bevy::ui::UiSystem::Focus),
+            )
+            .add_systems(Update, tooltip::show_tooltips)
+            .add_systems(
+                Update,
+                (
+                    settings::handle_settings_buttons,
+                    settings::update_settings_labels,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (panel_input, toggle_panels, apply_panel_visibility).chain(),
+            )
+            .add_systems(
+                PreUpdate,
+                (cursor::move_virtual_cursor, cursor::virtual_cursor_click)
+                    .before(bevy::input::InputSystem),
+            );
+    }
+}
+
+// Exclusive panels, at most one is open at a time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Panel {
+    Inventory,
+    Map,
+    Crafting,
+    Console,
+    Settings,
+    Stash,
+    Upgrade,
+    Character,
+    Lockpick,
+    Dialogue,
+    Journal,
+}
+
+impl Panel {
+    // Panels with a text field swallow letter keys that would otherwise toggle panels
+    pub fn captures_text(&self) -> bool {
+        matches!(self, Panel::Console | Panel::Stash)
+    }
+}
+
+#[derive(Component)]
+pub struct PanelRoot(pub Panel);
+
+#[derive(Event)]
+pub struct TogglePanel(pub Panel);
+
+#[derive(Resource)]
+pub struct PanelState {
+    pub active: Option<Panel>,
+    cooldown: Timer,
+}
+
+impl Default for PanelState {
+    fn default() -> Self {
+        let mut cooldown = Timer::new(Duration::from_secs_f32(TOGGLE_COOLDOWN), TimerMode::Once);
+        cooldown.tick(cooldown.duration());
+
+        PanelState {
+            active: None,
+            cooldown,
+        }
+    }
+}
+
+impl PanelState {
+    // Player input is paused while a panel has focus
+    pub fn is_focused(&self) -> bool {
+        self.active.is_some()
+    }
+}
+
+// Spawns a hidden, centered panel displaying the given texture
+pub fn spawn_panel(commands: &mut Commands, texture: Handle<Image>, panel: Panel) -> Entity {
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    let image_bundle = ImageBundle {
+        image: UiImage {
+            texture,
+            ..Default::default()
+        },
+        style: Style {
+            width: Val::Vw(20.),
+            ..Default::default()
+        },
+        visibility: Visibility::Inherited,
+        ..Default::default()
+    };
+
+    let container = commands.spawn(container_node).insert(PanelRoot(panel)).id();
+    let sprite = commands.spawn(image_bundle).id();
+
+    commands.entity(container).push_children(&[sprite]);
+
+    container
+}
+
+fn spawn_crafting_panel(mut commands: Commands, assets: Res<AssetServer>) {
+    info!("Initializing crafting panel");
+
+    let texture_handle = assets.load::<Image>("sprites/display/items/crafting.png");
+    spawn_panel(&mut commands, texture_handle, Panel::Crafting);
+}
+
+fn panel_input(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    gamepads: Res<Gamepads>,
+    pad: Res<Input<GamepadButton>>,
+    input_settings: Res<InputSettings>,
+    state: Res<PanelState>,
+    mut toggles: EventWriter<TogglePanel>,
+) {
+    if state.active.is_some_and(|panel| panel.captures_text()) {
+        return;
+    }
+
+    let bindings = [
+        (Action::Inventory, Panel::Inventory),
+        (Action::Map, Panel::Map),
+        (Action::Crafting, Panel::Crafting),
+        (Action::Console, Panel::Console),
+        (Action::Settings, Panel::Settings),
+        (Action::Journal, Panel::Journal),
+    ];
+
+    for (action, panel) in bindings {
+        if input_map.just_pressed(action, &input) {
+            toggles.send(TogglePanel(panel));
+        }
+    }
+
+    if input_settings.just_pressed(input_settings.inventory, &gamepads, &pad) {
+        toggles.send(TogglePanel(Panel::Inventory));
+    }
+}
+
+fn toggle_panels(
+    time: Res<Time>,
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut state: ResMut<PanelState>,
+    mut toggles: EventReader<TogglePanel>,
+) {
+    state.cooldown.tick(time.delta());
+
+    // Back always closes whatever is open
+    if input_map.just_pressed(Action::Back, &input) && state.active.is_some() {
+        state.active = None;
+        toggles.clear();
+        return;
+    }
+
+    let Some(TogglePanel(panel)) = toggles.read().last() else {
+        return;
+    };
+
+    if !state.cooldown.finished() {
+        debug!("Ignoring {:?} toggle during cooldown", panel);
+        return;
+    }
+
+    // Opening a panel closes any other, toggling the open one closes it
+    state.active = if state.active == Some(*panel) {
+        None
+    } else {
+        Some(*panel)
+    };
+
+    state.cooldown.reset();
+    info!("Active panel: {:?}", state.active);
+}
+
+fn apply_panel_visibility(
+    state: Res<PanelState>,
+    mut panels: Query<(&PanelRoot, &mut Visibility)>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+
+    for (root, mut visibility) in panels.iter_mut() {
+        *visibility = if state.active == Some(root.0) {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
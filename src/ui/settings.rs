@@ -0,0 +1,304 @@
+use std::fs;
+
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ticker::{EventCategory, TickerSettings},
+    transition::TransitionStyle,
+    Panel, PanelRoot,
+};
+use crate::{input::InputSettings, world::WorldSettings};
+
+const SETTINGS_PATH: &str = "settings.json";
+
+const RESOLUTIONS: [(u32, u32); 4] = [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440)];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DisplayMode {
+    Fullscreen,
+    Borderless,
+    Windowed,
+}
+
+#[derive(Resource, Clone, Debug, Deserialize, Serialize)]
+pub struct GraphicsSettings {
+    pub mode: DisplayMode,
+    pub resolution: (u32, u32),
+    pub vsync: bool,
+    #[serde(default)]
+    pub transition: TransitionStyle,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        GraphicsSettings {
+            mode: DisplayMode::Fullscreen,
+            resolution: (1920, 1080),
+            vsync: true,
+            transition: TransitionStyle::Fade,
+        }
+    }
+}
+
+impl GraphicsSettings {
+    // Falls back to defaults when no settings have been saved yet
+    pub fn load() -> GraphicsSettings {
+        fs::read(SETTINGS_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        match serde_json::to_vec_pretty(self) {
+            Ok(bytes) => {
+                if let Err(err) = fs::write(SETTINGS_PATH, bytes) {
+                    warn!("Failed to save settings! Err {err}");
+                }
+            }
+            Err(err) => warn!("Failed to serialize settings! Err {err}"),
+        }
+    }
+
+    pub fn apply(&self, window: &mut Window) {
+        window.mode = match self.mode {
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Windowed => WindowMode::Windowed,
+        };
+
+        window
+            .resolution
+            .set(self.resolution.0 as f32, self.resolution.1 as f32);
+
+        window.present_mode = if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum SettingsButton {
+    Mode,
+    Resolution,
+    VSync,
+    Transition,
+    RenderDistance,
+    PadAttack,
+    PadInventory,
+    Mute(EventCategory),
+}
+
+impl SettingsButton {
+    fn label(
+        &self,
+        settings: &GraphicsSettings,
+        world: &WorldSettings,
+        input: &InputSettings,
+        ticker: &TickerSettings,
+    ) -> String {
+        match self {
+            SettingsButton::Mode => format!("Mode: {:?}", settings.mode),
+            SettingsButton::Resolution => format!(
+                "Resolution: {}x{}",
+                settings.resolution.0, settings.resolution.1
+            ),
+            SettingsButton::VSync => {
+                format!("VSync: {}", if settings.vsync { "On" } else { "Off" })
+            }
+            SettingsButton::Transition => format!("Transitions: {:?}", settings.transition),
+            SettingsButton::RenderDistance => {
+                format!("Render Distance: {}", world.render_distance)
+            }
+            SettingsButton::PadAttack => format!("Gamepad Attack: {:?}", input.attack),
+            SettingsButton::PadInventory => {
+                format!("Gamepad Inventory: {:?}", input.inventory)
+            }
+            SettingsButton::Mute(category) => format!(
+                "{:?} Events: {}",
+                category,
+                if ticker.is_muted(*category) {
+                    "Muted"
+                } else {
+                    "Shown"
+                }
+            ),
+        }
+    }
+}
+
+pub fn spawn_settings_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<GraphicsSettings>,
+    world: Res<WorldSettings>,
+    input: Res<InputSettings>,
+    ticker: Res<TickerSettings>,
+) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+
+    let container_node = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(8.),
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    commands
+        .spawn(container_node)
+        .insert(PanelRoot(Panel::Settings))
+        .with_children(|parent| {
+            let buttons = [
+                SettingsButton::Mode,
+                SettingsButton::Resolution,
+                SettingsButton::VSync,
+                SettingsButton::Transition,
+                SettingsButton::RenderDistance,
+                SettingsButton::PadAttack,
+                SettingsButton::PadInventory,
+            ]
+            .into_iter()
+            .chain(EventCategory::ALL.map(SettingsButton::Mute));
+
+            for button in buttons {
+                let button_bundle = ButtonBundle {
+                    style: Style {
+                        width: Val::Px(320.),
+                        padding: UiRect::all(Val::Px(8.)),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.1, 0.1, 0.15, 0.9).into(),
+                    ..default()
+                };
+
+                parent
+                    .spawn(button_bundle)
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            button.label(&settings, &world, &input, &ticker),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 20.0,
+                                color: Color::WHITE,
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+pub fn handle_settings_buttons(
+    mut settings: ResMut<GraphicsSettings>,
+    mut world: ResMut<WorldSettings>,
+    mut input: ResMut<InputSettings>,
+    mut ticker: ResMut<TickerSettings>,
+    buttons: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let mut changed = false;
+
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            SettingsButton::Mode => {
+                settings.mode = match settings.mode {
+                    DisplayMode::Fullscreen => DisplayMode::Borderless,
+                    DisplayMode::Borderless => DisplayMode::Windowed,
+                    DisplayMode::Windowed => DisplayMode::Fullscreen,
+                };
+            }
+            SettingsButton::Resolution => {
+                let current = RESOLUTIONS
+                    .iter()
+                    .position(|resolution| *resolution == settings.resolution);
+                settings.resolution = RESOLUTIONS[current
+                    .map(|idx| (idx + 1) % RESOLUTIONS.len())
+                    .unwrap_or(0)];
+            }
+            SettingsButton::VSync => settings.vsync = !settings.vsync,
+            // Only read when the next transition plays, the window is left alone
+            SettingsButton::Transition => {
+                settings.transition = settings.transition.next();
+                settings.save();
+                continue;
+            }
+            // Chunks stream in and out around the player on the next update
+            SettingsButton::RenderDistance => {
+                world.cycle_render_distance();
+                world.save();
+                continue;
+            }
+            // Bindings take effect immediately, nothing about the window changes
+            SettingsButton::PadAttack => {
+                input.attack = input.attack.next();
+                input.save();
+                continue;
+            }
+            SettingsButton::PadInventory => {
+                input.inventory = input.inventory.next();
+                input.save();
+                continue;
+            }
+            // Events already queued still scroll by
+            SettingsButton::Mute(category) => {
+                ticker.toggle(*category);
+                ticker.save();
+                continue;
+            }
+        }
+
+        changed = true;
+    }
+
+    if !changed {
+        return;
+    }
+
+    // Applied live, the UI reflows from its percentage based layout
+    if let Ok(mut window) = windows.get_single_mut() {
+        settings.apply(&mut window);
+    }
+
+    settings.save();
+}
+
+pub fn update_settings_labels(
+    settings: Res<GraphicsSettings>,
+    world: Res<WorldSettings>,
+    input: Res<InputSettings>,
+    ticker: Res<TickerSettings>,
+    buttons: Query<(&SettingsButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if !settings.is_changed() && !world.is_changed() && !input.is_changed() && !ticker.is_changed()
+    {
+        return;
+    }
+
+    for (button, children) in buttons.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = button.label(&settings, &world, &input, &ticker);
+            }
+        }
+    }
+}
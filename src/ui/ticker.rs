@@ -0,0 +1,245 @@
+use std::{collections::VecDeque, fs};
+
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    items::{
+        definition::{ItemRegistry, Rarity},
+        dropped::DropItem,
+    },
+    menu::GameState,
+};
+
+const SETTINGS_PATH: &str = "ticker_settings.json";
+
+const TICKER_WIDTH: f32 = 420.;
+
+// Pixels per second the text scrolls by at
+const SCROLL_SPEED: f32 = 90.;
+
+// Gap between one message leaving and the next entering
+const MESSAGE_GAP: f32 = 40.;
+
+// Messages waiting beyond this are dropped, the ticker summarizes rather than logs
+const MAX_QUEUED: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EventCategory {
+    Camps,
+    Challenges,
+    Loot,
+    Quests,
+}
+
+impl EventCategory {
+    pub const ALL: [EventCategory; 4] = [
+        EventCategory::Camps,
+        EventCategory::Challenges,
+        EventCategory::Loot,
+        EventCategory::Quests,
+    ];
+}
+
+// Sent by anything worth announcing, shown on the ticker unless its category is muted
+#[derive(Event, Clone, Debug)]
+pub struct WorldEvent {
+    pub category: EventCategory,
+    pub message: String,
+}
+
+impl WorldEvent {
+    pub fn new(category: EventCategory, message: impl Into<String>) -> WorldEvent {
+        WorldEvent {
+            category,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TickerSettings {
+    pub muted: Vec<EventCategory>,
+}
+
+impl TickerSettings {
+    // Falls back to defaults when no settings have been saved yet
+    pub fn load() -> TickerSettings {
+        fs::read(SETTINGS_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        match serde_json::to_vec_pretty(self) {
+            Ok(bytes) => {
+                if let Err(err) = fs::write(SETTINGS_PATH, bytes) {
+                    warn!("Failed to save ticker settings! Err {err}");
+                }
+            }
+            Err(err) => warn!("Failed to serialize ticker settings! Err {err}"),
+        }
+    }
+
+    pub fn is_muted(&self, category: EventCategory) -> bool {
+        self.muted.contains(&category)
+    }
+
+    pub fn toggle(&mut self, category: EventCategory) {
+        if self.is_muted(category) {
+            self.muted.retain(|muted| *muted != category);
+        } else {
+            self.muted.push(category);
+        }
+    }
+}
+
+// Messages waiting to scroll by, the front one is on screen once it has an offset
+#[derive(Resource, Default)]
+pub struct Ticker {
+    queue: VecDeque<String>,
+    offset: Option<f32>,
+}
+
+#[derive(Component)]
+pub struct TickerRoot;
+
+#[derive(Component)]
+pub struct TickerText;
+
+pub struct TickerPlugin;
+
+impl Plugin for TickerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WorldEvent>()
+            .init_resource::<Ticker>()
+            .insert_resource(TickerSettings::load())
+            .add_systems(Startup, spawn_ticker)
+            .add_systems(
+                Update,
+                (announce_rare_drops, queue_world_events, scroll_ticker)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+// Clipped strip along the top of the screen, hidden while there is nothing to announce
+fn spawn_ticker(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let root = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.),
+            left: Val::Percent(50.),
+            margin: UiRect::left(Val::Px(-TICKER_WIDTH / 2.)),
+            width: Val::Px(TICKER_WIDTH),
+            height: Val::Px(26.),
+            overflow: Overflow::clip(),
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.5).into(),
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    let text = TextBundle {
+        text: Text::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                font_size: 18.0,
+                color: Color::WHITE,
+            },
+        ),
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(TICKER_WIDTH),
+            top: Val::Px(3.),
+            ..default()
+        },
+        ..default()
+    };
+
+    commands
+        .spawn(root)
+        .insert(TickerRoot)
+        .with_children(|parent| {
+            parent.spawn(text).insert(TickerText);
+        });
+}
+
+// Epic and legendary drops are news, anything commoner is not
+fn announce_rare_drops(
+    registry: Res<ItemRegistry>,
+    mut drops: EventReader<DropItem>,
+    mut events: EventWriter<WorldEvent>,
+) {
+    for drop in drops.read() {
+        let definition = registry.get(drop.stack.id);
+
+        if definition.rarity >= Rarity::Epic {
+            events.send(WorldEvent::new(
+                EventCategory::Loot,
+                format!("{} dropped nearby", definition.name),
+            ));
+        }
+    }
+}
+
+fn queue_world_events(
+    settings: Res<TickerSettings>,
+    mut ticker: ResMut<Ticker>,
+    mut events: EventReader<WorldEvent>,
+) {
+    for event in events.read() {
+        if settings.is_muted(event.category) || ticker.queue.len() >= MAX_QUEUED {
+            debug!(
+                "Not announcing {:?} event: {}",
+                event.category, event.message
+            );
+            continue;
+        }
+
+        ticker.queue.push_back(event.message.clone());
+    }
+}
+
+// The front message scrolls in from the right, once it has left on the left the next one follows
+fn scroll_ticker(
+    time: Res<Time>,
+    mut ticker: ResMut<Ticker>,
+    mut root: Query<&mut Visibility, With<TickerRoot>>,
+    mut text: Query<(&mut Text, &mut Style, &Node), With<TickerText>>,
+) {
+    let (Ok(mut visibility), Ok((mut text, mut style, node))) =
+        (root.get_single_mut(), text.get_single_mut())
+    else {
+        return;
+    };
+
+    let Some(message) = ticker.queue.front().cloned() else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let offset = match ticker.offset {
+        Some(offset) => offset - SCROLL_SPEED * time.delta_seconds(),
+        None => {
+            text.sections[0].value = message;
+            *visibility = Visibility::Inherited;
+            TICKER_WIDTH
+        }
+    };
+
+    // Text laid out this frame has no width yet, it is measured before it can leave
+    if node.size().x > 0. && offset < -node.size().x - MESSAGE_GAP {
+        ticker.queue.pop_front();
+        ticker.offset = None;
+        return;
+    }
+
+    ticker.offset = Some(offset);
+    style.left = Val::Px(offset);
+}
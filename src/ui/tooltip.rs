@@ -0,0 +1,57 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+// Offset from the cursor so the popup doesn't sit under it
+const CURSOR_OFFSET: Vec2 = Vec2::new(16., 16.);
+
+// Text shown next to the cursor while the widget carrying it is hovered
+#[derive(Component, Clone, Default)]
+pub struct Tooltip(pub Vec<TextSection>);
+
+#[derive(Component)]
+pub struct TooltipPopup;
+
+pub fn spawn_tooltip_popup(mut commands: Commands) {
+    let popup = TextBundle {
+        text: Text::default(),
+        style: Style {
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(Val::Px(6.)),
+            ..default()
+        },
+        background_color: Color::rgba(0.05, 0.05, 0.08, 0.95).into(),
+        visibility: Visibility::Hidden,
+        z_index: ZIndex::Global(100),
+        ..default()
+    };
+
+    commands.spawn(popup).insert(TooltipPopup);
+}
+
+pub fn show_tooltips(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    widgets: Query<(&Interaction, &Tooltip, &ViewVisibility)>,
+    mut popup: Query<(&mut Text, &mut Style, &mut Visibility), With<TooltipPopup>>,
+) {
+    let Ok((mut text, mut style, mut visibility)) = popup.get_single_mut() else {
+        return;
+    };
+
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+
+    let hovered = widgets
+        .iter()
+        .find(|(interaction, _, view)| **interaction != Interaction::None && view.get());
+
+    match (hovered, cursor) {
+        (Some((_, tooltip, _)), Some(cursor)) => {
+            text.sections = tooltip.0.clone();
+            style.left = Val::Px(cursor.x + CURSOR_OFFSET.x);
+            style.top = Val::Px(cursor.y + CURSOR_OFFSET.y);
+            *visibility = Visibility::Visible;
+        }
+        _ => *visibility = Visibility::Hidden,
+    }
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use super::settings::GraphicsSettings;
+
+// Seconds to cover the screen, and again to reveal it
+const HALF_SECONDS: f32 = 0.35;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TransitionStyle {
+    #[default]
+    Fade,
+    Wipe,
+}
+
+impl TransitionStyle {
+    pub fn next(&self) -> TransitionStyle {
+        match self {
+            TransitionStyle::Fade => TransitionStyle::Wipe,
+            TransitionStyle::Wipe => TransitionStyle::Fade,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionKind {
+    EnterWorld,
+    Teleport,
+}
+
+// Sent once the screen is fully covered, whatever the transition hides happens now
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TransitionCovered(pub TransitionKind);
+
+// Sent once the screen is revealed again and input is handed back
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TransitionFinished(pub TransitionKind);
+
+// The transition playing and seconds into it, at most one plays at a time
+#[derive(Resource, Default)]
+pub struct ScreenTransition(Option<(TransitionKind, f32)>);
+
+impl ScreenTransition {
+    // Refused while another transition is still playing, callers try again once it has finished
+    pub fn start(&mut self, kind: TransitionKind) -> bool {
+        if self.0.is_some() {
+            return false;
+        }
+
+        debug!("Starting {kind:?} transition");
+        self.0 = Some((kind, 0.));
+        true
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.0.is_some()
+    }
+
+    // Whether the transition of this kind has covered the screen and is revealing it again
+    pub fn is_covered(&self, kind: TransitionKind) -> bool {
+        matches!(self.0, Some((playing, elapsed)) if playing == kind && elapsed >= HALF_SECONDS)
+    }
+}
+
+#[derive(Component)]
+pub struct TransitionCurtain;
+
+// Drawn over everything, tooltips included
+pub fn spawn_transition_curtain(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            background_color: Color::BLACK.into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(200),
+            ..default()
+        })
+        .insert(TransitionCurtain);
+}
+
+// Nothing the player presses while the screen is covered reaches the game or the ui
+pub fn block_transition_input(
+    transition: Res<ScreenTransition>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut mouse: ResMut<Input<MouseButton>>,
+    mut pads: ResMut<Input<GamepadButton>>,
+) {
+    if !transition.is_playing() {
+        return;
+    }
+
+    keys.reset_all();
+    mouse.reset_all();
+    pads.reset_all();
+}
+
+pub fn play_transitions(
+    time: Res<Time>,
+    settings: Res<GraphicsSettings>,
+    mut transition: ResMut<ScreenTransition>,
+    mut curtains: Query<
+        (&mut Style, &mut BackgroundColor, &mut Visibility),
+        With<TransitionCurtain>,
+    >,
+    mut covered: EventWriter<TransitionCovered>,
+    mut finished: EventWriter<TransitionFinished>,
+) {
+    let Ok((mut style, mut background, mut visibility)) = curtains.get_single_mut() else {
+        return;
+    };
+
+    let Some((kind, elapsed)) = transition.0 else {
+        return;
+    };
+
+    let now = elapsed + time.delta_seconds();
+
+    if elapsed < HALF_SECONDS && now >= HALF_SECONDS {
+        covered.send(TransitionCovered(kind));
+    }
+
+    if now >= 2. * HALF_SECONDS {
+        debug!("Finished {kind:?} transition");
+        transition.0 = None;
+        *visibility = Visibility::Hidden;
+        finished.send(TransitionFinished(kind));
+        return;
+    }
+
+    transition.0 = Some((kind, now));
+    *visibility = Visibility::Inherited;
+
+    // Climbs to fully covered halfway through and falls back after
+    let coverage = 1. - (now / HALF_SECONDS - 1.).abs();
+
+    match settings.transition {
+        TransitionStyle::Fade => {
+            style.left = Val::Percent(0.);
+            style.width = Val::Percent(100.);
+            background.0 = Color::rgba(0., 0., 0., coverage);
+        }
+        // Sweeps in from the left and carries on off the right
+        TransitionStyle::Wipe => {
+            let left = if now < HALF_SECONDS {
+                0.
+            } else {
+                1. - coverage
+            };
+
+            style.left = Val::Percent(left * 100.);
+            style.width = Val::Percent(coverage * 100.);
+            background.0 = Color::BLACK;
+        }
+    }
+}
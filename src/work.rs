@@ -0,0 +1,90 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+
+// Time each frame may spend on deferred work, the first job of a frame always runs
+const FRAME_BUDGET: Duration = Duration::from_millis(3);
+
+pub struct WorkPlugin;
+
+impl Plugin for WorkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorkQueue>()
+            .add_systems(PostUpdate, run_deferred_work);
+    }
+}
+
+type Job = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+// Heavy work pushed by systems and run in order within a per frame budget, bursts are spread over
+// frames instead of landing in one
+#[derive(Resource)]
+pub struct WorkQueue {
+    // Keyed jobs are replaced rather than queued twice while still pending
+    jobs: VecDeque<(Option<String>, Job)>,
+    pub budget: Duration,
+}
+
+impl Default for WorkQueue {
+    fn default() -> Self {
+        WorkQueue {
+            jobs: VecDeque::new(),
+            budget: FRAME_BUDGET,
+        }
+    }
+}
+
+impl WorkQueue {
+    pub fn push(&mut self, job: impl FnOnce(&mut World) + Send + Sync + 'static) {
+        self.jobs.push_back((None, Box::new(job)));
+    }
+
+    // Only the latest job under the key runs, keeping the place in line of the one it replaces
+    pub fn push_latest(
+        &mut self,
+        key: impl Into<String>,
+        job: impl FnOnce(&mut World) + Send + Sync + 'static,
+    ) {
+        let key = key.into();
+
+        match self
+            .jobs
+            .iter_mut()
+            .find(|(pending, _)| pending.as_ref() == Some(&key))
+        {
+            Some((_, pending)) => *pending = Box::new(job),
+            None => self.jobs.push_back((Some(key), Box::new(job))),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+}
+
+pub fn run_deferred_work(world: &mut World) {
+    let started = Instant::now();
+    let budget = world.resource::<WorkQueue>().budget;
+    let mut ran = 0;
+
+    while let Some((_, job)) = world.resource_mut::<WorkQueue>().jobs.pop_front() {
+        job(world);
+        ran += 1;
+
+        if started.elapsed() >= budget {
+            break;
+        }
+    }
+
+    let left = world.resource::<WorkQueue>().len();
+    if left > 0 {
+        debug!("Ran {ran} deferred jobs, {left} left for later frames");
+    }
+}
@@ -0,0 +1,547 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    f32::consts::TAU,
+    fs,
+    hash::{Hash, Hasher},
+};
+
+use bevy::prelude::*;
+
+use rand::{Rng, SeedableRng};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    combat::{in_reach, Swing},
+    components::{Dead, Stack},
+    items::{definition::ItemRegistry, dropped::DropItem, wallet::COIN},
+    mobs::{spawn_enemy, Enemy, EnemyKind},
+    player::Player,
+    ui::{
+        console::ConsoleOutput,
+        ticker::{EventCategory, WorldEvent},
+    },
+};
+
+use super::{
+    claim::Claims,
+    decoration::{Prop, PropOwner},
+    elevation::{ElevationMap, Terrain},
+    get_chunk_at,
+    nav::NavGrid,
+    seed::WorldSeed,
+    Chunk, ChunkCoords, ChunkOccupant, OccupantKind,
+};
+
+const LEADERBOARD_PATH: &str = "saves/arena.json";
+
+// One in this many chunks holds a shrine
+const SHRINE_RARITY: u32 = 12;
+
+// Kept off the chunk center where camps sit
+const SHRINE_OFFSET: Vec2 = Vec2::new(-64., 64.);
+
+// Walking further than this from the shrine forfeits the challenge
+const ARENA_RADIUS: f32 = 240.;
+
+const WAVE_SPAWN_RADIUS: f32 = 120.;
+
+const WAVE_TIME_LIMIT: f32 = 150.;
+
+const CHECKPOINTS: usize = 4;
+
+const CHECKPOINT_RADIUS: f32 = 160.;
+
+const CHECKPOINT_REACH: f32 = 20.;
+
+const RACE_TIME_LIMIT: f32 = 60.;
+
+// Best times kept for each shrine
+const LEADERBOARD_SIZE: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ChallengeMode {
+    // Survive every wave before time runs out
+    Waves,
+    // Touch each checkpoint in order and return to the shrine
+    Race,
+}
+
+impl ChallengeMode {
+    fn time_limit(&self) -> f32 {
+        match self {
+            ChallengeMode::Waves => WAVE_TIME_LIMIT,
+            ChallengeMode::Race => RACE_TIME_LIMIT,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            ChallengeMode::Waves => Color::rgb(0.8, 0.3, 0.6),
+            ChallengeMode::Race => Color::rgb(0.3, 0.8, 0.7),
+        }
+    }
+}
+
+// Enemies making up each wave, the director spreads them around the shrine
+const WAVES: [&[EnemyKind]; 3] = [
+    &[EnemyKind::Slime, EnemyKind::Slime, EnemyKind::Slime],
+    &[
+        EnemyKind::Slime,
+        EnemyKind::Slime,
+        EnemyKind::Wraith,
+        EnemyKind::Wraith,
+    ],
+    &[
+        EnemyKind::Wraith,
+        EnemyKind::Wraith,
+        EnemyKind::Guard,
+        EnemyKind::Guard,
+    ],
+];
+
+#[derive(Component)]
+pub struct Shrine {
+    mode: ChallengeMode,
+}
+
+#[derive(Component)]
+pub struct Checkpoint(usize);
+
+#[derive(Component)]
+pub struct ChallengeTimer;
+
+#[derive(Clone, Debug)]
+enum Progress {
+    Waves { wave: usize, alive: Vec<Entity> },
+    Race { next: usize, checkpoints: Vec<Vec2> },
+}
+
+struct Challenge {
+    mode: ChallengeMode,
+    shrine: Vec2,
+    elapsed: f32,
+    progress: Progress,
+}
+
+#[derive(Resource, Default)]
+pub struct ActiveChallenge(Option<Challenge>);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct LeaderboardEntry {
+    shrine: (i64, i64),
+    mode: ChallengeMode,
+    seconds: f32,
+}
+
+// Best completion times per shrine, persisted with the world
+#[derive(Resource, Default, Deserialize, Serialize)]
+pub struct Leaderboard {
+    entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub fn load() -> Leaderboard {
+        fs::read(LEADERBOARD_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let written = fs::create_dir_all("saves").and_then(|_| {
+            fs::write(
+                LEADERBOARD_PATH,
+                serde_json::to_vec(self).map_err(std::io::Error::from)?,
+            )
+        });
+
+        if let Err(err) = written {
+            warn!("Failed to save the arena leaderboard! Err {err}");
+        }
+    }
+
+    // Records the time and returns its rank, None when it didn't make the board
+    fn record(&mut self, shrine: &ChunkCoords, mode: ChallengeMode, seconds: f32) -> Option<usize> {
+        let key = (shrine.0, shrine.1);
+
+        self.entries.push(LeaderboardEntry {
+            shrine: key,
+            mode,
+            seconds,
+        });
+        self.entries.sort_by(|a, b| a.seconds.total_cmp(&b.seconds));
+
+        let mut kept = 0;
+        self.entries.retain(|entry| {
+            if entry.shrine != key || entry.mode != mode {
+                return true;
+            }
+            kept += 1;
+            kept <= LEADERBOARD_SIZE
+        });
+
+        self.best(shrine, mode)
+            .iter()
+            .position(|best| *best == seconds)
+    }
+
+    fn best(&self, shrine: &ChunkCoords, mode: ChallengeMode) -> Vec<f32> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.shrine == (shrine.0, shrine.1) && entry.mode == mode)
+            .map(|entry| entry.seconds)
+            .collect()
+    }
+}
+
+pub fn load_leaderboard(mut commands: Commands) {
+    commands.insert_resource(Leaderboard::load());
+}
+
+pub fn place_shrines(
+    mut commands: Commands,
+    seed: Res<WorldSeed>,
+    elevation: Res<ElevationMap>,
+    claims: Res<Claims>,
+    new_chunks: Query<(Entity, &Transform), Added<Chunk>>,
+) {
+    for (entity, transform) in new_chunks.iter() {
+        let coords = ChunkCoords::from(transform.translation);
+
+        let mut hasher = DefaultHasher::new();
+        (coords.0, coords.1, seed.0, "shrine").hash(&mut hasher);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(hasher.finish());
+
+        if rng.gen_range(0..SHRINE_RARITY) != 0 || claims.is_claimed(&coords) {
+            continue;
+        }
+
+        let position = transform.translation.truncate() + SHRINE_OFFSET;
+
+        if elevation.terrain(NavGrid::cell_of(position)) != Terrain::Flat {
+            continue;
+        }
+
+        let mode = if rng.gen_bool(0.5) {
+            ChallengeMode::Waves
+        } else {
+            ChallengeMode::Race
+        };
+
+        let sprite = SpriteBundle {
+            sprite: Sprite {
+                color: mode.color(),
+                custom_size: Some(Vec2::new(16., 26.)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(0.8)),
+            ..default()
+        };
+
+        commands
+            .spawn(sprite)
+            .insert(Shrine { mode })
+            .insert(Prop)
+            .insert(PropOwner(entity))
+            .insert(ChunkOccupant(OccupantKind::Decoration));
+    }
+}
+
+pub fn spawn_challenge_timer(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_bundle = TextBundle::from_section(
+        "",
+        TextStyle {
+            font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+            font_size: 24.0,
+            color: Color::WHITE,
+        },
+    )
+    .with_style(Style {
+        position_type: PositionType::Absolute,
+        top: Val::Px(12.),
+        left: Val::Percent(42.),
+        ..default()
+    });
+
+    commands.spawn(text_bundle).insert(ChallengeTimer);
+}
+
+// Checkpoints are laid out around the shrine from its position, each shrine always runs the same course
+fn race_course(shrine: Vec2) -> Vec<Vec2> {
+    let mut hasher = DefaultHasher::new();
+    (shrine.x as i64, shrine.y as i64, "race").hash(&mut hasher);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(hasher.finish());
+
+    let start = rng.gen_range(0. ..TAU);
+
+    (0..CHECKPOINTS)
+        .map(|index| {
+            let angle = start + index as f32 / CHECKPOINTS as f32 * TAU;
+            let distance = CHECKPOINT_RADIUS * rng.gen_range(0.6..1.);
+            shrine + Vec2::new(angle.cos(), angle.sin()) * distance
+        })
+        .collect()
+}
+
+// Spawn director for wave mode, spreads a wave evenly on a ring around the shrine
+fn spawn_wave(commands: &mut Commands, shrine: Vec2, wave: usize) -> Vec<Entity> {
+    let kinds = WAVES[wave];
+
+    kinds
+        .iter()
+        .enumerate()
+        .map(|(index, kind)| {
+            let angle = index as f32 / kinds.len() as f32 * TAU;
+            let position = shrine + Vec2::new(angle.cos(), angle.sin()) * WAVE_SPAWN_RADIUS;
+            spawn_enemy(commands, *kind, position)
+        })
+        .collect()
+}
+
+fn spawn_checkpoint(commands: &mut Commands, index: usize, position: Vec2) {
+    let sprite = SpriteBundle {
+        sprite: Sprite {
+            color: ChallengeMode::Race.color().with_a(0.6),
+            custom_size: Some(Vec2::splat(CHECKPOINT_REACH * 2.)),
+            ..default()
+        },
+        transform: Transform::from_translation(position.extend(0.05)),
+        visibility: if index == 0 {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        },
+        ..default()
+    };
+
+    commands.spawn(sprite).insert(Checkpoint(index));
+}
+
+// Swinging at a shrine starts its challenge
+pub fn start_challenges(
+    mut commands: Commands,
+    mut swings: EventReader<Swing>,
+    mut active: ResMut<ActiveChallenge>,
+    shrines: Query<(&Transform, &Shrine)>,
+    leaderboard: Res<Leaderboard>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut events: EventWriter<WorldEvent>,
+) {
+    for swing in swings.read() {
+        if active.0.is_some() {
+            return;
+        }
+
+        let Some((transform, shrine)) = shrines.iter().find(|(transform, _)| {
+            in_reach(
+                swing.position,
+                swing.facing,
+                transform.translation.truncate(),
+            )
+        }) else {
+            continue;
+        };
+
+        let position = transform.translation.truncate();
+
+        let progress = match shrine.mode {
+            ChallengeMode::Waves => Progress::Waves {
+                wave: 0,
+                alive: spawn_wave(&mut commands, position, 0),
+            },
+            ChallengeMode::Race => {
+                let checkpoints = race_course(position);
+                for (index, checkpoint) in checkpoints.iter().enumerate() {
+                    spawn_checkpoint(&mut commands, index, *checkpoint);
+                }
+                Progress::Race {
+                    next: 0,
+                    checkpoints,
+                }
+            }
+        };
+
+        info!("Started {:?} challenge", shrine.mode);
+        events.send(WorldEvent::new(
+            EventCategory::Challenges,
+            format!("{:?} challenge started", shrine.mode),
+        ));
+
+        if let Some(best) = leaderboard
+            .best(&get_chunk_at((position.x, position.y)), shrine.mode)
+            .first()
+        {
+            output.send(ConsoleOutput(format!("Shrine record: {best:.1}s")));
+        }
+
+        active.0 = Some(Challenge {
+            mode: shrine.mode,
+            shrine: position,
+            elapsed: 0.,
+            progress,
+        });
+    }
+}
+
+enum Outcome {
+    Won,
+    Lost(&'static str),
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn run_challenges(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut active: ResMut<ActiveChallenge>,
+    mut leaderboard: ResMut<Leaderboard>,
+    registry: Res<ItemRegistry>,
+    player: Query<(&Transform, Has<Dead>), With<Player>>,
+    enemies: Query<(), (With<Enemy>, Without<Dead>)>,
+    mut checkpoints: Query<(Entity, &Checkpoint, &mut Visibility)>,
+    mut drops: EventWriter<DropItem>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut events: EventWriter<WorldEvent>,
+) {
+    let Some(challenge) = active.0.as_mut() else {
+        return;
+    };
+
+    let Ok((transform, dead)) = player.get_single() else {
+        return;
+    };
+
+    let position = transform.translation.truncate();
+    challenge.elapsed += time.delta_seconds();
+
+    let outcome = if dead {
+        Some(Outcome::Lost("Fell in the arena"))
+    } else if position.distance(challenge.shrine) > ARENA_RADIUS {
+        Some(Outcome::Lost("Left the arena"))
+    } else if challenge.elapsed > challenge.mode.time_limit() {
+        Some(Outcome::Lost("Out of time"))
+    } else {
+        match &mut challenge.progress {
+            Progress::Waves { wave, alive } => {
+                alive.retain(|enemy| enemies.contains(*enemy));
+
+                if !alive.is_empty() {
+                    None
+                } else if *wave + 1 == WAVES.len() {
+                    Some(Outcome::Won)
+                } else {
+                    *wave += 1;
+                    info!("Arena wave {} of {}", *wave + 1, WAVES.len());
+                    *alive = spawn_wave(&mut commands, challenge.shrine, *wave);
+                    None
+                }
+            }
+            Progress::Race {
+                next,
+                checkpoints: course,
+            } => {
+                // Past the last checkpoint the shrine itself is the finish line
+                let target = course.get(*next).copied().unwrap_or(challenge.shrine);
+
+                if position.distance(target) > CHECKPOINT_REACH {
+                    None
+                } else if *next == course.len() {
+                    Some(Outcome::Won)
+                } else {
+                    *next += 1;
+
+                    for (_, checkpoint, mut visibility) in checkpoints.iter_mut() {
+                        *visibility = if checkpoint.0 == *next {
+                            Visibility::Visible
+                        } else {
+                            Visibility::Hidden
+                        };
+                    }
+                    None
+                }
+            }
+        }
+    };
+
+    let Some(outcome) = outcome else {
+        return;
+    };
+
+    for (entity, _, _) in checkpoints.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    match outcome {
+        Outcome::Won => {
+            let seconds = challenge.elapsed;
+            let shrine = get_chunk_at((challenge.shrine.x, challenge.shrine.y));
+
+            info!("Completed {:?} challenge in {seconds:.1}s", challenge.mode);
+            events.send(WorldEvent::new(
+                EventCategory::Challenges,
+                format!("{:?} challenge completed in {seconds:.1}s", challenge.mode),
+            ));
+
+            let rank = leaderboard.record(&shrine, challenge.mode, seconds);
+            leaderboard.save();
+
+            output.send(ConsoleOutput(match rank {
+                Some(0) => format!("New shrine record: {seconds:.1}s"),
+                Some(rank) => format!("Finished in {seconds:.1}s, #{} on this shrine", rank + 1),
+                None => format!("Finished in {seconds:.1}s"),
+            }));
+
+            // Faster runs pay out more coin on top of a loot roll
+            let coins = 10 + (challenge.mode.time_limit() - seconds).max(0.) as u32 / 5;
+            drops.send(DropItem {
+                stack: Stack::new(COIN, coins),
+                position: challenge.shrine,
+            });
+
+            if let Some(stack) = registry.roll_loot(&mut rand::thread_rng()) {
+                drops.send(DropItem {
+                    stack,
+                    position: challenge.shrine,
+                });
+            }
+        }
+        Outcome::Lost(reason) => {
+            info!("Failed {:?} challenge: {reason}", challenge.mode);
+            events.send(WorldEvent::new(
+                EventCategory::Challenges,
+                format!("{:?} challenge failed", challenge.mode),
+            ));
+            output.send(ConsoleOutput(format!("Challenge failed: {reason}")));
+        }
+    }
+
+    active.0 = None;
+}
+
+pub fn update_challenge_timer(
+    active: Res<ActiveChallenge>,
+    mut timer: Query<&mut Text, With<ChallengeTimer>>,
+) {
+    let Ok(mut text) = timer.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match &active.0 {
+        Some(challenge) => {
+            let remaining = (challenge.mode.time_limit() - challenge.elapsed).max(0.);
+
+            let progress = match &challenge.progress {
+                Progress::Waves { wave, .. } => format!("Wave {}/{}", wave + 1, WAVES.len()),
+                Progress::Race { next, checkpoints } if *next == checkpoints.len() => {
+                    "Return to the shrine".to_string()
+                }
+                Progress::Race { next, checkpoints } => {
+                    format!("Checkpoint {}/{}", next + 1, checkpoints.len())
+                }
+            };
+
+            format!("{progress} - {remaining:.1}s")
+        }
+        None => "".into(),
+    };
+}
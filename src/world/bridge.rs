@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+
+use super::{
+    schematic::{SchematicAsset, SchematicResource},
+    Tile,
+};
+
+// Bridges sit just above the water tiles around them
+const BRIDGE_LAYER: f32 = 0.01;
+
+// Lifts bridge tiles above the water once spawned, so they draw over any water they overlap
+pub fn raise_bridges(
+    schematic: Option<Res<SchematicResource>>,
+    schematics: Res<Assets<SchematicAsset>>,
+    mut tiles: Query<(&Tile, &mut Transform), Added<Tile>>,
+) {
+    let Some(schematic) = schematic.and_then(|handle| schematics.get(&handle.0)) else {
+        return;
+    };
+
+    for (tile, mut transform) in tiles.iter_mut() {
+        if schematic
+            .tiles
+            .get(&tile.texture_id)
+            .is_some_and(|tile| tile.bridge)
+        {
+            transform.translation.z = BRIDGE_LAYER;
+        }
+    }
+}
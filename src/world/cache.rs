@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+
+use bevy::{ecs::system::Resource, log::debug};
+
+use super::ChunkCoords;
+
+// Number of unloaded chunks kept around for instant re-display
+const DEFAULT_CHUNK_BUDGET: usize = 64;
+
+// LRU cache of interior tile ids for chunks that have recently gone out of range
+#[derive(Resource)]
+pub struct ChunkCache {
+    pub budget: usize,
+    entries: VecDeque<(ChunkCoords, Vec<u8>)>,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl Default for ChunkCache {
+    fn default() -> Self {
+        ChunkCache::with_budget(DEFAULT_CHUNK_BUDGET)
+    }
+}
+
+impl ChunkCache {
+    pub fn with_budget(budget: usize) -> ChunkCache {
+        ChunkCache {
+            budget,
+            entries: VecDeque::with_capacity(budget),
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Most recently unloaded chunks live at the front
+    pub(super) fn insert(&mut self, coords: ChunkCoords, tiles: Vec<u8>) {
+        self.entries.retain(|(cached, _)| *cached != coords);
+        self.entries.push_front((coords, tiles));
+        self.evict();
+    }
+
+    pub(super) fn contains(&self, coords: &ChunkCoords) -> bool {
+        self.entries.iter().any(|(cached, _)| cached == coords)
+    }
+
+    // Removes the chunk from the cache, it will be re-inserted once it is unloaded again
+    pub(super) fn take(&mut self, coords: &ChunkCoords) -> Option<Vec<u8>> {
+        match self.entries.iter().position(|(cached, _)| cached == coords) {
+            Some(idx) => {
+                self.hits += 1;
+                self.entries.remove(idx).map(|(_, tiles)| tiles)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.budget {
+            if let Some((coords, _)) = self.entries.pop_back() {
+                debug!("Evicting cached chunk: ({},{})", coords.0, coords.1);
+                self.evictions += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::Stack,
+    items::{container::ItemContainer, definition::ItemRegistry, dropped::DropItem},
+    player::Player,
+    ui::console::{ConsoleCommand, ConsoleOutput},
+};
+
+use super::{
+    decoration::{Prop, PropOwner},
+    get_chunk_at, Chunk, ChunkCoords,
+};
+
+const CLAIM_SAVE_PATH: &str = "saves/claims.json";
+
+pub const CLAIM_STONE: u32 = 20;
+
+// Name claims are recorded under, there is only ever the one player until multiplayer lands
+pub const LOCAL_PLAYER: &str = "local";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Claim {
+    chunk: (i64, i64),
+    owner: String,
+    // Other players allowed to build inside the claim
+    permitted: Vec<String>,
+}
+
+// Chunks protected by a claim stone, persisted with the world
+#[derive(Resource, Default, Deserialize, Serialize)]
+pub struct Claims {
+    claims: Vec<Claim>,
+}
+
+impl Claims {
+    pub fn load() -> Claims {
+        fs::read(CLAIM_SAVE_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let written = fs::create_dir_all("saves").and_then(|_| {
+            fs::write(
+                CLAIM_SAVE_PATH,
+                serde_json::to_vec(self).map_err(std::io::Error::from)?,
+            )
+        });
+
+        if let Err(err) = written {
+            warn!("Failed to save claims! Err {err}");
+        }
+    }
+
+    fn get(&self, coords: &ChunkCoords) -> Option<&Claim> {
+        self.claims
+            .iter()
+            .find(|claim| claim.chunk == (coords.0, coords.1))
+    }
+
+    pub(super) fn is_claimed(&self, coords: &ChunkCoords) -> bool {
+        self.get(coords).is_some()
+    }
+
+    // Hostiles never spawn inside a claim
+    pub fn protects(&self, position: Vec2) -> bool {
+        self.is_claimed(&get_chunk_at((position.x, position.y)))
+    }
+
+    // Unclaimed ground is open to everyone, claimed ground only to its owner and whoever they permit
+    pub fn can_edit(&self, position: Vec2, player: &str) -> bool {
+        match self.get(&get_chunk_at((position.x, position.y))) {
+            Some(claim) => {
+                claim.owner == player || claim.permitted.iter().any(|name| name == player)
+            }
+            None => true,
+        }
+    }
+}
+
+pub fn load_claims(mut commands: Commands) {
+    commands.insert_resource(Claims::load());
+}
+
+// Stands in the middle of a claimed chunk
+#[derive(Component)]
+pub struct ClaimMarker;
+
+fn spawn_claim_marker(commands: &mut Commands, chunk: Entity, position: Vec2) {
+    let marker = SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgb(0.85, 0.75, 0.3),
+            custom_size: Some(Vec2::new(14., 20.)),
+            ..default()
+        },
+        transform: Transform::from_translation(position.extend(0.8)),
+        ..default()
+    };
+
+    commands
+        .spawn(marker)
+        .insert(ClaimMarker)
+        .insert(Prop)
+        .insert(PropOwner(chunk));
+}
+
+pub fn place_claim_markers(
+    mut commands: Commands,
+    claims: Res<Claims>,
+    new_chunks: Query<(Entity, &Transform), Added<Chunk>>,
+) {
+    for (entity, transform) in new_chunks.iter() {
+        if claims.is_claimed(&ChunkCoords::from(transform.translation)) {
+            spawn_claim_marker(&mut commands, entity, transform.translation.truncate());
+        }
+    }
+}
+
+// Console commands claiming the chunk the player stands in, releasing it and sharing it
+#[allow(clippy::too_many_arguments)]
+pub fn handle_claim_commands(
+    mut commands: Commands,
+    mut console: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut claims: ResMut<Claims>,
+    registry: Res<ItemRegistry>,
+    mut player: Query<(&Transform, &mut ItemContainer), With<Player>>,
+    chunks: Query<(Entity, &Transform), With<Chunk>>,
+    markers: Query<(Entity, &Transform), With<ClaimMarker>>,
+    mut drops: EventWriter<DropItem>,
+) {
+    for command in console.read() {
+        if !matches!(command.name.as_str(), "claim" | "unclaim" | "permit") {
+            continue;
+        }
+
+        let Ok((transform, mut inventory)) = player.get_single_mut() else {
+            continue;
+        };
+
+        let coords = get_chunk_at((transform.translation.x, transform.translation.y));
+        let chunk = chunks
+            .iter()
+            .find(|(_, chunk_transform)| coords == chunk_transform.translation);
+
+        match (command.name.as_str(), claims.get(&coords)) {
+            ("claim", Some(claim)) => {
+                output.send(ConsoleOutput(format!(
+                    "Chunk already claimed by {}",
+                    claim.owner
+                )));
+            }
+            ("claim", None) => {
+                if !inventory.remove(CLAIM_STONE, 1) {
+                    output.send(ConsoleOutput(format!(
+                        "Missing 1 {}",
+                        registry.get(CLAIM_STONE).name
+                    )));
+                    continue;
+                }
+
+                claims.claims.push(Claim {
+                    chunk: (coords.0, coords.1),
+                    owner: LOCAL_PLAYER.to_string(),
+                    permitted: Vec::new(),
+                });
+                claims.save();
+
+                if let Some((entity, chunk_transform)) = chunk {
+                    spawn_claim_marker(
+                        &mut commands,
+                        entity,
+                        chunk_transform.translation.truncate(),
+                    );
+                }
+
+                info!("Claimed chunk ({}, {})", coords.0, coords.1);
+                output.send(ConsoleOutput("Claimed this chunk".to_string()));
+            }
+            (_, None) => {
+                output.send(ConsoleOutput("This chunk is not claimed".to_string()));
+            }
+            (_, Some(claim)) if claim.owner != LOCAL_PLAYER => {
+                output.send(ConsoleOutput(format!(
+                    "Only {} may change this claim",
+                    claim.owner
+                )));
+            }
+            ("unclaim", Some(_)) => {
+                claims
+                    .claims
+                    .retain(|claim| claim.chunk != (coords.0, coords.1));
+                claims.save();
+
+                for (marker, marker_transform) in markers.iter() {
+                    if coords
+                        == get_chunk_at((
+                            marker_transform.translation.x,
+                            marker_transform.translation.y,
+                        ))
+                    {
+                        commands.entity(marker).despawn_recursive();
+                    }
+                }
+
+                // The stone is handed back, dropped at the player's feet if the inventory is full
+                if let Some(leftover) = inventory.insert(Stack::new(CLAIM_STONE, 1), &registry) {
+                    drops.send(DropItem {
+                        stack: leftover,
+                        position: transform.translation.truncate(),
+                    });
+                }
+
+                output.send(ConsoleOutput("Released this chunk".to_string()));
+            }
+            (_, Some(_)) => {
+                let Some(name) = command.args.first() else {
+                    output.send(ConsoleOutput("Usage: permit <player>".to_string()));
+                    continue;
+                };
+
+                let claim = claims
+                    .claims
+                    .iter_mut()
+                    .find(|claim| claim.chunk == (coords.0, coords.1))
+                    .expect("Claim checked above");
+
+                if !claim.permitted.contains(name) {
+                    claim.permitted.push(name.clone());
+                }
+                claims.save();
+
+                output.send(ConsoleOutput(format!("{name} may now build here")));
+            }
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use bevy::prelude::*;
+
+use rand::SeedableRng;
+
+use crate::{
+    components::Stack,
+    id::StableId,
+    items::{
+        definition::ItemRegistry,
+        lock::{spawn_locked_chest, LockTier},
+    },
+    mobs::{
+        patrol::Patrol,
+        spawn_enemy,
+        spawner::{spawn_spawner, Spawner},
+        EnemyKind,
+    },
+    npc::{dialogue::DialogueDefinitions, spawn_npc},
+};
+
+use travelers_worldgen::structure::{plan_chunk_props, PropPlacement};
+
+use super::{
+    claim::Claims,
+    get_chunk_at,
+    poi::{ConqueredPois, PoiMarker},
+    schematic::{SchematicAsset, SchematicResource},
+    seed::WorldSeed,
+    structure::StructureDefinitions,
+    Chunk, ChunkCoords, ChunkOccupant, OccupantKind,
+};
+
+// Distance below a camp's center its locked chest stands
+const CHEST_OFFSET: f32 = 36.;
+
+// Distance from a prefab's center its villagers stand around it
+const VILLAGER_SPREAD: f32 = 20.;
+
+#[derive(Component)]
+pub struct Prop;
+
+// Chunk the prop or guard was planned by, it is removed once the chunk unloads
+#[derive(Component)]
+pub struct PropOwner(pub(super) Entity);
+
+impl PropOwner {
+    pub fn chunk(&self) -> Entity {
+        self.0
+    }
+}
+
+// Props overlapping a chunk that hasn't been generated yet, completed once it loads
+#[derive(Resource, Default)]
+pub struct DeferredProps(Vec<PropPlacement>);
+
+pub fn place_props(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    seed: Res<WorldSeed>,
+    new_chunks: Query<&Transform, Added<Chunk>>,
+    chunks: Query<(Entity, &Transform), With<Chunk>>,
+    mut deferred: ResMut<DeferredProps>,
+) {
+    if new_chunks.is_empty() {
+        return;
+    }
+
+    let loaded: Vec<(ChunkCoords, Entity)> = chunks
+        .iter()
+        .map(|(entity, transform)| (ChunkCoords::from(transform.translation), entity))
+        .collect();
+
+    let owner_of = |coords: &ChunkCoords| {
+        loaded
+            .iter()
+            .find(|(loaded_coords, _)| loaded_coords == coords)
+            .map(|(_, entity)| *entity)
+    };
+
+    let mut candidates = std::mem::take(&mut deferred.0);
+    for transform in new_chunks.iter() {
+        for placement in plan_chunk_props(seed.0, ChunkCoords::from(transform.translation)) {
+            if !candidates.contains(&placement) {
+                candidates.push(placement);
+            }
+        }
+    }
+
+    for placement in candidates {
+        // Owner unloaded, it is planned again when it comes back
+        let Some(owner) = owner_of(&placement.owner) else {
+            continue;
+        };
+
+        let max = placement.origin + placement.size - Vec2::ONE;
+        let corners = [
+            placement.origin,
+            Vec2::new(max.x, placement.origin.y),
+            Vec2::new(placement.origin.x, max.y),
+            max,
+        ];
+
+        let spans_ungenerated = corners
+            .iter()
+            .any(|corner| owner_of(&get_chunk_at((corner.x, corner.y))).is_none());
+
+        if spans_ungenerated {
+            debug!(
+                "Deferring prop of chunk ({}, {}) until its neighbors load",
+                placement.owner.0, placement.owner.1
+            );
+            deferred.0.push(placement);
+            continue;
+        }
+
+        let center = placement.origin + placement.size / 2.;
+
+        let sprite = SpriteBundle {
+            texture: asset_server.load("sprites/world/decorations.png"),
+            sprite: Sprite {
+                custom_size: Some(placement.size),
+                ..default()
+            },
+            transform: Transform::from_translation(center.extend(0.2)),
+            ..default()
+        };
+
+        commands
+            .spawn(sprite)
+            .insert(Prop)
+            .insert(PropOwner(owner))
+            .insert(ChunkOccupant(OccupantKind::Decoration));
+    }
+}
+
+// Nests and portals stand where their structure declares them, the chunk holding the center of the
+// structure places them along with its chest and guards
+#[allow(clippy::too_many_arguments)]
+pub fn place_nests(
+    mut commands: Commands,
+    seed: Res<WorldSeed>,
+    schematic: Option<Res<SchematicResource>>,
+    schematics: Res<Assets<SchematicAsset>>,
+    new_chunks: Query<(Entity, &Transform), Added<Chunk>>,
+    pois: Res<ConqueredPois>,
+    registry: Res<ItemRegistry>,
+    structures: Res<StructureDefinitions>,
+    claims: Res<Claims>,
+) {
+    let Some(schematic) = schematic.and_then(|handle| schematics.get(&handle.0)) else {
+        return;
+    };
+
+    for (entity, transform) in new_chunks.iter() {
+        let coords = ChunkCoords::from(transform.translation);
+
+        // Claimed ground is kept free of camps
+        if claims.is_claimed(&coords) {
+            continue;
+        }
+
+        for (camp, center) in structures.centered_in(seed.0, schematic, coords) {
+            if camp.spawners.is_empty() {
+                continue;
+            }
+
+            // Cleared camps stay empty until repopulated
+            if pois.is_cleared(&coords) {
+                let marker = SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::new(28., 28.)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(center.extend(0.8)),
+                    ..default()
+                };
+
+                commands
+                    .spawn(marker)
+                    .insert(PoiMarker { cleared: true })
+                    .insert(Prop)
+                    .insert(PropOwner(entity));
+                continue;
+            }
+
+            let mut hasher = DefaultHasher::new();
+            (coords.0, coords.1, seed.0, "nest").hash(&mut hasher);
+            let mut rng = rand::rngs::StdRng::seed_from_u64(hasher.finish());
+
+            for definition in camp.spawners.iter() {
+                let Some(kind) = EnemyKind::from_key(&definition.enemy) else {
+                    warn!("Structure spawner has no enemy named {}", definition.enemy);
+                    continue;
+                };
+
+                let loot = registry.roll_loot(&mut rng).unwrap_or(Stack::new(1, 3));
+
+                let nest = spawn_spawner(
+                    &mut commands,
+                    Spawner::new(kind, definition.interval, definition.max_alive, loot),
+                    definition.health,
+                    definition.portal,
+                    definition.position(center),
+                );
+
+                commands
+                    .entity(nest)
+                    .insert(PoiMarker { cleared: false })
+                    .insert(Prop)
+                    .insert(PropOwner(entity));
+            }
+
+            // Every camp keeps its spoils locked up below its center
+            let chest_position = center + Vec2::new(0., -CHEST_OFFSET);
+            let chest = spawn_locked_chest(&mut commands, LockTier::roll(&mut rng), chest_position);
+            commands
+                .entity(chest)
+                .insert(StableId::generated("chest", chest_position))
+                .insert(Prop)
+                .insert(PropOwner(entity));
+
+            // Guards start spread along the camp's route and leave with its chunk
+            let route = camp.patrol_route(center);
+
+            for guard in 0..camp.guards as usize {
+                let start = guard * route.len() / camp.guards as usize;
+                let position = route.get(start).copied().unwrap_or(center);
+
+                let guard = spawn_enemy(&mut commands, EnemyKind::Guard, position);
+                commands
+                    .entity(guard)
+                    .insert(Patrol::new(route.clone(), start))
+                    .insert(PropOwner(entity));
+            }
+        }
+    }
+}
+
+// Villagers gather around the center of their prefab, the chunk holding the center places them all
+pub fn place_npcs(
+    mut commands: Commands,
+    seed: Res<WorldSeed>,
+    schematic: Option<Res<SchematicResource>>,
+    schematics: Res<Assets<SchematicAsset>>,
+    structures: Res<StructureDefinitions>,
+    dialogues: Res<DialogueDefinitions>,
+    new_chunks: Query<(Entity, &Transform), Added<Chunk>>,
+) {
+    let Some(schematic) = schematic.and_then(|handle| schematics.get(&handle.0)) else {
+        return;
+    };
+
+    for (entity, transform) in new_chunks.iter() {
+        let coords = ChunkCoords::from(transform.translation);
+
+        for (structure, center) in structures.centered_in(seed.0, schematic, coords) {
+            for (idx, name) in structure.npcs.iter().enumerate() {
+                let Some(dialogue) = dialogues.get(name) else {
+                    warn!("Structure villager has no dialogue named {name}");
+                    continue;
+                };
+
+                let angle = idx as f32 * std::f32::consts::TAU / structure.npcs.len() as f32;
+                let position = center + Vec2::from_angle(angle) * VILLAGER_SPREAD;
+
+                let npc = spawn_npc(&mut commands, dialogue.clone(), position);
+                commands
+                    .entity(npc)
+                    .insert(StableId::generated(name, position))
+                    .insert(PropOwner(entity));
+            }
+        }
+    }
+}
+
+pub fn remove_orphaned_props(
+    mut commands: Commands,
+    props: Query<(Entity, &PropOwner)>,
+    chunks: Query<(), With<Chunk>>,
+) {
+    for (entity, owner) in props.iter() {
+        if !chunks.contains(owner.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use std::fs;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use rand::Rng;
+
+use serde::{Deserialize, Serialize};
+
+use travelers_worldgen::schematic::{Schematic, TileSchematic};
+
+use crate::{
+    components::{Dirty, Stack},
+    items::dropped::DropItem,
+    player::Player,
+};
+
+use super::{
+    claim::Claims,
+    harvest::HARVEST_REACH,
+    schematic::{SchematicAsset, SchematicResource},
+    AtlasResource, Chunk, ChunkCoords, Tile, TileBundle, TileGrid, TileIndex, TileInfo, TileSource,
+    CHUNK_TILE_LENGTH, TILE_SIZE,
+};
+
+const EDITS_SAVE_PATH: &str = "saves/edits.json";
+
+// Leeway on reach for a player that moved a little between asking and the check
+const REACH_TOLERANCE: f32 = 8.;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileEditKind {
+    Harvest,
+}
+
+// Tile changes are asked for instead of made on the spot, and only land once checked against the
+// authoritative world. The check runs in the same app for now, with peers it is the host's to run
+#[derive(Event, Clone, Debug)]
+pub struct TileEditRequest {
+    pub player: String,
+    pub position: Vec2,
+    // Tile the player saw there, a different one means its copy of the chunk is stale
+    pub expected: u8,
+    pub kind: TileEditKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditRejection {
+    Unloaded,
+    Changed,
+    NotHarvestable,
+    OutOfReach,
+    Claimed,
+}
+
+impl EditRejection {
+    pub fn message(&self) -> &'static str {
+        match self {
+            EditRejection::Unloaded => "Nothing is loaded there",
+            EditRejection::Changed => "That tile has already changed",
+            EditRejection::NotHarvestable => "Nothing to harvest there",
+            EditRejection::OutOfReach => "Too far away",
+            EditRejection::Claimed => "This ground is claimed by someone else",
+        }
+    }
+}
+
+// Sent back to the player whose edit was refused, nothing about the world changed
+#[derive(Event, Clone, Debug)]
+pub struct TileEditRejected {
+    pub player: String,
+    pub position: Vec2,
+    pub reason: EditRejection,
+}
+
+// Checks run in order, from whether the tile is there at all to whether the player may touch it
+fn check_edit<'a>(
+    request: &TileEditRequest,
+    schematic: &'a Schematic,
+    editor: &WorldEditor,
+    claims: &Claims,
+    player_position: Option<Vec2>,
+) -> Result<&'a TileSchematic, EditRejection> {
+    let tile = editor
+        .tile_at(request.position)
+        .ok_or(EditRejection::Unloaded)?;
+
+    if tile.texture_id != request.expected {
+        return Err(EditRejection::Changed);
+    }
+
+    let definition = schematic
+        .tiles
+        .get(&tile.texture_id)
+        .ok_or(EditRejection::NotHarvestable)?;
+
+    let allowed = match request.kind {
+        TileEditKind::Harvest => definition.harvest.is_some(),
+    };
+    if !allowed {
+        return Err(EditRejection::NotHarvestable);
+    }
+
+    // Only the local player exists until there are peers to tell apart by name
+    let reach = player_position
+        .map(|position| position.distance(request.position))
+        .unwrap_or(f32::INFINITY);
+    if reach > HARVEST_REACH + REACH_TOLERANCE {
+        return Err(EditRejection::OutOfReach);
+    }
+
+    if !claims.can_edit(request.position, &request.player) {
+        return Err(EditRejection::Claimed);
+    }
+
+    Ok(definition)
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+struct TileEdit {
+    // World cell, counted in tiles from the origin
+    cell: (i32, i32),
+    tile: u8,
+}
+
+// Every tile changed since the world was generated, latest edit per cell. Interiors are saved with
+// their chunk anyway, seams are stitched again on every load and keep their edits by pinning them
+#[derive(Resource, Default, Deserialize, Serialize)]
+pub struct TileEdits {
+    edits: Vec<TileEdit>,
+}
+
+impl TileEdits {
+    pub fn load() -> TileEdits {
+        fs::read(EDITS_SAVE_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let written = fs::create_dir_all("saves").and_then(|_| {
+            fs::write(
+                EDITS_SAVE_PATH,
+                serde_json::to_vec(self).map_err(std::io::Error::from)?,
+            )
+        });
+
+        if let Err(err) = written {
+            warn!("Failed to save tile edits! Err {err}");
+        }
+    }
+
+    fn record(&mut self, cell: IVec2, tile: u8) {
+        let cell = (cell.x, cell.y);
+
+        match self.edits.iter_mut().find(|edit| edit.cell == cell) {
+            Some(edit) => edit.tile = tile,
+            None => self.edits.push(TileEdit { cell, tile }),
+        }
+    }
+
+    // Edits on the chunk and its seams, relative to the bottom left of its interior like prefab pins
+    pub fn pinned(&self, coords: ChunkCoords) -> Vec<(IVec2, u8)> {
+        let origin = IVec2::new(
+            coords.0.div_euclid(TILE_SIZE) as i32,
+            coords.1.div_euclid(TILE_SIZE) as i32,
+        );
+        let length = CHUNK_TILE_LENGTH as i32;
+
+        self.edits
+            .iter()
+            .map(|edit| (IVec2::new(edit.cell.0, edit.cell.1) - origin, edit.tile))
+            .filter(|(cell, _)| (-1..=length).contains(&cell.x) && (-1..=length).contains(&cell.y))
+            .collect()
+    }
+}
+
+pub fn load_tile_edits(mut commands: Commands) {
+    commands.insert_resource(TileEdits::load());
+}
+
+pub fn save_tile_edits(edits: Res<TileEdits>) {
+    if edits.is_changed() && !edits.is_added() {
+        edits.save();
+    }
+}
+
+// The one way to change a tile of the loaded world once it is generated. Keeps the tile entities,
+// the chunks' grids, the tile index and the seams in step, and records the edit so it outlives the chunk
+#[derive(SystemParam)]
+pub struct WorldEditor<'w, 's> {
+    commands: Commands<'w, 's>,
+    atlas: Res<'w, AtlasResource>,
+    index: ResMut<'w, TileIndex>,
+    edits: ResMut<'w, TileEdits>,
+    tiles: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Tile,
+            &'static Transform,
+            &'static Parent,
+            &'static GlobalTransform,
+        ),
+    >,
+    chunks: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Transform,
+            &'static mut TileGrid,
+            &'static mut Dirty,
+        ),
+        With<Chunk>,
+    >,
+}
+
+impl<'w, 's> WorldEditor<'w, 's> {
+    pub fn tile_at(&self, world_pos: Vec2) -> Option<TileInfo> {
+        self.index.tile_at(world_pos)
+    }
+
+    // False where nothing is loaded or the tile is already the one asked for
+    pub fn set_tile(&mut self, world_pos: Vec2, tile_id: u8) -> bool {
+        let Some(info) = self.index.tile_at(world_pos) else {
+            return false;
+        };
+
+        if info.texture_id == tile_id {
+            return false;
+        }
+
+        let cell = (world_pos / TILE_SIZE as f32).floor().as_ivec2();
+        let center = (cell.as_vec2() + Vec2::splat(0.5)) * TILE_SIZE as f32;
+
+        // Seam tiles are spawned by the chunks on both sides, each copy is swapped. Swapped for a
+        // fresh tile so colliders, shading and layers are worked out for the new one
+        for (entity, tile, transform, parent, global_transform) in self.tiles.iter() {
+            if global_transform.translation().truncate().distance(center) >= TILE_SIZE as f32 / 2. {
+                continue;
+            }
+
+            let replacement = TileBundle::new(
+                self.atlas.0.clone(),
+                tile_id,
+                tile.source,
+                transform.translation.x,
+                transform.translation.y,
+            );
+
+            self.commands.entity(entity).despawn_recursive();
+            self.commands.entity(parent.get()).with_children(|chunk| {
+                chunk.spawn(replacement);
+            });
+        }
+
+        let mut restitch = Vec::new();
+
+        for (entity, transform, mut grid, mut dirty) in self.chunks.iter_mut() {
+            let coords = ChunkCoords::from(transform.translation);
+            let relative = TileGrid::cell_of(center - transform.translation.truncate());
+
+            if grid.set(relative, tile_id) {
+                self.index.grids.insert(coords, grid.clone());
+                dirty.mark(Dirty::TILES);
+            }
+
+            // Seams stitched against the old tile may no longer fit next to the new one. Edits to
+            // the seams themselves stand as they are, nothing was stitched against them
+            let touches_seam = (-1..=1)
+                .flat_map(|x| (-1..=1).map(move |y| IVec2::new(x, y)))
+                .any(|offset| TileGrid::is_seam(relative + offset));
+
+            if info.source == TileSource::Collapsed && touches_seam {
+                restitch.push(entity);
+            }
+        }
+
+        for chunk in restitch {
+            self.restitch(chunk);
+        }
+
+        self.edits.record(cell, tile_id);
+
+        true
+    }
+
+    // Its seams are spawned again by the stitching pass, which pins every recorded edit on them
+    fn restitch(&mut self, chunk: Entity) {
+        for (entity, tile, _, parent, _) in self.tiles.iter() {
+            if parent.get() == chunk && tile.source == TileSource::Stitched {
+                self.commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        if let Ok((_, _, _, mut dirty)) = self.chunks.get_mut(chunk) {
+            dirty.stitch = true;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn apply_tile_edits(
+    mut requests: EventReader<TileEditRequest>,
+    mut editor: WorldEditor,
+    claims: Res<Claims>,
+    schematic: Option<Res<SchematicResource>>,
+    schematics: Res<Assets<SchematicAsset>>,
+    player: Query<&Transform, With<Player>>,
+    mut drops: EventWriter<DropItem>,
+    mut rejections: EventWriter<TileEditRejected>,
+) {
+    let Some(schematic) = schematic.and_then(|handle| schematics.get(&handle.0)) else {
+        requests.clear();
+        return;
+    };
+
+    let mut rng = rand::thread_rng();
+
+    let player_position = player
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation.truncate());
+
+    for request in requests.read() {
+        let checked = check_edit(request, schematic, &editor, &claims, player_position);
+
+        let definition = match checked {
+            Ok(definition) => definition,
+            Err(reason) => {
+                debug!("Rejected tile edit by {}: {:?}", request.player, reason);
+                rejections.send(TileEditRejected {
+                    player: request.player.clone(),
+                    position: request.position,
+                    reason,
+                });
+                continue;
+            }
+        };
+
+        let Some(harvest) = definition.harvest.as_ref() else {
+            continue;
+        };
+
+        if !editor.set_tile(request.position, harvest.into) {
+            continue;
+        }
+
+        info!("{} harvested {}", request.player, definition.name);
+
+        for entry in harvest.yields.iter() {
+            if !rng.gen_bool(entry.chance as f64) {
+                continue;
+            }
+
+            let count = rng.gen_range(entry.min..=entry.max);
+            if count > 0 {
+                drops.send(DropItem {
+                    stack: Stack::new(entry.item, count),
+                    position: request.position,
+                });
+            }
+        }
+    }
+}
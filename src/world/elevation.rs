@@ -0,0 +1,165 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use bevy::prelude::*;
+
+use rand::{Rng, SeedableRng};
+
+use super::{nav::NavGrid, seed::WorldSeed, Tile, TILE_SIZE};
+
+// Terrain rises through this many levels, level 0 is the lowest ground
+const LEVELS: f32 = 3.;
+
+// Side length in tiles of the coarse lattice heights are interpolated across
+const ELEVATION_CELL_TILES: i32 = 24;
+
+// Cliff edges are split into blocks of this many tiles, some of which become ramps
+const RAMP_BLOCK_TILES: i32 = 4;
+
+// One in this many edge blocks is a ramp
+const RAMP_CHANCE: u64 = 3;
+
+const CLIFF_COLOR: Color = Color::rgba(0.15, 0.1, 0.05, 0.55);
+const RAMP_COLOR: Color = Color::rgba(1., 1., 1., 0.15);
+const SHADOW_COLOR: Color = Color::rgba(0., 0., 0., 0.3);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Terrain {
+    Flat,
+    // Edge of higher ground dropping to a lower level, impassable
+    Cliff,
+    // Edge of higher ground that can be walked up and down
+    Ramp,
+}
+
+// Heights derived from the world seed, so nothing about elevation needs saving
+#[derive(Resource)]
+pub struct ElevationMap {
+    seed: u64,
+}
+
+impl FromWorld for ElevationMap {
+    fn from_world(world: &mut World) -> Self {
+        ElevationMap {
+            seed: world.resource::<WorldSeed>().0,
+        }
+    }
+}
+
+// A new game may have picked a different seed since the map was built
+pub fn reseed_elevation(seed: Res<WorldSeed>, mut map: ResMut<ElevationMap>) {
+    map.seed = seed.0;
+}
+
+impl ElevationMap {
+    pub fn level(&self, tile: IVec2) -> i32 {
+        let cell = IVec2::new(
+            tile.x.div_euclid(ELEVATION_CELL_TILES),
+            tile.y.div_euclid(ELEVATION_CELL_TILES),
+        );
+
+        let local = (tile - cell * ELEVATION_CELL_TILES).as_vec2() / ELEVATION_CELL_TILES as f32;
+        let smooth = local * local * (Vec2::splat(3.) - 2. * local);
+
+        let bottom = lerp(self.height(cell), self.height(cell + IVec2::X), smooth.x);
+        let top = lerp(
+            self.height(cell + IVec2::Y),
+            self.height(cell + IVec2::ONE),
+            smooth.x,
+        );
+
+        ((lerp(bottom, top, smooth.y) * LEVELS) as i32).clamp(0, LEVELS as i32 - 1)
+    }
+
+    // Tiles next to any lower tile form the edge of their level
+    pub fn terrain(&self, tile: IVec2) -> Terrain {
+        let level = self.level(tile);
+
+        let edge = (-1..=1)
+            .flat_map(|x| (-1..=1).map(move |y| IVec2::new(x, y)))
+            .any(|offset| self.level(tile + offset) < level);
+
+        if !edge {
+            return Terrain::Flat;
+        }
+
+        let block = IVec2::new(
+            tile.x.div_euclid(RAMP_BLOCK_TILES),
+            tile.y.div_euclid(RAMP_BLOCK_TILES),
+        );
+
+        let mut hasher = DefaultHasher::new();
+        (block.x, block.y, self.seed, "ramp").hash(&mut hasher);
+
+        if hasher.finish().is_multiple_of(RAMP_CHANCE) {
+            Terrain::Ramp
+        } else {
+            Terrain::Cliff
+        }
+    }
+
+    // Higher ground to the north casts a shadow over the tile
+    fn shadowed(&self, tile: IVec2) -> bool {
+        self.level(tile + IVec2::Y) > self.level(tile)
+    }
+
+    fn height(&self, cell: IVec2) -> f32 {
+        let mut hasher = DefaultHasher::new();
+        (cell.x, cell.y, self.seed, "elevation").hash(&mut hasher);
+        rand::rngs::StdRng::seed_from_u64(hasher.finish()).gen()
+    }
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+// Darkens lower levels and overlays cliff faces, ramps and shadows on newly spawned tiles
+pub fn shade_elevation(
+    mut commands: Commands,
+    elevation: Res<ElevationMap>,
+    mut tiles: Query<(Entity, &Transform, &Parent, &mut TextureAtlasSprite), Added<Tile>>,
+    parents: Query<&Transform, Without<Tile>>,
+) {
+    for (entity, transform, parent, mut sprite) in tiles.iter_mut() {
+        let Ok(parent_transform) = parents.get(parent.get()) else {
+            continue;
+        };
+
+        let cell =
+            NavGrid::cell_of((parent_transform.translation + transform.translation).truncate());
+
+        let level = elevation.level(cell);
+        let brightness = 1. - 0.1 * (LEVELS - 1. - level as f32);
+        sprite.color = Color::rgb(brightness, brightness, brightness);
+
+        let overlay = match elevation.terrain(cell) {
+            Terrain::Cliff => Some((CLIFF_COLOR, Vec2::splat(TILE_SIZE as f32), Vec2::ZERO)),
+            Terrain::Ramp => Some((RAMP_COLOR, Vec2::splat(TILE_SIZE as f32), Vec2::ZERO)),
+            Terrain::Flat if elevation.shadowed(cell) => Some((
+                SHADOW_COLOR,
+                Vec2::new(TILE_SIZE as f32, TILE_SIZE as f32 / 3.),
+                Vec2::new(0., TILE_SIZE as f32 / 3.),
+            )),
+            Terrain::Flat => None,
+        };
+
+        let Some((color, size, offset)) = overlay else {
+            continue;
+        };
+
+        commands.entity(entity).with_children(|tile| {
+            tile.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(offset.extend(0.05)),
+                ..default()
+            });
+        });
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use rand::{Rng, SeedableRng};
+
+use travelers_worldgen::{
+    schematic::{Schematic, TileSchematic},
+    ChunkCoords, CHUNK_TILE_LENGTH, TILE_SIZE,
+};
+
+// Rivers run east to west and roads north to south, each band of the world carries at most one
+struct Feature {
+    tag: &'static str,
+    // Width in tiles of the band the feature wanders inside
+    band: i64,
+    chance: f64,
+    // Furthest the feature strays from the middle of its band
+    meander: f32,
+    width: i64,
+}
+
+const RIVER: Feature = Feature {
+    tag: "river",
+    band: 96,
+    chance: 0.5,
+    meander: 5.,
+    width: 2,
+};
+
+const ROAD: Feature = Feature {
+    tag: "road",
+    band: 80,
+    chance: 0.5,
+    meander: 4.,
+    width: 2,
+};
+
+// Spacing in tiles of the offsets a feature bends between, wide enough that it never steps more than a tile sideways
+const MEANDER_TILES: i64 = 16;
+
+impl Feature {
+    fn hash(&self, seed: u64, band: i64, point: i64) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (seed, self.tag, band, point).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn present(&self, seed: u64, band: i64) -> bool {
+        rand::rngs::StdRng::seed_from_u64(self.hash(seed, band, i64::MIN)).gen_bool(self.chance)
+    }
+
+    fn offset(&self, seed: u64, band: i64, point: i64) -> f32 {
+        rand::rngs::StdRng::seed_from_u64(self.hash(seed, band, point))
+            .gen_range(-self.meander..=self.meander)
+    }
+
+    // Whether the feature covers the tile `across` its band at `along` its length
+    fn covers(&self, seed: u64, along: i64, across: i64) -> bool {
+        let band = across.div_euclid(self.band);
+
+        if !self.present(seed, band) {
+            return false;
+        }
+
+        let point = along.div_euclid(MEANDER_TILES);
+        let t = along.rem_euclid(MEANDER_TILES) as f32 / MEANDER_TILES as f32;
+        let t = t * t * (3. - 2. * t);
+
+        let offset = self.offset(seed, band, point)
+            + (self.offset(seed, band, point + 1) - self.offset(seed, band, point)) * t;
+
+        let center = (band * self.band + self.band / 2) as f32 + offset;
+        let start = (center - self.width as f32 / 2.).floor() as i64;
+
+        (start..start + self.width).contains(&across)
+    }
+}
+
+// Tiles of the chunk's interior crossed by a river or road, decided from the world seed alone so
+// neighboring chunks agree on where features cross their borders
+pub fn plan_features(
+    seed: u64,
+    schematic: &Schematic,
+    coords: ChunkCoords,
+) -> Vec<(usize, usize, u8)> {
+    let water = feature_tile(schematic, |tile| tile.water);
+    let road = feature_tile(schematic, |tile| tile.road);
+
+    let origin_x = coords.0.div_euclid(TILE_SIZE);
+    let origin_y = coords.1.div_euclid(TILE_SIZE);
+
+    let mut fixed = Vec::new();
+
+    for x in 0..CHUNK_TILE_LENGTH {
+        for y in 0..CHUNK_TILE_LENGTH {
+            let (tile_x, tile_y) = (origin_x + x, origin_y + y);
+
+            // Rivers win where the two cross, bridges span them once the chunk is solved
+            let tile = water
+                .filter(|_| RIVER.covers(seed, tile_x, tile_y))
+                .or(road.filter(|_| ROAD.covers(seed, tile_y, tile_x)));
+
+            if let Some(tile) = tile {
+                fixed.push((x as usize, y as usize, tile));
+            }
+        }
+    }
+
+    fixed
+}
+
+// Lowest flagged tile allowed next to itself on every side, features without one are never planned
+fn feature_tile(schematic: &Schematic, flag: fn(&TileSchematic) -> bool) -> Option<u8> {
+    schematic
+        .tiles
+        .iter()
+        .filter(|(idx, tile)| {
+            flag(tile)
+                && !tile.bridge
+                && [&tile.north, &tile.east, &tile.south, &tile.west]
+                    .iter()
+                    .all(|allowed| allowed.contains(idx))
+        })
+        .map(|(idx, _)| *idx)
+        .min()
+}
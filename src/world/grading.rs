@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+use travelers_worldgen::biome::BiomeMap;
+
+use crate::{menu::GameState, player::Player};
+
+use super::{
+    schematic::{SchematicAsset, SchematicResource},
+    seed::WorldSeed,
+    TILE_SIZE,
+};
+
+// Rate the view eases toward the biome's tint, quick enough to follow a teleport within a second or two
+const GRADING_FADE: f32 = 2.;
+
+pub struct GradingPlugin;
+
+impl Plugin for GradingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_grading_overlay)
+            .add_systems(Update, grade_view.run_if(in_state(GameState::InGame)));
+    }
+}
+
+// Full screen tint drawn over the world and under the rest of the ui
+#[derive(Component)]
+pub struct GradingOverlay;
+
+fn spawn_grading_overlay(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(-1),
+            ..default()
+        })
+        .insert(GradingOverlay);
+}
+
+// Tint of the biomes weighted at the tile, blended by how strongly each is weighted and how strong its tint is
+fn biome_tint(schematic: &SchematicAsset, seed: u64, tile: IVec2) -> Vec4 {
+    if schematic.biomes.is_empty() {
+        return Vec4::ZERO;
+    }
+
+    let mut color = Vec3::ZERO;
+    let mut strength = 0.;
+
+    for (biome, weight) in BiomeMap::new(seed, schematic.biomes.len()).weights(tile) {
+        let Some([r, g, b, a]) = schematic.grading.get(biome).copied().flatten() else {
+            continue;
+        };
+
+        color += Vec3::new(r, g, b) * a * weight;
+        strength += a * weight;
+    }
+
+    if strength <= 0. {
+        return Vec4::ZERO;
+    }
+
+    (color / strength).extend(strength)
+}
+
+fn grade_view(
+    time: Res<Time>,
+    seed: Res<WorldSeed>,
+    schematic: Option<Res<SchematicResource>>,
+    schematics: Res<Assets<SchematicAsset>>,
+    player: Query<&Transform, With<Player>>,
+    mut overlay: Query<&mut BackgroundColor, With<GradingOverlay>>,
+) {
+    let (Ok(transform), Ok(mut background)) = (player.get_single(), overlay.get_single_mut())
+    else {
+        return;
+    };
+
+    let Some(schematic) = schematic.and_then(|handle| schematics.get(&handle.0)) else {
+        return;
+    };
+
+    let tile = (transform.translation.truncate() / TILE_SIZE as f32)
+        .floor()
+        .as_ivec2();
+
+    let target = biome_tint(schematic, seed.0, tile);
+
+    // Only the strength fades in and out of untinted views, so tints never wash through black
+    let mut current = Vec4::from_array(background.0.as_rgba_f32());
+    let target = if target.w > 0. {
+        if current.w <= 0. {
+            current = target.truncate().extend(0.);
+        }
+        target
+    } else {
+        current.truncate().extend(0.)
+    };
+
+    let blended = current.lerp(target, 1. - (-GRADING_FADE * time.delta_seconds()).exp());
+
+    if blended.distance_squared(current) > f32::EPSILON {
+        background.0 = Color::rgba(blended.x, blended.y, blended.z, blended.w);
+    }
+}
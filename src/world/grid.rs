@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+
+use travelers_worldgen::stitcher::{seam_cell, TileLookup};
+
+use super::{TileBundle, CHUNK_SIZE, CHUNK_TILE_LENGTH, TILE_SIZE};
+
+const LENGTH: usize = CHUNK_TILE_LENGTH as usize;
+
+// Stitched ring around the interior, one side per compass direction plus its corner
+const SEAM_LENGTH: usize = 4 * (LENGTH + 1);
+
+// How a chunk's interior came to be, kept for the debug overlay
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChunkOrigin {
+    Collapsed,
+    Cached,
+    Saved,
+}
+
+// Tile ids of a chunk, looked up by cell instead of walking the chunk's tile children.
+// Cells are counted in tiles from the bottom left of the interior, the seams sit at -1 and CHUNK_TILE_LENGTH
+#[derive(Component, Clone, Debug)]
+pub struct TileGrid {
+    // Column major, matching the order chunks are collapsed and saved in
+    interior: [u8; LENGTH * LENGTH],
+    // Perimeter in stitching order, filled in once the chunk is stitched
+    seams: Option<[u8; SEAM_LENGTH]>,
+    pub origin: ChunkOrigin,
+}
+
+impl TileGrid {
+    pub(super) fn from_bundles(bundles: &[TileBundle], origin: ChunkOrigin) -> TileGrid {
+        let mut interior = [0; LENGTH * LENGTH];
+
+        for bundle in bundles {
+            let cell = TileGrid::cell_of(bundle.sprite.transform.translation.truncate());
+
+            if let Some(index) = TileGrid::interior_index(cell) {
+                interior[index] = bundle.tile.texture_id;
+            }
+        }
+
+        TileGrid {
+            interior,
+            seams: None,
+            origin,
+        }
+    }
+
+    // Cell of a translation relative to the chunk's center
+    pub fn cell_of(relative: Vec2) -> IVec2 {
+        ((relative + Vec2::splat(CHUNK_SIZE as f32 / 2.)) / TILE_SIZE as f32)
+            .floor()
+            .as_ivec2()
+    }
+
+    // Translation of a cell's center relative to the chunk's center
+    pub fn center_of(cell: IVec2) -> Vec2 {
+        cell.as_vec2() * TILE_SIZE as f32 + Vec2::splat(TILE_SIZE as f32 / 2.)
+            - Vec2::splat(CHUNK_SIZE as f32 / 2.)
+    }
+
+    pub fn get(&self, cell: IVec2) -> Option<u8> {
+        if let Some(index) = TileGrid::interior_index(cell) {
+            return Some(self.interior[index]);
+        }
+
+        let seams = self.seams.as_ref()?;
+        TileGrid::seam_index(cell).map(|index| seams[index])
+    }
+
+    // False for cells outside the grid, or on seams not stitched yet
+    pub fn set(&mut self, cell: IVec2, id: u8) -> bool {
+        if let Some(index) = TileGrid::interior_index(cell) {
+            self.interior[index] = id;
+            return true;
+        }
+
+        match (self.seams.as_mut(), TileGrid::seam_index(cell)) {
+            (Some(seams), Some(index)) => {
+                seams[index] = id;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_seam(cell: IVec2) -> bool {
+        TileGrid::seam_index(cell).is_some()
+    }
+
+    pub fn interior_ids(&self) -> Vec<u8> {
+        self.interior.to_vec()
+    }
+
+    pub fn stitch(&mut self, seams: &[u8]) {
+        let mut stitched = [0; SEAM_LENGTH];
+        stitched.copy_from_slice(&seams[..SEAM_LENGTH]);
+        self.seams = Some(stitched);
+    }
+
+    // Every known cell with its tile id, seams included once stitched
+    pub fn cells(&self) -> impl Iterator<Item = (IVec2, u8)> + '_ {
+        let interior = self.interior.iter().enumerate().map(|(index, id)| {
+            (
+                IVec2::new((index / LENGTH) as i32, (index % LENGTH) as i32),
+                *id,
+            )
+        });
+
+        let seams = self
+            .seams
+            .iter()
+            .flat_map(|seams| seams.iter().enumerate())
+            .map(|(index, id)| (seam_cell(index), *id));
+
+        interior.chain(seams)
+    }
+
+    fn interior_index(cell: IVec2) -> Option<usize> {
+        let length = LENGTH as i32;
+
+        if cell.x < 0 || cell.y < 0 || cell.x >= length || cell.y >= length {
+            return None;
+        }
+
+        Some(cell.x as usize * LENGTH + cell.y as usize)
+    }
+
+    fn seam_index(cell: IVec2) -> Option<usize> {
+        let length = LENGTH as i32;
+        let side_length = LENGTH as i32 + 1;
+
+        let (side, rank) = if cell.y == length && (-1..length).contains(&cell.x) {
+            (0, cell.x + 1)
+        } else if cell.x == length && (0..=length).contains(&cell.y) {
+            (1, length - cell.y)
+        } else if cell.y == -1 && (0..=length).contains(&cell.x) {
+            (2, length - cell.x)
+        } else if cell.x == -1 && (-1..length).contains(&cell.y) {
+            (3, cell.y + 1)
+        } else {
+            return None;
+        };
+
+        Some((side * side_length + rank) as usize)
+    }
+}
+
+impl TileLookup for TileGrid {
+    fn tile(&self, cell: IVec2) -> Option<u8> {
+        self.get(cell)
+    }
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    input::{Action, InputMap},
+    player::{
+        channel::{CancelChannel, Channel, ChannelAction, ChannelFinished, StartChannel},
+        Player,
+    },
+    ui::PanelState,
+};
+
+use super::{
+    claim::LOCAL_PLAYER,
+    edit::{TileEditKind, TileEditRequest},
+    schematic::{SchematicAsset, SchematicResource},
+    Tile,
+};
+
+// Distance from the player's center to the center of a tile it can harvest
+pub(super) const HARVEST_REACH: f32 = 36.;
+
+// Holding interact next to harvestable tiles harvests the closest, letting go stops it
+#[allow(clippy::too_many_arguments)]
+pub fn gather_tiles(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    state: Res<PanelState>,
+    schematic: Option<Res<SchematicResource>>,
+    schematics: Res<Assets<SchematicAsset>>,
+    player: Query<(&Transform, Option<&Channel>), With<Player>>,
+    tiles: Query<(Entity, &Tile, &GlobalTransform)>,
+    mut channels: EventWriter<StartChannel>,
+    mut cancels: EventWriter<CancelChannel>,
+) {
+    let Ok((transform, channel)) = player.get_single() else {
+        return;
+    };
+
+    let gathering =
+        channel.is_some_and(|channel| matches!(channel.action, ChannelAction::Gather(_)));
+
+    if gathering && input_map.just_released(Action::Interact, &input) {
+        cancels.send(CancelChannel);
+        return;
+    }
+
+    if channel.is_some() || !input_map.pressed(Action::Interact, &input) || state.is_focused() {
+        return;
+    }
+
+    let Some(schematic) = schematic.and_then(|handle| schematics.get(&handle.0)) else {
+        return;
+    };
+
+    let position = transform.translation.truncate();
+
+    let Some((entity, seconds, _)) = tiles
+        .iter()
+        .filter_map(|(entity, tile, tile_transform)| {
+            let harvest = schematic.tiles.get(&tile.texture_id)?.harvest.as_ref()?;
+            let distance = tile_transform.translation().truncate().distance(position);
+
+            (distance <= HARVEST_REACH).then_some((entity, harvest.seconds, distance))
+        })
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+    else {
+        return;
+    };
+
+    channels.send(StartChannel {
+        action: ChannelAction::Gather(entity),
+        duration: Duration::from_secs_f32(seconds),
+    });
+}
+
+// A finished harvest asks for the tile to be swapped, the edit lands once it passes the checks
+pub fn finish_gathering(
+    mut finished: EventReader<ChannelFinished>,
+    tiles: Query<(&Tile, &GlobalTransform)>,
+    mut edits: EventWriter<TileEditRequest>,
+) {
+    for ChannelFinished(action) in finished.read() {
+        let ChannelAction::Gather(entity) = action else {
+            continue;
+        };
+
+        // The tile's chunk may have unloaded while it was harvested
+        let Ok((tile, transform)) = tiles.get(*entity) else {
+            continue;
+        };
+
+        edits.send(TileEditRequest {
+            player: LOCAL_PLAYER.to_string(),
+            position: transform.translation().truncate(),
+            expected: tile.texture_id,
+            kind: TileEditKind::Harvest,
+        });
+    }
+}
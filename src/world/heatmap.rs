@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use super::{get_chunk_at, Chunk, ChunkCoords, ChunkOccupant, CHUNK_SIZE};
+
+// Measured when a chunk is spawned, from a fresh collapse or the cache
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct ChunkStats {
+    pub generation_ms: f32,
+    // Tiles left uncollapsed and filled with the not found tile
+    pub contradictions: u32,
+}
+
+// Debug overlay colorizing loaded chunks by a metric, from cold blue to hot red
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeatmapMode {
+    #[default]
+    Off,
+    GenerationTime,
+    Contradictions,
+    Entities,
+}
+
+impl HeatmapMode {
+    pub fn next(&self) -> HeatmapMode {
+        match self {
+            HeatmapMode::Off => HeatmapMode::GenerationTime,
+            HeatmapMode::GenerationTime => HeatmapMode::Contradictions,
+            HeatmapMode::Contradictions => HeatmapMode::Entities,
+            HeatmapMode::Entities => HeatmapMode::Off,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct HeatmapCell;
+
+#[derive(Component)]
+pub struct HeatmapLegend;
+
+pub fn spawn_heatmap_legend(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_bundle = TextBundle {
+        text: Text::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                font_size: 16.0,
+                color: Color::WHITE,
+            },
+        ),
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(12.),
+            bottom: Val::Px(12.),
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    commands.spawn(text_bundle).insert(HeatmapLegend);
+}
+
+pub fn update_heatmap(
+    mut commands: Commands,
+    mode: Res<HeatmapMode>,
+    chunks: Query<(Entity, &Transform, &ChunkStats, Option<&Children>), With<Chunk>>,
+    occupants: Query<&Transform, With<ChunkOccupant>>,
+    mut cells: Query<&mut Sprite, With<HeatmapCell>>,
+    cell_entities: Query<Entity, With<HeatmapCell>>,
+    mut legend: Query<(&mut Text, &mut Visibility), With<HeatmapLegend>>,
+) {
+    let Ok((mut legend_text, mut legend_visibility)) = legend.get_single_mut() else {
+        return;
+    };
+
+    if *mode == HeatmapMode::Off {
+        if mode.is_changed() {
+            for cell in cell_entities.iter() {
+                commands.entity(cell).despawn();
+            }
+
+            *legend_visibility = Visibility::Hidden;
+        }
+
+        return;
+    }
+
+    let mut occupancy: HashMap<ChunkCoords, u32> = HashMap::new();
+    if *mode == HeatmapMode::Entities {
+        for transform in occupants.iter() {
+            let chunk = get_chunk_at((transform.translation.x, transform.translation.y));
+            *occupancy.entry(chunk).or_default() += 1;
+        }
+    }
+
+    let values: Vec<(Entity, Option<&Children>, f32)> = chunks
+        .iter()
+        .map(|(entity, transform, stats, children)| {
+            let value = match *mode {
+                HeatmapMode::GenerationTime => stats.generation_ms,
+                HeatmapMode::Contradictions => stats.contradictions as f32,
+                HeatmapMode::Entities => occupancy
+                    .get(&ChunkCoords::from(transform.translation))
+                    .copied()
+                    .unwrap_or(0) as f32,
+                HeatmapMode::Off => 0.,
+            };
+
+            (entity, children, value)
+        })
+        .collect();
+
+    // Normalized against the loaded chunks so outliers stand out wherever the player is
+    let min = values
+        .iter()
+        .map(|(_, _, value)| *value)
+        .fold(f32::MAX, f32::min);
+    let max = values
+        .iter()
+        .map(|(_, _, value)| *value)
+        .fold(f32::MIN, f32::max);
+
+    for (entity, children, value) in values.iter() {
+        let heat = if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.
+        };
+        let color = Color::rgba(heat, 0.2, 1. - heat, 0.45);
+
+        let existing = children
+            .iter()
+            .flat_map(|children| children.iter())
+            .find(|child| cells.contains(**child));
+
+        match existing {
+            Some(cell) => {
+                if let Ok(mut sprite) = cells.get_mut(*cell) {
+                    sprite.color = color;
+                }
+            }
+            None => {
+                let cell = SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::splat(CHUNK_SIZE as f32)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0., 0., 5.),
+                    ..default()
+                };
+
+                commands.entity(*entity).with_children(|parent| {
+                    parent.spawn(cell).insert(HeatmapCell);
+                });
+            }
+        }
+    }
+
+    legend_text.sections[0].value = format!("Heatmap {:?}: {:.1} - {:.1}", *mode, min, max);
+    *legend_visibility = Visibility::Visible;
+}
@@ -0,0 +1,100 @@
+use std::{fs, time::SystemTime};
+
+use bevy::prelude::*;
+
+use crate::player::Player;
+
+use super::{
+    cache::ChunkCache,
+    nav::{Movement, SpawnSafely},
+    persistence::SavedChunks,
+    schematic::{SchematicAsset, SchematicResource},
+    Chunk,
+};
+
+const SCHEMATIC_PATH: &str = "assets/schematic.json";
+
+const POLL_SECONDS: f32 = 1.;
+
+// Polls the schematic on disk, the asset server only watches files with bevy's file_watcher feature
+#[derive(Resource)]
+pub struct SchematicWatcher {
+    modified: Option<SystemTime>,
+    timer: Timer,
+}
+
+impl Default for SchematicWatcher {
+    fn default() -> Self {
+        SchematicWatcher {
+            modified: modified_at(),
+            timer: Timer::from_seconds(POLL_SECONDS, TimerMode::Repeating),
+        }
+    }
+}
+
+fn modified_at() -> Option<SystemTime> {
+    fs::metadata(SCHEMATIC_PATH)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+pub fn watch_schematic(
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut watcher: ResMut<SchematicWatcher>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let modified = modified_at();
+
+    if modified != watcher.modified {
+        watcher.modified = modified;
+        info!("Schematic changed on disk, reloading");
+        asset_server.reload("schematic.json");
+    }
+}
+
+// A schematic that fails to parse never reaches here, the world keeps the last good rules
+pub fn regenerate_on_schematic_change(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<SchematicAsset>>,
+    schematic: Option<Res<SchematicResource>>,
+    chunks: Query<Entity, With<Chunk>>,
+    player: Query<Entity, With<Player>>,
+    mut cache: ResMut<ChunkCache>,
+    mut saved: ResMut<SavedChunks>,
+) {
+    let Some(schematic) = schematic else {
+        return;
+    };
+
+    let modified = events
+        .read()
+        .any(|event| matches!(event, AssetEvent::Modified { id } if *id == schematic.0.id()));
+
+    if !modified {
+        return;
+    }
+
+    info!(
+        "Regenerating {} chunks with the new schematic",
+        chunks.iter().len()
+    );
+
+    // Chunks are generated again around the player next frame, their props and guards leave with them
+    for chunk in chunks.iter() {
+        commands.entity(chunk).despawn_recursive();
+    }
+
+    cache.clear();
+    saved.forget();
+
+    // The new terrain may have put rock under the player
+    for entity in player.iter() {
+        commands
+            .entity(entity)
+            .insert(SpawnSafely(Movement::Ground));
+    }
+}
@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+
+use super::{ChunkCoords, CHUNK_SIZE};
+
+// Chunks in range that are still waiting to be collapsed
+#[derive(Resource, Default)]
+pub struct PendingChunks(pub(super) Vec<ChunkCoords>);
+
+impl PendingChunks {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Component)]
+pub struct GenerationIndicator;
+
+// Stand-in quad drawn where terrain has not been generated yet
+#[derive(Component)]
+pub struct ChunkPlaceholder(ChunkCoords);
+
+pub fn spawn_generation_indicator(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_bundle = TextBundle {
+        text: Text::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                font_size: 16.0,
+                color: Color::rgba(1., 1., 1., 0.7),
+            },
+        ),
+        style: Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(12.),
+            bottom: Val::Px(12.),
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    commands.spawn(text_bundle).insert(GenerationIndicator);
+}
+
+pub fn update_generation_indicator(
+    pending: Res<PendingChunks>,
+    mut indicator: Query<(&mut Text, &mut Visibility), With<GenerationIndicator>>,
+) {
+    if !pending.is_changed() {
+        return;
+    }
+
+    if let Ok((mut text, mut visibility)) = indicator.get_single_mut() {
+        if pending.is_empty() {
+            *visibility = Visibility::Hidden;
+        } else {
+            text.sections[0].value = format!("Generating {} chunks...", pending.len());
+            *visibility = Visibility::Visible;
+        }
+    }
+}
+
+pub fn update_placeholders(
+    mut commands: Commands,
+    pending: Res<PendingChunks>,
+    placeholders: Query<(Entity, &ChunkPlaceholder)>,
+) {
+    if !pending.is_changed() {
+        return;
+    }
+
+    // Remove placeholders for chunks that have been generated or left range
+    for (entity, placeholder) in placeholders.iter() {
+        if !pending.0.contains(&placeholder.0) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for coords in pending.0.iter() {
+        if placeholders
+            .iter()
+            .any(|(_, placeholder)| placeholder.0 == *coords)
+        {
+            continue;
+        }
+
+        let sprite = SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.6, 0.6, 0.7, 0.2),
+                custom_size: Some(Vec2::new(CHUNK_SIZE as f32, CHUNK_SIZE as f32)),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(
+                coords.0 as f32 + (CHUNK_SIZE as f32 / 2.),
+                coords.1 as f32 + (CHUNK_SIZE as f32 / 2.),
+                0.,
+            )),
+            ..default()
+        };
+
+        commands.spawn(sprite).insert(ChunkPlaceholder(*coords));
+    }
+}
+
+pub fn shimmer_placeholders(
+    time: Res<Time>,
+    mut placeholders: Query<&mut Sprite, With<ChunkPlaceholder>>,
+) {
+    let alpha = 0.2 + 0.1 * (time.elapsed_seconds() * 3.).sin();
+
+    for mut sprite in placeholders.iter_mut() {
+        sprite.color.set_a(alpha);
+    }
+}
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use super::{
+    decoration::PropOwner,
+    schematic::{SchematicAsset, SchematicResource},
+    seed::WorldSeed,
+    structure::StructureDefinitions,
+    Chunk, ChunkCoords,
+};
+
+// Metadata of a finite interior map such as a dungeon, in world coordinates
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Interior {
+    pub bounds: Rect,
+}
+
+impl Interior {
+    // Keeps a view of the given half size inside the interior, centering on axes where it doesn't fit
+    pub fn clamp_view(&self, center: Vec2, half_view: Vec2) -> Vec2 {
+        let clamp_axis = |value: f32, min: f32, max: f32, half: f32| {
+            if max - min <= half * 2. {
+                (min + max) / 2.
+            } else {
+                value.clamp(min + half, max - half)
+            }
+        };
+
+        Vec2::new(
+            clamp_axis(center.x, self.bounds.min.x, self.bounds.max.x, half_view.x),
+            clamp_axis(center.y, self.bounds.min.y, self.bounds.max.y, half_view.y),
+        )
+    }
+}
+
+// Prefabs declaring an interior get one around their center, placed by the chunk holding the center
+pub fn place_interiors(
+    mut commands: Commands,
+    seed: Res<WorldSeed>,
+    schematic: Option<Res<SchematicResource>>,
+    schematics: Res<Assets<SchematicAsset>>,
+    structures: Res<StructureDefinitions>,
+    new_chunks: Query<(Entity, &Transform), Added<Chunk>>,
+) {
+    let Some(schematic) = schematic.and_then(|handle| schematics.get(&handle.0)) else {
+        return;
+    };
+
+    for (entity, transform) in new_chunks.iter() {
+        let coords = ChunkCoords::from(transform.translation);
+
+        for (structure, center) in structures.centered_in(seed.0, schematic, coords) {
+            let Some((min, max)) = structure.interior_bounds(center) else {
+                continue;
+            };
+
+            commands
+                .spawn(Interior {
+                    bounds: Rect::from_corners(min, max),
+                })
+                .insert(PropOwner(entity));
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::mem;
+
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    tasks::{block_on, IoTaskPool, Task},
+};
+
+use super::{
+    region::{ChunkUnloaded, RegionStore},
+    ChunkCoords,
+};
+
+pub(super) type ChunkBatch = Vec<(ChunkCoords, Vec<u8>)>;
+
+// Sent once a batch of chunks has been written to disk
+#[derive(Event)]
+pub struct ChunksSaved {
+    pub count: usize,
+    // The batch as it was written, no longer answered from the write queue
+    pub chunks: ChunkBatch,
+}
+
+#[derive(Event)]
+pub struct ChunkRead {
+    pub coords: ChunkCoords,
+    pub tiles: Option<Vec<u8>>,
+}
+
+#[derive(Event)]
+pub struct RegionRead {
+    pub region: (i64, i64),
+    pub chunks: ChunkBatch,
+}
+
+enum ReadOutcome {
+    Chunk(ChunkCoords, Option<Vec<u8>>),
+    Region((i64, i64), ChunkBatch),
+}
+
+// Region reads and writes run on the IO task pool so disk access never stalls a frame
+#[derive(Resource, Default)]
+pub struct RegionIo {
+    store: RegionStore,
+    queued: ChunkBatch,
+    // A single batch is written at a time so writes to the same region can't interleave
+    in_flight: ChunkBatch,
+    write_task: Option<Task<usize>>,
+    read_tasks: Vec<Task<ReadOutcome>>,
+}
+
+impl RegionIo {
+    // Chunks not yet on disk are answered from memory, newest write first
+    pub fn unsaved(&self, coords: ChunkCoords) -> Option<Vec<u8>> {
+        self.queued
+            .iter()
+            .chain(self.in_flight.iter())
+            .find(|(queued, _)| *queued == coords)
+            .map(|(_, tiles)| tiles.clone())
+    }
+
+    pub fn request_chunk(&mut self, coords: ChunkCoords) {
+        let unsaved = self.unsaved(coords);
+
+        let store = self.store.clone();
+
+        let task = IoTaskPool::get().spawn(async move {
+            let tiles = match unsaved {
+                Some(tiles) => Some(tiles),
+                None => store.read_chunk(coords).unwrap_or_else(|err| {
+                    warn!(
+                        "Failed to read chunk ({}, {})! Err {err}",
+                        coords.0, coords.1
+                    );
+                    None
+                }),
+            };
+
+            ReadOutcome::Chunk(coords, tiles)
+        });
+
+        self.read_tasks.push(task);
+    }
+
+    pub fn request_region(&mut self, region: (i64, i64)) {
+        let store = self.store.clone();
+
+        let task = IoTaskPool::get().spawn(async move {
+            let chunks = store.read_region(region).unwrap_or_else(|err| {
+                warn!(
+                    "Failed to read region ({}, {})! Err {err}",
+                    region.0, region.1
+                );
+                Vec::new()
+            });
+
+            ReadOutcome::Region(region, chunks)
+        });
+
+        self.read_tasks.push(task);
+    }
+
+    fn write_batch(store: &RegionStore, batch: &[(ChunkCoords, Vec<u8>)]) -> usize {
+        match store.write_chunks(batch) {
+            Ok(()) => batch.len(),
+            Err(err) => {
+                warn!("Failed to save {} chunks! Err {err}", batch.len());
+                0
+            }
+        }
+    }
+}
+
+pub fn queue_unloaded_chunks(mut io: ResMut<RegionIo>, mut unloaded: EventReader<ChunkUnloaded>) {
+    // Chunks unloaded as they were loaded are already on disk or queued
+    for chunk in unloaded.read().filter(|chunk| chunk.changed) {
+        // Only the latest state of a chunk needs writing
+        io.queued.retain(|(coords, _)| *coords != chunk.coords);
+        io.queued.push((chunk.coords, chunk.tiles.clone()));
+    }
+}
+
+pub fn poll_region_io(
+    mut io: ResMut<RegionIo>,
+    mut saved: EventWriter<ChunksSaved>,
+    mut chunk_reads: EventWriter<ChunkRead>,
+    mut region_reads: EventWriter<RegionRead>,
+) {
+    let io = io.as_mut();
+
+    if io
+        .write_task
+        .as_ref()
+        .is_some_and(|task| task.is_finished())
+    {
+        if let Some(task) = io.write_task.take() {
+            let count = block_on(task);
+            debug!("Saved {} chunks", count);
+
+            saved.send(ChunksSaved {
+                count,
+                chunks: mem::take(&mut io.in_flight),
+            });
+        }
+    }
+
+    if io.write_task.is_none() && !io.queued.is_empty() {
+        io.in_flight = mem::take(&mut io.queued);
+
+        let store = io.store.clone();
+        let batch = io.in_flight.clone();
+
+        io.write_task =
+            Some(IoTaskPool::get().spawn(async move { RegionIo::write_batch(&store, &batch) }));
+    }
+
+    let (finished, running) = mem::take(&mut io.read_tasks)
+        .into_iter()
+        .partition::<Vec<_>, _>(|task| task.is_finished());
+    io.read_tasks = running;
+
+    for task in finished {
+        match block_on(task) {
+            ReadOutcome::Chunk(coords, tiles) => chunk_reads.send(ChunkRead { coords, tiles }),
+            ReadOutcome::Region(region, chunks) => region_reads.send(RegionRead { region, chunks }),
+        }
+    }
+}
+
+// Exiting waits for the batch in flight and writes whatever is still queued
+pub fn flush_region_io_on_exit(exit: EventReader<AppExit>, mut io: ResMut<RegionIo>) {
+    if exit.is_empty() {
+        return;
+    }
+
+    if let Some(task) = io.write_task.take() {
+        block_on(task);
+    }
+
+    let queued = mem::take(&mut io.queued);
+    RegionIo::write_batch(&io.store, &queued);
+}
@@ -0,0 +1,87 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use bevy::prelude::*;
+
+use rand::{Rng, SeedableRng};
+
+use travelers_worldgen::schematic::LayerKind;
+
+use super::{
+    nav::NavGrid,
+    schematic::{SchematicAsset, SchematicResource},
+    seed::WorldSeed,
+    AtlasResource, Tile,
+};
+
+// Above elevation shading, below anything standing on the tile
+const DECORATION_LAYER: f32 = 0.06;
+
+// Above the player and enemies, canopies hide whoever walks under them
+const OVERLAY_LAYER: f32 = 1.4;
+
+fn layer_z(kind: LayerKind) -> f32 {
+    match kind {
+        LayerKind::Decoration => DECORATION_LAYER,
+        LayerKind::Overlay => OVERLAY_LAYER,
+    }
+}
+
+// Stacks the schematic's layers on newly spawned tiles, each cell rolls the same way whenever it loads
+pub fn spawn_tile_layers(
+    mut commands: Commands,
+    seed: Res<WorldSeed>,
+    atlas: Res<AtlasResource>,
+    schematic: Option<Res<SchematicResource>>,
+    schematics: Res<Assets<SchematicAsset>>,
+    tiles: Query<(Entity, &Tile, &Transform, &Parent, &TextureAtlasSprite), Added<Tile>>,
+    parents: Query<&Transform, Without<Tile>>,
+) {
+    let Some(schematic) = schematic.and_then(|handle| schematics.get(&handle.0)) else {
+        return;
+    };
+
+    for (entity, tile, transform, parent, tile_sprite) in tiles.iter() {
+        let Some(layers) = schematic
+            .tiles
+            .get(&tile.texture_id())
+            .map(|tile| &tile.layers)
+            .filter(|layers| !layers.is_empty())
+        else {
+            continue;
+        };
+
+        let Ok(parent_transform) = parents.get(parent.get()) else {
+            continue;
+        };
+
+        let cell =
+            NavGrid::cell_of((parent_transform.translation + transform.translation).truncate());
+
+        let mut hasher = DefaultHasher::new();
+        (seed.0, cell.x, cell.y, "layers").hash(&mut hasher);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(hasher.finish());
+
+        commands.entity(entity).with_children(|tile| {
+            for layer in layers {
+                // Rolled for every layer so one layer's chance never shifts another's
+                if !rng.gen_bool(layer.chance as f64) {
+                    continue;
+                }
+
+                // Shaded like the tile beneath so layers on low ground darken with it
+                tile.spawn(SpriteSheetBundle {
+                    texture_atlas: atlas.0.clone(),
+                    sprite: TextureAtlasSprite {
+                        color: tile_sprite.color,
+                        ..TextureAtlasSprite::new(layer.index as usize)
+                    },
+                    transform: Transform::from_xyz(0., 0., layer_z(layer.layer)),
+                    ..default()
+                });
+            }
+        });
+    }
+}
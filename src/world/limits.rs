@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::components::Stack;
+
+use super::{get_chunk_at, ChunkCoords};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OccupantKind {
+    Decoration,
+    Item,
+    Enemy,
+}
+
+// Marks a world space entity as counting towards its chunk's cap for the kind
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ChunkOccupant(pub OccupantKind);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    // Despawn whatever exceeds the cap
+    Skip,
+    // Fold matching stacks into one already in the chunk, skipping anything that can't merge
+    Merge,
+}
+
+#[derive(Resource)]
+pub struct ChunkEntityLimits {
+    pub caps: HashMap<OccupantKind, (usize, OverflowPolicy)>,
+}
+
+impl Default for ChunkEntityLimits {
+    fn default() -> Self {
+        ChunkEntityLimits {
+            caps: HashMap::from([
+                (OccupantKind::Decoration, (128, OverflowPolicy::Skip)),
+                (OccupantKind::Item, (64, OverflowPolicy::Merge)),
+                (OccupantKind::Enemy, (16, OverflowPolicy::Skip)),
+            ]),
+        }
+    }
+}
+
+pub fn enforce_chunk_limits(
+    mut commands: Commands,
+    limits: Res<ChunkEntityLimits>,
+    added: Query<(Entity, &ChunkOccupant, &Transform, Option<&Stack>), Added<ChunkOccupant>>,
+    mut occupants: Query<(Entity, &ChunkOccupant, &Transform, Option<&mut Stack>)>,
+) {
+    if added.is_empty() {
+        return;
+    }
+
+    // Count what was already in each chunk before this frame's spawns
+    let mut counts: HashMap<(ChunkCoords, OccupantKind), usize> = HashMap::new();
+    for (entity, occupant, transform, _) in occupants.iter() {
+        if added.contains(entity) {
+            continue;
+        }
+
+        let chunk = get_chunk_at((transform.translation.x, transform.translation.y));
+        *counts.entry((chunk, occupant.0)).or_default() += 1;
+    }
+
+    for (entity, occupant, transform, stack) in added.iter() {
+        let Some((cap, policy)) = limits.caps.get(&occupant.0) else {
+            continue;
+        };
+
+        let chunk = get_chunk_at((transform.translation.x, transform.translation.y));
+        let count = counts.entry((chunk, occupant.0)).or_default();
+
+        if *count < *cap {
+            *count += 1;
+            continue;
+        }
+
+        if *policy == OverflowPolicy::Merge {
+            if let Some(stack) = stack {
+                let target = occupants.iter_mut().find(
+                    |(other, other_occupant, other_transform, other_stack)| {
+                        *other != entity
+                            && !added.contains(*other)
+                            && other_occupant.0 == occupant.0
+                            && other_stack
+                                .as_ref()
+                                .is_some_and(|other_stack| other_stack.id == stack.id)
+                            && get_chunk_at((
+                                other_transform.translation.x,
+                                other_transform.translation.y,
+                            )) == chunk
+                    },
+                );
+
+                if let Some((_, _, _, Some(mut other_stack))) = target {
+                    debug!(
+                        "Merging overflowing stack into chunk ({}, {})",
+                        chunk.0, chunk.1
+                    );
+                    other_stack.count += stack.count;
+                    commands.entity(entity).despawn_recursive();
+                    continue;
+                }
+            }
+        }
+
+        debug!(
+            "Chunk ({}, {}) is at its {:?} cap, skipping spawn",
+            chunk.0, chunk.1, occupant.0
+        );
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,474 @@
+use std::{collections::HashMap, fs};
+
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::Dirty,
+    menu::GameState,
+    player::{ping::Ping, Player},
+    ui::{Panel, PanelRoot},
+    work::WorkQueue,
+};
+
+use super::{
+    get_chunk_at, grid::TileGrid, AtlasResource, Chunk, ChunkCoords, CHUNK_TILE_LENGTH, TILE_SIZE,
+};
+
+const EXPLORED_SAVE_PATH: &str = "saves/explored.json";
+
+// One pixel per tile, a chunk's interior plus the stitched seam on its east and north sides,
+// matching the area get_chunk_at assigns to the chunk
+const CHUNK_PIXELS: usize = CHUNK_TILE_LENGTH as usize + 1;
+
+// Tiles across the corner map and the full map, both centered on the player
+const MINIMAP_TILES: usize = 48;
+const WORLD_MAP_TILES: usize = 192;
+
+const MINIMAP_SIZE: f32 = 160.;
+
+// Unexplored ground
+const FOG: [u8; 4] = [12, 12, 16, 255];
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExploredMap>()
+            .add_systems(OnExit(GameState::MainMenu), load_explored_map)
+            .add_systems(Startup, spawn_maps)
+            .add_systems(
+                Update,
+                (
+                    average_tile_colors,
+                    record_explored_chunks,
+                    draw_maps,
+                    draw_map_pings,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(Update, save_explored_on_exit);
+    }
+}
+
+// Average color of each atlas tile, worked out once the terrain sheet has loaded
+#[derive(Resource)]
+pub struct TileColors(HashMap<u8, [u8; 4]>);
+
+// Low res picture of every chunk the player has loaded, kept across sessions
+#[derive(Resource, Default, Deserialize, Serialize)]
+pub struct ExploredMap {
+    // Row major from the bottom left, CHUNK_PIXELS squared rgb triples
+    chunks: HashMap<String, Vec<u8>>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl ExploredMap {
+    pub fn load() -> ExploredMap {
+        fs::read(EXPLORED_SAVE_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let written = fs::create_dir_all("saves").and_then(|_| {
+            fs::write(
+                EXPLORED_SAVE_PATH,
+                serde_json::to_vec(self).map_err(std::io::Error::from)?,
+            )
+        });
+
+        if let Err(err) = written {
+            warn!("Failed to save explored map! Err {err}");
+        }
+    }
+
+    // Json object keys have to be strings
+    fn key(coords: ChunkCoords) -> String {
+        format!("{},{}", coords.0, coords.1)
+    }
+
+    // Color of the world tile, None until its chunk has been explored
+    fn pixel(&self, tile: IVec2) -> Option<[u8; 4]> {
+        let position = (tile.as_vec2() + Vec2::splat(0.5)) * TILE_SIZE as f32;
+        let coords = get_chunk_at((position.x, position.y));
+
+        let pixels = self.chunks.get(&Self::key(coords))?;
+
+        let origin = IVec2::new(
+            coords.0.div_euclid(TILE_SIZE) as i32,
+            coords.1.div_euclid(TILE_SIZE) as i32,
+        );
+        let local = tile - origin;
+        let index = 3 * (local.y as usize * CHUNK_PIXELS + local.x as usize);
+
+        let rgb = pixels.get(index..index + 3)?;
+        Some([rgb[0], rgb[1], rgb[2], 255])
+    }
+}
+
+#[derive(Component)]
+struct MapImage {
+    tiles: usize,
+    // Tile the map was last drawn around, nothing is redrawn while it stays the same
+    center: Option<IVec2>,
+}
+
+fn load_explored_map(mut commands: Commands) {
+    commands.insert_resource(ExploredMap::load());
+}
+
+fn map_image(images: &mut Assets<Image>, tiles: usize) -> Handle<Image> {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: tiles as u32,
+            height: tiles as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &FOG,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+
+    // Tiles stay crisp squares when scaled up
+    image.sampler = ImageSampler::nearest();
+
+    images.add(image)
+}
+
+// Marks the player in the middle of a map
+fn player_dot(parent: &mut ChildBuilder) {
+    parent.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(50.),
+            top: Val::Percent(50.),
+            width: Val::Px(4.),
+            height: Val::Px(4.),
+            margin: UiRect::all(Val::Px(-2.)),
+            ..default()
+        },
+        background_color: Color::rgb(1., 0.2, 0.2).into(),
+        ..default()
+    });
+}
+
+// The corner map is always shown, the full map is the map panel
+fn spawn_maps(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let corner = map_image(&mut images, MINIMAP_TILES);
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(12.),
+                top: Val::Px(40.),
+                width: Val::Px(MINIMAP_SIZE),
+                height: Val::Px(MINIMAP_SIZE),
+                border: UiRect::all(Val::Px(2.)),
+                ..default()
+            },
+            border_color: Color::rgba(1., 1., 1., 0.5).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(ImageBundle {
+                    image: UiImage::new(corner),
+                    style: Style {
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(MapImage {
+                    tiles: MINIMAP_TILES,
+                    center: None,
+                })
+                .with_children(player_dot);
+        });
+
+    let world = map_image(&mut images, WORLD_MAP_TILES);
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(PanelRoot(Panel::Map))
+        .with_children(|parent| {
+            parent
+                .spawn(ImageBundle {
+                    image: UiImage::new(world),
+                    style: Style {
+                        width: Val::Vh(80.),
+                        height: Val::Vh(80.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(MapImage {
+                    tiles: WORLD_MAP_TILES,
+                    center: None,
+                })
+                .with_children(player_dot);
+        });
+}
+
+// Transparent pixels are skipped so sprites with cut out edges aren't darkened
+fn average_tile_colors(
+    mut commands: Commands,
+    colors: Option<Res<TileColors>>,
+    atlas: Option<Res<AtlasResource>>,
+    atlases: Res<Assets<TextureAtlas>>,
+    images: Res<Assets<Image>>,
+) {
+    if colors.is_some() {
+        return;
+    }
+
+    let Some(atlas) = atlas.and_then(|atlas| atlases.get(&atlas.0)) else {
+        return;
+    };
+
+    let Some(image) = images.get(&atlas.texture) else {
+        return;
+    };
+
+    let width = image.texture_descriptor.size.width as usize;
+
+    if image.data.len() != width * image.texture_descriptor.size.height as usize * 4 {
+        warn!("Terrain sheet isn't 8 bit rgba, the map can't be colored");
+        commands.insert_resource(TileColors(HashMap::new()));
+        return;
+    }
+
+    let mut colors = HashMap::new();
+
+    for (index, rect) in atlas.textures.iter().enumerate() {
+        let mut sum = [0u64; 3];
+        let mut count = 0;
+
+        for y in rect.min.y as usize..rect.max.y as usize {
+            for x in rect.min.x as usize..rect.max.x as usize {
+                let pixel = &image.data[4 * (y * width + x)..4 * (y * width + x) + 4];
+
+                if pixel[3] == 0 {
+                    continue;
+                }
+
+                for channel in 0..3 {
+                    sum[channel] += pixel[channel] as u64;
+                }
+                count += 1;
+            }
+        }
+
+        // Fully transparent tiles are left off the map
+        if let [Some(r), Some(g), Some(b)] =
+            sum.map(|channel| channel.checked_div(count).map(|value| value as u8))
+        {
+            colors.insert(index as u8, [r, g, b, 255]);
+        }
+    }
+
+    debug!("Averaged the colors of {} terrain tiles", colors.len());
+    commands.insert_resource(TileColors(colors));
+}
+
+// Chunks are pictured once their seams are stitched and again whenever their tiles change. Chunks
+// loaded before the colors were known stay marked until they are
+fn record_explored_chunks(
+    colors: Option<Res<TileColors>>,
+    mut explored: ResMut<ExploredMap>,
+    mut chunks: Query<(&TileGrid, &Transform, &mut Dirty), With<Chunk>>,
+) {
+    let Some(colors) = colors else {
+        return;
+    };
+
+    for (grid, transform, mut dirty) in chunks.iter_mut() {
+        if !dirty.visual || dirty.stitch {
+            continue;
+        }
+
+        dirty.visual = false;
+
+        let mut pixels = Vec::with_capacity(CHUNK_PIXELS * CHUNK_PIXELS * 3);
+
+        for y in 0..CHUNK_PIXELS as i32 {
+            for x in 0..CHUNK_PIXELS as i32 {
+                let color = grid
+                    .get(IVec2::new(x, y))
+                    .and_then(|id| colors.0.get(&id))
+                    .copied()
+                    .unwrap_or(FOG);
+
+                pixels.extend_from_slice(&color[..3]);
+            }
+        }
+
+        let coords = ChunkCoords::from(transform.translation);
+        explored.chunks.insert(ExploredMap::key(coords), pixels);
+        explored.dirty = true;
+    }
+}
+
+// Redrawn around the player whenever they cross into another tile or more of the world is explored.
+// Painting is deferred work, only the latest redraw of each map is painted
+fn draw_maps(
+    mut explored: ResMut<ExploredMap>,
+    player: Query<&Transform, With<Player>>,
+    mut maps: Query<(Entity, &UiImage, &mut MapImage, &InheritedVisibility)>,
+    mut work: ResMut<WorkQueue>,
+) {
+    let Ok(transform) = player.get_single() else {
+        return;
+    };
+
+    let center = (transform.translation.truncate() / TILE_SIZE as f32)
+        .floor()
+        .as_ivec2();
+
+    let dirty = explored.dirty;
+    explored.dirty = false;
+
+    for (entity, image, mut map, visibility) in maps.iter_mut() {
+        // A hidden full map is drawn again once opened
+        if !visibility.get() {
+            map.center = None;
+            continue;
+        }
+
+        if map.center == Some(center) && !dirty {
+            continue;
+        }
+
+        map.center = Some(center);
+
+        let (texture, tiles) = (image.texture.clone(), map.tiles);
+
+        work.push_latest(format!("draw map {entity:?}"), move |world| {
+            world.resource_scope(|world, mut images: Mut<Assets<Image>>| {
+                if let Some(image) = images.get_mut(&texture) {
+                    paint_map(image, tiles, center, world.resource::<ExploredMap>());
+                }
+            });
+        });
+    }
+}
+
+#[derive(Component)]
+struct MapPing;
+
+// Rebuilt whenever a ping comes or goes or the player crosses into another tile, pings off the
+// edge of a map aren't shown on it
+#[allow(clippy::too_many_arguments)]
+fn draw_map_pings(
+    mut commands: Commands,
+    mut last_center: Local<Option<IVec2>>,
+    player: Query<&Transform, With<Player>>,
+    maps: Query<(Entity, &MapImage)>,
+    pings: Query<(&Ping, &Transform)>,
+    added: Query<(), Added<Ping>>,
+    mut removed: RemovedComponents<Ping>,
+    dots: Query<Entity, With<MapPing>>,
+) {
+    let Ok(transform) = player.get_single() else {
+        return;
+    };
+
+    let center = (transform.translation.truncate() / TILE_SIZE as f32)
+        .floor()
+        .as_ivec2();
+
+    let removed = removed.read().count() > 0;
+    if *last_center == Some(center) && added.is_empty() && !removed {
+        return;
+    }
+
+    *last_center = Some(center);
+
+    for dot in dots.iter() {
+        commands.entity(dot).despawn_recursive();
+    }
+
+    for (entity, map) in maps.iter() {
+        let half = (map.tiles / 2) as i32;
+
+        commands.entity(entity).with_children(|parent| {
+            for (ping, ping_transform) in pings.iter() {
+                let tile = (ping_transform.translation.truncate() / TILE_SIZE as f32)
+                    .floor()
+                    .as_ivec2();
+
+                // Columns run from the left, rows from the top
+                let column = tile.x - center.x + half;
+                let row = half - (tile.y - center.y);
+
+                if !(0..map.tiles as i32).contains(&column) || !(0..map.tiles as i32).contains(&row)
+                {
+                    continue;
+                }
+
+                let percent = |cell: i32| (cell as f32 + 0.5) / map.tiles as f32 * 100.;
+
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Percent(percent(column)),
+                            top: Val::Percent(percent(row)),
+                            width: Val::Px(6.),
+                            height: Val::Px(6.),
+                            margin: UiRect::all(Val::Px(-3.)),
+                            ..default()
+                        },
+                        background_color: ping.kind.color().into(),
+                        ..default()
+                    })
+                    .insert(MapPing);
+            }
+        });
+    }
+}
+
+fn paint_map(image: &mut Image, tiles: usize, center: IVec2, explored: &ExploredMap) {
+    let half = (tiles / 2) as i32;
+
+    // Image rows run from the top, world rows from the bottom
+    for row in 0..tiles {
+        for column in 0..tiles {
+            let tile = center + IVec2::new(column as i32 - half, half - row as i32);
+            let color = explored.pixel(tile).unwrap_or(FOG);
+
+            let index = 4 * (row * tiles + column);
+            image.data[index..index + 4].copy_from_slice(&color);
+        }
+    }
+}
+
+fn save_explored_on_exit(exit: EventReader<AppExit>, explored: Res<ExploredMap>) {
+    if !exit.is_empty() {
+        explored.save();
+    }
+}
@@ -0,0 +1,405 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use bevy::prelude::*;
+
+use crate::components::Dirty;
+
+use super::{
+    decoration::Prop,
+    elevation::{ElevationMap, Terrain},
+    grid::TileGrid,
+    schematic::{SchematicAsset, SchematicResource},
+    Chunk, TILE_SIZE,
+};
+
+// Upper bound on cells expanded per search, keeps unreachable goals from scanning every loaded chunk
+const MAX_EXPANSIONS: usize = 4096;
+
+// Furthest ring of cells searched for somewhere safe to place an entity
+const MAX_SPAWN_RINGS: i32 = 8;
+
+// Extra cost of moving through a cell another creature is standing in, enough to route around crowds
+const CROWD_COST: i32 = 3;
+
+// Extra cost of wading through a water cell for enemies that walk
+const WADING_COST: i32 = 4;
+
+// How an enemy gets around, each archetype reads its own layers of the grid
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Movement {
+    // Blocked by solid terrain and obstacles, slowed by water
+    #[default]
+    Ground,
+    // Confined to water
+    Aquatic,
+    // Passes over terrain at no extra cost, only obstacles stop it
+    Flying,
+}
+
+// Tile aligned layers of the loaded world, rebuilt whenever chunks or props change
+#[derive(Resource, Default)]
+pub struct NavGrid {
+    solid: HashSet<IVec2>,
+    // Props tall enough to stop even flyers
+    obstacles: HashSet<IVec2>,
+    water: HashSet<IVec2>,
+    // Every cell of a loaded tile, anything else has not been generated yet
+    known: HashSet<IVec2>,
+    // Entities standing in each cell, refreshed every frame
+    occupants: HashMap<IVec2, Vec<(Entity, NavOccupant)>>,
+}
+
+// Entities taking up the cell they stand in
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavOccupant {
+    // Furniture such as chests, blocks paths like a wall
+    Static,
+    // Creatures, paths may pass through them at a cost
+    Dynamic,
+}
+
+// Moves the entity onto the nearest cell it can stand on once its surroundings have loaded
+#[derive(Component)]
+pub struct SpawnSafely(pub Movement);
+
+impl NavGrid {
+    pub fn cell_of(position: Vec2) -> IVec2 {
+        (position / TILE_SIZE as f32).floor().as_ivec2()
+    }
+
+    pub fn center_of(cell: IVec2) -> Vec2 {
+        (cell.as_vec2() + Vec2::splat(0.5)) * TILE_SIZE as f32
+    }
+
+    pub fn is_walkable(&self, cell: IVec2) -> bool {
+        !self.solid.contains(&cell) && !self.obstacles.contains(&cell)
+    }
+
+    // Cost of entering the cell, None where the archetype cannot go at all
+    pub fn cost(&self, cell: IVec2, movement: Movement) -> Option<i32> {
+        let occupancy = match self.occupancy(cell) {
+            Some(NavOccupant::Static) => return None,
+            Some(NavOccupant::Dynamic) => CROWD_COST,
+            None => 0,
+        };
+
+        self.terrain_cost(cell, movement)
+            .map(|cost| cost + occupancy)
+    }
+
+    fn terrain_cost(&self, cell: IVec2, movement: Movement) -> Option<i32> {
+        let water = self.water.contains(&cell);
+
+        match movement {
+            Movement::Ground if !self.is_walkable(cell) => None,
+            Movement::Ground if water => Some(WADING_COST),
+            Movement::Aquatic if !water || self.obstacles.contains(&cell) => None,
+            Movement::Flying if self.obstacles.contains(&cell) => None,
+            _ => Some(1),
+        }
+    }
+
+    // Static occupants win over dynamic ones sharing the cell
+    fn occupancy(&self, cell: IVec2) -> Option<NavOccupant> {
+        let occupants = self.occupants.get(&cell)?;
+
+        occupants
+            .iter()
+            .map(|(_, occupant)| *occupant)
+            .find(|occupant| *occupant == NavOccupant::Static)
+            .or(occupants.first().map(|(_, occupant)| *occupant))
+    }
+
+    // Whether anything other than the given entity stands in the cell
+    pub fn is_occupied(&self, cell: IVec2, except: Option<Entity>) -> bool {
+        self.occupants
+            .get(&cell)
+            .is_some_and(|occupants| occupants.iter().any(|(entity, _)| Some(*entity) != except))
+    }
+
+    pub fn occupied(&self) -> impl Iterator<Item = &IVec2> {
+        self.occupants.keys()
+    }
+
+    // Samples the segment every quarter tile, enough that it cannot skip a blocked cell's corner
+    pub fn line_of_sight(&self, from: Vec2, to: Vec2) -> bool {
+        let steps = (from.distance(to) / (TILE_SIZE as f32 / 4.)).ceil() as usize;
+
+        (0..=steps).all(|step| {
+            let t = step as f32 / steps.max(1) as f32;
+            self.is_walkable(Self::cell_of(from.lerp(to, t)))
+        })
+    }
+
+    pub fn covers(&self, position: Vec2) -> bool {
+        self.known.contains(&Self::cell_of(position))
+    }
+
+    // Swimmers need water, everything else needs open dry ground, and nobody else may stand there
+    fn can_spawn(&self, cell: IVec2, movement: Movement, except: Option<Entity>) -> bool {
+        if !self.known.contains(&cell) || self.is_occupied(cell, except) {
+            return false;
+        }
+
+        match movement {
+            Movement::Aquatic => self.terrain_cost(cell, Movement::Aquatic).is_some(),
+            Movement::Ground | Movement::Flying => {
+                self.terrain_cost(cell, Movement::Ground) == Some(1)
+            }
+        }
+    }
+
+    // Nearest cell center the archetype can be placed at, searched in growing rings around the point
+    pub fn find_spawn_point(
+        &self,
+        point: Vec2,
+        movement: Movement,
+        except: Option<Entity>,
+    ) -> Option<Vec2> {
+        let origin = Self::cell_of(point);
+
+        if self.can_spawn(origin, movement, except) {
+            return Some(point);
+        }
+
+        for ring in 1..=MAX_SPAWN_RINGS {
+            let candidates = (-ring..=ring).flat_map(|x| {
+                (-ring..=ring)
+                    .filter(move |y| x.abs() == ring || y.abs() == ring)
+                    .map(move |y| origin + IVec2::new(x, y))
+            });
+
+            let nearest = candidates
+                .filter(|cell| self.can_spawn(*cell, movement, except))
+                .map(Self::center_of)
+                .min_by(|a, b| a.distance(point).total_cmp(&b.distance(point)));
+
+            if nearest.is_some() {
+                return nearest;
+            }
+        }
+
+        None
+    }
+
+    pub fn blocked(&self) -> impl Iterator<Item = &IVec2> {
+        self.solid.union(&self.obstacles)
+    }
+
+    pub fn water(&self) -> impl Iterator<Item = &IVec2> {
+        self.water.iter()
+    }
+}
+
+// Result of a search, the expanded cells are kept for debugging even when no path was found
+#[derive(Clone, Debug, Default)]
+pub struct PathSearch {
+    pub waypoints: Vec<Vec2>,
+    pub expanded: Vec<IVec2>,
+}
+
+// A* over the grid's four neighbors, waypoints are cell centers leading up to the goal
+pub fn find_path(grid: &NavGrid, from: Vec2, to: Vec2, movement: Movement) -> PathSearch {
+    let start = NavGrid::cell_of(from);
+    let goal = NavGrid::cell_of(to);
+
+    let mut search = PathSearch::default();
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+    let mut cost: HashMap<IVec2, i32> = HashMap::new();
+    let mut closed = HashSet::new();
+
+    let heuristic = |cell: IVec2| (cell.x - goal.x).abs() + (cell.y - goal.y).abs();
+
+    open.push(Reverse((heuristic(start), start.x, start.y)));
+    cost.insert(start, 0);
+
+    while let Some(Reverse((_, x, y))) = open.pop() {
+        let cell = IVec2::new(x, y);
+
+        if !closed.insert(cell) {
+            continue;
+        }
+
+        search.expanded.push(cell);
+
+        if cell == goal {
+            let mut waypoints = vec![to];
+            let mut current = cell;
+
+            while let Some(previous) = came_from.get(&current) {
+                if *previous != start {
+                    waypoints.push(NavGrid::center_of(*previous));
+                }
+                current = *previous;
+            }
+
+            waypoints.reverse();
+            search.waypoints = waypoints;
+            return search;
+        }
+
+        if search.expanded.len() >= MAX_EXPANSIONS {
+            break;
+        }
+
+        for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+            let neighbor = cell + offset;
+
+            if closed.contains(&neighbor) {
+                continue;
+            }
+
+            let Some(step) = grid.cost(neighbor, movement) else {
+                continue;
+            };
+
+            let neighbor_cost = cost[&cell] + step;
+            if cost
+                .get(&neighbor)
+                .is_some_and(|known| *known <= neighbor_cost)
+            {
+                continue;
+            }
+
+            cost.insert(neighbor, neighbor_cost);
+            came_from.insert(neighbor, cell);
+            open.push(Reverse((
+                neighbor_cost + heuristic(neighbor),
+                neighbor.x,
+                neighbor.y,
+            )));
+        }
+    }
+
+    search
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn rebuild_nav_grid(
+    mut grid: ResMut<NavGrid>,
+    schematic: Option<Res<SchematicResource>>,
+    schematics: Res<Assets<SchematicAsset>>,
+    elevation: Res<ElevationMap>,
+    mut dirty: Query<&mut Dirty, With<Chunk>>,
+    added_props: Query<(), Added<Prop>>,
+    mut removed_chunks: RemovedComponents<Chunk>,
+    chunks: Query<(&Transform, &TileGrid), With<Chunk>>,
+    props: Query<(&Transform, &Sprite), With<Prop>>,
+) {
+    // Chunks are waited on until their seams are stitched, the rebuild then covers them whole
+    let changed = dirty.iter().any(|dirty| dirty.nav && !dirty.stitch)
+        || !added_props.is_empty()
+        || removed_chunks.read().count() > 0;
+
+    if !changed {
+        return;
+    }
+
+    let Some(schematic) = schematic.and_then(|handle| schematics.get(&handle.0)) else {
+        return;
+    };
+
+    let mut solid = HashSet::new();
+    let mut water = HashSet::new();
+    let mut known = HashSet::new();
+
+    for (chunk_transform, grid) in chunks.iter() {
+        for (cell, tile_id) in grid.cells() {
+            let Some(tile) = schematic.tiles.get(&tile_id) else {
+                continue;
+            };
+
+            let position = chunk_transform.translation.truncate() + TileGrid::center_of(cell);
+            known.insert(NavGrid::cell_of(position));
+
+            // Cliff edges block walkers and sight alike, flyers pass over them like any solid tile
+            if tile.solid || elevation.terrain(NavGrid::cell_of(position)) == Terrain::Cliff {
+                solid.insert(NavGrid::cell_of(position));
+            }
+
+            if tile.water {
+                water.insert(NavGrid::cell_of(position));
+            }
+        }
+    }
+
+    let mut obstacles = HashSet::new();
+
+    // Props block every cell their sprite covers
+    for (transform, sprite) in props.iter() {
+        let half = sprite.custom_size.unwrap_or(Vec2::splat(TILE_SIZE as f32)) / 2. - Vec2::ONE;
+        let center = transform.translation.truncate();
+
+        let min = NavGrid::cell_of(center - half);
+        let max = NavGrid::cell_of(center + half);
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                obstacles.insert(IVec2::new(x, y));
+            }
+        }
+    }
+
+    debug!(
+        "Rebuilt nav grid with {} solid, {} obstacle and {} water cells",
+        solid.len(),
+        obstacles.len(),
+        water.len()
+    );
+
+    grid.solid = solid;
+    grid.obstacles = obstacles;
+    grid.water = water;
+    grid.known = known;
+
+    for mut dirty in dirty.iter_mut() {
+        if dirty.nav && !dirty.stitch {
+            dirty.nav = false;
+        }
+    }
+}
+
+pub fn track_occupants(
+    mut grid: ResMut<NavGrid>,
+    occupants: Query<(Entity, &Transform, &NavOccupant)>,
+) {
+    grid.occupants.clear();
+
+    for (entity, transform, occupant) in occupants.iter() {
+        grid.occupants
+            .entry(NavGrid::cell_of(transform.translation.truncate()))
+            .or_default()
+            .push((entity, *occupant));
+    }
+}
+
+pub fn settle_safe_spawns(
+    mut commands: Commands,
+    grid: Res<NavGrid>,
+    mut spawns: Query<(Entity, &mut Transform, &SpawnSafely)>,
+) {
+    for (entity, mut transform, spawn) in spawns.iter_mut() {
+        let position = transform.translation.truncate();
+
+        if !grid.covers(position) {
+            continue;
+        }
+
+        match grid.find_spawn_point(position, spawn.0, Some(entity)) {
+            Some(point) => {
+                transform.translation.x = point.x;
+                transform.translation.y = point.y;
+            }
+            None => warn!(
+                "No safe spot near ({}, {}), leaving {:?} in place",
+                position.x, position.y, entity
+            ),
+        }
+
+        commands.entity(entity).remove::<SpawnSafely>();
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use rand::{Rng, SeedableRng};
+
+use crate::{
+    combat::{in_reach, Swing},
+    components::Stack,
+    items::{
+        definition::ItemRegistry,
+        dropped::DropItem,
+        durability::WearEquipment,
+        equipment::{EquipSlot, Equipment},
+    },
+    player::{
+        channel::{ChannelAction, ChannelFinished, StartChannel},
+        Player,
+    },
+};
+
+use super::{
+    decoration::{Prop, PropOwner},
+    elevation::{ElevationMap, Terrain},
+    nav::NavGrid,
+    seed::WorldSeed,
+    Chunk, ChunkCoords, ChunkOccupant, OccupantKind, CHUNK_TILE_LENGTH, TILE_SIZE,
+};
+
+// Tiles tried for a deposit in each chunk, only those on high ground get one
+const DEPOSIT_ATTEMPTS: u32 = 3;
+
+const DEPOSIT_SIZE: f32 = 22.;
+
+// Ore dropped by a mined out deposit
+const DEPOSIT_YIELD: u32 = 2;
+
+// Channel time of each strike a deposit takes to mine out
+const STRIKE_SECONDS: f32 = 0.4;
+
+// Ordered by the pickaxe tier needed to mine them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OreKind {
+    Copper,
+    Iron,
+    Mythril,
+}
+
+impl OreKind {
+    fn tier(&self) -> u8 {
+        match self {
+            OreKind::Copper => 0,
+            OreKind::Iron => 1,
+            OreKind::Mythril => 2,
+        }
+    }
+
+    fn item(&self) -> u32 {
+        match self {
+            OreKind::Copper => 10,
+            OreKind::Iron => 11,
+            OreKind::Mythril => 12,
+        }
+    }
+
+    // Strikes needed to mine it out with a pickaxe of exactly its tier
+    fn hardness(&self) -> u8 {
+        match self {
+            OreKind::Copper => 3,
+            OreKind::Iron => 5,
+            OreKind::Mythril => 8,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            OreKind::Copper => Color::rgb(0.75, 0.45, 0.25),
+            OreKind::Iron => Color::rgb(0.6, 0.6, 0.65),
+            OreKind::Mythril => Color::rgb(0.35, 0.75, 0.85),
+        }
+    }
+
+    // Rarer ores only surface on the highest ground
+    fn roll(level: i32, rng: &mut impl Rng) -> Option<OreKind> {
+        match level {
+            1 if rng.gen_bool(0.7) => Some(OreKind::Copper),
+            1 => Some(OreKind::Iron),
+            2 if rng.gen_bool(0.6) => Some(OreKind::Iron),
+            2 => Some(OreKind::Mythril),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct OreDeposit {
+    kind: OreKind,
+}
+
+// Deposits crop out of the rock of raised ground, they grow back whenever their chunk reloads
+pub fn place_ore_deposits(
+    mut commands: Commands,
+    seed: Res<WorldSeed>,
+    elevation: Res<ElevationMap>,
+    new_chunks: Query<(Entity, &Transform), Added<Chunk>>,
+) {
+    for (entity, transform) in new_chunks.iter() {
+        let coords = ChunkCoords::from(transform.translation);
+
+        let mut hasher = DefaultHasher::new();
+        (coords.0, coords.1, seed.0, "ore").hash(&mut hasher);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(hasher.finish());
+
+        for _ in 0..DEPOSIT_ATTEMPTS {
+            let x = rng.gen_range(0..CHUNK_TILE_LENGTH);
+            let y = rng.gen_range(0..CHUNK_TILE_LENGTH);
+
+            let position = Vec2::new(
+                (coords.0 + x * TILE_SIZE + TILE_SIZE / 2) as f32,
+                (coords.1 + y * TILE_SIZE + TILE_SIZE / 2) as f32,
+            );
+            let cell = NavGrid::cell_of(position);
+
+            if elevation.terrain(cell) != Terrain::Flat {
+                continue;
+            }
+
+            let Some(kind) = OreKind::roll(elevation.level(cell), &mut rng) else {
+                continue;
+            };
+
+            let sprite = SpriteBundle {
+                sprite: Sprite {
+                    color: kind.color(),
+                    custom_size: Some(Vec2::splat(DEPOSIT_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.extend(0.3)),
+                ..default()
+            };
+
+            commands
+                .spawn(sprite)
+                .insert(OreDeposit { kind })
+                .insert(Prop)
+                .insert(PropOwner(entity))
+                .insert(ChunkOccupant(OccupantKind::Decoration));
+        }
+    }
+}
+
+// Swinging at a deposit in reach starts harvesting it, better pickaxes than needed mine faster
+pub fn mine_deposits(
+    mut swings: EventReader<Swing>,
+    registry: Res<ItemRegistry>,
+    player: Query<&Equipment, With<Player>>,
+    deposits: Query<(Entity, &Transform, &OreDeposit)>,
+    mut channels: EventWriter<StartChannel>,
+) {
+    let Ok(equipment) = player.get_single() else {
+        return;
+    };
+
+    let tier = pickaxe_tier(equipment, &registry);
+
+    for swing in swings.read() {
+        for (entity, transform, deposit) in deposits.iter() {
+            if !in_reach(
+                swing.position,
+                swing.facing,
+                transform.translation.truncate(),
+            ) {
+                continue;
+            }
+
+            if tier < deposit.kind.tier() {
+                info!(
+                    "Mining {:?} ore needs a tier {} pickaxe",
+                    deposit.kind,
+                    deposit.kind.tier()
+                );
+                continue;
+            }
+
+            channels.send(StartChannel {
+                action: ChannelAction::Harvest(entity),
+                duration: Duration::from_secs_f32(
+                    strikes(deposit.kind, tier) as f32 * STRIKE_SECONDS,
+                ),
+            });
+        }
+    }
+}
+
+// A finished harvest mines the deposit out, wearing the pickaxe once per strike
+pub fn harvest_deposits(
+    mut commands: Commands,
+    mut finished: EventReader<ChannelFinished>,
+    registry: Res<ItemRegistry>,
+    player: Query<&Equipment, With<Player>>,
+    deposits: Query<(&Transform, &OreDeposit)>,
+    mut drops: EventWriter<DropItem>,
+    mut wear: EventWriter<WearEquipment>,
+) {
+    let Ok(equipment) = player.get_single() else {
+        return;
+    };
+
+    for ChannelFinished(action) in finished.read() {
+        let ChannelAction::Harvest(entity) = action else {
+            continue;
+        };
+
+        // The deposit's chunk may have unloaded while it was harvested
+        let Ok((transform, deposit)) = deposits.get(*entity) else {
+            continue;
+        };
+
+        info!("Mined out {:?} ore", deposit.kind);
+
+        if equipment.main_hand.is_some() {
+            wear.send(WearEquipment {
+                slot: EquipSlot::MainHand,
+                amount: strikes(deposit.kind, pickaxe_tier(equipment, &registry)),
+            });
+        }
+
+        drops.send(DropItem {
+            stack: Stack::new(deposit.kind.item(), DEPOSIT_YIELD),
+            position: transform.translation.truncate(),
+        });
+
+        commands.entity(*entity).despawn_recursive();
+    }
+}
+
+fn pickaxe_tier(equipment: &Equipment, registry: &ItemRegistry) -> u8 {
+    equipment
+        .main_hand
+        .map(|stack| registry.get(stack.id).tool_tier)
+        .unwrap_or(0)
+}
+
+// Each tier above the one needed adds a strike's worth of progress
+fn strikes(kind: OreKind, tier: u8) -> u32 {
+    let strength = 1 + tier - kind.tier();
+    kind.hardness().div_ceil(strength) as u32
+}
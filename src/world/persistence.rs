@@ -0,0 +1,157 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use super::{
+    io::{ChunksSaved, RegionIo, RegionRead},
+    region::locate,
+    ChunkCoords, TileBundle, TileSource, CHUNK_SIZE, CHUNK_TILE_LENGTH, TILE_SIZE,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RegionState {
+    Loading,
+    Loaded,
+}
+
+pub enum SavedChunk {
+    Saved(Vec<u8>),
+    // The chunk's region is still being read, generation waits rather than collapsing over a save
+    Loading,
+    Missing,
+}
+
+// Saved chunks read back from the region files, consulted before a chunk is collapsed from scratch.
+// Only regions around the camera are kept, chunks still waiting to be written are answered by RegionIo
+#[derive(Resource, Default)]
+pub struct SavedChunks {
+    regions: HashMap<(i64, i64), RegionState>,
+    chunks: HashMap<ChunkCoords, Vec<u8>>,
+    // Set once the schematic is hot reloaded, saves made under the old rules are no longer restored
+    ignore_disk: bool,
+}
+
+impl SavedChunks {
+    // The first lookup in a region reads the whole region, neighbors are usually generated next
+    pub fn take(&mut self, coords: ChunkCoords, io: &mut RegionIo) -> SavedChunk {
+        // Writes still queued or in flight are newer than anything read from disk. Read chunks are
+        // kept with their region, unchanged chunks aren't written again when they unload
+        let unsaved = io.unsaved(coords);
+
+        if let Some(tiles) = unsaved.or_else(|| self.chunks.get(&coords).cloned()) {
+            let length = (CHUNK_TILE_LENGTH * CHUNK_TILE_LENGTH) as usize;
+
+            if tiles.len() == length {
+                return SavedChunk::Saved(tiles);
+            }
+
+            warn!(
+                "Discarding saved chunk ({}, {}) with {} tiles",
+                coords.0,
+                coords.1,
+                tiles.len()
+            );
+            return SavedChunk::Missing;
+        }
+
+        if self.ignore_disk {
+            return SavedChunk::Missing;
+        }
+
+        let (region, _) = locate(coords);
+
+        match self.regions.get(&region) {
+            Some(RegionState::Loaded) => SavedChunk::Missing,
+            Some(RegionState::Loading) => SavedChunk::Loading,
+            None => {
+                debug!("Reading saved region ({}, {})", region.0, region.1);
+
+                io.request_region(region);
+                self.regions.insert(region, RegionState::Loading);
+                SavedChunk::Loading
+            }
+        }
+    }
+
+    // Chunks unloaded from here on are still answered while waiting to be written, they were
+    // generated under the new rules
+    pub fn forget(&mut self) {
+        self.chunks.clear();
+        self.ignore_disk = true;
+    }
+
+    // Drops regions with no chunk left in range, they are read again if the camera comes back
+    pub fn evict(&mut self, in_range: &[ChunkCoords]) {
+        let regions: HashSet<(i64, i64)> =
+            in_range.iter().map(|coords| locate(*coords).0).collect();
+
+        self.regions.retain(|region, _| regions.contains(region));
+        self.chunks
+            .retain(|coords, _| regions.contains(&locate(*coords).0));
+    }
+}
+
+// Rebuilds interior tiles from saved column major ids, mirroring the layout of a fresh collapse
+pub fn saved_tile_bundles(tiles: &[u8], atlas_handle: &Handle<TextureAtlas>) -> Vec<TileBundle> {
+    let length = CHUNK_TILE_LENGTH as usize;
+
+    tiles
+        .iter()
+        .enumerate()
+        .map(|(index, tile_id)| {
+            let x = (index / length) as f32;
+            let y = (index % length) as f32;
+
+            let x_rel = (x * TILE_SIZE as f32) + (TILE_SIZE as f32 / 2.) - (CHUNK_SIZE as f32 / 2.);
+            let y_rel = (y * TILE_SIZE as f32) + (TILE_SIZE as f32 / 2.) - (CHUNK_SIZE as f32 / 2.);
+
+            // Only collapsed interiors are saved, seams are stitched again on load
+            TileBundle::new(
+                atlas_handle.clone(),
+                *tile_id,
+                TileSource::Collapsed,
+                x_rel,
+                y_rel,
+            )
+        })
+        .collect()
+}
+
+pub fn receive_saved_regions(
+    mut saved: ResMut<SavedChunks>,
+    mut region_reads: EventReader<RegionRead>,
+) {
+    for read in region_reads.read() {
+        debug!(
+            "Read {} saved chunks from region ({}, {})",
+            read.chunks.len(),
+            read.region.0,
+            read.region.1
+        );
+
+        // The region left range while it was being read
+        if !saved.regions.contains_key(&read.region) {
+            continue;
+        }
+
+        // Chunks written while the read was in flight are newer than what was on disk
+        if !saved.ignore_disk {
+            for (coords, tiles) in read.chunks.iter() {
+                saved.chunks.entry(*coords).or_insert_with(|| tiles.clone());
+            }
+        }
+
+        saved.regions.insert(read.region, RegionState::Loaded);
+    }
+}
+
+// Written chunks leave the write queue, regions already read keep them up to date in their place
+pub fn receive_saved_chunks(mut saved: ResMut<SavedChunks>, mut written: EventReader<ChunksSaved>) {
+    for batch in written.read() {
+        for (coords, tiles) in batch.chunks.iter() {
+            if saved.regions.contains_key(&locate(*coords).0) {
+                saved.chunks.insert(*coords, tiles.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::fs;
+
+use bevy::{app::AppExit, prelude::*};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::Dead,
+    mobs::spawner::{Spawner, SpawnerDestroyed},
+    ui::ticker::{EventCategory, WorldEvent},
+};
+
+use super::{Chunk, ChunkCoords, PropOwner, WorldTick, TICKS_PER_SECOND};
+
+const POI_SAVE_PATH: &str = "saves/pois.json";
+
+// An in-game day is twenty real minutes
+const TICKS_PER_DAY: u64 = (TICKS_PER_SECOND as u64) * 60 * 20;
+
+// Cleared camps are repopulated after this many in-game days
+const REPOPULATE_DAYS: u64 = 7;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+struct ClearedPoi {
+    chunk: (i64, i64),
+    // Counted down rather than stored as a tick so it survives the world tick restarting
+    remaining_ticks: u64,
+}
+
+// Points of interest whose spawners have all been destroyed, persisted with the world
+#[derive(Resource, Default, Deserialize, Serialize)]
+pub struct ConqueredPois {
+    cleared: Vec<ClearedPoi>,
+    #[serde(skip)]
+    last_tick: u64,
+}
+
+impl ConqueredPois {
+    pub fn load() -> ConqueredPois {
+        fs::read(POI_SAVE_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let written = fs::create_dir_all("saves").and_then(|_| {
+            fs::write(
+                POI_SAVE_PATH,
+                serde_json::to_vec(self).map_err(std::io::Error::from)?,
+            )
+        });
+
+        if let Err(err) = written {
+            warn!("Failed to save points of interest! Err {err}");
+        }
+    }
+
+    pub(super) fn is_cleared(&self, coords: &ChunkCoords) -> bool {
+        self.cleared
+            .iter()
+            .any(|poi| poi.chunk == (coords.0, coords.1))
+    }
+}
+
+// Marks the entity standing in for a point of interest, shown differently once cleared
+#[derive(Component)]
+pub struct PoiMarker {
+    pub cleared: bool,
+}
+
+// Cleared camps are greyed out so they read as safe at a glance
+pub fn tint_poi_icons(mut markers: Query<(&PoiMarker, &mut Sprite), Added<PoiMarker>>) {
+    for (marker, mut sprite) in markers.iter_mut() {
+        if marker.cleared {
+            sprite.color = Color::rgb(0.35, 0.35, 0.35);
+        }
+    }
+}
+
+pub fn load_conquered_pois(mut commands: Commands) {
+    commands.insert_resource(ConqueredPois::load());
+}
+
+// Camps are keyed by the chunk holding their center, wherever their spawners stand
+pub fn record_cleared_pois(
+    mut destroyed: EventReader<SpawnerDestroyed>,
+    mut pois: ResMut<ConqueredPois>,
+    mut events: EventWriter<WorldEvent>,
+    chunks: Query<&Transform, With<Chunk>>,
+    standing: Query<&PropOwner, (With<Spawner>, Without<Dead>)>,
+) {
+    let mut changed = false;
+
+    for event in destroyed.read() {
+        let Some(chunk) = event.camp.and_then(|camp| chunks.get(camp).ok()) else {
+            continue;
+        };
+
+        let coords = ChunkCoords::from(chunk.translation);
+
+        // A camp is only cleared once its last nest or portal falls
+        if pois.is_cleared(&coords)
+            || standing
+                .iter()
+                .any(|owner| Some(owner.chunk()) == event.camp)
+        {
+            continue;
+        }
+
+        info!(
+            "Point of interest cleared in chunk ({}, {})",
+            coords.0, coords.1
+        );
+        events.send(WorldEvent::new(
+            EventCategory::Camps,
+            "A camp has been cleared",
+        ));
+
+        pois.cleared.push(ClearedPoi {
+            chunk: (coords.0, coords.1),
+            remaining_ticks: TICKS_PER_DAY * REPOPULATE_DAYS,
+        });
+        changed = true;
+    }
+
+    if changed {
+        pois.save();
+    }
+}
+
+pub fn repopulate_pois(
+    world_tick: Res<WorldTick>,
+    mut pois: ResMut<ConqueredPois>,
+    mut events: EventWriter<WorldEvent>,
+) {
+    let elapsed = world_tick.tick.saturating_sub(pois.last_tick);
+    if elapsed == 0 {
+        return;
+    }
+
+    pois.last_tick = world_tick.tick;
+
+    for poi in pois.cleared.iter_mut() {
+        poi.remaining_ticks = poi.remaining_ticks.saturating_sub(elapsed);
+    }
+
+    pois.cleared.retain(|poi| {
+        if poi.remaining_ticks == 0 {
+            info!(
+                "Point of interest in chunk ({}, {}) repopulated",
+                poi.chunk.0, poi.chunk.1
+            );
+            events.send(WorldEvent::new(
+                EventCategory::Camps,
+                "Enemies have returned to a cleared camp",
+            ));
+        }
+
+        poi.remaining_ticks > 0
+    });
+}
+
+pub fn save_pois_on_exit(exit: EventReader<AppExit>, pois: Res<ConqueredPois>) {
+    if !exit.is_empty() {
+        pois.save();
+    }
+}
@@ -0,0 +1,263 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::PathBuf,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    player::Player,
+    ui::console::{ConsoleCommand, ConsoleOutput},
+};
+
+use super::{
+    get_chunk_at,
+    io::{ChunkRead, RegionIo, RegionRead},
+    ChunkCoords, CHUNK_SIZE, TILE_SIZE,
+};
+
+// Chunks along each side of a region file
+pub(super) const REGION_LENGTH: i64 = 16;
+const REGION_CHUNKS: usize = (REGION_LENGTH * REGION_LENGTH) as usize;
+
+const MAGIC: &[u8; 4] = b"TRRG";
+const VERSION: u32 = 1;
+
+// Magic and version followed by an offset and length per chunk, zero length marks a missing chunk
+const HEADER_SIZE: usize = 8 + REGION_CHUNKS * 8;
+
+// Sent when a chunk leaves range with its collapsed tile ids in column major order
+#[derive(Event)]
+pub struct ChunkUnloaded {
+    pub coords: ChunkCoords,
+    pub tiles: Vec<u8>,
+    // Whether the tiles differ from what was saved or queued for saving before
+    pub changed: bool,
+}
+
+// Saved chunks grouped into one file per region, keeps large worlds from creating a file per chunk
+#[derive(Clone)]
+pub struct RegionStore {
+    directory: PathBuf,
+}
+
+impl Default for RegionStore {
+    fn default() -> Self {
+        RegionStore {
+            directory: PathBuf::from("saves/regions"),
+        }
+    }
+}
+
+impl RegionStore {
+    // Each region touched by the batch is read and rewritten once
+    pub fn write_chunks(&self, chunks: &[(ChunkCoords, Vec<u8>)]) -> io::Result<()> {
+        let mut regions: Vec<(i64, i64)> =
+            chunks.iter().map(|(coords, _)| locate(*coords).0).collect();
+        regions.sort();
+        regions.dedup();
+
+        fs::create_dir_all(&self.directory)?;
+
+        for region in regions {
+            let mut entries = self.read_entries(region)?;
+
+            for (coords, data) in chunks {
+                let (chunk_region, slot) = locate(*coords);
+
+                if chunk_region == region {
+                    entries[slot] = Some(data.clone());
+                }
+            }
+
+            // Written aside and renamed over so a crash never leaves a torn region
+            let path = self.path(region);
+            let staging = path.with_extension("tmp");
+            fs::write(&staging, encode(&entries))?;
+            fs::rename(staging, path)?;
+        }
+
+        Ok(())
+    }
+
+    // Reads only the header and the requested chunk
+    pub fn read_chunk(&self, coords: ChunkCoords) -> io::Result<Option<Vec<u8>>> {
+        let (region, slot) = locate(coords);
+
+        let mut file = match File::open(self.path(region)) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let mut header = vec![0; HEADER_SIZE];
+        file.read_exact(&mut header)?;
+
+        let Some((offset, length)) = index_entry(&header, slot)? else {
+            return Ok(None);
+        };
+
+        let mut data = vec![0; length];
+        file.seek(SeekFrom::Start(offset as u64))?;
+        file.read_exact(&mut data)?;
+
+        Ok(Some(data))
+    }
+
+    // Every saved chunk of a region in one read, used by views covering many chunks at once
+    pub fn read_region(&self, region: (i64, i64)) -> io::Result<Vec<(ChunkCoords, Vec<u8>)>> {
+        let entries = self.read_entries(region)?;
+
+        Ok(entries
+            .into_iter()
+            .enumerate()
+            .filter_map(|(slot, data)| data.map(|data| (chunk_of(region, slot), data)))
+            .collect())
+    }
+
+    fn read_entries(&self, region: (i64, i64)) -> io::Result<Vec<Option<Vec<u8>>>> {
+        match fs::read(self.path(region)) {
+            Ok(bytes) => decode(&bytes),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(vec![None; REGION_CHUNKS]),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn path(&self, region: (i64, i64)) -> PathBuf {
+        self.directory
+            .join(format!("r.{}.{}.bin", region.0, region.1))
+    }
+}
+
+// Region and slot within it holding the chunk
+pub fn locate(coords: ChunkCoords) -> ((i64, i64), usize) {
+    let x = (coords.0 + TILE_SIZE) / (CHUNK_SIZE + TILE_SIZE);
+    let y = (coords.1 + TILE_SIZE) / (CHUNK_SIZE + TILE_SIZE);
+
+    let region = (x.div_euclid(REGION_LENGTH), y.div_euclid(REGION_LENGTH));
+    let slot = x.rem_euclid(REGION_LENGTH) * REGION_LENGTH + y.rem_euclid(REGION_LENGTH);
+
+    (region, slot as usize)
+}
+
+fn chunk_of(region: (i64, i64), slot: usize) -> ChunkCoords {
+    let x = region.0 * REGION_LENGTH + slot as i64 / REGION_LENGTH;
+    let y = region.1 * REGION_LENGTH + slot as i64 % REGION_LENGTH;
+
+    ChunkCoords(
+        x * (CHUNK_SIZE + TILE_SIZE) - TILE_SIZE,
+        y * (CHUNK_SIZE + TILE_SIZE) - TILE_SIZE,
+    )
+}
+
+fn encode(entries: &[Option<Vec<u8>>]) -> Vec<u8> {
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    let mut body = Vec::new();
+
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&VERSION.to_le_bytes());
+
+    for entry in entries {
+        let (offset, length) = match entry {
+            Some(data) => {
+                let offset = HEADER_SIZE + body.len();
+                body.extend_from_slice(data);
+                (offset as u32, data.len() as u32)
+            }
+            None => (0, 0),
+        };
+
+        header.extend_from_slice(&offset.to_le_bytes());
+        header.extend_from_slice(&length.to_le_bytes());
+    }
+
+    header.extend(body);
+    header
+}
+
+fn decode(bytes: &[u8]) -> io::Result<Vec<Option<Vec<u8>>>> {
+    if bytes.len() < HEADER_SIZE {
+        return Err(invalid("Region file is shorter than its header"));
+    }
+
+    (0..REGION_CHUNKS)
+        .map(|slot| match index_entry(bytes, slot)? {
+            Some((offset, length)) => bytes
+                .get(offset..offset + length)
+                .map(|data| Some(data.to_vec()))
+                .ok_or_else(|| invalid("Region chunk lies outside of the file")),
+            None => Ok(None),
+        })
+        .collect()
+}
+
+fn index_entry(header: &[u8], slot: usize) -> io::Result<Option<(usize, usize)>> {
+    if &header[0..4] != MAGIC {
+        return Err(invalid("Not a region file"));
+    }
+
+    let version = u32::from_le_bytes(header[4..8].try_into().unwrap_or_default());
+    if version != VERSION {
+        return Err(invalid("Unsupported region file version"));
+    }
+
+    let start = 8 + slot * 8;
+    let offset = u32::from_le_bytes(header[start..start + 4].try_into().unwrap_or_default());
+    let length = u32::from_le_bytes(header[start + 4..start + 8].try_into().unwrap_or_default());
+
+    if length == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some((offset as usize, length as usize)))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// `region` reports what has been saved around the player once the reads complete
+pub fn handle_region_commands(
+    mut io: ResMut<RegionIo>,
+    mut commands: EventReader<ConsoleCommand>,
+    player: Query<&Transform, With<Player>>,
+) {
+    for command in commands.read() {
+        if command.name != "region" {
+            continue;
+        }
+
+        let Ok(transform) = player.get_single() else {
+            continue;
+        };
+
+        let coords = get_chunk_at((transform.translation.x, transform.translation.y));
+        io.request_region(locate(coords).0);
+        io.request_chunk(coords);
+    }
+}
+
+pub fn report_region_reads(
+    mut chunk_reads: EventReader<ChunkRead>,
+    mut region_reads: EventReader<RegionRead>,
+    mut output: EventWriter<ConsoleOutput>,
+) {
+    for read in region_reads.read() {
+        output.send(ConsoleOutput(format!(
+            "Region ({}, {}): {} chunks saved",
+            read.region.0,
+            read.region.1,
+            read.chunks.len()
+        )));
+    }
+
+    for read in chunk_reads.read() {
+        output.send(ConsoleOutput(format!(
+            "Chunk ({}, {}) saved: {}",
+            read.coords.0,
+            read.coords.1,
+            read.tiles.is_some()
+        )));
+    }
+}
@@ -0,0 +1,117 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ui::console::{ConsoleCommand, ConsoleOutput};
+
+const RULES_PATH: &str = "saves/rules.json";
+
+// Gameplay toggles chosen when the world is created and stored with it
+#[derive(Resource, Clone, Debug, Deserialize, Serialize)]
+pub struct WorldRules {
+    pub keep_inventory: bool,
+    pub enemy_spawning: bool,
+}
+
+impl Default for WorldRules {
+    fn default() -> Self {
+        WorldRules {
+            keep_inventory: false,
+            enemy_spawning: true,
+        }
+    }
+}
+
+impl WorldRules {
+    // A world without saved rules is new, its rules are written out immediately
+    pub fn load() -> WorldRules {
+        let saved = fs::read(RULES_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+
+        saved.unwrap_or_else(|| {
+            info!("Creating world rules");
+
+            let rules = WorldRules::default();
+            rules.save();
+            rules
+        })
+    }
+
+    pub fn save(&self) {
+        let written = fs::create_dir_all("saves").and_then(|_| {
+            fs::write(
+                RULES_PATH,
+                serde_json::to_vec_pretty(self).map_err(std::io::Error::from)?,
+            )
+        });
+
+        if let Err(err) = written {
+            warn!("Failed to save world rules! Err {err}");
+        }
+    }
+
+    fn entries(&self) -> [(&'static str, bool); 2] {
+        [
+            ("keep_inventory", self.keep_inventory),
+            ("enemy_spawning", self.enemy_spawning),
+        ]
+    }
+
+    fn rule_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "keep_inventory" => Some(&mut self.keep_inventory),
+            "enemy_spawning" => Some(&mut self.enemy_spawning),
+            _ => None,
+        }
+    }
+}
+
+pub fn load_world_rules(mut commands: Commands) {
+    commands.insert_resource(WorldRules::load());
+}
+
+// `rule` lists every rule, `rule <name> <on|off>` changes one
+pub fn handle_rule_commands(
+    mut commands: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut rules: ResMut<WorldRules>,
+) {
+    for command in commands.read() {
+        if command.name != "rule" {
+            continue;
+        }
+
+        match command.args.as_slice() {
+            [] => {
+                for (name, enabled) in rules.entries() {
+                    output.send(ConsoleOutput(format!("{name}: {enabled}")));
+                }
+            }
+            [name, value] => {
+                let value = match value.as_str() {
+                    "on" | "true" => true,
+                    "off" | "false" => false,
+                    _ => {
+                        output.send(ConsoleOutput(format!(
+                            "Invalid value {value}, use on or off"
+                        )));
+                        continue;
+                    }
+                };
+
+                let Some(rule) = rules.rule_mut(name) else {
+                    output.send(ConsoleOutput(format!("Unknown rule {name}")));
+                    continue;
+                };
+
+                *rule = value;
+                rules.save();
+                output.send(ConsoleOutput(format!("{name} set to {value}")));
+            }
+            _ => output.send(ConsoleOutput("Usage: rule [<name> <on|off>]".to_string())),
+        }
+    }
+}
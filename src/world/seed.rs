@@ -0,0 +1,58 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+const SEED_PATH: &str = "saves/seed.json";
+
+// Seed used before worlds could be configured, kept so existing saves regenerate the same terrain
+const DEFAULT_SEED: u64 = 42;
+
+// Seed every chunk's generation is derived from, shared worlds reproduce exactly given the same seed
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldSeed(pub u64);
+
+impl WorldSeed {
+    // A `--seed <n>` launch argument picks the seed and is remembered, otherwise the saved one is reused
+    pub fn load(args: &[String]) -> WorldSeed {
+        let requested = args
+            .windows(2)
+            .find(|pair| pair[0] == "--seed")
+            .map(|pair| pair[1].parse::<u64>());
+
+        match requested {
+            Some(Ok(seed)) => {
+                let seed = WorldSeed(seed);
+                seed.save();
+                return seed;
+            }
+            Some(Err(err)) => warn!("Ignoring invalid --seed! Err {err}"),
+            None => {}
+        }
+
+        fs::read(SEED_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .map(WorldSeed)
+            .unwrap_or(WorldSeed(DEFAULT_SEED))
+    }
+
+    // Fresh seed for a new game, remembered like one passed on launch
+    pub fn random() -> WorldSeed {
+        let seed = WorldSeed(rand::random());
+        seed.save();
+        seed
+    }
+
+    fn save(&self) {
+        let written = fs::create_dir_all("saves").and_then(|_| {
+            fs::write(
+                SEED_PATH,
+                serde_json::to_vec(&self.0).map_err(std::io::Error::from)?,
+            )
+        });
+
+        if let Err(err) = written {
+            warn!("Failed to save world seed! Err {err}");
+        }
+    }
+}
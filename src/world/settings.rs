@@ -0,0 +1,56 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_PATH: &str = "world_settings.json";
+
+// Chunks in each direction of the player, wider rings generate more chunks per step
+const MAX_RENDER_DISTANCE: i8 = 6;
+
+#[derive(Resource, Clone, Debug, Deserialize, Serialize)]
+pub struct WorldSettings {
+    pub render_distance: i8,
+}
+
+impl Default for WorldSettings {
+    fn default() -> Self {
+        WorldSettings { render_distance: 2 }
+    }
+}
+
+impl WorldSettings {
+    // Falls back to defaults when no settings have been saved yet
+    pub fn load() -> WorldSettings {
+        fs::read(SETTINGS_PATH)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<WorldSettings>(&bytes).ok())
+            .map(|mut settings| {
+                settings.render_distance = settings.render_distance.clamp(1, MAX_RENDER_DISTANCE);
+                settings
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        match serde_json::to_vec_pretty(self) {
+            Ok(bytes) => {
+                if let Err(err) = fs::write(SETTINGS_PATH, bytes) {
+                    warn!("Failed to save world settings! Err {err}");
+                }
+            }
+            Err(err) => warn!("Failed to serialize world settings! Err {err}"),
+        }
+    }
+
+    // Chunks are only removed once outside of this distance so that jitter on a chunk boundary doesn't thrash
+    pub fn unload_distance(&self) -> i8 {
+        self.render_distance + 1
+    }
+
+    // Steps up to the maximum, then wraps back around to a single ring
+    pub fn cycle_render_distance(&mut self) {
+        self.render_distance = self.render_distance % MAX_RENDER_DISTANCE + 1;
+    }
+}
@@ -0,0 +1,129 @@
+use std::{collections::HashMap, fs};
+
+use bevy::prelude::*;
+
+use travelers_worldgen::{
+    get_chunk_at,
+    schematic::Schematic,
+    structure::{prefab_origins, prefab_tiles, Prefab, StructureDefinition},
+    ChunkCoords, CHUNK_TILE_LENGTH, TILE_SIZE,
+};
+
+const STRUCTURES_PATH: &str = "assets/structures.json";
+
+#[derive(Resource, Default)]
+pub struct StructureDefinitions(HashMap<String, StructureDefinition>);
+
+impl StructureDefinitions {
+    pub fn load() -> StructureDefinitions {
+        let definitions = fs::read(STRUCTURES_PATH)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|err| err.to_string()));
+
+        match definitions {
+            Ok(definitions) => StructureDefinitions(definitions),
+            Err(err) => {
+                warn!(
+                    "Failed to load structure definitions, structures will be unguarded! Err {err}"
+                );
+                StructureDefinitions::default()
+            }
+        }
+    }
+
+    // Structures with a layout, in name order so every run picks between them the same way
+    fn prefab_names(&self) -> Vec<&String> {
+        let mut names: Vec<&String> = self
+            .0
+            .iter()
+            .filter(|(_, definition)| !definition.layout.is_empty())
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+        names
+    }
+
+    // Names missing from the schematic are left to the collapse like empty ones
+    pub fn prefabs(&self, schematic: &Schematic) -> Vec<Prefab> {
+        let names = self.prefab_names();
+
+        let id = |name: &String| {
+            schematic
+                .tiles
+                .iter()
+                .find(|(_, tile)| tile.name == *name)
+                .map(|(idx, _)| *idx)
+        };
+
+        names
+            .into_iter()
+            .map(|name| {
+                let definition = &self.0[name];
+
+                Prefab {
+                    tiles: definition
+                        .layout
+                        .iter()
+                        .map(|row| row.iter().map(id).collect())
+                        .collect(),
+                    chance: definition.chance,
+                }
+            })
+            .collect()
+    }
+
+    // Tiles prefabs pin in the chunk and its seams, counted from the bottom left of its interior
+    pub fn pinned(
+        &self,
+        seed: u64,
+        schematic: &Schematic,
+        coords: ChunkCoords,
+    ) -> Vec<(IVec2, u8)> {
+        let origin = IVec2::new(
+            coords.0.div_euclid(TILE_SIZE) as i32,
+            coords.1.div_euclid(TILE_SIZE) as i32,
+        );
+
+        prefab_tiles(
+            seed,
+            &self.prefabs(schematic),
+            origin - IVec2::ONE,
+            origin + IVec2::splat(CHUNK_TILE_LENGTH as i32 + 1),
+        )
+        .into_iter()
+        .map(|(cell, tile)| (cell - origin, tile))
+        .collect()
+    }
+
+    // Prefabs whose center falls in the chunk, with that center in world coordinates. Each prefab
+    // has its center in exactly one chunk however many it overlaps
+    pub fn centered_in(
+        &self,
+        seed: u64,
+        schematic: &Schematic,
+        coords: ChunkCoords,
+    ) -> Vec<(&StructureDefinition, Vec2)> {
+        let origin = IVec2::new(
+            coords.0.div_euclid(TILE_SIZE) as i32,
+            coords.1.div_euclid(TILE_SIZE) as i32,
+        );
+
+        let names = self.prefab_names();
+        let prefabs = self.prefabs(schematic);
+
+        prefab_origins(
+            seed,
+            &prefabs,
+            origin,
+            origin + IVec2::splat(CHUNK_TILE_LENGTH as i32 + 1),
+        )
+        .into_iter()
+        .filter_map(|(index, prefab_origin)| {
+            let center =
+                (prefab_origin.as_vec2() + prefabs[index].size().as_vec2() / 2.) * TILE_SIZE as f32;
+
+            (get_chunk_at((center.x, center.y)) == coords).then(|| (&self.0[names[index]], center))
+        })
+        .collect()
+    }
+}
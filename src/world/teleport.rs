@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::Dirty,
+    items::wallet::Wallet,
+    player::Player,
+    ui::transition::{ScreenTransition, TransitionKind},
+};
+
+use super::{get_chunks_in_range, Chunk, Movement, SpawnSafely, WorldSettings};
+
+// Moves the player once the chunk ring around the destination is generated and stitched
+#[derive(Event)]
+pub struct TeleportRequest {
+    pub destination: Vec2,
+    // Coins charged for fast travel, the request is refused if the wallet can't cover it
+    pub fee: u64,
+}
+
+#[derive(Resource, Default)]
+pub struct PendingTeleport(pub Option<Vec2>);
+
+#[derive(Component)]
+pub struct LoadingSpinner;
+
+pub fn begin_teleport(
+    mut commands: Commands,
+    mut requests: EventReader<TeleportRequest>,
+    mut pending: ResMut<PendingTeleport>,
+    mut wallet: ResMut<Wallet>,
+    spinners: Query<Entity, With<LoadingSpinner>>,
+) {
+    if let Some(request) = requests.read().last() {
+        if !wallet.try_spend(request.fee) {
+            warn!(
+                "Cannot afford fast travel fee of {} coins, have {}",
+                request.fee, wallet.coins
+            );
+            return;
+        }
+
+        info!(
+            "Pregenerating teleport destination: ({}, {})",
+            request.destination.x, request.destination.y
+        );
+
+        pending.0 = Some(request.destination);
+
+        if spinners.is_empty() {
+            let spinner = NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(24.),
+                    height: Val::Px(24.),
+                    left: Val::Percent(50.),
+                    top: Val::Percent(50.),
+                    ..default()
+                },
+                background_color: Color::rgba(1., 1., 1., 0.8).into(),
+                ..default()
+            };
+
+            commands.spawn(spinner).insert(LoadingSpinner);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn complete_teleport(
+    mut commands: Commands,
+    mut pending: ResMut<PendingTeleport>,
+    chunks: Query<(&Transform, &Dirty), With<Chunk>>,
+    mut player: Query<&mut Transform, (With<Player>, Without<Chunk>)>,
+    player_entity: Query<Entity, With<Player>>,
+    spinners: Query<Entity, With<LoadingSpinner>>,
+    settings: Res<WorldSettings>,
+    transition: Option<ResMut<ScreenTransition>>,
+) {
+    let Some(destination) = pending.0 else {
+        return;
+    };
+
+    // Wait for every chunk in range of the destination to be generated and stitched
+    let ready = get_chunks_in_range((destination.x, destination.y), settings.render_distance)
+        .iter()
+        .all(|coords| {
+            chunks
+                .iter()
+                .any(|(transform, dirty)| *coords == transform.translation && !dirty.stitch)
+        });
+
+    if !ready {
+        return;
+    }
+
+    // The jump happens behind a covered screen, headless apps without a ui move straight away
+    if let Some(mut transition) = transition {
+        if !transition.is_covered(TransitionKind::Teleport) {
+            transition.start(TransitionKind::Teleport);
+            return;
+        }
+    }
+
+    if let Ok(mut transform) = player.get_single_mut() {
+        info!("Teleporting to ({}, {})", destination.x, destination.y);
+        transform.translation.x = destination.x;
+        transform.translation.y = destination.y;
+    }
+
+    // Destinations are picked blind, the player is moved out of any rock or water they land in
+    if let Ok(entity) = player_entity.get_single() {
+        commands
+            .entity(entity)
+            .insert(SpawnSafely(Movement::Ground));
+    }
+
+    pending.0 = None;
+
+    for entity in spinners.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+pub fn spin_loading_spinner(
+    time: Res<Time>,
+    mut spinners: Query<&mut Transform, With<LoadingSpinner>>,
+) {
+    for mut transform in spinners.iter_mut() {
+        transform.rotate_z(time.delta_seconds() * 4.);
+    }
+}
@@ -0,0 +1,98 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::prelude::*;
+
+use super::{cache::ChunkCache, Chunk, ChunkCoords};
+
+// Off-screen state such as crops and respawns advances in world ticks rather than frames
+pub(super) const TICKS_PER_SECOND: f32 = 4.;
+
+#[derive(Resource)]
+pub struct WorldTick {
+    pub tick: u64,
+    timer: Timer,
+    last_seen: HashMap<ChunkCoords, u64>,
+}
+
+impl Default for WorldTick {
+    fn default() -> Self {
+        WorldTick {
+            tick: 0,
+            timer: Timer::new(
+                Duration::from_secs_f32(1. / TICKS_PER_SECOND),
+                TimerMode::Repeating,
+            ),
+            last_seen: HashMap::new(),
+        }
+    }
+}
+
+// Sent when a chunk is loaded again so its state can be advanced by the ticks it missed
+#[derive(Event)]
+pub struct ChunkCatchUp {
+    pub chunk: Entity,
+    pub elapsed_ticks: u64,
+}
+
+impl ChunkCatchUp {
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs_f32(self.elapsed_ticks as f32 / TICKS_PER_SECOND)
+    }
+}
+
+pub fn advance_world_tick(
+    time: Res<Time>,
+    mut world_tick: ResMut<WorldTick>,
+    cache: Res<ChunkCache>,
+    chunks: Query<&Transform, With<Chunk>>,
+) {
+    world_tick.timer.tick(time.delta());
+
+    let elapsed = world_tick.timer.times_finished_this_tick() as u64;
+    if elapsed == 0 {
+        return;
+    }
+
+    world_tick.tick += elapsed;
+
+    // Loaded chunks are up to date, remember when they were last seen for catch-up on reload
+    let tick = world_tick.tick;
+    for transform in chunks.iter() {
+        world_tick
+            .last_seen
+            .insert(ChunkCoords::from(transform.translation), tick);
+    }
+
+    // Only chunks the cache still holds are caught up, the rest are restored or collapsed anew and
+    // would otherwise be remembered for good
+    let loaded: Vec<ChunkCoords> = chunks
+        .iter()
+        .map(|transform| ChunkCoords::from(transform.translation))
+        .collect();
+
+    world_tick
+        .last_seen
+        .retain(|coords, _| loaded.contains(coords) || cache.contains(coords));
+}
+
+pub fn catch_up_loaded_chunks(
+    world_tick: Res<WorldTick>,
+    chunks: Query<(Entity, &Transform), Added<Chunk>>,
+    mut catch_up: EventWriter<ChunkCatchUp>,
+) {
+    for (entity, transform) in chunks.iter() {
+        if let Some(last_seen) = world_tick
+            .last_seen
+            .get(&ChunkCoords::from(transform.translation))
+        {
+            let elapsed_ticks = world_tick.tick - last_seen;
+
+            if elapsed_ticks > 0 {
+                catch_up.send(ChunkCatchUp {
+                    chunk: entity,
+                    elapsed_ticks,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use rand::{Rng, SeedableRng};
+
+use crate::{
+    combat::Damage,
+    components::{Stack, Velocity},
+    input::{Action, InputMap},
+    items::dropped::DropItem,
+    player::{
+        channel::{ChannelAction, ChannelFinished, StartChannel},
+        Player,
+    },
+    status::{ApplyStatus, StatusEffect},
+    ui::PanelState,
+};
+
+use super::{
+    decoration::PropOwner,
+    elevation::{ElevationMap, Terrain},
+    nav::NavGrid,
+    seed::WorldSeed,
+    Chunk, ChunkCoords, ChunkOccupant, OccupantKind, CHUNK_TILE_LENGTH, TILE_SIZE,
+};
+
+pub const TRAP_SPRING: u32 = 25;
+
+// Iron shards salvaged alongside the spring
+const IRON_SHARD: u32 = 2;
+
+// Chance a chunk hides a trap on its low ground
+const TRAP_CHANCE: f64 = 0.35;
+
+const TRAP_SIZE: f32 = 18.;
+
+const TRAP_DAMAGE: u8 = 2;
+
+// The jaws hold on a moment after they snap shut
+const TRAP_SLOW: StatusEffect = StatusEffect::Slow {
+    factor: 0.5,
+    seconds: 3.,
+};
+
+// Distance from a trap's center at which walking over it springs it
+const TRIGGER_RADIUS: f32 = 12.;
+
+// Standing still this long reveals hidden traps close by
+const DETECT_SECONDS: f32 = 1.;
+
+const DETECT_RADIUS: f32 = 96.;
+
+// Speed below which the player counts as standing still
+const STILL_SPEED: f32 = 1.;
+
+const DISARM_REACH: f32 = 32.;
+
+const DISARM_SECONDS: f32 = 1.5;
+
+#[derive(Component)]
+pub struct Trap {
+    revealed: bool,
+    // Springs once per step onto it, not every frame the player stands there
+    sprung: bool,
+}
+
+// Seconds the player has stood still, reset as soon as they move
+#[derive(Resource, Default)]
+pub struct Stillness(f32);
+
+// Traps hide on flat low ground, like deposits they are set again whenever their chunk reloads
+pub fn place_traps(
+    mut commands: Commands,
+    seed: Res<WorldSeed>,
+    elevation: Res<ElevationMap>,
+    new_chunks: Query<(Entity, &Transform), Added<Chunk>>,
+) {
+    for (entity, transform) in new_chunks.iter() {
+        let coords = ChunkCoords::from(transform.translation);
+
+        let mut hasher = DefaultHasher::new();
+        (coords.0, coords.1, seed.0, "trap").hash(&mut hasher);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(hasher.finish());
+
+        if !rng.gen_bool(TRAP_CHANCE) {
+            continue;
+        }
+
+        let x = rng.gen_range(0..CHUNK_TILE_LENGTH);
+        let y = rng.gen_range(0..CHUNK_TILE_LENGTH);
+
+        let position = Vec2::new(
+            (coords.0 + x * TILE_SIZE + TILE_SIZE / 2) as f32,
+            (coords.1 + y * TILE_SIZE + TILE_SIZE / 2) as f32,
+        );
+        let cell = NavGrid::cell_of(position);
+
+        if elevation.level(cell) != 0 || elevation.terrain(cell) != Terrain::Flat {
+            continue;
+        }
+
+        let sprite = SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb(0.45, 0.3, 0.25),
+                custom_size: Some(Vec2::splat(TRAP_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(0.2)),
+            visibility: Visibility::Hidden,
+            ..default()
+        };
+
+        commands
+            .spawn(sprite)
+            .insert(Trap {
+                revealed: false,
+                sprung: false,
+            })
+            .insert(PropOwner(entity))
+            .insert(ChunkOccupant(OccupantKind::Decoration));
+    }
+}
+
+// Walking onto a trap springs it, hidden or not, and leaves it revealed
+pub fn spring_traps(
+    player: Query<(Entity, &Transform), With<Player>>,
+    mut traps: Query<(&Transform, &mut Trap, &mut Visibility)>,
+    mut damage: EventWriter<Damage>,
+    mut statuses: EventWriter<ApplyStatus>,
+) {
+    let Ok((player, transform)) = player.get_single() else {
+        return;
+    };
+
+    let position = transform.translation.truncate();
+
+    for (trap_transform, mut trap, mut visibility) in traps.iter_mut() {
+        let trap_position = trap_transform.translation.truncate();
+
+        if trap_position.distance(position) > TRIGGER_RADIUS {
+            trap.sprung = false;
+            continue;
+        }
+
+        if trap.sprung {
+            continue;
+        }
+
+        info!("Sprung a trap");
+
+        trap.sprung = true;
+        trap.revealed = true;
+        *visibility = Visibility::Inherited;
+
+        damage.send(Damage {
+            target: player,
+            amount: TRAP_DAMAGE,
+            source: trap_position,
+        });
+        statuses.send(ApplyStatus {
+            target: player,
+            effect: TRAP_SLOW,
+        });
+    }
+}
+
+// Standing still for a moment gives the player time to spot traps nearby
+pub fn detect_traps(
+    time: Res<Time>,
+    mut stillness: ResMut<Stillness>,
+    player: Query<(&Transform, &Velocity), With<Player>>,
+    mut traps: Query<(&Transform, &mut Trap, &mut Visibility)>,
+) {
+    let Ok((transform, velocity)) = player.get_single() else {
+        return;
+    };
+
+    if Vec2::new(velocity.dx, velocity.dy).length() > STILL_SPEED {
+        stillness.0 = 0.;
+        return;
+    }
+
+    stillness.0 += time.delta_seconds();
+
+    if stillness.0 < DETECT_SECONDS {
+        return;
+    }
+
+    let position = transform.translation.truncate();
+
+    for (trap_transform, mut trap, mut visibility) in traps.iter_mut() {
+        if trap.revealed || trap_transform.translation.truncate().distance(position) > DETECT_RADIUS
+        {
+            continue;
+        }
+
+        info!("Spotted a hidden trap");
+
+        trap.revealed = true;
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// Only revealed traps can be disarmed, it takes a steady hand and some time
+pub fn disarm_traps(
+    input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    state: Res<PanelState>,
+    player: Query<&Transform, With<Player>>,
+    traps: Query<(Entity, &Transform, &Trap)>,
+    mut channels: EventWriter<StartChannel>,
+) {
+    if !input_map.just_pressed(Action::Interact, &input) || state.is_focused() {
+        return;
+    }
+
+    let Ok(transform) = player.get_single() else {
+        return;
+    };
+
+    let position = transform.translation.truncate();
+
+    let Some((entity, _, _)) = traps.iter().find(|(_, trap_transform, trap)| {
+        trap.revealed && trap_transform.translation.truncate().distance(position) <= DISARM_REACH
+    }) else {
+        return;
+    };
+
+    channels.send(StartChannel {
+        action: ChannelAction::Disarm(entity),
+        duration: Duration::from_secs_f32(DISARM_SECONDS),
+    });
+}
+
+// A disarmed trap comes apart into its spring and some scrap iron
+pub fn finish_disarming(
+    mut commands: Commands,
+    mut finished: EventReader<ChannelFinished>,
+    traps: Query<&Transform, With<Trap>>,
+    mut drops: EventWriter<DropItem>,
+) {
+    for ChannelFinished(action) in finished.read() {
+        let ChannelAction::Disarm(entity) = action else {
+            continue;
+        };
+
+        // The trap's chunk may have unloaded while it was disarmed
+        let Ok(transform) = traps.get(*entity) else {
+            continue;
+        };
+
+        info!("Disarmed a trap");
+
+        let position = transform.translation.truncate();
+
+        drops.send(DropItem {
+            stack: Stack::new(TRAP_SPRING, 1),
+            position,
+        });
+        drops.send(DropItem {
+            stack: Stack::new(IRON_SHARD, 1),
+            position,
+        });
+
+        commands.entity(*entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    physics::{Barrier, Collider, Hitbox, Traversal},
+    player::Player,
+    ui::console::{ConsoleCommand, ConsoleOutput},
+};
+
+use super::{nav::NavGrid, Movement, SpawnSafely, TeleportRequest, TILE_SIZE};
+
+// Time between uses of the unstuck command
+const UNSTUCK_COOLDOWN: Duration = Duration::from_secs(60);
+
+type Colliders<'w, 's> =
+    Query<'w, 's, (&'static GlobalTransform, Option<&'static Barrier>), With<Collider>>;
+
+// Whether the player's feet stand in a tile that blocks it. Hitboxes only ever brush the tiles they
+// walk against, feet in the middle of one mean it was put there, by a teleport or an edited tile
+fn is_embedded(feet: Vec2, traversal: Option<&Traversal>, colliders: &Colliders) -> bool {
+    colliders.iter().any(|(collider, barrier)| {
+        let crossed = barrier
+            .is_some_and(|barrier| traversal.is_some_and(|traversal| traversal.crosses(*barrier)));
+
+        !crossed
+            && Rect::from_center_size(
+                collider.translation().truncate(),
+                Vec2::splat(TILE_SIZE as f32),
+            )
+            .contains(feet)
+    })
+}
+
+// Moves the player so its feet stand on the nearest open tile, false if there is none close by
+fn nudge(grid: &NavGrid, entity: Entity, transform: &mut Transform, hitbox: &Hitbox) -> bool {
+    let feet = transform.translation.truncate() + hitbox.offset;
+
+    let Some(point) = grid.find_spawn_point(feet, Movement::Ground, Some(entity)) else {
+        return false;
+    };
+
+    transform.translation.x = point.x - hitbox.offset.x;
+    transform.translation.y = point.y - hitbox.offset.y;
+    true
+}
+
+// Players waiting to be placed safely are left to that, anyone else found inside a wall is pushed out
+pub fn free_embedded_players(
+    grid: Res<NavGrid>,
+    mut player: Query<(Entity, &mut Transform, &Hitbox, Option<&Traversal>), With<Player>>,
+    settling: Query<(), (With<Player>, With<SpawnSafely>)>,
+    colliders: Colliders,
+) {
+    if !settling.is_empty() {
+        return;
+    }
+
+    let Ok((entity, mut transform, hitbox, traversal)) = player.get_single_mut() else {
+        return;
+    };
+
+    let feet = transform.translation.truncate() + hitbox.offset;
+
+    if !grid.covers(feet) || !is_embedded(feet, traversal, &colliders) {
+        return;
+    }
+
+    if nudge(&grid, entity, &mut transform, hitbox) {
+        info!(
+            "Player was stuck at ({}, {}), moved to ({}, {})",
+            feet.x, feet.y, transform.translation.x, transform.translation.y
+        );
+    }
+}
+
+// Frees a player stuck in a wall on the spot, one merely walled in is sent back to the world spawn
+#[allow(clippy::too_many_arguments)]
+pub fn handle_unstuck_command(
+    mut last_used: Local<Option<Duration>>,
+    time: Res<Time>,
+    grid: Res<NavGrid>,
+    mut console: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut teleports: EventWriter<TeleportRequest>,
+    mut player: Query<(Entity, &mut Transform, &Hitbox, Option<&Traversal>), With<Player>>,
+    colliders: Colliders,
+) {
+    for command in console.read() {
+        if command.name != "unstuck" {
+            continue;
+        }
+
+        if let Some(remaining) =
+            last_used.and_then(|used| (used + UNSTUCK_COOLDOWN).checked_sub(time.elapsed()))
+        {
+            output.send(ConsoleOutput(format!(
+                "Unstuck can be used again in {} seconds",
+                remaining.as_secs() + 1
+            )));
+            continue;
+        }
+
+        let Ok((entity, mut transform, hitbox, traversal)) = player.get_single_mut() else {
+            continue;
+        };
+
+        let feet = transform.translation.truncate() + hitbox.offset;
+
+        if is_embedded(feet, traversal, &colliders) && nudge(&grid, entity, &mut transform, hitbox)
+        {
+            output.send(ConsoleOutput(
+                "Moved to the nearest open ground".to_string(),
+            ));
+        } else {
+            teleports.send(TeleportRequest {
+                destination: Vec2::ZERO,
+                fee: 0,
+            });
+            output.send(ConsoleOutput("Returning to the world spawn".to_string()));
+        }
+
+        *last_used = Some(time.elapsed());
+    }
+}
@@ -0,0 +1,415 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use bevy::prelude::*;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use travelers_worldgen::{biome::BiomeMap, schematic::BiomeWeather};
+
+use crate::{
+    menu::GameState,
+    particles::{ParticleEffect, ParticleSpawner},
+    player::Player,
+    ui::console::{ConsoleCommand, ConsoleOutput},
+};
+
+use super::{
+    get_chunk_at,
+    region::{locate, REGION_LENGTH},
+    schematic::{SchematicAsset, SchematicResource},
+    seed::WorldSeed,
+    tick::{WorldTick, TICKS_PER_SECOND},
+    Chunk, ChunkCoords, CHUNK_SIZE, TILE_SIZE,
+};
+
+// Ticks each kind of weather holds for before it may change
+const WEATHER_PERIOD: u64 = 90 * TICKS_PER_SECOND as u64;
+
+// Odds of biomes without their own and of worlds without biomes, snow only falls where a biome asks for it
+const DEFAULT_WEATHER: BiomeWeather = BiomeWeather {
+    clear: 6.,
+    rain: 2.,
+    storm: 1.,
+    snow: 0.,
+};
+
+// Rate the overlay eases toward the weather's shade, lightning fades a lot quicker
+const WEATHER_FADE: f32 = 0.5;
+const FLASH_FADE: f32 = 6.;
+
+// Lightning strikes per second during a storm, on average
+const LIGHTNING_RATE: f64 = 0.08;
+
+// Around the camera, wide enough that rain and snow cover the view as it moves
+const WEATHER_AREA: Vec2 = Vec2::new(360., 240.);
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WeatherMap>()
+            .init_resource::<CurrentWeather>()
+            .add_event::<ConsoleCommand>()
+            .add_event::<ConsoleOutput>()
+            .add_systems(Startup, spawn_weather_overlay)
+            .add_systems(
+                Update,
+                (
+                    advance_weather,
+                    handle_weather_commands,
+                    follow_player_weather,
+                    (spawn_weather_particles, shade_weather_overlay),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeatherState {
+    #[default]
+    Clear,
+    Rain,
+    Storm,
+    Snow,
+}
+
+impl WeatherState {
+    const ALL: [WeatherState; 4] = [
+        WeatherState::Clear,
+        WeatherState::Rain,
+        WeatherState::Storm,
+        WeatherState::Snow,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            WeatherState::Clear => "clear",
+            WeatherState::Rain => "rain",
+            WeatherState::Storm => "storm",
+            WeatherState::Snow => "snow",
+        }
+    }
+
+    fn odds(&self, weather: &BiomeWeather) -> f32 {
+        match self {
+            WeatherState::Clear => weather.clear,
+            WeatherState::Rain => weather.rain,
+            WeatherState::Storm => weather.storm,
+            WeatherState::Snow => weather.snow,
+        }
+    }
+
+    // Storms build up from rain and die down into it, snow and rain clear up before turning into each other
+    fn next(&self) -> &'static [WeatherState] {
+        match self {
+            WeatherState::Clear => &[WeatherState::Clear, WeatherState::Rain, WeatherState::Snow],
+            WeatherState::Rain => &[WeatherState::Clear, WeatherState::Rain, WeatherState::Storm],
+            WeatherState::Storm => &[WeatherState::Rain, WeatherState::Storm],
+            WeatherState::Snow => &[WeatherState::Clear, WeatherState::Snow],
+        }
+    }
+
+    // Share of their speed walkers keep
+    pub fn movement_factor(&self) -> f32 {
+        match self {
+            WeatherState::Snow => 0.8,
+            WeatherState::Storm => 0.9,
+            WeatherState::Clear | WeatherState::Rain => 1.,
+        }
+    }
+
+    fn shade(&self) -> Color {
+        match self {
+            WeatherState::Clear => Color::rgba(0., 0., 0., 0.),
+            WeatherState::Rain => Color::rgba(0.1, 0.12, 0.2, 0.15),
+            WeatherState::Storm => Color::rgba(0.05, 0.06, 0.12, 0.3),
+            WeatherState::Snow => Color::rgba(0.9, 0.92, 1., 0.1),
+        }
+    }
+
+    // Effect and particles per second falling around the camera
+    fn particles(&self) -> Option<(ParticleEffect, f32)> {
+        match self {
+            WeatherState::Clear => None,
+            WeatherState::Rain => Some((ParticleEffect::RAIN, 60.)),
+            WeatherState::Storm => Some((ParticleEffect::RAIN, 140.)),
+            WeatherState::Snow => Some((ParticleEffect::SNOW, 40.)),
+        }
+    }
+}
+
+// Weighted pick among the states, None if the biome gives all of them zero odds
+fn pick(states: &[WeatherState], weather: &BiomeWeather, rng: &mut StdRng) -> Option<WeatherState> {
+    let total: f32 = states.iter().map(|state| state.odds(weather).max(0.)).sum();
+    if total <= 0. {
+        return None;
+    }
+
+    let mut roll = rng.gen_range(0. ..total);
+
+    for state in states {
+        let odds = state.odds(weather).max(0.);
+        if roll < odds {
+            return Some(*state);
+        }
+        roll -= odds;
+    }
+
+    states.last().copied()
+}
+
+// Same roll for the same region and period in every session of the world
+fn roll(seed: u64, region: (i64, i64), period: u64) -> StdRng {
+    let mut hasher = DefaultHasher::new();
+    (seed, region, period).hash(&mut hasher);
+    StdRng::seed_from_u64(hasher.finish())
+}
+
+fn region_of(position: Vec2) -> (i64, i64) {
+    locate(get_chunk_at((position.x, position.y))).0
+}
+
+// Odds of the biome weighted most at the middle of the region
+fn region_odds(schematic: &SchematicAsset, seed: u64, region: (i64, i64)) -> BiomeWeather {
+    if schematic.biomes.is_empty() {
+        return DEFAULT_WEATHER;
+    }
+
+    let step = (CHUNK_SIZE + TILE_SIZE) as f32;
+    let center = (Vec2::new(region.0 as f32, region.1 as f32) + Vec2::splat(0.5))
+        * REGION_LENGTH as f32
+        * step;
+    let tile = (center / TILE_SIZE as f32).floor().as_ivec2();
+
+    BiomeMap::new(seed, schematic.biomes.len())
+        .weights(tile)
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .and_then(|(biome, _)| schematic.weather.get(biome).copied().flatten())
+        .unwrap_or(DEFAULT_WEATHER)
+}
+
+#[derive(Clone, Copy, Debug)]
+struct RegionWeather {
+    state: WeatherState,
+    period: u64,
+    // Set from the console, shown instead of the rolled state until the period is over
+    forced: Option<WeatherState>,
+}
+
+impl RegionWeather {
+    fn shown(&self) -> WeatherState {
+        self.forced.unwrap_or(self.state)
+    }
+}
+
+// Weather of every region that has had chunks loaded, each region file's worth of chunks shares one
+#[derive(Resource, Default)]
+pub struct WeatherMap {
+    regions: HashMap<(i64, i64), RegionWeather>,
+}
+
+impl WeatherMap {
+    // Clear where no weather has been worked out yet
+    pub fn state_at(&self, position: Vec2) -> WeatherState {
+        self.regions
+            .get(&region_of(position))
+            .map(RegionWeather::shown)
+            .unwrap_or_default()
+    }
+}
+
+// Weather where the player stands, for anything that changes with it
+#[derive(Resource, Default)]
+pub struct CurrentWeather {
+    pub state: WeatherState,
+}
+
+impl CurrentWeather {
+    pub fn movement_factor(&self) -> f32 {
+        self.state.movement_factor()
+    }
+}
+
+// Loaded regions step through every period they missed, one change of weather at a time. Regions
+// seen for the first time replay from period 0, so a region's weather only depends on the seed, the
+// region and the period, whenever it is first loaded
+fn advance_weather(
+    world_tick: Res<WorldTick>,
+    seed: Res<WorldSeed>,
+    schematic: Option<Res<SchematicResource>>,
+    schematics: Res<Assets<SchematicAsset>>,
+    chunks: Query<&Transform, With<Chunk>>,
+    mut map: ResMut<WeatherMap>,
+) {
+    let Some(schematic) = schematic.and_then(|handle| schematics.get(&handle.0)) else {
+        return;
+    };
+
+    let period = world_tick.tick / WEATHER_PERIOD;
+
+    let regions: HashSet<(i64, i64)> = chunks
+        .iter()
+        .map(|transform| locate(ChunkCoords::from(transform.translation)).0)
+        .collect();
+
+    for region in regions {
+        let odds = region_odds(schematic, seed.0, region);
+
+        let weather = map.regions.entry(region).or_insert_with(|| RegionWeather {
+            state: pick(&WeatherState::ALL, &odds, &mut roll(seed.0, region, 0))
+                .unwrap_or_default(),
+            period: 0,
+            forced: None,
+        });
+
+        while weather.period < period {
+            weather.period += 1;
+            weather.forced = None;
+
+            let mut rng = roll(seed.0, region, weather.period);
+            if let Some(state) = pick(weather.state.next(), &odds, &mut rng) {
+                weather.state = state;
+            }
+        }
+    }
+}
+
+// Sets the weather where the player stands until the period is over
+fn handle_weather_commands(
+    mut console: EventReader<ConsoleCommand>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut map: ResMut<WeatherMap>,
+    player: Query<&Transform, With<Player>>,
+) {
+    for command in console.read() {
+        if command.name != "weather" {
+            continue;
+        }
+
+        let state = command.args.first().and_then(|key| {
+            WeatherState::ALL
+                .into_iter()
+                .find(|state| state.key() == key.as_str())
+        });
+
+        let (Some(state), Ok(transform)) = (state, player.get_single()) else {
+            output.send(ConsoleOutput(
+                "Usage: weather <clear|rain|storm|snow>".to_string(),
+            ));
+            continue;
+        };
+
+        match map
+            .regions
+            .get_mut(&region_of(transform.translation.truncate()))
+        {
+            Some(weather) => {
+                weather.forced = Some(state);
+                output.send(ConsoleOutput(format!("Weather set to {}", state.key())));
+            }
+            None => output.send(ConsoleOutput("No weather here yet".to_string())),
+        }
+    }
+}
+
+fn follow_player_weather(
+    map: Res<WeatherMap>,
+    mut current: ResMut<CurrentWeather>,
+    player: Query<&Transform, With<Player>>,
+) {
+    let Ok(transform) = player.get_single() else {
+        return;
+    };
+
+    let state = map.state_at(transform.translation.truncate());
+
+    if current.state != state {
+        info!("Weather is now {}", state.key());
+        current.state = state;
+    }
+}
+
+fn spawn_weather_particles(
+    mut commands: Commands,
+    current: Res<CurrentWeather>,
+    camera: Query<Entity, With<Camera>>,
+) {
+    if !current.is_changed() {
+        return;
+    }
+
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+
+    match current.state.particles() {
+        Some((effect, rate)) => {
+            commands
+                .entity(camera)
+                .insert(ParticleSpawner::new(effect, rate, WEATHER_AREA));
+        }
+        None => {
+            commands.entity(camera).remove::<ParticleSpawner>();
+        }
+    }
+}
+
+// Full screen shade drawn over the world, under the biome tint
+#[derive(Component)]
+pub struct WeatherOverlay;
+
+fn spawn_weather_overlay(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(-2),
+            ..default()
+        })
+        .insert(WeatherOverlay);
+}
+
+// Eases toward the weather's shade, storms now and then flash it white
+fn shade_weather_overlay(
+    time: Res<Time>,
+    current: Res<CurrentWeather>,
+    mut overlay: Query<&mut BackgroundColor, With<WeatherOverlay>>,
+) {
+    let Ok(mut background) = overlay.get_single_mut() else {
+        return;
+    };
+
+    let lightning = current.state == WeatherState::Storm
+        && rand::thread_rng().gen_bool((LIGHTNING_RATE * time.delta_seconds_f64()).min(1.));
+
+    if lightning {
+        background.0 = Color::rgba(0.95, 0.95, 1., 0.45);
+        return;
+    }
+
+    let current_shade = Vec4::from_array(background.0.as_rgba_f32());
+    let target = Vec4::from_array(current.state.shade().as_rgba_f32());
+
+    let fade = if current.state == WeatherState::Storm {
+        FLASH_FADE
+    } else {
+        WEATHER_FADE
+    };
+
+    let blended = current_shade.lerp(target, 1. - (-fade * time.delta_seconds()).exp());
+
+    if blended.distance_squared(current_shade) > f32::EPSILON {
+        background.0 = Color::rgba(blended.x, blended.y, blended.z, blended.w);
+    }
+}
@@ -0,0 +1,514 @@
+// Game systems run in a headless App, without a window or renderer, and checked after a number of frames
+use std::{thread, time::Duration};
+
+use bevy::prelude::*;
+
+use travelers::{
+    combat::{check_death, Killed},
+    components::{Dead, Dirty, Health, Stack},
+    id::{index_stable_ids, EntityIds, StableId, StableIds},
+    items::{container::ItemContainer, definition::ItemRegistry, dropped::ItemCollected},
+    menu::GameState,
+    mobs::EnemyKind,
+    particles::{Particle, ParticleBurst, ParticleEffect, ParticlePlugin},
+    pool::Pool,
+    quest::{advance_objectives, QuestDefinition, QuestDefinitions, QuestLog},
+    status::{ApplyStatus, StatusEffect, StatusPlugin},
+    ui::ticker::WorldEvent,
+    work::{WorkPlugin, WorkQueue},
+    world::{
+        Chunk, ChunkEntered, ChunkStreamingPlugin, PendingTeleport, SavedChunks, TileIndex,
+        WorldSeed, WorldSettings,
+    },
+};
+use travelers_worldgen::{get_chunks_in_range, ChunkCoords, CHUNK_SIZE, TILE_SIZE};
+
+// Upper bound on frames waited for assets and generation, a hung system fails instead of spinning
+const MAX_FRAMES: usize = 500;
+
+fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_state::<GameState>();
+    app
+}
+
+// Assets load on other threads, frames are spaced out a little so they get a chance to finish
+fn update_until(app: &mut App, mut done: impl FnMut(&mut World) -> bool) -> bool {
+    for _ in 0..MAX_FRAMES {
+        app.update();
+
+        if done(&mut app.world) {
+            return true;
+        }
+
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    false
+}
+
+fn loaded_chunks(world: &mut World) -> Vec<ChunkCoords> {
+    world
+        .query_filtered::<&Transform, With<Chunk>>()
+        .iter(world)
+        .map(|transform| ChunkCoords::from(transform.translation))
+        .collect()
+}
+
+fn streaming_app() -> App {
+    let mut app = headless_app();
+    app.add_plugins(AssetPlugin::default())
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlas>()
+        .insert_resource(WorldSeed(7))
+        .insert_resource(WorldSettings { render_distance: 1 })
+        .add_plugins(ChunkStreamingPlugin);
+
+    app.world.spawn((Camera::default(), Transform::default()));
+
+    // Saves from playing the game are left alone, every chunk is collapsed fresh
+    app.world.resource_mut::<SavedChunks>().forget();
+
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::InGame);
+
+    app
+}
+
+#[test]
+fn chunks_spawn_within_render_distance() {
+    let mut app = streaming_app();
+
+    let in_range = get_chunks_in_range((0., 0.), 1);
+
+    let settled = update_until(&mut app, |world| {
+        let dirty = world
+            .query_filtered::<&Dirty, With<Chunk>>()
+            .iter(world)
+            .filter(|dirty| dirty.stitch)
+            .count();
+
+        loaded_chunks(world).len() == in_range.len() && dirty == 0
+    });
+    assert!(settled, "chunks around the camera were never generated");
+
+    for coords in loaded_chunks(&mut app.world) {
+        assert!(
+            in_range.contains(&coords),
+            "chunk ({}, {}) spawned out of range",
+            coords.0,
+            coords.1
+        );
+    }
+}
+
+#[test]
+fn stale_chunks_despawn() {
+    let mut app = streaming_app();
+
+    let in_range = get_chunks_in_range((0., 0.), 1);
+    let generated = update_until(&mut app, |world| {
+        loaded_chunks(world).len() == in_range.len()
+    });
+    assert!(generated, "chunks around the camera were never generated");
+
+    // Far past the unload distance, none of the starting chunks are kept
+    let destination = Vec3::new(20_000., 20_000., 0.);
+    let mut cameras = app.world.query_filtered::<&mut Transform, With<Camera>>();
+    cameras.single_mut(&mut app.world).translation = destination;
+
+    let moved_to = get_chunks_in_range((destination.x, destination.y), 1);
+
+    let settled = update_until(&mut app, |world| {
+        let loaded = loaded_chunks(world);
+        loaded.len() == moved_to.len() && loaded.iter().all(|coords| moved_to.contains(coords))
+    });
+    assert!(settled, "chunks were not regenerated around the camera");
+
+    for coords in loaded_chunks(&mut app.world) {
+        assert!(
+            !in_range.contains(&coords),
+            "chunk ({}, {}) was never unloaded",
+            coords.0,
+            coords.1
+        );
+    }
+}
+
+#[test]
+fn overlapping_teleport_rings_spawn_chunks_once() {
+    let mut app = streaming_app();
+
+    let in_range = get_chunks_in_range((0., 0.), 1);
+    let generated = update_until(&mut app, |world| {
+        loaded_chunks(world).len() == in_range.len()
+    });
+    assert!(generated, "chunks around the camera were never generated");
+
+    // Moving away caches the starting chunks, restoring them isn't limited to a few per frame
+    let away = Vec3::new(20_000., 20_000., 0.);
+    let mut cameras = app.world.query_filtered::<&mut Transform, With<Camera>>();
+    cameras.single_mut(&mut app.world).translation = away;
+
+    let moved = update_until(&mut app, |world| {
+        loaded_chunks(world)
+            .iter()
+            .all(|coords| !in_range.contains(coords))
+    });
+    assert!(moved, "starting chunks were never unloaded");
+
+    // Back home with a teleport one chunk over pending, most of both rings are the same chunks
+    let destination = Vec2::new(CHUNK_SIZE as f32, 0.);
+    app.world.resource_mut::<PendingTeleport>().0 = Some(destination);
+    cameras.single_mut(&mut app.world).translation = Vec3::ZERO;
+
+    let mut expected = in_range.clone();
+    for coords in get_chunks_in_range((destination.x, destination.y), 1) {
+        if !expected.contains(&coords) {
+            expected.push(coords);
+        }
+    }
+
+    let generated = update_until(&mut app, |world| {
+        let loaded = loaded_chunks(world);
+        expected.iter().all(|coords| loaded.contains(coords))
+    });
+    assert!(
+        generated,
+        "chunks around the destination were never generated"
+    );
+
+    let loaded = loaded_chunks(&mut app.world);
+    for coords in expected.iter() {
+        let spawned = loaded.iter().filter(|loaded| *loaded == coords).count();
+        assert_eq!(
+            spawned, 1,
+            "chunk ({}, {}) spawned {spawned} times",
+            coords.0, coords.1
+        );
+    }
+}
+
+#[test]
+fn tile_index_follows_loaded_chunks() {
+    let mut app = streaming_app();
+
+    let in_range = get_chunks_in_range((0., 0.), 1);
+    let generated = update_until(&mut app, |world| {
+        loaded_chunks(world).len() == in_range.len()
+    });
+    assert!(generated, "chunks around the camera were never generated");
+
+    let origin = Vec2::new(in_range[0].0 as f32, in_range[0].1 as f32) + Vec2::splat(1.);
+    let tile = app.world.resource::<TileIndex>().tile_at(origin);
+    assert_eq!(tile.map(|tile| tile.chunk), Some(in_range[0]));
+
+    let destination = Vec3::new(20_000., 20_000., 0.);
+    let mut cameras = app.world.query_filtered::<&mut Transform, With<Camera>>();
+    cameras.single_mut(&mut app.world).translation = destination;
+
+    let unloaded = update_until(&mut app, |world| {
+        !loaded_chunks(world).contains(&in_range[0])
+    });
+    assert!(unloaded, "chunk was never unloaded");
+
+    app.update();
+    assert!(app.world.resource::<TileIndex>().tile_at(origin).is_none());
+}
+
+#[test]
+fn death_marker_applied_at_zero_health() {
+    let mut app = headless_app();
+    app.add_systems(Update, check_death);
+
+    let dying = app
+        .world
+        .spawn(Health {
+            current: 0,
+            max: 10,
+        })
+        .id();
+    let alive = app
+        .world
+        .spawn(Health {
+            current: 5,
+            max: 10,
+        })
+        .id();
+
+    app.update();
+
+    assert!(app.world.get::<Dead>(dying).is_some());
+    assert!(app.world.get::<Dead>(alive).is_none());
+}
+
+#[test]
+fn poison_wears_down_health() {
+    let mut app = headless_app();
+    app.add_plugins(StatusPlugin);
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::InGame);
+
+    let target = app
+        .world
+        .spawn(Health {
+            current: 10,
+            max: 10,
+        })
+        .id();
+
+    app.world.send_event(ApplyStatus {
+        target,
+        effect: StatusEffect::Poison {
+            damage: 3,
+            seconds: 1.5,
+        },
+    });
+
+    let ticked = update_until(&mut app, |world| {
+        world.get::<Health>(target).unwrap().current < 10
+    });
+    assert!(ticked, "poison never ticked");
+    assert_eq!(app.world.get::<Health>(target).unwrap().current, 7);
+}
+
+#[derive(Component)]
+struct Pooled;
+
+#[test]
+fn pool_reuses_released_entities() {
+    let mut app = headless_app();
+    app.insert_resource(Pool::<Pooled>::new(1));
+
+    let acquire = |world: &mut World| {
+        world.resource_scope(|world, mut pool: Mut<Pool<Pooled>>| {
+            let mut queue = bevy::ecs::system::CommandQueue::default();
+            let mut commands = Commands::new(&mut queue, world);
+            let entity = pool.acquire(&mut commands, (Pooled, Visibility::Inherited));
+            queue.apply(world);
+            entity
+        })
+    };
+    let release = |world: &mut World, entity: Entity| {
+        world.resource_scope(|world, mut pool: Mut<Pool<Pooled>>| {
+            let mut queue = bevy::ecs::system::CommandQueue::default();
+            let mut commands = Commands::new(&mut queue, world);
+            pool.release(&mut commands, entity);
+            queue.apply(world);
+        })
+    };
+
+    let first = acquire(&mut app.world);
+    let second = acquire(&mut app.world);
+    assert_ne!(first, second);
+
+    release(&mut app.world, first);
+    assert!(app.world.get::<Pooled>(first).is_none());
+    assert_eq!(
+        app.world.get::<Visibility>(first),
+        Some(&Visibility::Hidden)
+    );
+
+    // Past the limit released entities are despawned instead of kept
+    release(&mut app.world, second);
+    assert!(app.world.get_entity(second).is_none());
+    assert_eq!(app.world.resource::<Pool<Pooled>>().free(), 1);
+
+    assert_eq!(acquire(&mut app.world), first);
+    assert!(app.world.get::<Pooled>(first).is_some());
+    assert_eq!(
+        app.world.get::<Visibility>(first),
+        Some(&Visibility::Inherited)
+    );
+}
+
+#[test]
+fn particle_bursts_expire_into_the_pool() {
+    let mut app = headless_app();
+    app.add_plugins(ParticlePlugin);
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::InGame);
+    app.update();
+
+    let effect = ParticleEffect {
+        count: 5,
+        lifetime: (0.05, 0.1),
+        ..ParticleEffect::SPARKS
+    };
+    app.world.send_event(ParticleBurst {
+        position: Vec2::ZERO,
+        effect,
+    });
+    app.update();
+
+    let live = |world: &mut World| world.query::<&Particle>().iter(world).count();
+    assert_eq!(live(&mut app.world), 5);
+
+    let expired = update_until(&mut app, |world| live(world) == 0);
+    assert!(expired, "particles never expired");
+    assert_eq!(app.world.resource::<Pool<Particle>>().free(), 5);
+}
+
+#[derive(Resource, Default)]
+struct Finished(Vec<u32>);
+
+#[test]
+fn deferred_work_spreads_over_frames() {
+    let mut app = headless_app();
+    app.add_plugins(WorkPlugin).init_resource::<Finished>();
+
+    {
+        let mut work = app.world.resource_mut::<WorkQueue>();
+
+        // Nothing fits in the budget, a single job runs each frame
+        work.budget = Duration::ZERO;
+
+        for job in 0..3 {
+            work.push(move |world| world.resource_mut::<Finished>().0.push(job));
+        }
+
+        // Replaces the first keyed job in its place instead of queueing behind it
+        work.push_latest("redraw", |world| {
+            world.resource_mut::<Finished>().0.push(10)
+        });
+        work.push_latest("redraw", |world| {
+            world.resource_mut::<Finished>().0.push(11)
+        });
+    }
+
+    app.update();
+    assert_eq!(app.world.resource::<Finished>().0, vec![0]);
+
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(app.world.resource::<Finished>().0, vec![0, 1, 2, 11]);
+    assert!(app.world.resource::<WorkQueue>().is_empty());
+}
+
+#[test]
+fn quest_objectives_follow_events() {
+    let quest: QuestDefinition = serde_json::from_str(
+        r#"{
+            "title": "Test",
+            "objectives": [
+                { "kill": { "enemy": "slime", "count": 2 } },
+                { "collect": { "item": 1, "count": 3 } },
+                { "reach": { "chunk": [1, -1] } }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let mut log = QuestLog::default();
+    log.start("test", &quest);
+
+    let mut app = headless_app();
+    app.insert_resource(QuestDefinitions([("test".to_string(), quest)].into()))
+        .insert_resource(log)
+        .add_event::<Killed>()
+        .add_event::<ItemCollected>()
+        .add_event::<ChunkEntered>()
+        .add_event::<WorldEvent>()
+        .add_systems(Update, advance_objectives);
+
+    for kind in [
+        EnemyKind::Slime,
+        EnemyKind::Wraith,
+        EnemyKind::Slime,
+        EnemyKind::Slime,
+    ] {
+        app.world.send_event(Killed {
+            kind,
+            position: Vec2::ZERO,
+        });
+    }
+    app.world.send_event(ItemCollected { id: 1, count: 2 });
+    app.world.send_event(ItemCollected { id: 0, count: 5 });
+    app.update();
+
+    // Kills stop counting once the objective is met, other enemies and items count for nothing
+    assert_eq!(
+        app.world.resource::<QuestLog>().progress("test"),
+        Some(&[2, 2, 0][..])
+    );
+
+    // The chunk one step east and one south of the origin's
+    let step = CHUNK_SIZE + TILE_SIZE;
+    app.world.send_event(ItemCollected { id: 1, count: 4 });
+    app.world.send_event(ChunkEntered(ChunkCoords(
+        step - TILE_SIZE,
+        -step - TILE_SIZE,
+    )));
+    app.update();
+
+    assert_eq!(
+        app.world.resource::<QuestLog>().progress("test"),
+        Some(&[2, 3, 1][..])
+    );
+    assert_eq!(app.world.resource::<Events<WorldEvent>>().len(), 1);
+}
+
+#[test]
+fn stable_ids_outlive_entities() {
+    let mut ids = EntityIds::default();
+    let crate_id = ids.allocate();
+    assert_ne!(crate_id, ids.allocate());
+    assert!(!crate_id.is_generated());
+
+    // Regenerated entities come back with the id they had, wherever they are on their tile
+    let npc_id = StableId::generated("wanderer", Vec2::new(40., -10.));
+    assert_eq!(npc_id, StableId::generated("wanderer", Vec2::new(35., -2.)));
+    assert_ne!(
+        npc_id,
+        StableId::generated("merchant", Vec2::new(40., -10.))
+    );
+    assert!(npc_id.is_generated());
+
+    let mut app = headless_app();
+    app.init_resource::<StableIds>()
+        .add_systems(Update, index_stable_ids);
+
+    let first = app.world.spawn(npc_id).id();
+    app.update();
+    assert_eq!(app.world.resource::<StableIds>().get(npc_id), Some(first));
+
+    // The chunk reloads, despawning the villager and spawning it again
+    app.world.despawn(first);
+    let second = app.world.spawn(npc_id).id();
+    app.update();
+
+    let index = app.world.resource::<StableIds>();
+    assert_eq!(index.get(npc_id), Some(second));
+    assert_eq!(index.id_of(first), None);
+    assert_eq!(index.id_of(second), Some(npc_id));
+}
+
+#[test]
+fn inventory_add_and_remove() {
+    // Sticks stack to 64
+    let registry = ItemRegistry::default();
+    let mut container = ItemContainer::with_capacity(3);
+
+    assert_eq!(container.insert(Stack::new(0, 100), &registry), None);
+    assert_eq!(container.count(0), 100);
+    assert_eq!(container.slots.iter().flatten().count(), 2);
+
+    // Whatever does not fit is handed back
+    let leftover = container.insert(Stack::new(0, 200), &registry);
+    assert_eq!(leftover.map(|stack| stack.count), Some(108));
+    assert_eq!(container.count(0), 192);
+
+    assert!(container.remove(0, 50));
+    assert_eq!(container.count(0), 142);
+
+    // Removing more than is held removes nothing
+    assert!(!container.remove(0, 500));
+    assert_eq!(container.count(0), 142);
+
+    assert!(container.remove(0, 142));
+    assert!(container.slots.iter().all(Option::is_none));
+}
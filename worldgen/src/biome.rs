@@ -0,0 +1,77 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use glam::{IVec2, Vec2};
+
+use rand::{Rng, SeedableRng};
+
+// Side length in tiles of the cells biome sites are scattered over
+const BIOME_CELL_TILES: f32 = 48.;
+
+// Distance in tiles from a biome border over which the neighboring biome blends in
+const BLEND_TILES: f32 = 6.;
+
+// Biomes laid over the world as seeded voronoi cells, one site per cell
+pub struct BiomeMap {
+    seed: u64,
+    count: usize,
+}
+
+impl BiomeMap {
+    pub fn new(seed: u64, count: usize) -> BiomeMap {
+        BiomeMap { seed, count }
+    }
+
+    // Weight of each biome at the given tile, a single biome away from borders and two fading into each other near them
+    pub fn weights(&self, tile: IVec2) -> Vec<(usize, f32)> {
+        if self.count <= 1 {
+            return vec![(0, 1.)];
+        }
+
+        let point = tile.as_vec2() + Vec2::splat(0.5);
+        let cell = (point / BIOME_CELL_TILES).floor().as_ivec2();
+
+        let mut sites = Vec::with_capacity(9);
+        for x in -1..=1 {
+            for y in -1..=1 {
+                let (site, biome) = self.site(cell + IVec2::new(x, y));
+                sites.push((site.distance_squared(point), site, biome));
+            }
+        }
+
+        sites.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let (nearest_dist, nearest, biome) = sites[0];
+
+        let Some(&(other_dist, other, other_biome)) =
+            sites.iter().find(|(_, _, other)| *other != biome)
+        else {
+            return vec![(biome, 1.)];
+        };
+
+        // Distance to the bisector between the two sites
+        let border = (other_dist - nearest_dist) / (2. * nearest.distance(other));
+
+        if border >= BLEND_TILES {
+            return vec![(biome, 1.)];
+        }
+
+        let blend = 0.5 * (1. - border / BLEND_TILES);
+        vec![(biome, 1. - blend), (other_biome, blend)]
+    }
+
+    fn site(&self, cell: IVec2) -> (Vec2, usize) {
+        let mut hasher = DefaultHasher::new();
+        (cell.x, cell.y, self.seed, "biome").hash(&mut hasher);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(hasher.finish());
+
+        let jitter = Vec2::new(rng.gen_range(0.1..0.9), rng.gen_range(0.1..0.9));
+
+        (
+            (cell.as_vec2() + jitter) * BIOME_CELL_TILES,
+            rng.gen_range(0..self.count),
+        )
+    }
+}
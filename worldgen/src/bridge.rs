@@ -0,0 +1,72 @@
+use super::{
+    schematic::{Schematic, TileSchematic},
+    CHUNK_TILE_LENGTH,
+};
+
+// Longest stretch of water a single bridge spans
+const MAX_BRIDGE_SPAN: usize = 6;
+
+// Crosses water lying between two road tiles of the same row or column with the schematic's bridge tile
+pub fn place_bridges(tiles: &mut [Vec<Option<u8>>], schematic: &Schematic) {
+    // Lowest index keeps the choice stable when a schematic has several bridge tiles
+    let Some(bridge) = schematic
+        .tiles
+        .iter()
+        .filter(|(_, tile)| tile.bridge)
+        .map(|(idx, _)| *idx)
+        .min()
+    else {
+        return;
+    };
+
+    let length = CHUNK_TILE_LENGTH as usize;
+
+    for y in 0..length {
+        let row: Vec<(usize, usize)> = (0..length).map(|x| (x, y)).collect();
+        bridge_line(tiles, &row, schematic, bridge);
+    }
+
+    for x in 0..length {
+        let column: Vec<(usize, usize)> = (0..length).map(|y| (x, y)).collect();
+        bridge_line(tiles, &column, schematic, bridge);
+    }
+}
+
+fn bridge_line(
+    tiles: &mut [Vec<Option<u8>>],
+    line: &[(usize, usize)],
+    schematic: &Schematic,
+    bridge: u8,
+) {
+    let is = |tiles: &[Vec<Option<u8>>], idx: usize, flag: fn(&TileSchematic) -> bool| {
+        let (x, y) = line[idx];
+        tiles[x][y]
+            .and_then(|tile| schematic.tiles.get(&tile))
+            .is_some_and(flag)
+    };
+
+    let mut start = None;
+
+    for idx in 0..line.len() {
+        if is(tiles, idx, |tile| tile.water) {
+            start.get_or_insert(idx);
+            continue;
+        }
+
+        let Some(first) = start.take() else {
+            continue;
+        };
+
+        // Water running off the edge of the chunk has no road on its far bank
+        let spanned = first > 0
+            && idx - first <= MAX_BRIDGE_SPAN
+            && is(tiles, first - 1, |tile| tile.road)
+            && is(tiles, idx, |tile| tile.road);
+
+        if spanned {
+            for &(x, y) in &line[first..idx] {
+                tiles[x][y] = Some(bridge);
+            }
+        }
+    }
+}
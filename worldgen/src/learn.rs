@@ -0,0 +1,198 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+};
+
+use super::schematic::{invalid_data, SchematicJsonV2, Side, TileSchematicV2, SCHEMATIC_VERSION};
+
+// Last tile of the terrain atlas, reserved for tiles that failed to collapse
+const NOT_FOUND_INDEX: u8 = 159;
+
+const NOT_FOUND_NAME: &str = "not_found";
+
+const SHEET: &str = "terrain_1";
+
+// Derives adjacency rules and weights from an example map, a CSV of atlas indices with the first row at the top
+pub fn learn_schematic_file(input: &str, output: &str) -> Result<(), std::io::Error> {
+    let example = fs::read_to_string(input)?;
+    let learned = learn_schematic(&example)?;
+
+    let json = serde_json::to_string_pretty(&learned)
+        .map_err(|err| invalid_data(format!("Failed to serialize schematic! Err {err}")))?;
+
+    fs::write(output, json)
+}
+
+fn learn_schematic(example: &str) -> Result<SchematicJsonV2, std::io::Error> {
+    let map = parse_example(example)?;
+
+    let mut counts: HashMap<u8, u32> = HashMap::new();
+    let mut observed: HashMap<u8, BTreeMap<Side, BTreeSet<u8>>> = HashMap::new();
+
+    for (row, tiles) in map.iter().enumerate() {
+        for (col, tile) in tiles.iter().enumerate() {
+            *counts.entry(*tile).or_default() += 1;
+
+            let mut neighbors = Vec::new();
+
+            if row > 0 {
+                neighbors.push((Side::North, map[row - 1].get(col)));
+            }
+            neighbors.push((Side::East, tiles.get(col + 1)));
+            neighbors.push((
+                Side::South,
+                map.get(row + 1).and_then(|below| below.get(col)),
+            ));
+            if col > 0 {
+                neighbors.push((Side::West, tiles.get(col - 1)));
+            }
+
+            let rules = observed.entry(*tile).or_default();
+
+            for (side, neighbor) in neighbors {
+                if let Some(neighbor) = neighbor {
+                    rules.entry(side).or_default().insert(*neighbor);
+                }
+            }
+        }
+    }
+
+    let most_common = counts.values().copied().max().unwrap_or(1);
+
+    let name_of = |idx: &u8| {
+        if *idx == NOT_FOUND_INDEX {
+            NOT_FOUND_NAME.to_string()
+        } else {
+            format!("tile_{idx}")
+        }
+    };
+
+    let mut tiles = BTreeMap::new();
+
+    for (idx, count) in counts.iter() {
+        let rules = observed
+            .remove(idx)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(side, neighbors)| (side, neighbors.iter().map(name_of).collect()))
+            .collect();
+
+        tiles.insert(
+            name_of(idx),
+            TileSchematicV2 {
+                index: *idx,
+                sheet: SHEET.to_string(),
+                // Scale so the most frequent tile weighs 100
+                weight: ((count * 100) / most_common).max(1) as u8,
+                solid: false,
+                water: false,
+                road: false,
+                bridge: false,
+                symmetric: false,
+                layers: Vec::new(),
+                harvest: None,
+                rules,
+            },
+        );
+    }
+
+    tiles
+        .entry(NOT_FOUND_NAME.to_string())
+        .or_insert(TileSchematicV2 {
+            index: NOT_FOUND_INDEX,
+            sheet: SHEET.to_string(),
+            weight: 0,
+            solid: false,
+            water: false,
+            road: false,
+            bridge: false,
+            symmetric: false,
+            layers: Vec::new(),
+            harvest: None,
+            rules: BTreeMap::new(),
+        });
+
+    Ok(SchematicJsonV2 {
+        version: SCHEMATIC_VERSION,
+        not_found: NOT_FOUND_NAME.to_string(),
+        pattern_size: None,
+        tiles,
+        constraints: Vec::new(),
+        biomes: BTreeMap::new(),
+        grading: BTreeMap::new(),
+        weather: BTreeMap::new(),
+    })
+}
+
+fn parse_example(example: &str) -> Result<Vec<Vec<u8>>, std::io::Error> {
+    let mut map = Vec::new();
+
+    for (line_no, line) in example.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let row = line
+            .split(',')
+            .map(|cell| {
+                cell.trim().parse::<u8>().map_err(|err| {
+                    invalid_data(format!(
+                        "Invalid tile {} on line {}! Err {err}",
+                        cell.trim(),
+                        line_no + 1
+                    ))
+                })
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+
+        map.push(row);
+    }
+
+    if map.is_empty() {
+        return Err(invalid_data("Example map is empty!".to_string()));
+    }
+
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schematic::parse_schematic;
+
+    #[test]
+    fn rules_follow_the_example() {
+        let learned = learn_schematic("1,2\n1,2\n1,1\n").unwrap();
+
+        let one = &learned.tiles["tile_1"];
+        assert_eq!(one.index, 1);
+        assert_eq!(one.rules[&Side::East], vec!["tile_1", "tile_2"]);
+        assert_eq!(one.rules[&Side::South], vec!["tile_1"]);
+
+        let two = &learned.tiles["tile_2"];
+        assert_eq!(two.rules[&Side::West], vec!["tile_1"]);
+        assert_eq!(two.rules[&Side::South], vec!["tile_1", "tile_2"]);
+        assert!(!two.rules.contains_key(&Side::East));
+
+        // Weights are scaled to the most frequent tile
+        assert_eq!((one.weight, two.weight), (100, 50));
+    }
+
+    #[test]
+    fn learned_schematics_load() {
+        let learned = learn_schematic("1,2\n2,1\n").unwrap();
+        let json = serde_json::to_vec(&learned).unwrap();
+        let schematic = parse_schematic(&json).unwrap();
+
+        assert_eq!(schematic.not_found, NOT_FOUND_INDEX);
+        assert_eq!(schematic.tiles[&1].east, vec![2]);
+        assert_eq!(schematic.tiles[&2].north, vec![1]);
+    }
+
+    #[test]
+    fn malformed_examples_are_rejected() {
+        assert!(learn_schematic("").is_err());
+        assert!(learn_schematic("1,2\n1,x\n").is_err());
+        assert!(learn_schematic("1,300\n").is_err());
+    }
+}
@@ -0,0 +1,89 @@
+// Engine agnostic world generation, chunks are collapsed from a schematic and stitched together along their seams
+use glam::Vec3;
+
+pub mod biome;
+pub mod bridge;
+pub mod learn;
+pub mod pattern;
+pub mod schematic;
+pub mod stitcher;
+pub mod structure;
+pub mod wfc;
+
+pub const CHUNK_TILE_LENGTH: i64 = 8;
+pub const TILE_SIZE: i64 = 32;
+pub const CHUNK_SIZE: i64 = CHUNK_TILE_LENGTH * TILE_SIZE;
+
+// Bottom left corner of a chunk's interior in world coordinates
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ChunkCoords(pub i64, pub i64);
+
+// Chunks are positioned by their center
+impl From<Vec3> for ChunkCoords {
+    fn from(value: Vec3) -> Self {
+        ChunkCoords(
+            (value.x - (CHUNK_SIZE / 2) as f32) as i64,
+            (value.y - (CHUNK_SIZE / 2) as f32) as i64,
+        )
+    }
+}
+
+impl PartialEq<Vec3> for ChunkCoords {
+    fn eq(&self, center: &Vec3) -> bool {
+        self.0 == (center.x - (CHUNK_SIZE as f32 / 2.)) as i64
+            && self.1 == (center.y - (CHUNK_SIZE as f32 / 2.)) as i64
+    }
+}
+
+// Get coords of chunks that are in the range of the camera, should account for chunk stitching
+pub fn get_chunks_in_range(pos: (f32, f32), distance: i8) -> Vec<ChunkCoords> {
+    // Inverse linear equation to get offset with floor
+    let offset_x = ((pos.0 - TILE_SIZE as f32) / (CHUNK_SIZE + TILE_SIZE) as f32).floor();
+    let offset_y = ((pos.1 - TILE_SIZE as f32) / (CHUNK_SIZE + TILE_SIZE) as f32).floor();
+
+    let mut coords = Vec::with_capacity(((2 * distance + 1) as usize).pow(2));
+
+    // Feed offset back into linear equation and extrapolate to the distance
+    for x in -distance..=distance {
+        for y in -distance..=distance {
+            coords.push(ChunkCoords(
+                ((offset_x as i64 + x as i64) * (CHUNK_SIZE + TILE_SIZE)) - TILE_SIZE,
+                ((offset_y as i64 + y as i64) * (CHUNK_SIZE + TILE_SIZE)) - TILE_SIZE,
+            ));
+        }
+    }
+
+    coords
+}
+
+// Chunk whose area, including its stitched gap, contains the world position
+pub fn get_chunk_at(pos: (f32, f32)) -> ChunkCoords {
+    let offset_x = ((pos.0 + TILE_SIZE as f32) / (CHUNK_SIZE + TILE_SIZE) as f32).floor();
+    let offset_y = ((pos.1 + TILE_SIZE as f32) / (CHUNK_SIZE + TILE_SIZE) as f32).floor();
+
+    ChunkCoords(
+        (offset_x as i64 * (CHUNK_SIZE + TILE_SIZE)) - TILE_SIZE,
+        (offset_y as i64 * (CHUNK_SIZE + TILE_SIZE)) - TILE_SIZE,
+    )
+}
+
+pub fn get_perimeter_world_coord(coords: &ChunkCoords, side: i64, rank: i64) -> ChunkCoords {
+    match side {
+        0 => ChunkCoords(
+            coords.0 - TILE_SIZE + (rank * TILE_SIZE),
+            coords.1 + CHUNK_SIZE,
+        ),
+        1 => ChunkCoords(
+            coords.0 + CHUNK_SIZE,
+            coords.1 + CHUNK_SIZE - (rank * TILE_SIZE),
+        ),
+        2 => ChunkCoords(
+            coords.0 + CHUNK_SIZE - (rank * TILE_SIZE),
+            coords.1 - TILE_SIZE,
+        ),
+        _ => ChunkCoords(
+            coords.0 - TILE_SIZE,
+            coords.1 - TILE_SIZE + (rank * TILE_SIZE),
+        ),
+    }
+}
@@ -0,0 +1,397 @@
+use log::{debug, warn};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{schematic::Schematic, CHUNK_TILE_LENGTH};
+
+// Upper bound on enumerated patterns, large schematics quickly explode at bigger sizes
+const MAX_PATTERNS: usize = 4096;
+
+type Pattern = Vec<Vec<u8>>;
+
+// Every NxN pattern of a schematic with the patterns allowed next to it, enumerated once and shared
+// by every chunk collapsed from the schematic
+#[derive(Debug)]
+pub struct PatternSet {
+    size: usize,
+    patterns: Vec<Pattern>,
+    // Product of the weights of each pattern's tiles
+    weights: Vec<f32>,
+    // Patterns allowed to the east and north of each pattern
+    east: Vec<Vec<usize>>,
+    north: Vec<Vec<usize>>,
+}
+
+impl PatternSet {
+    pub fn build(schematic: &Schematic, size: usize) -> Option<PatternSet> {
+        if size < 2 || !(CHUNK_TILE_LENGTH as usize).is_multiple_of(size) {
+            warn!(
+                "Pattern size {} does not divide chunk length {}, falling back to tiles",
+                size, CHUNK_TILE_LENGTH
+            );
+            return None;
+        }
+
+        let patterns = Self::enumerate_patterns(schematic, size);
+        if patterns.is_empty() {
+            warn!("Schematic has no valid {}x{} patterns", size, size);
+            return None;
+        }
+
+        debug!("Collapsing with {} patterns", patterns.len());
+
+        let weights = patterns
+            .iter()
+            .map(|pattern| {
+                pattern
+                    .iter()
+                    .flatten()
+                    .map(|tile| schematic.tiles[tile].weight as f32)
+                    .product()
+            })
+            .collect();
+
+        let east = patterns
+            .iter()
+            .map(|from| {
+                (0..patterns.len())
+                    .filter(|to| {
+                        (0..size)
+                            .all(|y| horizontal(schematic, from[size - 1][y], patterns[*to][0][y]))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let north = patterns
+            .iter()
+            .map(|from| {
+                (0..patterns.len())
+                    .filter(|to| {
+                        (0..size)
+                            .all(|x| vertical(schematic, from[x][size - 1], patterns[*to][x][0]))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Some(PatternSet {
+            size,
+            patterns,
+            weights,
+            east,
+            north,
+        })
+    }
+
+    // Backtracking fill of every internally consistent pattern, column major to match chunk tiles
+    fn enumerate_patterns(schematic: &Schematic, size: usize) -> Vec<Pattern> {
+        let mut keys: Vec<u8> = schematic
+            .tiles
+            .keys()
+            .copied()
+            .filter(|key| *key != schematic.not_found)
+            .collect();
+        keys.sort();
+
+        let mut patterns = Vec::new();
+        let mut current = vec![vec![0; size]; size];
+
+        Self::fill(schematic, &keys, size, 0, &mut current, &mut patterns);
+
+        if patterns.len() >= MAX_PATTERNS {
+            warn!("Pattern limit of {} reached", MAX_PATTERNS);
+        }
+
+        patterns
+    }
+
+    fn fill(
+        schematic: &Schematic,
+        keys: &[u8],
+        size: usize,
+        cell: usize,
+        current: &mut Pattern,
+        patterns: &mut Vec<Pattern>,
+    ) {
+        if patterns.len() >= MAX_PATTERNS {
+            return;
+        }
+
+        if cell == size * size {
+            patterns.push(current.clone());
+            return;
+        }
+
+        let (x, y) = (cell / size, cell % size);
+
+        for key in keys {
+            if x > 0 && !horizontal(schematic, current[x - 1][y], *key) {
+                continue;
+            }
+
+            if y > 0 && !vertical(schematic, current[x][y - 1], *key) {
+                continue;
+            }
+
+            current[x][y] = *key;
+            Self::fill(schematic, keys, size, cell + 1, current, patterns);
+        }
+    }
+}
+
+// Collapses a chunk from NxN patterns of tiles rather than single tiles, giving more coherent features
+pub struct PatternCollapse<'a> {
+    set: &'a PatternSet,
+    candidates: Vec<Vec<Vec<usize>>>,
+    collapsed: Vec<Vec<Option<usize>>>,
+    rng: StdRng,
+}
+
+impl<'a> PatternCollapse<'a> {
+    pub fn init(set: &'a PatternSet, hash: u64) -> PatternCollapse<'a> {
+        let grid = CHUNK_TILE_LENGTH as usize / set.size;
+        let all: Vec<usize> = (0..set.patterns.len()).collect();
+
+        PatternCollapse {
+            set,
+            candidates: vec![vec![all; grid]; grid],
+            collapsed: vec![vec![None; grid]; grid],
+            rng: StdRng::seed_from_u64(hash),
+        }
+    }
+
+    pub fn collapse(&mut self) -> Vec<Vec<Option<u8>>> {
+        while let Some((x, y)) = self.lowest_entropy() {
+            let pick = self.pick(x, y);
+
+            self.collapsed[x][y] = Some(pick);
+            self.candidates[x][y].clear();
+            self.update_candidates();
+        }
+
+        let size = self.set.size;
+        let mut tiles = vec![vec![None; CHUNK_TILE_LENGTH as usize]; CHUNK_TILE_LENGTH as usize];
+
+        for (x, column) in self.collapsed.iter().enumerate() {
+            for (y, pattern) in column.iter().enumerate() {
+                if let Some(pattern) = pattern {
+                    for px in 0..size {
+                        for py in 0..size {
+                            tiles[x * size + px][y * size + py] =
+                                Some(self.set.patterns[*pattern][px][py]);
+                        }
+                    }
+                }
+            }
+        }
+
+        tiles
+    }
+
+    // Weighted by the pattern's tiles, candidates that all weigh nothing are picked evenly
+    fn pick(&mut self, x: usize, y: usize) -> usize {
+        let available = &self.candidates[x][y];
+        let total: f32 = available.iter().map(|idx| self.set.weights[*idx]).sum();
+
+        if total <= 0. {
+            return available[self.rng.gen_range(0..available.len())];
+        }
+
+        let mut roll = self.rng.gen_range(0.0..total);
+
+        for idx in available {
+            let weight = self.set.weights[*idx];
+            if roll < weight {
+                return *idx;
+            }
+            roll -= weight;
+        }
+
+        available[available.len() - 1]
+    }
+
+    fn lowest_entropy(&self) -> Option<(usize, usize)> {
+        let mut index = None;
+        let mut lowest = 0;
+
+        for (x, column) in self.candidates.iter().enumerate() {
+            for (y, candidates) in column.iter().enumerate() {
+                let n_candidates = candidates.len();
+                if n_candidates > 0 && (lowest == 0 || n_candidates < lowest) {
+                    lowest = n_candidates;
+                    index = Some((x, y));
+                }
+            }
+        }
+
+        index
+    }
+
+    fn update_candidates(&mut self) {
+        let grid = self.collapsed.len();
+        let set = self.set;
+
+        for x in 0..grid {
+            for y in 0..grid {
+                if self.collapsed[x][y].is_some() {
+                    continue;
+                }
+
+                let mut allowed = std::mem::take(&mut self.candidates[x][y]);
+
+                if x > 0 {
+                    if let Some(left) = self.collapsed[x - 1][y] {
+                        allowed.retain(|candidate| set.east[left].contains(candidate));
+                    }
+                }
+
+                if y > 0 {
+                    if let Some(down) = self.collapsed[x][y - 1] {
+                        allowed.retain(|candidate| set.north[down].contains(candidate));
+                    }
+                }
+
+                if x + 1 < grid {
+                    if let Some(right) = self.collapsed[x + 1][y] {
+                        allowed.retain(|candidate| set.east[*candidate].contains(&right));
+                    }
+                }
+
+                if y + 1 < grid {
+                    if let Some(up) = self.collapsed[x][y + 1] {
+                        allowed.retain(|candidate| set.north[*candidate].contains(&up));
+                    }
+                }
+
+                self.candidates[x][y] = allowed;
+            }
+        }
+    }
+}
+
+// Whether right may sit east of left, checked from both tiles as the tile solver does
+fn horizontal(schematic: &Schematic, left: u8, right: u8) -> bool {
+    schematic.tiles[&left].east.contains(&right) && schematic.tiles[&right].west.contains(&left)
+}
+
+fn vertical(schematic: &Schematic, down: u8, up: u8) -> bool {
+    schematic.tiles[&down].north.contains(&up) && schematic.tiles[&up].south.contains(&down)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schematic::parse_schematic;
+
+    // Light and dark tiles may only sit next to the other, any valid chunk is a checkerboard
+    fn checkerboard() -> Schematic {
+        parse_schematic(
+            br#"{
+                "version": 2,
+                "not_found": "missing",
+                "pattern_size": 2,
+                "tiles": {
+                    "missing": { "index": 9, "sheet": "terrain", "weight": 0 },
+                    "light": {
+                        "index": 0,
+                        "sheet": "terrain",
+                        "weight": 1,
+                        "rules": { "north": ["dark"], "east": ["dark"], "south": ["dark"], "west": ["dark"] }
+                    },
+                    "dark": {
+                        "index": 1,
+                        "sheet": "terrain",
+                        "weight": 1,
+                        "rules": { "north": ["light"], "east": ["light"], "south": ["light"], "west": ["light"] }
+                    }
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn patterns_are_internally_consistent() {
+        let patterns = PatternSet::enumerate_patterns(&checkerboard(), 2);
+
+        assert_eq!(
+            patterns,
+            vec![vec![vec![0, 1], vec![1, 0]], vec![vec![1, 0], vec![0, 1]]]
+        );
+    }
+
+    #[test]
+    fn collapsed_patterns_fit_together() {
+        let schematic = checkerboard();
+        let tiles = PatternCollapse::init(schematic.patterns().unwrap(), 7).collapse();
+        let length = CHUNK_TILE_LENGTH as usize;
+
+        for x in 0..length {
+            for y in 0..length {
+                let tile = tiles[x][y].expect("every cell is covered by a pattern");
+
+                if x + 1 < length {
+                    assert!(horizontal(&schematic, tile, tiles[x + 1][y].unwrap()));
+                }
+                if y + 1 < length {
+                    assert!(vertical(&schematic, tile, tiles[x][y + 1].unwrap()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sizes_must_divide_the_chunk() {
+        let schematic = checkerboard();
+
+        assert!(PatternSet::build(&schematic, 1).is_none());
+        assert!(PatternSet::build(&schematic, 3).is_none());
+    }
+
+    #[test]
+    fn patterns_are_shared_between_clones() {
+        let schematic = checkerboard();
+        let clone = schematic.clone();
+
+        assert!(std::ptr::eq(
+            schematic.patterns().unwrap(),
+            clone.patterns().unwrap()
+        ));
+    }
+
+    #[test]
+    fn patterns_are_picked_by_weight() {
+        // Sand weighs nothing, only patterns of grass alone can be picked
+        let schematic = parse_schematic(
+            br#"{
+                "version": 2,
+                "not_found": "missing",
+                "pattern_size": 2,
+                "tiles": {
+                    "missing": { "index": 9, "sheet": "terrain", "weight": 0 },
+                    "grass": {
+                        "index": 0,
+                        "sheet": "terrain",
+                        "weight": 3,
+                        "symmetric": true,
+                        "rules": { "north": ["grass", "sand"], "east": ["grass", "sand"] }
+                    },
+                    "sand": {
+                        "index": 1,
+                        "sheet": "terrain",
+                        "weight": 0,
+                        "symmetric": true,
+                        "rules": { "north": ["grass", "sand"], "east": ["grass", "sand"] }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        for seed in 0..8 {
+            let tiles = PatternCollapse::init(schematic.patterns().unwrap(), seed).collapse();
+            assert!(tiles.iter().flatten().all(|tile| *tile == Some(0)));
+        }
+    }
+}
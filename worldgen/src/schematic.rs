@@ -0,0 +1,762 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    io::ErrorKind,
+    sync::{Arc, OnceLock},
+};
+
+use log::warn;
+
+use serde::{Deserialize, Serialize};
+
+use super::pattern::PatternSet;
+
+pub(crate) const SCHEMATIC_VERSION: u64 = 2;
+
+#[derive(Clone, Debug)]
+pub struct Schematic {
+    pub not_found: u8,
+    // Side length of the patterns collapsed together, 1 collapses single tiles
+    pub pattern_size: u8,
+    pub tiles: HashMap<u8, TileSchematic>,
+    pub constraints: Vec<ChunkConstraint>,
+    // Tile sets of each biome ordered by name, tiles in none of them appear in every biome
+    pub biomes: Vec<HashSet<u8>>,
+    // Screen tint of each biome in the same order, None leaves the view as it is
+    pub grading: Vec<Option<[f32; 4]>>,
+    // Weather odds of each biome in the same order, None falls back to the game's default odds
+    pub weather: Vec<Option<BiomeWeather>>,
+    // Patterns enumerated on first use, shared with every clone so chunks don't enumerate them again
+    patterns: Arc<OnceLock<Option<PatternSet>>>,
+}
+
+impl Schematic {
+    // None when the schematic collapses single tiles or has no usable patterns
+    pub fn patterns(&self) -> Option<&PatternSet> {
+        if self.pattern_size < 2 {
+            return None;
+        }
+
+        self.patterns
+            .get_or_init(|| PatternSet::build(self, self.pattern_size as usize))
+            .as_ref()
+    }
+}
+
+// Relative odds of each kind of weather, states left out never happen in the biome
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BiomeWeather {
+    pub clear: f32,
+    pub rain: f32,
+    pub storm: f32,
+    pub snow: f32,
+}
+
+// Chunk wide bound on how many of the given tiles may be collapsed
+#[derive(Clone, Debug)]
+pub struct ChunkConstraint {
+    pub tiles: Vec<u8>,
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+    pub min_ratio: Option<f32>,
+    pub max_ratio: Option<f32>,
+}
+
+impl ChunkConstraint {
+    // Resolves counts and ratios into an inclusive range of tile counts for a chunk of the given area
+    pub fn bounds(&self, area: usize) -> (usize, usize) {
+        let mut min = self.min.unwrap_or(0) as usize;
+        let mut max = self.max.map(|max| max as usize).unwrap_or(area);
+
+        if let Some(ratio) = self.min_ratio {
+            min = min.max((ratio * area as f32).ceil() as usize);
+        }
+
+        if let Some(ratio) = self.max_ratio {
+            max = max.min((ratio * area as f32).floor() as usize);
+        }
+
+        (min, max)
+    }
+}
+
+// Legacy format, tiles keyed by atlas index with directions keyed "0" to "3"
+#[derive(Clone, Debug, Deserialize)]
+struct SchematicJson {
+    pub not_found: u8,
+    #[serde(flatten)]
+    pub tiles: HashMap<String, TileSchematic>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct SchematicJsonV2 {
+    pub version: u64,
+    pub not_found: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern_size: Option<u8>,
+    pub tiles: BTreeMap<String, TileSchematicV2>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constraints: Vec<ChunkConstraintV2>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub biomes: BTreeMap<String, Vec<String>>,
+    // Tint laid over the view inside each named biome, rgb then strength
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub grading: BTreeMap<String, [f32; 4]>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub weather: BTreeMap<String, BiomeWeather>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct ChunkConstraintV2 {
+    pub tiles: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_ratio: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_ratio: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    North,
+    East,
+    South,
+    West,
+}
+
+// Where a layer is drawn, decorations lie on the ground under everything standing on it
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LayerKind {
+    Decoration,
+    Overlay,
+}
+
+// Extra sprite drawn over a tile, such as flowers on grass or a canopy over a trunk
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TileLayer {
+    pub layer: LayerKind,
+    pub index: u8,
+    // Share of the tile's cells showing the layer
+    #[serde(default = "always")]
+    pub chance: f32,
+}
+
+fn always() -> f32 {
+    1.
+}
+
+// Items a harvest drops, each entry rolled on its own
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HarvestYield {
+    pub item: u32,
+    #[serde(default = "one")]
+    pub min: u32,
+    #[serde(default = "one")]
+    pub max: u32,
+    #[serde(default = "always")]
+    pub chance: f32,
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn harvest_seconds() -> f32 {
+    1.
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct TileHarvestV2 {
+    // Tile left behind once harvested, such as a stump for a tree
+    pub into: String,
+    #[serde(default = "harvest_seconds")]
+    pub seconds: f32,
+    #[serde(default, rename = "yield")]
+    pub yields: Vec<HarvestYield>,
+}
+
+// Harvestable tiles are held near for a while and turn into another tile, dropping their yield
+#[derive(Clone, Debug)]
+pub struct TileHarvest {
+    pub into: u8,
+    pub seconds: f32,
+    pub yields: Vec<HarvestYield>,
+}
+
+impl Side {
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::North => Side::South,
+            Side::East => Side::West,
+            Side::South => Side::North,
+            Side::West => Side::East,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct TileSchematicV2 {
+    pub index: u8,
+    pub sheet: String,
+    pub weight: u8,
+    // Solid tiles block movement and pathfinding
+    #[serde(default)]
+    pub solid: bool,
+    // Water tiles are the only ones aquatic enemies move through, and slow everyone else
+    #[serde(default)]
+    pub water: bool,
+    // Road tiles are joined across water by bridges
+    #[serde(default)]
+    pub road: bool,
+    // Placed over water where a road crosses it, walkable and drawn above the water
+    #[serde(default)]
+    pub bridge: bool,
+    // Mirrors every rule onto the neighbor, north onto south and east onto west
+    #[serde(default)]
+    pub symmetric: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<TileLayer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub harvest: Option<TileHarvestV2>,
+    #[serde(default)]
+    pub rules: BTreeMap<Side, Vec<String>>,
+}
+
+impl TileSchematic {
+    // Whether walkers can stand on the tile, rock and water both stop them
+    pub fn passable(&self) -> bool {
+        !self.solid && !self.water
+    }
+
+    pub fn side(&self, side: Side) -> &[u8] {
+        match side {
+            Side::North => &self.north,
+            Side::East => &self.east,
+            Side::South => &self.south,
+            Side::West => &self.west,
+        }
+    }
+
+    pub fn side_mut(&mut self, side: Side) -> &mut Vec<u8> {
+        match side {
+            Side::North => &mut self.north,
+            Side::East => &mut self.east,
+            Side::South => &mut self.south,
+            Side::West => &mut self.west,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TileSchematic {
+    pub name: String,
+    pub sheet: String,
+    pub weight: u8,
+    #[serde(default)]
+    pub solid: bool,
+    #[serde(default)]
+    pub water: bool,
+    #[serde(default)]
+    pub road: bool,
+    #[serde(default)]
+    pub bridge: bool,
+    #[serde(default)]
+    pub layers: Vec<TileLayer>,
+    // Legacy schematics predate harvesting
+    #[serde(skip)]
+    pub harvest: Option<TileHarvest>,
+    #[serde(rename = "0")]
+    pub north: Vec<u8>,
+    #[serde(rename = "1")]
+    pub east: Vec<u8>,
+    #[serde(rename = "2")]
+    pub south: Vec<u8>,
+    #[serde(rename = "3")]
+    pub west: Vec<u8>,
+}
+
+pub(crate) fn invalid_data(msg: String) -> std::io::Error {
+    std::io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+pub fn parse_schematic(bytes: &[u8]) -> Result<Schematic, std::io::Error> {
+    let value = serde_json::from_slice::<serde_json::Value>(bytes)
+        .map_err(|err| invalid_data(format!("Failed to deserialize Json File! Err {err}")))?;
+
+    match value.get("version").and_then(|version| version.as_u64()) {
+        Some(SCHEMATIC_VERSION) => {
+            let data = serde_json::from_value::<SchematicJsonV2>(value).map_err(|err| {
+                invalid_data(format!("Failed to deserialize Json File! Err {err}"))
+            })?;
+
+            from_v2(data)
+        }
+        Some(version) => Err(invalid_data(format!(
+            "Unsupported schematic version {version}!"
+        ))),
+        None => {
+            warn!("Loading legacy schematic, run convert-schematic to upgrade it");
+
+            let data = serde_json::from_value::<SchematicJson>(value).map_err(|err| {
+                invalid_data(format!("Failed to deserialize Json File! Err {err}"))
+            })?;
+
+            from_legacy(data)
+        }
+    }
+}
+
+fn from_legacy(data: SchematicJson) -> Result<Schematic, std::io::Error> {
+    let mut tiles = HashMap::new();
+
+    for (key, val) in data.tiles {
+        let idx = key
+            .parse::<u8>()
+            .map_err(|err| invalid_data(format!("Invalid tile index {key}! Err {err}")))?;
+
+        tiles.insert(idx, val);
+    }
+
+    Ok(Schematic {
+        not_found: data.not_found,
+        pattern_size: 1,
+        tiles,
+        constraints: Vec::new(),
+        biomes: Vec::new(),
+        grading: Vec::new(),
+        weather: Vec::new(),
+        patterns: Arc::default(),
+    })
+}
+
+fn from_v2(data: SchematicJsonV2) -> Result<Schematic, std::io::Error> {
+    let lookup = |name: &String| {
+        data.tiles
+            .get(name)
+            .map(|tile| tile.index)
+            .ok_or_else(|| invalid_data(format!("Unknown tile {name} in schematic!")))
+    };
+
+    let mut tiles = HashMap::new();
+
+    for (name, tile) in data.tiles.iter() {
+        let harvest = match tile.harvest.as_ref() {
+            Some(harvest) => Some(TileHarvest {
+                into: lookup(&harvest.into)?,
+                seconds: harvest.seconds.max(0.),
+                yields: harvest
+                    .yields
+                    .iter()
+                    .map(|entry| HarvestYield {
+                        max: entry.max.max(entry.min),
+                        chance: entry.chance.clamp(0., 1.),
+                        ..entry.clone()
+                    })
+                    .collect(),
+            }),
+            None => None,
+        };
+
+        let previous = tiles.insert(
+            tile.index,
+            TileSchematic {
+                name: name.clone(),
+                sheet: tile.sheet.clone(),
+                weight: tile.weight,
+                solid: tile.solid,
+                water: tile.water,
+                road: tile.road,
+                bridge: tile.bridge,
+                layers: tile
+                    .layers
+                    .iter()
+                    .map(|layer| TileLayer {
+                        chance: layer.chance.clamp(0., 1.),
+                        ..layer.clone()
+                    })
+                    .collect(),
+                harvest,
+                north: Vec::new(),
+                east: Vec::new(),
+                south: Vec::new(),
+                west: Vec::new(),
+            },
+        );
+
+        // Tiles sharing an index would overwrite each other's rules
+        if let Some(previous) = previous {
+            return Err(invalid_data(format!(
+                "Duplicate tile index {} for {} and {name} in schematic!",
+                tile.index, previous.name
+            )));
+        }
+    }
+
+    for tile in data.tiles.values() {
+        for (side, neighbors) in tile.rules.iter() {
+            for neighbor in neighbors {
+                let neighbor_idx = lookup(neighbor)?;
+
+                push_unique(tiles.get_mut(&tile.index), *side, neighbor_idx);
+
+                if tile.symmetric {
+                    push_unique(tiles.get_mut(&neighbor_idx), side.opposite(), tile.index);
+                }
+            }
+        }
+    }
+
+    let mut constraints = Vec::new();
+
+    for constraint in data.constraints.iter() {
+        constraints.push(ChunkConstraint {
+            tiles: constraint
+                .tiles
+                .iter()
+                .map(lookup)
+                .collect::<Result<Vec<u8>, _>>()?,
+            min: constraint.min,
+            max: constraint.max,
+            min_ratio: constraint.min_ratio,
+            max_ratio: constraint.max_ratio,
+        });
+    }
+
+    let mut biomes = Vec::new();
+
+    for biome_tiles in data.biomes.values() {
+        biomes.push(
+            biome_tiles
+                .iter()
+                .map(lookup)
+                .collect::<Result<HashSet<u8>, _>>()?,
+        );
+    }
+
+    if let Some(name) = data
+        .grading
+        .keys()
+        .find(|name| !data.biomes.contains_key(*name))
+    {
+        return Err(invalid_data(format!("Unknown biome {name} in grading!")));
+    }
+
+    let grading = data
+        .biomes
+        .keys()
+        .map(|name| data.grading.get(name).copied())
+        .collect();
+
+    if let Some(name) = data
+        .weather
+        .keys()
+        .find(|name| !data.biomes.contains_key(*name))
+    {
+        return Err(invalid_data(format!("Unknown biome {name} in weather!")));
+    }
+
+    let weather = data
+        .biomes
+        .keys()
+        .map(|name| data.weather.get(name).copied())
+        .collect();
+
+    Ok(Schematic {
+        not_found: lookup(&data.not_found)?,
+        pattern_size: data.pattern_size.unwrap_or(1),
+        tiles,
+        constraints,
+        biomes,
+        grading,
+        weather,
+        patterns: Arc::default(),
+    })
+}
+
+fn push_unique(tile: Option<&mut TileSchematic>, side: Side, idx: u8) {
+    if let Some(tile) = tile {
+        let allowed = tile.side_mut(side);
+        if !allowed.contains(&idx) {
+            allowed.push(idx);
+        }
+    }
+}
+
+// Converts a legacy schematic into the named v2 format
+fn convert_legacy(bytes: &[u8]) -> Result<String, std::io::Error> {
+    let legacy = from_legacy(
+        serde_json::from_slice::<SchematicJson>(bytes)
+            .map_err(|err| invalid_data(format!("Failed to deserialize Json File! Err {err}")))?,
+    )?;
+
+    let name_of = |idx: &u8| {
+        legacy
+            .tiles
+            .get(idx)
+            .map(|tile| tile.name.clone())
+            .ok_or_else(|| invalid_data(format!("Unknown tile index {idx} in schematic!")))
+    };
+
+    let names_of = |indices: &Vec<u8>| indices.iter().map(name_of).collect::<Result<Vec<_>, _>>();
+
+    let mut tiles = BTreeMap::new();
+
+    for (idx, tile) in legacy.tiles.iter() {
+        let mut rules = BTreeMap::new();
+        rules.insert(Side::North, names_of(&tile.north)?);
+        rules.insert(Side::East, names_of(&tile.east)?);
+        rules.insert(Side::South, names_of(&tile.south)?);
+        rules.insert(Side::West, names_of(&tile.west)?);
+
+        let previous = tiles.insert(
+            tile.name.clone(),
+            TileSchematicV2 {
+                index: *idx,
+                sheet: tile.sheet.clone(),
+                weight: tile.weight,
+                solid: tile.solid,
+                water: tile.water,
+                road: tile.road,
+                bridge: tile.bridge,
+                symmetric: false,
+                layers: tile.layers.clone(),
+                harvest: None,
+                rules,
+            },
+        );
+
+        // Tiles sharing a name would overwrite each other once keyed by it
+        if let Some(previous) = previous {
+            return Err(invalid_data(format!(
+                "Duplicate tile name {} for indices {} and {idx} in schematic!",
+                tile.name, previous.index
+            )));
+        }
+    }
+
+    let converted = SchematicJsonV2 {
+        version: SCHEMATIC_VERSION,
+        not_found: name_of(&legacy.not_found)?,
+        pattern_size: None,
+        tiles,
+        constraints: Vec::new(),
+        biomes: BTreeMap::new(),
+        grading: BTreeMap::new(),
+        weather: BTreeMap::new(),
+    };
+
+    serde_json::to_string_pretty(&converted)
+        .map_err(|err| invalid_data(format!("Failed to serialize schematic! Err {err}")))
+}
+
+pub fn convert_schematic_file(input: &str, output: &str) -> Result<(), std::io::Error> {
+    let converted = convert_legacy(&fs::read(input)?)?;
+    fs::write(output, converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V2: &[u8] = br#"{
+        "version": 2,
+        "not_found": "missing",
+        "tiles": {
+            "missing": { "index": 9, "sheet": "terrain", "weight": 0 },
+            "grass": {
+                "index": 0,
+                "sheet": "terrain",
+                "weight": 10,
+                "symmetric": true,
+                "rules": { "north": ["grass", "sand"], "east": ["grass"] }
+            },
+            "sand": {
+                "index": 1,
+                "sheet": "terrain",
+                "weight": 5,
+                "layers": [{ "layer": "decoration", "index": 4, "chance": 2.0 }]
+            }
+        },
+        "constraints": [{ "tiles": ["sand"], "max": 20, "min_ratio": 0.25 }],
+        "biomes": { "beach": ["sand"], "meadow": ["grass"] },
+        "grading": { "beach": [1.0, 0.9, 0.6, 0.2] }
+    }"#;
+
+    const LEGACY: &[u8] = br#"{
+        "not_found": 9,
+        "0": { "name": "grass", "sheet": "terrain", "weight": 10, "0": [0, 1], "1": [0], "2": [0], "3": [0] },
+        "1": { "name": "sand", "sheet": "terrain", "weight": 5, "0": [], "1": [], "2": [0], "3": [] },
+        "9": { "name": "missing", "sheet": "terrain", "weight": 0, "0": [], "1": [], "2": [], "3": [] }
+    }"#;
+
+    #[test]
+    fn v2_rules_resolve_by_name() {
+        let schematic = parse_schematic(V2).unwrap();
+
+        assert_eq!(schematic.not_found, 9);
+        assert_eq!(schematic.pattern_size, 1);
+
+        let grass = &schematic.tiles[&0];
+        assert_eq!(grass.north, vec![0, 1]);
+        assert_eq!(grass.east, vec![0]);
+
+        // Symmetric rules are mirrored onto the neighbor
+        assert_eq!(grass.south, vec![0]);
+        assert_eq!(grass.west, vec![0]);
+        assert_eq!(schematic.tiles[&1].south, vec![0]);
+
+        assert_eq!(schematic.tiles[&1].layers[0].chance, 1.);
+    }
+
+    #[test]
+    fn v2_constraints_and_biomes_resolve() {
+        let schematic = parse_schematic(V2).unwrap();
+
+        assert_eq!(schematic.constraints[0].tiles, vec![1]);
+        assert_eq!(schematic.constraints[0].bounds(64), (16, 20));
+
+        // Biomes are ordered by name, grading and weather follow the same order
+        assert_eq!(
+            schematic.biomes,
+            vec![HashSet::from([1]), HashSet::from([0])]
+        );
+        assert_eq!(schematic.grading, vec![Some([1.0, 0.9, 0.6, 0.2]), None]);
+        assert_eq!(schematic.weather, vec![None, None]);
+    }
+
+    #[test]
+    fn v2_rejects_unknown_names_and_versions() {
+        let unknown_tile = br#"{
+            "version": 2,
+            "not_found": "missing",
+            "tiles": { "grass": { "index": 0, "sheet": "terrain", "weight": 1 } }
+        }"#;
+        assert!(parse_schematic(unknown_tile).is_err());
+
+        let unknown_biome = br#"{
+            "version": 2,
+            "not_found": "grass",
+            "tiles": { "grass": { "index": 0, "sheet": "terrain", "weight": 1 } },
+            "weather": { "tundra": { "snow": 1.0 } }
+        }"#;
+        assert!(parse_schematic(unknown_biome).is_err());
+
+        assert!(parse_schematic(br#"{ "version": 3 }"#).is_err());
+    }
+
+    #[test]
+    fn v2_rejects_duplicate_indices() {
+        let duplicate = br#"{
+            "version": 2,
+            "not_found": "grass",
+            "tiles": {
+                "grass": { "index": 0, "sheet": "terrain", "weight": 1 },
+                "sand": { "index": 0, "sheet": "terrain", "weight": 1 }
+            }
+        }"#;
+
+        let err = parse_schematic(duplicate).unwrap_err();
+        assert!(err.to_string().contains("Duplicate tile index 0"));
+    }
+
+    #[test]
+    fn legacy_schematics_load() {
+        let schematic = parse_schematic(LEGACY).unwrap();
+
+        assert_eq!(schematic.not_found, 9);
+        assert_eq!(schematic.tiles[&0].name, "grass");
+        assert_eq!(schematic.tiles[&0].north, vec![0, 1]);
+        assert_eq!(schematic.tiles[&1].south, vec![0]);
+    }
+
+    #[test]
+    fn converted_legacy_schematics_keep_their_rules() {
+        let legacy = parse_schematic(LEGACY).unwrap();
+        let converted = parse_schematic(convert_legacy(LEGACY).unwrap().as_bytes()).unwrap();
+
+        assert_eq!(converted.not_found, legacy.not_found);
+        assert_eq!(converted.tiles.len(), legacy.tiles.len());
+
+        for (idx, tile) in legacy.tiles.iter() {
+            let converted = &converted.tiles[idx];
+
+            assert_eq!(converted.name, tile.name);
+            for side in [Side::North, Side::East, Side::South, Side::West] {
+                assert_eq!(converted.side(side), tile.side(side));
+            }
+        }
+    }
+
+    #[test]
+    fn converting_rejects_duplicate_names() {
+        let duplicate = br#"{
+            "not_found": 0,
+            "0": { "name": "grass", "sheet": "terrain", "weight": 1, "0": [], "1": [], "2": [], "3": [] },
+            "1": { "name": "grass", "sheet": "terrain", "weight": 1, "0": [], "1": [], "2": [], "3": [] }
+        }"#;
+
+        let err = convert_legacy(duplicate).unwrap_err();
+        assert!(err.to_string().contains("Duplicate tile name grass"));
+    }
+
+    #[test]
+    fn harvest_tables_resolve_their_tiles() {
+        let schematic = parse_schematic(
+            br#"{
+                "version": 2,
+                "not_found": "grass",
+                "tiles": {
+                    "grass": { "index": 0, "sheet": "terrain", "weight": 10 },
+                    "stump": { "index": 1, "sheet": "terrain", "weight": 0 },
+                    "tree": {
+                        "index": 2,
+                        "sheet": "terrain",
+                        "weight": 5,
+                        "solid": true,
+                        "harvest": {
+                            "into": "stump",
+                            "seconds": 2.5,
+                            "yield": [
+                                { "item": 0, "min": 2, "max": 4 },
+                                { "item": 3, "min": 3, "max": 1, "chance": 1.5 }
+                            ]
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let harvest = schematic.tiles[&2].harvest.as_ref().unwrap();
+        assert_eq!(harvest.into, 1);
+        assert_eq!(harvest.seconds, 2.5);
+
+        // Ranges and chances out of bounds are pulled back into them
+        assert_eq!((harvest.yields[0].min, harvest.yields[0].max), (2, 4));
+        assert_eq!((harvest.yields[1].min, harvest.yields[1].max), (3, 3));
+        assert_eq!(harvest.yields[1].chance, 1.);
+        assert!(schematic.tiles[&0].harvest.is_none());
+
+        let unknown = parse_schematic(
+            br#"{
+                "version": 2,
+                "not_found": "tree",
+                "tiles": {
+                    "tree": {
+                        "index": 2,
+                        "sheet": "terrain",
+                        "weight": 5,
+                        "harvest": { "into": "stump" }
+                    }
+                }
+            }"#,
+        );
+        assert!(unknown.is_err());
+    }
+}
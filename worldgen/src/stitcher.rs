@@ -0,0 +1,400 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use glam::IVec2;
+
+use log::debug;
+
+use super::{
+    get_perimeter_world_coord,
+    schematic::{Schematic, Side},
+    ChunkCoords, CHUNK_SIZE, CHUNK_TILE_LENGTH, TILE_SIZE,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const LENGTH: i32 = CHUNK_TILE_LENGTH as i32;
+
+const SEAM_LENGTH: usize = (4 * CHUNK_TILE_LENGTH + 4) as usize;
+
+// Sides in stitching order, with the direction of the neighboring chunk on each
+const SIDES: [Side; 4] = [Side::North, Side::East, Side::South, Side::West];
+const OUTWARD: [(i64, i64); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+// Tile ids of a generated chunk, by cell counted in tiles from the bottom left of its interior.
+// Cells of the stitched seams sit at -1 and CHUNK_TILE_LENGTH, and are None until the chunk is stitched
+pub trait TileLookup {
+    fn tile(&self, cell: IVec2) -> Option<u8>;
+}
+
+// Neighboring chunks to the north, east, south and west
+pub type Adjacencies<'a> = (
+    Option<&'a dyn TileLookup>,
+    Option<&'a dyn TileLookup>,
+    Option<&'a dyn TileLookup>,
+    Option<&'a dyn TileLookup>,
+);
+
+pub struct Stitcher<'a> {
+    schematic: &'a Schematic,
+    constraint_map: Vec<HashSet<u8>>,
+    tiles: Vec<Option<u8>>,
+    rng: StdRng,
+}
+
+impl<'a> Stitcher<'a> {
+    pub fn init(
+        world_seed: u64,
+        schematic: &'a Schematic,
+        coords: ChunkCoords,
+        chunk: &dyn TileLookup,
+        adj: Adjacencies,
+    ) -> Stitcher<'a> {
+        Stitcher {
+            schematic,
+            constraint_map: Self::init_stitching_constaints(schematic, coords, chunk, adj),
+            tiles: vec![None; SEAM_LENGTH],
+            rng: StdRng::seed_from_u64(Self::get_hash(world_seed, &coords)),
+        }
+    }
+
+    // Fixes seam cells, counted from the bottom left of the chunk's interior, before stitching.
+    // Cells without a neighboring chunk to stitch against are left alone
+    pub fn pin(&mut self, fixed: &[(IVec2, u8)]) {
+        for idx in 0..SEAM_LENGTH {
+            if self.constraint_map[idx].is_empty() {
+                continue;
+            }
+
+            let cell = seam_cell(idx);
+
+            let Some(&(_, tile)) = fixed
+                .iter()
+                .find(|(fixed, tile)| *fixed == cell && self.schematic.tiles.contains_key(tile))
+            else {
+                continue;
+            };
+
+            self.tiles[idx] = Some(tile);
+            self.update_constraint_map(idx, tile);
+        }
+    }
+
+    pub fn stitch(&mut self) -> &Vec<Option<u8>> {
+        // Collapse Chunk
+        while let Some(next) = self.lowest_entropy() {
+            let tile = self.collapse_tile(next);
+            self.tiles[next] = Some(tile);
+            self.update_constraint_map(next, tile);
+        }
+
+        debug!("{:?}", self.tiles);
+        &self.tiles
+    }
+
+    fn lowest_entropy(&self) -> Option<usize> {
+        let mut index = None;
+        let mut lowest = 0;
+
+        for (idx, constraint) in self.constraint_map.iter().enumerate() {
+            let n_constraints = constraint.len();
+            if n_constraints > 0 && (lowest == 0 || n_constraints < lowest) {
+                lowest = n_constraints;
+                index = Some(idx);
+            }
+        }
+
+        #[cfg(feature = "trace")]
+        if let Some(index) = index {
+            log::trace!("Entropy minima: ({})", index);
+        }
+
+        index
+    }
+
+    // Only the seam cells on either side of a collapsed one are affected by it, the
+    // surrounding chunks were already accounted for when the stitcher was made
+    fn update_constraint_map(&mut self, idx: usize, tile: u8) {
+        self.constraint_map[idx].clear();
+
+        let Some(collapsed) = self.schematic.tiles.get(&tile) else {
+            return;
+        };
+
+        let cell = seam_cell(idx);
+
+        for neighbor in [
+            (idx + SEAM_LENGTH - 1) % SEAM_LENGTH,
+            (idx + 1) % SEAM_LENGTH,
+        ] {
+            let allowed = collapsed.side(side_towards(seam_cell(neighbor) - cell));
+
+            self.constraint_map[neighbor].retain(|to_retain| allowed.contains(to_retain));
+        }
+    }
+
+    fn collapse_tile(&mut self, idx: usize) -> u8 {
+        #[cfg(feature = "trace")]
+        log::trace!("Collapsing stitched tile {}", idx);
+        // Sorted so the pick depends only on the seed, not on set iteration order
+        let mut available: Vec<u8> = self.constraint_map[idx].iter().copied().collect();
+        available.sort_unstable();
+        let rand = self.rng.gen_range(0..available.len());
+        available[rand]
+    }
+
+    fn get_hash(world_seed: u64, coords: &ChunkCoords) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (coords.0, coords.1, world_seed, "stitch").hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Seam cells facing a neighboring chunk are stitched, each starts out limited by the tiles
+    // of the chunk and its neighbor next to it, which never change while stitching
+    fn init_stitching_constaints(
+        schematic: &Schematic,
+        coords: ChunkCoords,
+        chunk: &dyn TileLookup,
+        adj: Adjacencies,
+    ) -> Vec<HashSet<u8>> {
+        let unconstrained: HashSet<u8> = schematic.tiles.keys().copied().collect();
+        let neighbors = [adj.0, adj.1, adj.2, adj.3];
+
+        (0..SEAM_LENGTH)
+            .map(|idx| {
+                let side = idx / (LENGTH as usize + 1);
+                let rank = idx % (LENGTH as usize + 1);
+
+                // Corners sit between two sides and are stitched when either neighbor is present
+                let facing = if rank == 0 {
+                    [side, (side + 3) % 4]
+                } else {
+                    [side, side]
+                };
+
+                if facing.iter().all(|&facing| neighbors[facing].is_none()) {
+                    return HashSet::new();
+                }
+
+                let mut constraint = unconstrained.clone();
+
+                // Corners are checked against the side before them, apart from the north west one
+                let checked = if rank == 0 && side != 0 {
+                    side - 1
+                } else {
+                    side
+                };
+                let (dx, dy) = OUTWARD[checked];
+                let perim = get_perimeter_world_coord(&coords, side as i64, rank as i64);
+
+                let neighbor_tile = neighbors[checked].and_then(|neighbor| {
+                    tile_at(neighbor, perim.0 + dx * TILE_SIZE, perim.1 + dy * TILE_SIZE)
+                });
+
+                if let Some(tile) = neighbor_tile.and_then(|tile| schematic.tiles.get(&tile)) {
+                    let allowed = tile.side(SIDES[checked].opposite());
+                    constraint.retain(|to_retain| allowed.contains(to_retain));
+                }
+
+                // Corners don't touch the chunk's interior
+                let chunk_tile = (rank != 0)
+                    .then(|| tile_at(chunk, perim.0 - dx * TILE_SIZE, perim.1 - dy * TILE_SIZE))
+                    .flatten();
+
+                if let Some(tile) = chunk_tile.and_then(|tile| schematic.tiles.get(&tile)) {
+                    constraint.retain(|to_retain| tile.south.contains(to_retain));
+                }
+
+                constraint
+            })
+            .collect()
+    }
+}
+
+// Cell of a seam in the stitching order, North, East, South, West, each side starting at its corner
+pub fn seam_cell(index: usize) -> IVec2 {
+    let side = index / (LENGTH as usize + 1);
+    let rank = (index % (LENGTH as usize + 1)) as i32;
+
+    match side {
+        0 => IVec2::new(rank - 1, LENGTH),
+        1 => IVec2::new(LENGTH, LENGTH - rank),
+        2 => IVec2::new(LENGTH - rank, -1),
+        _ => IVec2::new(-1, rank - 1),
+    }
+}
+
+// Side of a cell its neighbor at the offset is on
+fn side_towards(offset: IVec2) -> Side {
+    match (offset.x, offset.y) {
+        (0, 1) => Side::North,
+        (1, 0) => Side::East,
+        (0, -1) => Side::South,
+        _ => Side::West,
+    }
+}
+
+// Tile whose bottom left corner sits at the offset from its chunk's center, in the stitcher's frame
+fn tile_at(grid: &dyn TileLookup, x: i64, y: i64) -> Option<u8> {
+    let (x, y) = (x + CHUNK_SIZE / 2, y + CHUNK_SIZE / 2);
+
+    if x.rem_euclid(TILE_SIZE) != 0 || y.rem_euclid(TILE_SIZE) != 0 {
+        return None;
+    }
+
+    grid.tile(IVec2::new(
+        x.div_euclid(TILE_SIZE) as i32,
+        y.div_euclid(TILE_SIZE) as i32,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schematic::parse_schematic, wfc::WaveFunctionCollapse};
+
+    // Chunk interior of a single tile
+    struct Filled(u8);
+
+    impl TileLookup for Filled {
+        fn tile(&self, cell: IVec2) -> Option<u8> {
+            (cell.cmpge(IVec2::ZERO).all() && cell.cmplt(IVec2::splat(LENGTH)).all())
+                .then_some(self.0)
+        }
+    }
+
+    fn schematic() -> Schematic {
+        parse_schematic(
+            br#"{
+                "version": 2,
+                "not_found": "grass",
+                "tiles": {
+                    "grass": {
+                        "index": 0,
+                        "sheet": "terrain",
+                        "weight": 1,
+                        "symmetric": true,
+                        "rules": { "north": ["grass", "sand", "dirt"], "east": ["grass", "sand", "dirt"] }
+                    },
+                    "sand": {
+                        "index": 1,
+                        "sheet": "terrain",
+                        "weight": 1,
+                        "symmetric": true,
+                        "rules": { "north": ["grass", "sand", "dirt"], "east": ["grass", "sand", "dirt"] }
+                    },
+                    "dirt": {
+                        "index": 2,
+                        "sheet": "terrain",
+                        "weight": 1,
+                        "symmetric": true,
+                        "rules": { "north": ["grass", "sand", "dirt"], "east": ["grass", "sand", "dirt"] }
+                    }
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn stitch(seed: u64, pinned: &[(IVec2, u8)]) -> Vec<Option<u8>> {
+        let schematic = schematic();
+        let (chunk, north, east, south, west) =
+            (Filled(0), Filled(0), Filled(1), Filled(2), Filled(0));
+
+        let mut stitcher = Stitcher::init(
+            seed,
+            &schematic,
+            ChunkCoords(0, 0),
+            &chunk,
+            (Some(&north), Some(&east), Some(&south), Some(&west)),
+        );
+        stitcher.pin(pinned);
+        stitcher.stitch().clone()
+    }
+
+    #[test]
+    fn stitching_is_deterministic_for_a_seed() {
+        let first = stitch(42, &[]);
+
+        assert_eq!(first.len(), SEAM_LENGTH);
+        assert!(first.iter().all(Option::is_some));
+        assert_eq!(first, stitch(42, &[]));
+    }
+
+    // Interior collapsed from the schematic, indexed by column then row like the collapse itself
+    struct Collapsed(Vec<Vec<Option<u8>>>);
+
+    impl TileLookup for Collapsed {
+        fn tile(&self, cell: IVec2) -> Option<u8> {
+            if cell.cmplt(IVec2::ZERO).any() || cell.cmpge(IVec2::splat(LENGTH)).any() {
+                return None;
+            }
+
+            self.0[cell.x as usize][cell.y as usize]
+        }
+    }
+
+    // Collapses a chunk and its neighbors from scratch, then stitches the chunk against them
+    fn generate(
+        seed: u64,
+        schematic: &Schematic,
+        coords: ChunkCoords,
+    ) -> (Collapsed, Vec<Option<u8>>) {
+        let step = CHUNK_SIZE + TILE_SIZE;
+        let collapse = |(x, y): (i64, i64)| {
+            let coords = ChunkCoords(coords.0 + x * step, coords.1 + y * step);
+            Collapsed(
+                WaveFunctionCollapse::init(seed, schematic, coords, &[])
+                    .collapse()
+                    .clone(),
+            )
+        };
+
+        let chunk = collapse((0, 0));
+        let [north, east, south, west] = OUTWARD.map(collapse);
+
+        let seams = Stitcher::init(
+            seed,
+            schematic,
+            coords,
+            &chunk,
+            (Some(&north), Some(&east), Some(&south), Some(&west)),
+        )
+        .stitch()
+        .clone();
+
+        (chunk, seams)
+    }
+
+    #[test]
+    fn generated_chunks_are_reproducible_for_a_seed() {
+        let schematic = parse_schematic(include_bytes!("../../assets/schematic.json")).unwrap();
+        let coords = ChunkCoords(-8192, -8192);
+
+        let (first_chunk, first_seams) = generate(1234, &schematic, coords);
+        let (second_chunk, second_seams) = generate(1234, &schematic, coords);
+
+        assert_eq!(first_chunk.0, second_chunk.0);
+        assert_eq!(first_seams, second_seams);
+    }
+
+    #[test]
+    fn pinned_seams_are_kept() {
+        let cell = seam_cell(3);
+        let tiles = stitch(42, &[(cell, 2)]);
+
+        assert_eq!(tiles[3], Some(2));
+    }
+
+    #[test]
+    fn seam_cells_ring_the_interior() {
+        let cells: HashSet<IVec2> = (0..SEAM_LENGTH).map(seam_cell).collect();
+
+        assert_eq!(cells.len(), SEAM_LENGTH);
+        assert!(cells
+            .iter()
+            .all(|cell| { cell.x == -1 || cell.y == -1 || cell.x == LENGTH || cell.y == LENGTH }));
+    }
+}
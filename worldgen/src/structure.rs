@@ -0,0 +1,240 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use glam::{IVec2, Vec2};
+
+use rand::{Rng, SeedableRng};
+
+use serde::Deserialize;
+
+use super::{ChunkCoords, CHUNK_TILE_LENGTH, TILE_SIZE};
+
+// Footprints of multi-tile props in tiles, free to overhang into neighboring chunks
+const PROP_SIZES: [(i64, i64); 3] = [(2, 2), (3, 2), (2, 3)];
+
+const MAX_PROPS_PER_CHUNK: u32 = 2;
+
+// Side length in tiles of the regions prefabs are scattered over, each holds at most one
+const PREFAB_REGION_TILES: i32 = 64;
+
+// Nest or portal standing in a structure, spawning its enemy until destroyed
+#[derive(Clone, Debug, Deserialize)]
+pub struct SpawnerDefinition {
+    // Key of the enemy kind it spawns
+    pub enemy: String,
+    // Portals are drawn differently but otherwise behave like nests
+    #[serde(default)]
+    pub portal: bool,
+    #[serde(default)]
+    pub offset: (f32, f32),
+    // Seconds between spawns
+    pub interval: f32,
+    // Rate limit on how many of its spawns may be alive at once
+    pub max_alive: usize,
+    pub health: u8,
+}
+
+impl SpawnerDefinition {
+    pub fn position(&self, center: Vec2) -> Vec2 {
+        center + Vec2::from(self.offset)
+    }
+}
+
+// Per structure settings, offsets are relative to the structure's center
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct StructureDefinition {
+    #[serde(default)]
+    pub guards: u32,
+    // Loop of waypoints guards walk in order
+    #[serde(default)]
+    pub patrol: Vec<(f32, f32)>,
+    // Rows of schematic tile names from the top, empty names are left to the collapse
+    #[serde(default)]
+    pub layout: Vec<Vec<String>>,
+    // Chance of the prefab standing in a region it is picked for
+    #[serde(default)]
+    pub chance: f64,
+    // Dialogues of the villagers standing around the prefab, one villager each
+    #[serde(default)]
+    pub npcs: Vec<String>,
+    // Bottom left and top right corners of a walled interior, the camera keeps inside them while
+    // the player is
+    #[serde(default)]
+    pub interior: Option<((f32, f32), (f32, f32))>,
+    // Nests and portals guarding the structure, a structure with any is a camp that can be cleared
+    #[serde(default)]
+    pub spawners: Vec<SpawnerDefinition>,
+}
+
+impl StructureDefinition {
+    pub fn patrol_route(&self, center: Vec2) -> Vec<Vec2> {
+        self.patrol
+            .iter()
+            .map(|(x, y)| center + Vec2::new(*x, *y))
+            .collect()
+    }
+
+    // Corners of the interior in world coordinates
+    pub fn interior_bounds(&self, center: Vec2) -> Option<(Vec2, Vec2)> {
+        self.interior
+            .map(|(min, max)| (center + Vec2::from(min), center + Vec2::from(max)))
+    }
+}
+
+// Tile layout stamped into the world over whatever the collapse would have picked
+#[derive(Clone, Debug)]
+pub struct Prefab {
+    // Rows from the top, None cells are collapsed as usual
+    pub tiles: Vec<Vec<Option<u8>>>,
+    pub chance: f64,
+}
+
+impl Prefab {
+    pub fn size(&self) -> IVec2 {
+        IVec2::new(
+            self.tiles.iter().map(Vec::len).max().unwrap_or(0) as i32,
+            self.tiles.len() as i32,
+        )
+    }
+}
+
+// Index and bottom left tile of every prefab standing in the regions between the two world tiles,
+// the second exclusive. Each region picks its prefab and spot from the seed alone, so every chunk a
+// prefab overlaps finds the same one
+pub fn prefab_origins(
+    seed: u64,
+    prefabs: &[Prefab],
+    from: IVec2,
+    to: IVec2,
+) -> Vec<(usize, IVec2)> {
+    let mut origins = Vec::new();
+
+    if prefabs.is_empty() {
+        return origins;
+    }
+
+    let first = IVec2::new(
+        from.x.div_euclid(PREFAB_REGION_TILES),
+        from.y.div_euclid(PREFAB_REGION_TILES),
+    );
+    let last = IVec2::new(
+        (to.x - 1).div_euclid(PREFAB_REGION_TILES),
+        (to.y - 1).div_euclid(PREFAB_REGION_TILES),
+    );
+
+    for region_x in first.x..=last.x {
+        for region_y in first.y..=last.y {
+            let mut hasher = DefaultHasher::new();
+            (region_x, region_y, seed, "prefab").hash(&mut hasher);
+            let mut rng = rand::rngs::StdRng::seed_from_u64(hasher.finish());
+
+            let index = rng.gen_range(0..prefabs.len());
+            let prefab = &prefabs[index];
+            let size = prefab.size();
+
+            // Kept inside the region so prefabs of neighboring regions never overlap
+            if !rng.gen_bool(prefab.chance.clamp(0., 1.))
+                || size.x == 0
+                || size.x > PREFAB_REGION_TILES
+                || size.y > PREFAB_REGION_TILES
+            {
+                continue;
+            }
+
+            let origin = IVec2::new(region_x, region_y) * PREFAB_REGION_TILES
+                + IVec2::new(
+                    rng.gen_range(0..=PREFAB_REGION_TILES - size.x),
+                    rng.gen_range(0..=PREFAB_REGION_TILES - size.y),
+                );
+
+            origins.push((index, origin));
+        }
+    }
+
+    origins
+}
+
+// Tiles pinned by prefabs between the two world tiles, the second exclusive
+pub fn prefab_tiles(seed: u64, prefabs: &[Prefab], from: IVec2, to: IVec2) -> Vec<(IVec2, u8)> {
+    let mut pinned = Vec::new();
+
+    for (index, origin) in prefab_origins(seed, prefabs, from, to) {
+        let prefab = &prefabs[index];
+        let size = prefab.size();
+
+        for (row, tiles) in prefab.tiles.iter().enumerate() {
+            for (column, tile) in tiles.iter().enumerate() {
+                let Some(tile) = tile else {
+                    continue;
+                };
+
+                let cell = origin + IVec2::new(column as i32, size.y - 1 - row as i32);
+
+                if cell.cmpge(from).all() && cell.cmplt(to).all() {
+                    pinned.push((cell, *tile));
+                }
+            }
+        }
+    }
+
+    pinned
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PropPlacement {
+    pub owner: ChunkCoords,
+    // Bottom left corner and size in world coordinates
+    pub origin: Vec2,
+    pub size: Vec2,
+}
+
+// Props a chunk places, the same for a chunk every time it is generated
+pub fn plan_chunk_props(seed: u64, coords: ChunkCoords) -> Vec<PropPlacement> {
+    let mut hasher = DefaultHasher::new();
+    (coords.0, coords.1, seed, "props").hash(&mut hasher);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(hasher.finish());
+
+    (0..rng.gen_range(0..=MAX_PROPS_PER_CHUNK))
+        .map(|_| {
+            let (width, height) = PROP_SIZES[rng.gen_range(0..PROP_SIZES.len())];
+            let x = rng.gen_range(0..CHUNK_TILE_LENGTH);
+            let y = rng.gen_range(0..CHUNK_TILE_LENGTH);
+
+            PropPlacement {
+                owner: coords,
+                origin: Vec2::new(
+                    (coords.0 + x * TILE_SIZE) as f32,
+                    (coords.1 + y * TILE_SIZE) as f32,
+                ),
+                size: Vec2::new((width * TILE_SIZE) as f32, (height * TILE_SIZE) as f32),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn bundled_camps_declare_their_spawners() {
+        let structures: HashMap<String, StructureDefinition> =
+            serde_json::from_str(include_str!("../../assets/structures.json")).unwrap();
+
+        let camp = &structures["camp"];
+        assert!(!camp.layout.is_empty());
+        assert!(camp.spawners.iter().any(|spawner| spawner.portal));
+        assert!(camp.spawners.iter().all(|spawner| spawner.health > 0));
+
+        // Spawners stand on the camp's own footprint
+        let half = Vec2::new(camp.layout[0].len() as f32, camp.layout.len() as f32)
+            * TILE_SIZE as f32
+            / 2.;
+        for spawner in camp.spawners.iter() {
+            let offset = spawner.position(Vec2::ZERO);
+            assert!(offset.abs().cmple(half).all());
+        }
+    }
+}
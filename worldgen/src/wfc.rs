@@ -0,0 +1,627 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use glam::IVec2;
+
+use log::{debug, warn};
+
+use super::{
+    biome::BiomeMap, pattern::PatternCollapse, schematic::Schematic, ChunkCoords,
+    CHUNK_TILE_LENGTH, TILE_SIZE,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use serde::{Deserialize, Serialize};
+
+// Number of solves attempted before accepting a chunk that falls short of the schematic constraints
+const MAX_ATTEMPTS: u64 = 8;
+
+// Snapshot of a chunk's solve, dumped when a contradiction leaves tiles uncollapsed
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct WfcTrace {
+    pub chunk: (i64, i64),
+    pub order: Vec<(usize, usize, u8)>,
+    pub constraint_map: Vec<Vec<Vec<u8>>>,
+    pub contradictions: Vec<(usize, usize)>,
+}
+
+pub struct WaveFunctionCollapse {
+    hash: u64,
+    coords: ChunkCoords,
+    schematic: Schematic,
+    constraint_map: Vec<Vec<HashSet<u8>>>,
+    // Biome weights of each tile, empty when the schematic defines no biomes
+    biome_weights: Vec<Vec<Vec<(usize, f32)>>>,
+    tiles: Vec<Vec<Option<u8>>>,
+    // Tiles decided before the solve, by features planned across chunks
+    fixed: Vec<(usize, usize, u8)>,
+    order: Option<Vec<(usize, usize, u8)>>,
+    snapshot: Option<Vec<Vec<HashSet<u8>>>>,
+    attempts: u64,
+    // Reseeded from the hash at the start of every solve, and drawn from by every pick in it
+    rng: StdRng,
+}
+
+impl WaveFunctionCollapse {
+    pub fn init(
+        world_seed: u64,
+        schematic: &Schematic,
+        coords: ChunkCoords,
+        fixed: &[(usize, usize, u8)],
+    ) -> WaveFunctionCollapse {
+        let mut biome_weights =
+            vec![vec![Vec::new(); CHUNK_TILE_LENGTH as usize]; CHUNK_TILE_LENGTH as usize];
+
+        if !schematic.biomes.is_empty() {
+            let biomes = BiomeMap::new(world_seed, schematic.biomes.len());
+            let origin = IVec2::new(
+                coords.0.div_euclid(TILE_SIZE) as i32,
+                coords.1.div_euclid(TILE_SIZE) as i32,
+            );
+
+            for (x, column) in biome_weights.iter_mut().enumerate() {
+                for (y, weights) in column.iter_mut().enumerate() {
+                    *weights = biomes.weights(origin + IVec2::new(x as i32, y as i32));
+                }
+            }
+        }
+
+        WaveFunctionCollapse {
+            hash: Self::get_hash(world_seed, &coords),
+            coords,
+            schematic: schematic.clone(),
+            constraint_map: vec![
+                vec![
+                    schematic.tiles.keys().copied().collect();
+                    CHUNK_TILE_LENGTH as usize
+                ];
+                CHUNK_TILE_LENGTH as usize
+            ],
+            biome_weights,
+            tiles: vec![vec![None; CHUNK_TILE_LENGTH as usize]; CHUNK_TILE_LENGTH as usize],
+            fixed: fixed
+                .iter()
+                .copied()
+                .filter(|(x, y, tile)| {
+                    *x < CHUNK_TILE_LENGTH as usize
+                        && *y < CHUNK_TILE_LENGTH as usize
+                        && schematic.tiles.contains_key(tile)
+                })
+                .collect(),
+            order: None,
+            snapshot: None,
+            attempts: 0,
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+
+    // Opt-in, records collapse order and the constraint map so contradictions can be replayed
+    pub fn enable_trace(&mut self) {
+        self.order = Some(Vec::new());
+    }
+
+    // Solves the last collapse took, more than one when chunk constraints rejected a solve
+    pub fn attempts(&self) -> u64 {
+        self.attempts
+    }
+
+    pub fn contradiction_trace(&self) -> Option<WfcTrace> {
+        let order = self.order.as_ref()?;
+        let snapshot = self.snapshot.as_ref()?;
+
+        let mut contradictions = Vec::new();
+        for (x, column) in self.tiles.iter().enumerate() {
+            for (y, tile) in column.iter().enumerate() {
+                if tile.is_none() {
+                    contradictions.push((x, y));
+                }
+            }
+        }
+
+        if contradictions.is_empty() {
+            return None;
+        }
+
+        Some(WfcTrace {
+            chunk: (self.coords.0, self.coords.1),
+            order: order.clone(),
+            constraint_map: snapshot
+                .iter()
+                .map(|column| {
+                    column
+                        .iter()
+                        .map(|constraint| {
+                            let mut allowed: Vec<u8> = constraint.iter().copied().collect();
+                            allowed.sort();
+                            allowed
+                        })
+                        .collect()
+                })
+                .collect(),
+            contradictions,
+        })
+    }
+
+    pub fn collapse(&mut self) -> &Vec<Vec<Option<u8>>> {
+        let seed = self.hash;
+        let mut satisfied = false;
+
+        // Maximums are enforced while collapsing, reject chunks short of a minimum and solve again with a new seed
+        for attempt in 0..MAX_ATTEMPTS {
+            self.hash = seed.wrapping_add(attempt);
+            self.attempts = attempt + 1;
+            self.solve();
+
+            satisfied = self.satisfies_constraints();
+            if satisfied {
+                break;
+            }
+
+            debug!(
+                "Chunk ({}, {}) rejected by schematic constraints, attempt {}",
+                self.coords.0,
+                self.coords.1,
+                attempt + 1
+            );
+        }
+
+        if !satisfied {
+            warn!(
+                "Chunk ({}, {}) still breaks schematic constraints after {} attempts, keeping the last solve",
+                self.coords.0, self.coords.1, MAX_ATTEMPTS
+            );
+        }
+
+        &self.tiles
+    }
+
+    fn solve(&mut self) {
+        self.reset();
+
+        // Schematics may opt into collapsing whole patterns instead of single tiles, patterns can't honour fixed tiles
+        if self.fixed.is_empty() {
+            if let Some(patterns) = self.schematic.patterns() {
+                self.tiles = PatternCollapse::init(patterns, self.hash).collapse();
+                return;
+            }
+        }
+
+        // Generate bottom left of tile of chunk, fixed tiles already give the solve somewhere to grow from
+        if self.fixed.is_empty() {
+            self.tiles[0][0] = self.scratch();
+
+            if let (Some(order), Some(first)) = (self.order.as_mut(), self.tiles[0][0]) {
+                order.push((0, 0, first));
+            }
+        } else {
+            self.update_constraint_map();
+        }
+
+        // Collapse Chunk
+        while let Some(next) = self.lowest_entropy() {
+            self.tiles[next.0][next.1] = self.collapse_tile(next);
+
+            // No candidate could be picked, leave the tile uncollapsed
+            if self.tiles[next.0][next.1].is_none() {
+                self.constraint_map[next.0][next.1].clear();
+            }
+
+            if let (Some(order), Some(collapsed)) =
+                (self.order.as_mut(), self.tiles[next.0][next.1])
+            {
+                order.push((next.0, next.1, collapsed));
+            }
+
+            // Keep the last non-empty constraints, once the solve stalls the map is cleared
+            if self.order.is_some() {
+                self.snapshot = Some(self.constraint_map.clone());
+            }
+
+            self.update_constraint_map();
+        }
+    }
+
+    fn reset(&mut self) {
+        let unconstrained: HashSet<u8> = self.schematic.tiles.keys().copied().collect();
+
+        for x in 0..CHUNK_TILE_LENGTH as usize {
+            for y in 0..CHUNK_TILE_LENGTH as usize {
+                self.tiles[x][y] = None;
+                self.constraint_map[x][y] = unconstrained.clone();
+            }
+        }
+
+        self.constrain_biomes();
+        self.rng = StdRng::seed_from_u64(self.hash);
+
+        if let Some(order) = self.order.as_mut() {
+            order.clear();
+        }
+        self.snapshot = None;
+
+        for &(x, y, tile) in &self.fixed {
+            self.tiles[x][y] = Some(tile);
+
+            if let Some(order) = self.order.as_mut() {
+                order.push((x, y, tile));
+            }
+        }
+
+        self.prune_capped();
+    }
+
+    // Pre-constraint pass, limits each tile to the tile sets of the biomes weighted at it
+    fn constrain_biomes(&mut self) {
+        if self.schematic.biomes.is_empty() {
+            return;
+        }
+
+        for x in 0..CHUNK_TILE_LENGTH as usize {
+            for y in 0..CHUNK_TILE_LENGTH as usize {
+                let weights = &self.biome_weights[x][y];
+                let biomes = &self.schematic.biomes;
+
+                self.constraint_map[x][y].retain(|tile| {
+                    !biomes.iter().any(|biome| biome.contains(tile))
+                        || weights
+                            .iter()
+                            .any(|(biome, weight)| *weight > 0. && biomes[*biome].contains(tile))
+                });
+            }
+        }
+    }
+
+    // Shared tiles count fully in every biome, biome tiles by how strongly their biome is weighted here
+    fn biome_weight(&self, idx: (usize, usize), tile: u8) -> f32 {
+        let biomes = &self.schematic.biomes;
+
+        if !biomes.iter().any(|biome| biome.contains(&tile)) {
+            return 1.;
+        }
+
+        self.biome_weights[idx.0][idx.1]
+            .iter()
+            .filter(|(biome, _)| biomes[*biome].contains(&tile))
+            .map(|(_, weight)| weight)
+            .sum()
+    }
+
+    fn count_tiles(&self, tiles: &[u8]) -> usize {
+        self.tiles
+            .iter()
+            .flatten()
+            .filter(|tile| tile.is_some_and(|tile| tiles.contains(&tile)))
+            .count()
+    }
+
+    fn satisfies_constraints(&self) -> bool {
+        let area = (CHUNK_TILE_LENGTH * CHUNK_TILE_LENGTH) as usize;
+
+        self.schematic.constraints.iter().all(|constraint| {
+            let (min, max) = constraint.bounds(area);
+            let count = self.count_tiles(&constraint.tiles);
+            count >= min && count <= max
+        })
+    }
+
+    // Tiles of every chunk constraint that already hit its maximum
+    fn capped_tiles(&self) -> HashSet<u8> {
+        let area = (CHUNK_TILE_LENGTH * CHUNK_TILE_LENGTH) as usize;
+
+        self.schematic
+            .constraints
+            .iter()
+            .filter(|constraint| self.count_tiles(&constraint.tiles) >= constraint.bounds(area).1)
+            .flat_map(|constraint| constraint.tiles.iter().copied())
+            .collect()
+    }
+
+    // Removes capped tiles from every domain, so a maximum can't be exceeded
+    fn prune_capped(&mut self) {
+        if self.schematic.constraints.is_empty() {
+            return;
+        }
+
+        let capped = self.capped_tiles();
+        if capped.is_empty() {
+            return;
+        }
+
+        for domain in self.constraint_map.iter_mut().flatten() {
+            domain.retain(|tile| !capped.contains(tile));
+        }
+    }
+
+    fn update_constraint_map(&mut self) {
+        #[cfg(feature = "trace")]
+        log::trace!("Updating constraint map");
+
+        for x in 0..CHUNK_TILE_LENGTH {
+            for y in 0..CHUNK_TILE_LENGTH {
+                if self.tiles[x as usize][y as usize].is_some() {
+                    self.constraint_map[x as usize][y as usize].clear();
+                    continue;
+                }
+
+                if x >= 1 {
+                    if let Some(left) = self.tiles[(x - 1) as usize][y as usize] {
+                        let allowed = self.schematic.tiles[&left].east.clone();
+
+                        self.constraint_map[x as usize][y as usize]
+                            .retain(|&to_retain| allowed.contains(&to_retain));
+                    }
+                }
+
+                if y >= 1 {
+                    if let Some(down) = self.tiles[x as usize][(y - 1) as usize] {
+                        let allowed = self.schematic.tiles[&down].north.clone();
+
+                        self.constraint_map[x as usize][y as usize]
+                            .retain(|&to_retain| allowed.contains(&to_retain));
+                    }
+                }
+
+                if x + 1 < CHUNK_TILE_LENGTH {
+                    if let Some(right) = self.tiles[(x + 1) as usize][y as usize] {
+                        let allowed = self.schematic.tiles[&right].west.clone();
+
+                        self.constraint_map[x as usize][y as usize]
+                            .retain(|&to_retain| allowed.contains(&to_retain));
+                    }
+                }
+
+                if y + 1 < CHUNK_TILE_LENGTH {
+                    if let Some(up) = self.tiles[x as usize][(y + 1) as usize] {
+                        let allowed = self.schematic.tiles[&up].south.clone();
+
+                        self.constraint_map[x as usize][y as usize]
+                            .retain(|&to_retain| allowed.contains(&to_retain));
+                    }
+                }
+            }
+        }
+
+        self.prune_capped();
+    }
+
+    // Finds lowest non-zero entry in constraint map and returns it's index.
+    fn lowest_entropy(&self) -> Option<(usize, usize)> {
+        let mut index = None;
+        let mut lowest = 0;
+
+        for x in 0..CHUNK_TILE_LENGTH {
+            for y in 0..CHUNK_TILE_LENGTH {
+                let n_constraints = self.constraint_map[x as usize][y as usize].len();
+                if n_constraints > 0 && (lowest == 0 || n_constraints < lowest) {
+                    lowest = n_constraints;
+                    index = Some((x as usize, y as usize))
+                }
+            }
+        }
+
+        #[cfg(feature = "trace")]
+        if let Some((x, y)) = index {
+            log::trace!("Entropy minima: ({}, {})", x, y);
+        }
+
+        index
+    }
+
+    // From scratch
+    fn scratch(&mut self) -> Option<u8> {
+        // The first tile has to come from its biomes and respect chunk maximums as well
+        if !self.schematic.biomes.is_empty() || !self.schematic.constraints.is_empty() {
+            return self.collapse_tile((0, 0));
+        }
+
+        // Sorted so the pick depends only on the seed, not on map iteration order
+        let mut keys: Vec<u8> = self.schematic.tiles.keys().copied().collect();
+        keys.sort_unstable();
+
+        let idx = self.rng.gen_range(0..keys.len());
+        Some(keys[idx])
+    }
+
+    fn collapse_tile(&mut self, idx: (usize, usize)) -> Option<u8> {
+        #[cfg(feature = "trace")]
+        log::trace!("Collapsing tile ({}, {})", idx.0, idx.1);
+        let available = self.constraint_map[idx.0][idx.1].clone();
+
+        // Capped tiles are pruned from every domain, which can leave nothing to pick
+        if available.is_empty() {
+            return None;
+        }
+
+        if !self.schematic.biomes.is_empty() {
+            return self.pick_blended(idx, available);
+        }
+
+        // Sorted so the pick depends only on the seed, not on set iteration order
+        let mut available: Vec<u8> = available.into_iter().collect();
+        available.sort_unstable();
+
+        let rand = self.rng.gen_range(0..available.len());
+        Some(available[rand])
+    }
+
+    // Samples the blended tile set, so tiles near a border favour the closer biome
+    fn pick_blended(&mut self, idx: (usize, usize), available: HashSet<u8>) -> Option<u8> {
+        let mut candidates: Vec<(u8, f32)> = available
+            .into_iter()
+            .map(|tile| (tile, self.biome_weight(idx, tile)))
+            .filter(|(_, weight)| *weight > 0.)
+            .collect();
+
+        if candidates.is_empty() {
+            return None;
+        }
+
+        candidates.sort_by_key(|(tile, _)| *tile);
+
+        let total: f32 = candidates.iter().map(|(_, weight)| weight).sum();
+        let mut roll = self.rng.gen_range(0.0..total);
+
+        for (tile, weight) in candidates.iter() {
+            if roll < *weight {
+                return Some(*tile);
+            }
+            roll -= weight;
+        }
+
+        candidates.last().map(|(tile, _)| *tile)
+    }
+
+    fn get_hash(world_seed: u64, coords: &ChunkCoords) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (coords.0, coords.1, world_seed).hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schematic::{parse_schematic, ChunkConstraint};
+
+    // Grass and sand fit next to each other and themselves on every side
+    fn meadow(constraints: &str) -> Schematic {
+        let json = format!(
+            r#"{{
+                "version": 2,
+                "not_found": "grass",
+                "tiles": {{
+                    "grass": {{
+                        "index": 0,
+                        "sheet": "terrain",
+                        "weight": 1,
+                        "symmetric": true,
+                        "rules": {{ "north": ["grass", "sand"], "east": ["grass", "sand"] }}
+                    }},
+                    "sand": {{
+                        "index": 1,
+                        "sheet": "terrain",
+                        "weight": 1,
+                        "symmetric": true,
+                        "rules": {{ "north": ["grass", "sand"], "east": ["grass", "sand"] }}
+                    }}
+                }},
+                "constraints": [{constraints}]
+            }}"#
+        );
+
+        parse_schematic(json.as_bytes()).unwrap()
+    }
+
+    fn count(tiles: &[Vec<Option<u8>>], tile: u8) -> usize {
+        tiles
+            .iter()
+            .flatten()
+            .filter(|cell| **cell == Some(tile))
+            .count()
+    }
+
+    #[test]
+    fn constraint_bounds_combine_counts_and_ratios() {
+        let constraint = ChunkConstraint {
+            tiles: vec![1],
+            min: Some(4),
+            max: Some(40),
+            min_ratio: Some(0.1),
+            max_ratio: Some(0.5),
+        };
+
+        assert_eq!(constraint.bounds(64), (7, 32));
+
+        let unbounded = ChunkConstraint {
+            tiles: vec![1],
+            min: None,
+            max: None,
+            min_ratio: None,
+            max_ratio: None,
+        };
+
+        assert_eq!(unbounded.bounds(64), (0, 64));
+    }
+
+    #[test]
+    fn chunks_respect_their_maximum() {
+        let schematic = meadow(r#"{ "tiles": ["sand"], "max": 3 }"#);
+
+        for seed in 0..8 {
+            let mut wfc = WaveFunctionCollapse::init(seed, &schematic, ChunkCoords(0, 0), &[]);
+            let tiles = wfc.collapse().clone();
+
+            assert!(tiles.iter().flatten().all(Option::is_some));
+            assert!(count(&tiles, 1) <= 3);
+            assert_eq!(wfc.attempts(), 1);
+        }
+    }
+
+    #[test]
+    fn capped_tiles_are_never_placed() {
+        let schematic = meadow(r#"{ "tiles": ["sand"], "max": 0 }"#);
+
+        for seed in 0..8 {
+            let mut wfc = WaveFunctionCollapse::init(seed, &schematic, ChunkCoords(0, 0), &[]);
+            let tiles = wfc.collapse().clone();
+
+            assert_eq!(count(&tiles, 1), 0);
+            assert_eq!(wfc.attempts(), 1);
+        }
+    }
+
+    #[test]
+    fn fixed_tiles_count_towards_maximums() {
+        let schematic = meadow(r#"{ "tiles": ["sand"], "max": 2 }"#);
+        let fixed = [(1, 1, 1), (6, 6, 1)];
+
+        let mut wfc = WaveFunctionCollapse::init(3, &schematic, ChunkCoords(0, 0), &fixed);
+        let tiles = wfc.collapse();
+
+        assert_eq!(count(tiles, 1), 2);
+    }
+
+    #[test]
+    fn chunks_are_reproducible_for_a_seed() {
+        let schematic = parse_schematic(include_bytes!("../../assets/schematic.json")).unwrap();
+
+        // Every solve builds its own sets, so iteration order differs between the two
+        for coords in [
+            ChunkCoords(-8192, -8192),
+            ChunkCoords(0, 256),
+            ChunkCoords(512, -256),
+        ] {
+            let first = WaveFunctionCollapse::init(1234, &schematic, coords, &[])
+                .collapse()
+                .clone();
+            let second = WaveFunctionCollapse::init(1234, &schematic, coords, &[])
+                .collapse()
+                .clone();
+
+            assert_eq!(first, second);
+        }
+    }
+
+    #[test]
+    fn chunk_hashes_depend_on_each_coordinate() {
+        let hash = |seed, x, y| WaveFunctionCollapse::get_hash(seed, &ChunkCoords(x, y));
+
+        assert_ne!(hash(7, 256, 512), hash(7, 512, 256));
+        assert_ne!(hash(7, 256, 512), hash(8, 0, 512));
+    }
+
+    #[test]
+    fn fixed_tiles_are_kept() {
+        let schematic = meadow("");
+        let fixed = [(2, 3, 1), (5, 5, 0)];
+
+        let mut wfc = WaveFunctionCollapse::init(7, &schematic, ChunkCoords(0, 0), &fixed);
+        let tiles = wfc.collapse();
+
+        assert_eq!(tiles[2][3], Some(1));
+        assert_eq!(tiles[5][5], Some(0));
+    }
+}